@@ -1,6 +1,6 @@
 //! The [`CachedOnDiskCorpus`] stores [`Testcase`]s to disk, keeping a subset of them in memory/cache, evicting in a FIFO manner.
 
-use alloc::{collections::vec_deque::VecDeque, string::String};
+use alloc::{collections::vec_deque::VecDeque, string::String, vec::Vec};
 use core::cell::RefCell;
 use std::path::Path;
 
@@ -8,8 +8,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     corpus::{
-        inmemory_ondisk::InMemoryOnDiskCorpus, ondisk::OnDiskMetadataFormat, Corpus, CorpusId,
-        HasTestcase, Testcase,
+        inmemory_ondisk::InMemoryOnDiskCorpus, ondisk::OnDiskMetadataFormat,
+        prefetch::InputPrefetcher, Corpus, CorpusId, HasTestcase, Testcase,
     },
     inputs::{Input, UsesInput},
     Error,
@@ -28,6 +28,7 @@ where
     inner: InMemoryOnDiskCorpus<I>,
     cached_indexes: RefCell<VecDeque<CorpusId>>,
     cache_max_len: usize,
+    prefetcher: InputPrefetcher<I>,
 }
 
 impl<I> UsesInput for CachedOnDiskCorpus<I>
@@ -110,7 +111,11 @@ where
     #[inline]
     fn replace(&mut self, idx: CorpusId, testcase: Testcase<I>) -> Result<Testcase<I>, Error> {
         // TODO finish
-        self.inner.replace(idx, testcase)
+        let prev = self.inner.replace(idx, testcase)?;
+        if let Some(path) = prev.file_path() {
+            self.prefetcher.forget(path);
+        }
+        Ok(prev)
     }
 
     /// Removes an entry from the corpus, returning it if it was present.
@@ -118,6 +123,9 @@ where
     fn remove(&mut self, idx: CorpusId) -> Result<Testcase<I>, Error> {
         let testcase = self.inner.remove(idx)?;
         self.cached_indexes.borrow_mut().retain(|e| *e != idx);
+        if let Some(path) = testcase.file_path() {
+            self.prefetcher.forget(path);
+        }
         Ok(testcase)
     }
 
@@ -179,15 +187,53 @@ where
         self.inner.nth_from_all(nth)
     }
 
+    #[inline]
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
+
     #[inline]
     fn load_input_into(&self, testcase: &mut Testcase<Self::Input>) -> Result<(), Error> {
+        if testcase.input().is_none() {
+            let prefetched = testcase
+                .file_path()
+                .as_ref()
+                .and_then(|path| self.prefetcher.take(path));
+            if let Some(input) = prefetched {
+                testcase.set_input(input);
+                return Ok(());
+            }
+        }
         self.inner.load_input_into(testcase)
     }
 
     #[inline]
     fn store_input_from(&self, testcase: &Testcase<Self::Input>) -> Result<(), Error> {
+        if let Some(path) = testcase.file_path() {
+            self.prefetcher.forget(path);
+        }
         self.inner.store_input_from(testcase)
     }
+
+    #[inline]
+    fn prefetch_count(&self) -> usize {
+        self.prefetcher.count()
+    }
+
+    /// Starts loading the inputs of the given testcases, that are not in memory yet, in the background
+    fn prefetch(&self, ids: &[CorpusId]) {
+        let mut paths = Vec::new();
+        for id in ids {
+            if let Ok(Ok(testcase)) = self.inner.get(*id).map(RefCell::try_borrow) {
+                if testcase.input().is_none() {
+                    if let Some(path) = testcase.file_path() {
+                        paths.push(path.clone());
+                    }
+                }
+            }
+        }
+        self.prefetcher.prefetch(paths);
+    }
 }
 
 impl<I> HasTestcase for CachedOnDiskCorpus<I>
@@ -290,9 +336,46 @@ where
             inner: on_disk_corpus,
             cached_indexes: RefCell::new(VecDeque::new()),
             cache_max_len,
+            prefetcher: InputPrefetcher::default(),
         })
     }
 
+    /// Stores the inputs in the given [`OnDiskMetadataFormat`], see [`InMemoryOnDiskCorpus::with_input_format`]
+    #[must_use]
+    pub fn with_input_format(mut self, format: OnDiskMetadataFormat) -> Self {
+        self.inner = self.inner.with_input_format(format);
+        self
+    }
+
+    /// Loads the inputs of the next `count` testcases in the background, whenever the fuzzer schedules a testcase.
+    ///
+    /// The next testcases are predicted by the scheduler, see [`crate::schedulers::Scheduler::upcoming`].
+    /// Wrong predictions only cost memory and disk bandwidth, the inputs are then loaded from disk as usual.
+    /// The inputs need to be [`Send`], so that they can be loaded on another thread.
+    /// A deserialized corpus, for example of a restarting event manager, only prefetches again after [`Self::resume_prefetch`].
+    #[must_use]
+    pub fn with_prefetch(mut self, count: usize) -> Self
+    where
+        I: Send + 'static,
+    {
+        self.prefetcher = InputPrefetcher::new(count, self.inner.input_format());
+        self
+    }
+
+    /// Restarts the prefetching of a deserialized corpus, with the settings of [`Self::with_prefetch`]
+    pub fn resume_prefetch(&mut self)
+    where
+        I: Send + 'static,
+    {
+        self.prefetcher.resume();
+    }
+
+    /// The [`InputPrefetcher`] of this corpus
+    #[must_use]
+    pub fn prefetcher(&self) -> &InputPrefetcher<I> {
+        &self.prefetcher
+    }
+
     /// Fetch the inner corpus
     pub fn inner(&self) -> &InMemoryOnDiskCorpus<I> {
         &self.inner
@@ -12,9 +12,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-#[cfg(feature = "gzip")]
-use libafl_bolts::compress::GzipCompressor;
-use libafl_bolts::serdeany::SerdeAnyMap;
+use libafl_bolts::{fs::write_file_atomic, serdeany::SerdeAnyMap};
 use serde::{Deserialize, Serialize};
 
 use super::{
@@ -51,6 +49,8 @@ where
     meta_format: Option<OnDiskMetadataFormat>,
     prefix: Option<String>,
     locking: bool,
+    /// The format to store inputs in, or `None` to use [`Input::to_file`]
+    input_format: Option<OnDiskMetadataFormat>,
 }
 
 impl<I> UsesInput for InMemoryOnDiskCorpus<I>
@@ -175,6 +175,20 @@ where
         self.inner.nth_from_all(nth)
     }
 
+    /// Rewrites the metadata files of all testcases
+    fn flush(&mut self) -> Result<(), Error> {
+        if self.meta_format.is_none() {
+            return Ok(());
+        }
+        for nth in 0..self.count_all() {
+            let testcase = &mut self.get_from_all(self.nth_from_all(nth))?.borrow_mut();
+            if testcase.filename().is_some() {
+                self.save_metadata(testcase)?;
+            }
+        }
+        Ok(())
+    }
+
     fn load_input_into(&self, testcase: &mut Testcase<Self::Input>) -> Result<(), Error> {
         if testcase.input_mut().is_none() {
             let Some(file_path) = testcase.file_path().as_ref() else {
@@ -182,7 +196,10 @@ where
                     "No file path set for testcase. Could not load inputs.",
                 ));
             };
-            let input = I::from_file(file_path)?;
+            let input = match self.input_format {
+                Some(format) => format.deserialize(&fs::read(file_path)?)?,
+                None => I::from_file(file_path)?,
+            };
             testcase.set_input(input);
         }
         Ok(())
@@ -200,7 +217,10 @@ where
                 "No input available for testcase. Could not store anything.",
             ));
         };
-        input.to_file(file_path)
+        match self.input_format {
+            Some(format) => write_file_atomic(file_path, &format.serialize(input)?),
+            None => input.to_file(file_path),
+        }
     }
 }
 
@@ -308,9 +328,26 @@ where
             meta_format,
             prefix,
             locking,
+            input_format: None,
         })
     }
 
+    /// Stores the inputs in the given [`OnDiskMetadataFormat`], instead of using [`Input::to_file`].
+    ///
+    /// Inputs usually store themselves compactly, some, like [`crate::inputs::BytesInput`], as raw bytes.
+    /// Storing them as JSON instead eases debugging, and reading them with other tools.
+    #[must_use]
+    pub fn with_input_format(mut self, format: OnDiskMetadataFormat) -> Self {
+        self.input_format = Some(format);
+        self
+    }
+
+    /// The format inputs are stored in, or `None`, if they use [`Input::to_file`]
+    #[must_use]
+    pub fn input_format(&self) -> Option<OnDiskMetadataFormat> {
+        self.input_format
+    }
+
     /// Sets the filename for a [`Testcase`].
     /// If an error gets returned from the corpus (i.e., file exists), we'll have to retry with a different filename.
     #[inline]
@@ -416,7 +453,14 @@ where
         }
         *testcase.filename_mut() = Some(file_name);
 
-        if self.meta_format.is_some() {
+        self.save_metadata(testcase)?;
+        self.store_input_from(testcase)?;
+        Ok(())
+    }
+
+    /// Writes the metadata of a testcase, which already has a filename, if this corpus stores metadata
+    fn save_metadata(&self, testcase: &mut Testcase<I>) -> Result<(), Error> {
+        if let Some(meta_format) = self.meta_format {
             let metafile_name = format!(".{}.metadata", testcase.filename().as_ref().unwrap());
             let metafile_path = self.dir_path.join(&metafile_name);
             let mut tmpfile_path = metafile_path.clone();
@@ -430,21 +474,11 @@ where
 
             let mut tmpfile = File::create(&tmpfile_path)?;
 
-            let serialized = match self.meta_format.as_ref().unwrap() {
-                OnDiskMetadataFormat::Postcard => postcard::to_allocvec(&ondisk_meta)?,
-                OnDiskMetadataFormat::Json => serde_json::to_vec(&ondisk_meta)?,
-                OnDiskMetadataFormat::JsonPretty => serde_json::to_vec_pretty(&ondisk_meta)?,
-                #[cfg(feature = "gzip")]
-                OnDiskMetadataFormat::JsonGzip => GzipCompressor::new(0)
-                    .compress(&serde_json::to_vec_pretty(&ondisk_meta)?)?
-                    .unwrap(),
-            };
+            let serialized = meta_format.serialize(&ondisk_meta)?;
             tmpfile.write_all(&serialized)?;
             fs::rename(&tmpfile_path, &metafile_path)?;
             *testcase.metadata_path_mut() = Some(metafile_path);
         }
-
-        self.store_input_from(testcase)?;
         Ok(())
     }
 
@@ -1,7 +1,7 @@
 //! Corpuses contain the testcases, either in memory, on disk, or somewhere else.
 
 pub mod testcase;
-pub use testcase::{HasTestcase, SchedulerTestcaseMetadata, Testcase};
+pub use testcase::{HasTestcase, ObjectiveKind, SchedulerTestcaseMetadata, Testcase};
 
 pub mod inmemory;
 pub use inmemory::InMemoryCorpus;
@@ -14,22 +14,47 @@ pub use inmemory_ondisk::InMemoryOnDiskCorpus;
 #[cfg(feature = "std")]
 pub mod ondisk;
 #[cfg(feature = "std")]
-pub use ondisk::OnDiskCorpus;
+pub use ondisk::{OnDiskCorpus, OnDiskMetadataFormat};
+
+#[cfg(feature = "std")]
+pub mod prefetch;
+#[cfg(feature = "std")]
+pub use prefetch::InputPrefetcher;
 
 #[cfg(feature = "std")]
 pub mod cached;
 #[cfg(feature = "std")]
 pub use cached::CachedOnDiskCorpus;
 
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub use stats::{StatsFormat, TestcaseStats};
+
 #[cfg(feature = "cmin")]
 pub mod minimizer;
 use core::{cell::RefCell, fmt};
 
 pub mod nop;
+pub mod snapshot;
 #[cfg(feature = "cmin")]
 pub use minimizer::*;
 pub use nop::NopCorpus;
+pub use snapshot::{CorpusSnapshot, CorpusSnapshotDiff};
+
+pub mod tee;
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use libafl_bolts::rands::Rand;
+#[cfg(feature = "std")]
+use libafl_bolts::HasLen;
 use serde::{Deserialize, Serialize};
+pub use tee::TeeCorpus;
 
 use crate::{inputs::UsesInput, Error};
 
@@ -149,6 +174,17 @@ pub trait Corpus: UsesInput + Serialize + for<'de> Deserialize<'de> {
         }
     }
 
+    /// All active corpus ids, in a random order reproducible for a given [`Rand`] seed.
+    /// Use it for passes over the whole corpus, to not depend on the insertion order.
+    fn shuffled_ids<R>(&self, rand: &mut R) -> Vec<CorpusId>
+    where
+        R: Rand,
+    {
+        let mut ids: Vec<CorpusId> = self.ids().collect();
+        rand.shuffle(&mut ids);
+        ids
+    }
+
     /// Get the nth corpus id; considers only enabled testcases
     fn nth(&self, nth: usize) -> CorpusId {
         self.ids()
@@ -159,6 +195,21 @@ pub trait Corpus: UsesInput + Serialize + for<'de> Deserialize<'de> {
     /// Get the nth corpus id; considers both enabled and disabled testcases
     fn nth_from_all(&self, nth: usize) -> CorpusId;
 
+    /// The position of the [`Corpus::current`] testcase among the enabled testcases, in insertion order.
+    /// Returns `None`, if no testcase is scheduled yet.
+    fn current_index(&self) -> Option<usize> {
+        let current = (*self.current())?;
+        self.ids().position(|id| id == current)
+    }
+
+    /// The number of enabled testcases after the [`Corpus::current`] one, i.e., the number of entries
+    /// left in this pass of a queue-like scheduler, such as the [`crate::schedulers::QueueScheduler`].
+    /// Before the first testcase got scheduled, this is the whole corpus.
+    fn remaining_in_cycle(&self) -> usize {
+        self.current_index()
+            .map_or(self.count(), |idx| self.count().saturating_sub(idx + 1))
+    }
+
     /// Method to load the input for this [`Testcase`] from persistent storage,
     /// if necessary, and if was not already loaded (`== Some(input)`).
     /// After this call, `testcase.input()` must always return `Some(input)`.
@@ -167,6 +218,44 @@ pub trait Corpus: UsesInput + Serialize + for<'de> Deserialize<'de> {
     /// Method to store the input of this `Testcase` to persistent storage, if necessary.
     fn store_input_from(&self, testcase: &Testcase<Self::Input>) -> Result<(), Error>;
 
+    /// The number of upcoming testcases this corpus wants to load ahead, see [`Corpus::prefetch`].
+    /// `0` if this corpus does not prefetch.
+    fn prefetch_count(&self) -> usize {
+        0
+    }
+
+    /// Writes the [`Testcase`]s of this corpus to persistent storage again, if it has any,
+    /// as their metadata may have changed since they were added.
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Starts loading the inputs of the given testcases in the background, as they are likely needed next,
+    /// see [`crate::schedulers::Scheduler::upcoming`].
+    ///
+    /// This is only a hint, inputs that were not prefetched get loaded by [`Corpus::load_input_into`] as usual.
+    fn prefetch(&self, _ids: &[CorpusId]) {}
+
+    /// Writes the [`TestcaseStats`] of all enabled testcases, such as their size, execution time, and coverage,
+    /// as a table to the file at `path`, for offline analysis.
+    ///
+    /// The times the testcases were added are relative to the `start_time` of the fuzzer,
+    /// see [`crate::state::HasStartTime::start_time`].
+    #[cfg(feature = "std")]
+    fn export_stats<P>(
+        &self,
+        path: P,
+        format: StatsFormat,
+        start_time: Duration,
+    ) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        Self: Sized,
+        Self::Input: HasLen,
+    {
+        stats::write_stats(&stats::corpus_stats(self, start_time)?, path, format)
+    }
+
     /// Loads the `Input` for a given [`CorpusId`] from the [`Corpus`], and returns the clone.
     fn cloned_input_for_id(&self, idx: CorpusId) -> Result<Self::Input, Error> {
         let mut testcase = self.get(idx)?.borrow_mut();
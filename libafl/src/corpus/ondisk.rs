@@ -4,23 +4,28 @@
 //! For any other occasions, consider using [`crate::corpus::CachedOnDiskCorpus`]
 //! which stores a certain number of testcases in memory and removes additional ones in a FIFO manner.
 
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 use core::{cell::RefCell, time::Duration};
 use std::path::{Path, PathBuf};
 
+#[cfg(feature = "gzip")]
+use libafl_bolts::compress::GzipCompressor;
 use libafl_bolts::serdeany::SerdeAnyMap;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use super::{CachedOnDiskCorpus, HasTestcase};
 use crate::{
-    corpus::{Corpus, CorpusId, Testcase},
+    corpus::{prefetch::InputPrefetcher, Corpus, CorpusId, Testcase},
     inputs::{Input, UsesInput},
     Error,
 };
 
-/// Options for the the format of the on-disk metadata
+/// Options for the the format of the on-disk metadata.
+///
+/// The same formats serialize whole [`Testcase`]s, see [`Testcase::to_bytes`],
+/// and inputs stored by an on-disk corpus, see [`crate::corpus::InMemoryOnDiskCorpus::with_input_format`].
 #[cfg(feature = "std")]
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OnDiskMetadataFormat {
     /// A binary-encoded postcard
     Postcard,
@@ -34,6 +39,38 @@ pub enum OnDiskMetadataFormat {
     JsonGzip,
 }
 
+#[cfg(feature = "std")]
+impl OnDiskMetadataFormat {
+    /// Serializes the `value` in this format
+    pub fn serialize<T>(self, value: &T) -> Result<Vec<u8>, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(match self {
+            Self::Postcard => postcard::to_allocvec(value)?,
+            Self::Json => serde_json::to_vec(value)?,
+            Self::JsonPretty => serde_json::to_vec_pretty(value)?,
+            #[cfg(feature = "gzip")]
+            Self::JsonGzip => GzipCompressor::new(0)
+                .compress(&serde_json::to_vec_pretty(value)?)?
+                .unwrap(),
+        })
+    }
+
+    /// Deserializes a value in this format from the `bytes`
+    pub fn deserialize<T>(self, bytes: &[u8]) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(match self {
+            Self::Postcard => postcard::from_bytes(bytes)?,
+            Self::Json | Self::JsonPretty => serde_json::from_slice(bytes)?,
+            #[cfg(feature = "gzip")]
+            Self::JsonGzip => serde_json::from_slice(&GzipCompressor::new(0).decompress(bytes)?)?,
+        })
+    }
+}
+
 /// The [`Testcase`] metadata that'll be stored to disk
 #[derive(Debug, Serialize)]
 pub struct OnDiskMetadata<'a> {
@@ -167,6 +204,11 @@ where
         self.inner.nth_from_all(nth)
     }
 
+    #[inline]
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
+
     #[inline]
     fn load_input_into(&self, testcase: &mut Testcase<Self::Input>) -> Result<(), Error> {
         self.inner.load_input_into(testcase)
@@ -176,6 +218,16 @@ where
     fn store_input_from(&self, testcase: &Testcase<Self::Input>) -> Result<(), Error> {
         self.inner.store_input_from(testcase)
     }
+
+    #[inline]
+    fn prefetch_count(&self) -> usize {
+        self.inner.prefetch_count()
+    }
+
+    #[inline]
+    fn prefetch(&self, ids: &[CorpusId]) {
+        self.inner.prefetch(ids);
+    }
 }
 
 impl<I> HasTestcase for OnDiskCorpus<I>
@@ -284,8 +336,133 @@ where
         })
     }
 
+    /// Stores the inputs in the given [`OnDiskMetadataFormat`], see [`crate::corpus::InMemoryOnDiskCorpus::with_input_format`]
+    #[must_use]
+    pub fn with_input_format(mut self, format: OnDiskMetadataFormat) -> Self {
+        self.inner = self.inner.with_input_format(format);
+        self
+    }
+
+    /// Loads the inputs of the next `count` testcases in the background, see [`CachedOnDiskCorpus::with_prefetch`]
+    #[must_use]
+    pub fn with_prefetch(mut self, count: usize) -> Self
+    where
+        I: Send + 'static,
+    {
+        self.inner = self.inner.with_prefetch(count);
+        self
+    }
+
+    /// Restarts the prefetching of a deserialized corpus, see [`CachedOnDiskCorpus::resume_prefetch`]
+    pub fn resume_prefetch(&mut self)
+    where
+        I: Send + 'static,
+    {
+        self.inner.resume_prefetch();
+    }
+
+    /// The [`InputPrefetcher`] of this corpus
+    #[must_use]
+    pub fn prefetcher(&self) -> &InputPrefetcher<I> {
+        self.inner.prefetcher()
+    }
+
     /// Path to the corpus directory associated with this corpus
     pub fn dir_path(&self) -> &PathBuf {
         &self.dir_path
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use std::{fs, thread, time::Duration};
+
+    use libafl_bolts::{rands::StdRand, tuples::tuple_list};
+
+    use crate::{
+        corpus::{Corpus, InMemoryCorpus, OnDiskCorpus, Testcase},
+        events::NopEventManager,
+        executors::{ExitKind, InProcessExecutor},
+        feedbacks::ConstFeedback,
+        fuzzer::Fuzzer,
+        inputs::BytesInput,
+        schedulers::QueueScheduler,
+        state::{HasCorpus, StdState},
+        StdFuzzer,
+    };
+
+    #[test]
+    fn test_ondisk_prefetch() {
+        let dir = "target/.test/ondisk_prefetch";
+        let _ = fs::remove_dir_all(dir);
+        let mut corpus = OnDiskCorpus::<BytesInput>::no_meta(dir)
+            .unwrap()
+            .with_prefetch(2);
+        let ids: Vec<_> = (0..3)
+            .map(|i| {
+                corpus
+                    .add(Testcase::new(BytesInput::new(vec![i; 4])))
+                    .unwrap()
+            })
+            .collect();
+        let path = corpus
+            .inner
+            .inner()
+            .get(ids[1])
+            .unwrap()
+            .borrow()
+            .file_path()
+            .clone()
+            .unwrap();
+
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            corpus,
+            InMemoryCorpus::new(),
+            &mut feedback,
+            &mut objective,
+        )
+        .unwrap();
+        let mut mgr = NopEventManager::new();
+        let mut fuzzer = StdFuzzer::<_, _, _, ()>::new(QueueScheduler::new(), feedback, objective);
+        let mut harness = |_input: &BytesInput| ExitKind::Ok;
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut mgr,
+        )
+        .unwrap();
+
+        // Scheduling the first testcase prefetches the ones the scheduler picks next
+        let idx = fuzzer
+            .fuzz_one(&mut tuple_list!(), &mut executor, &mut state, &mut mgr)
+            .unwrap();
+        assert_eq!(idx, ids[0]);
+        for _ in 0..500 {
+            if state.corpus().prefetcher().is_prefetched(&path) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(state.corpus().prefetcher().is_prefetched(&path));
+
+        // Without the file, the input of the next testcase can only come from the prefetched inputs
+        fs::remove_file(&path).unwrap();
+        let idx = fuzzer
+            .fuzz_one(&mut tuple_list!(), &mut executor, &mut state, &mut mgr)
+            .unwrap();
+        assert_eq!(idx, ids[1]);
+        assert_eq!(
+            state.corpus().cloned_input_for_id(idx).unwrap(),
+            BytesInput::new(vec![1; 4])
+        );
+        assert_eq!(state.corpus().prefetcher().hits(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,346 @@
+//! The [`InputPrefetcher`] loads the inputs of on-disk testcases in the background,
+//! before they are needed, to hide the disk latency of the [`crate::corpus::CachedOnDiskCorpus`]
+//! and the [`crate::corpus::OnDiskCorpus`].
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{cell::Cell, fmt};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Sender},
+        Mutex,
+    },
+    thread,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{corpus::OnDiskMetadataFormat, inputs::Input, Error};
+
+/// The inputs shared with the background worker of an [`InputPrefetcher`]
+#[derive(Debug)]
+struct Prefetched<I> {
+    /// The prefetched inputs, by the path they were loaded from
+    inputs: HashMap<PathBuf, I>,
+    /// The paths still waiting to be loaded, with the generation of their request
+    pending: HashMap<PathBuf, u64>,
+    /// The generation of the next request
+    generation: u64,
+}
+
+impl<I> Default for Prefetched<I> {
+    fn default() -> Self {
+        Self {
+            inputs: HashMap::new(),
+            pending: HashMap::new(),
+            generation: 0,
+        }
+    }
+}
+
+/// The settings of an [`InputPrefetcher`], the only part of it that gets serialized
+#[derive(Debug, Serialize, Deserialize)]
+struct PrefetchSettings {
+    /// The number of testcases to load ahead, `None` if prefetching is disabled
+    count: Option<usize>,
+    /// The format the inputs are stored in
+    format: Option<OnDiskMetadataFormat>,
+}
+
+/// Loads the inputs of testcases in a background thread, and keeps them until they are needed.
+///
+/// A single worker thread loads the inputs, in the order they were requested, and exits with the last clone of the prefetcher.
+/// If the prediction of the next testcases was wrong, the prefetched inputs are simply not used,
+/// and get dropped once too many of them piled up.
+///
+/// Only the settings are serialized. A deserialized prefetcher, for example after a restart,
+/// does not prefetch until it is resumed with [`InputPrefetcher::resume`], since only then the inputs are known to be [`Send`].
+pub struct InputPrefetcher<I> {
+    /// The prefetched and pending inputs
+    prefetched: Arc<Mutex<Prefetched<I>>>,
+    /// Sends the paths to load to the worker, `None` if prefetching is disabled or not resumed yet
+    worker: Option<Sender<(PathBuf, u64)>>,
+    /// The number of testcases to load ahead, `0` if prefetching is disabled
+    count: usize,
+    /// The format the inputs are stored in, `None` for raw inputs
+    format: Option<OnDiskMetadataFormat>,
+    /// The number of inputs that were taken from the prefetched inputs, instead of being loaded from disk
+    hits: Cell<usize>,
+}
+
+impl<I> InputPrefetcher<I>
+where
+    I: Input,
+{
+    /// Creates a new [`InputPrefetcher`], loading `count` testcases ahead,
+    /// stored as raw inputs, or in the given [`OnDiskMetadataFormat`]
+    #[must_use]
+    pub fn new(count: usize, format: Option<OnDiskMetadataFormat>) -> Self
+    where
+        I: Send + 'static,
+    {
+        let prefetched: Arc<Mutex<Prefetched<I>>> = Arc::default();
+        let shared = prefetched.clone();
+        let (worker, requests) = mpsc::channel::<(PathBuf, u64)>();
+        thread::spawn(move || {
+            for (path, generation) in requests {
+                if shared.lock().unwrap().pending.get(&path) != Some(&generation) {
+                    // Taken or forgotten in the meantime
+                    continue;
+                }
+                let input = match format {
+                    Some(format) => fs::read(&path)
+                        .map_err(Error::from)
+                        .and_then(|bytes| format.deserialize(&bytes)),
+                    None => I::from_file(&path),
+                };
+                let mut prefetched = shared.lock().unwrap();
+                // Only keep the input if it was not taken, forgotten, or requested again while loading
+                if prefetched.pending.get(&path) == Some(&generation) {
+                    prefetched.pending.remove(&path);
+                    // Entries that can't be loaded are loaded (and fail) again, when they are needed
+                    if let Ok(input) = input {
+                        prefetched.inputs.insert(path, input);
+                    }
+                }
+            }
+        });
+        Self {
+            prefetched,
+            worker: Some(worker),
+            count,
+            format,
+            hits: Cell::new(0),
+        }
+    }
+
+    /// Starts the worker of a deserialized prefetcher again, if prefetching was enabled before
+    pub fn resume(&mut self)
+    where
+        I: Send + 'static,
+    {
+        if self.worker.is_none() && self.count > 0 {
+            *self = Self::new(self.count, self.format);
+        }
+    }
+
+    /// The number of testcases to load ahead, `0` if prefetching is disabled or not resumed yet
+    #[must_use]
+    pub fn count(&self) -> usize {
+        if self.worker.is_some() {
+            self.count
+        } else {
+            0
+        }
+    }
+
+    /// The number of inputs that were taken from the prefetched inputs, instead of being loaded from disk
+    #[must_use]
+    pub fn hits(&self) -> usize {
+        self.hits.get()
+    }
+
+    /// If the input at the given path was already prefetched
+    #[must_use]
+    pub fn is_prefetched(&self, path: &Path) -> bool {
+        self.prefetched.lock().unwrap().inputs.contains_key(path)
+    }
+
+    /// Queues the inputs at the given paths to be loaded in the background
+    pub fn prefetch(&self, paths: Vec<PathBuf>) {
+        let Some(worker) = &self.worker else {
+            return;
+        };
+        let mut prefetched = self.prefetched.lock().unwrap();
+        // The predictions were wrong for a while, drop the unused inputs
+        if prefetched.inputs.len() > 4 * self.count.max(1) {
+            prefetched.inputs.clear();
+        }
+        for path in paths {
+            if prefetched.inputs.contains_key(&path) || prefetched.pending.contains_key(&path) {
+                continue;
+            }
+            prefetched.generation += 1;
+            let generation = prefetched.generation;
+            prefetched.pending.insert(path.clone(), generation);
+            if worker.send((path, generation)).is_err() {
+                // The worker is gone, the inputs are loaded from disk when they are needed
+                prefetched.pending.clear();
+                return;
+            }
+        }
+    }
+
+    /// Takes the prefetched input loaded from the given path, if any.
+    /// If it is still being loaded, the worker drops it.
+    pub fn take(&self, path: &Path) -> Option<I> {
+        let mut prefetched = self.prefetched.lock().unwrap();
+        prefetched.pending.remove(path);
+        let input = prefetched.inputs.remove(path);
+        if input.is_some() {
+            self.hits.set(self.hits.get() + 1);
+        }
+        input
+    }
+
+    /// Drops the prefetched input loaded from the given path, for example because the file changed.
+    /// If it is still being loaded, the worker drops it, too.
+    pub fn forget(&self, path: &Path) {
+        let mut prefetched = self.prefetched.lock().unwrap();
+        prefetched.pending.remove(path);
+        prefetched.inputs.remove(path);
+    }
+}
+
+impl<I> Default for InputPrefetcher<I> {
+    /// An [`InputPrefetcher`] that does not prefetch anything
+    fn default() -> Self {
+        Self {
+            prefetched: Arc::default(),
+            worker: None,
+            count: 0,
+            format: None,
+            hits: Cell::new(0),
+        }
+    }
+}
+
+impl<I> Clone for InputPrefetcher<I> {
+    fn clone(&self) -> Self {
+        Self {
+            prefetched: self.prefetched.clone(),
+            worker: self.worker.clone(),
+            count: self.count,
+            format: self.format,
+            hits: self.hits.clone(),
+        }
+    }
+}
+
+impl<I> Serialize for InputPrefetcher<I> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        PrefetchSettings {
+            count: (self.count > 0).then_some(self.count),
+            format: self.format,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, I> Deserialize<'de> for InputPrefetcher<I> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let settings = PrefetchSettings::deserialize(deserializer)?;
+        Ok(Self {
+            count: settings.count.unwrap_or(0),
+            format: settings.format,
+            ..Self::default()
+        })
+    }
+}
+
+impl<I> fmt::Debug for InputPrefetcher<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InputPrefetcher")
+            .field("enabled", &self.worker.is_some())
+            .field("count", &self.count)
+            .field("hits", &self.hits.get())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use std::{fs, path::PathBuf, thread, time::Duration};
+
+    use crate::{
+        corpus::{CachedOnDiskCorpus, Corpus, InputPrefetcher, Testcase},
+        inputs::BytesInput,
+    };
+
+    #[test]
+    fn test_forget_pending() {
+        let dir = "target/.test/prefetch_forget";
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        let forgotten = PathBuf::from(format!("{dir}/forgotten"));
+        let kept = PathBuf::from(format!("{dir}/kept"));
+        fs::write(&forgotten, [0; 4]).unwrap();
+        fs::write(&kept, [1; 4]).unwrap();
+
+        let prefetcher = InputPrefetcher::<BytesInput>::new(2, None);
+        prefetcher.prefetch(vec![forgotten.clone()]);
+        prefetcher.forget(&forgotten);
+        prefetcher.prefetch(vec![kept.clone()]);
+        // The worker loads in order, once the second input is there, it is done with the forgotten one
+        for _ in 0..500 {
+            if prefetcher.is_prefetched(&kept) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(prefetcher.is_prefetched(&kept));
+        assert!(!prefetcher.is_prefetched(&forgotten));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_prefetch_serde_roundtrip() {
+        let dir = "target/.test/prefetch_serde";
+        let _ = fs::remove_dir_all(dir);
+        let mut corpus = CachedOnDiskCorpus::<BytesInput>::no_meta(dir, 1)
+            .unwrap()
+            .with_prefetch(2);
+        let first = corpus
+            .add(Testcase::new(BytesInput::new(vec![0; 4])))
+            .unwrap();
+        let second = corpus
+            .add(Testcase::new(BytesInput::new(vec![1; 4])))
+            .unwrap();
+        // Only the second input stays in the cache
+        corpus.get(second).unwrap();
+
+        let mut corpus: CachedOnDiskCorpus<BytesInput> =
+            postcard::from_bytes(&postcard::to_allocvec(&corpus).unwrap()).unwrap();
+        // Only prefetches again once resumed
+        assert_eq!(corpus.prefetch_count(), 0);
+        corpus.resume_prefetch();
+        assert_eq!(corpus.prefetch_count(), 2);
+
+        let path = corpus
+            .inner()
+            .get(first)
+            .unwrap()
+            .borrow()
+            .file_path()
+            .clone()
+            .unwrap();
+        corpus.prefetch(&[first]);
+        for _ in 0..500 {
+            if corpus.prefetcher().is_prefetched(&path) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(corpus.prefetcher().is_prefetched(&path));
+
+        // A disabled prefetcher stays disabled
+        let mut disabled: InputPrefetcher<BytesInput> = postcard::from_bytes(
+            &postcard::to_allocvec(&InputPrefetcher::<BytesInput>::default()).unwrap(),
+        )
+        .unwrap();
+        disabled.resume();
+        assert_eq!(disabled.count(), 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+//! A [`CorpusSnapshot`] records which inputs a corpus held at some point in time.
+//!
+//! Comparing two snapshots, for example taken an hour apart, shows what the fuzzer added, or removed, in between.
+
+use alloc::collections::BTreeSet;
+
+use libafl_bolts::hash_std;
+use serde::{Deserialize, Serialize};
+
+use crate::{corpus::Corpus, inputs::Input, Error};
+
+/// The hashes of all inputs in a corpus, at the time the snapshot was taken
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorpusSnapshot {
+    hashes: BTreeSet<u64>,
+}
+
+impl CorpusSnapshot {
+    /// Takes a snapshot of all enabled entries of the `corpus`, loading their inputs if needed
+    pub fn new<C>(corpus: &C) -> Result<Self, Error>
+    where
+        C: Corpus,
+    {
+        let mut hashes = BTreeSet::new();
+        for id in corpus.ids() {
+            hashes.insert(Self::input_hash(&corpus.cloned_input_for_id(id)?)?);
+        }
+        Ok(Self { hashes })
+    }
+
+    /// The hash identifying the `input` in a snapshot
+    pub fn input_hash<I>(input: &I) -> Result<u64, Error>
+    where
+        I: Input,
+    {
+        Ok(hash_std(&postcard::to_allocvec(input)?))
+    }
+
+    /// The hashes of all inputs in this snapshot
+    #[must_use]
+    pub fn hashes(&self) -> &BTreeSet<u64> {
+        &self.hashes
+    }
+
+    /// If the snapshot contains the input with the given hash, see [`CorpusSnapshot::input_hash`]
+    #[must_use]
+    pub fn contains(&self, hash: u64) -> bool {
+        self.hashes.contains(&hash)
+    }
+
+    /// The number of distinct inputs in this snapshot
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// If this snapshot is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// The inputs added and removed between this snapshot and the `later` one
+    #[must_use]
+    pub fn diff(&self, later: &CorpusSnapshot) -> CorpusSnapshotDiff {
+        CorpusSnapshotDiff {
+            added: later.hashes.difference(&self.hashes).copied().collect(),
+            removed: self.hashes.difference(&later.hashes).copied().collect(),
+        }
+    }
+}
+
+/// The difference between two [`CorpusSnapshot`]s, as hashes of the inputs
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorpusSnapshotDiff {
+    /// The inputs only in the later snapshot
+    pub added: BTreeSet<u64>,
+    /// The inputs only in the earlier snapshot
+    pub removed: BTreeSet<u64>,
+}
+
+impl CorpusSnapshotDiff {
+    /// If the corpus did not change between the two snapshots
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        corpus::{Corpus, CorpusSnapshot, InMemoryCorpus, Testcase},
+        inputs::BytesInput,
+    };
+
+    #[test]
+    fn test_corpus_snapshot_diff() {
+        let mut corpus = InMemoryCorpus::<BytesInput>::new();
+        let first = corpus
+            .add(Testcase::new(BytesInput::new(b"first".to_vec())))
+            .unwrap();
+        let before = CorpusSnapshot::new(&corpus).unwrap();
+        assert!(before.diff(&before).is_empty());
+
+        let new_input = BytesInput::new(b"second".to_vec());
+        corpus.add(Testcase::new(new_input.clone())).unwrap();
+        let after = CorpusSnapshot::new(&corpus).unwrap();
+        assert_eq!(after.len(), 2);
+
+        let diff = before.diff(&after);
+        let new_hash = CorpusSnapshot::input_hash(&new_input).unwrap();
+        assert_eq!(diff.added.iter().copied().collect::<Vec<_>>(), [new_hash]);
+        assert!(diff.removed.is_empty());
+
+        // Removals show up the other way around
+        corpus.remove(first).unwrap();
+        let removed = after.diff(&CorpusSnapshot::new(&corpus).unwrap());
+        assert!(removed.added.is_empty());
+        assert_eq!(removed.removed.len(), 1);
+        assert!(before.hashes().is_superset(&removed.removed));
+    }
+}
@@ -0,0 +1,202 @@
+//! Per-testcase statistics of a [`Corpus`], exported as a table for offline analysis and plotting,
+//! see [`Corpus::export_stats`].
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt::Write, time::Duration};
+use std::path::Path;
+
+use libafl_bolts::{fs::write_file_atomic, HasLen};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::{Corpus, CorpusId},
+    feedbacks::MapIndexesMetadata,
+    Error, HasMetadata,
+};
+
+/// The file format of [`Corpus::export_stats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatsFormat {
+    /// Comma-separated values, with a header line, and empty fields for unknown values
+    Csv,
+    /// A JSON array with one object per testcase
+    Json,
+}
+
+/// The statistics of one testcase, a row of [`Corpus::export_stats`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestcaseStats {
+    /// The [`CorpusId`] of the testcase
+    pub id: CorpusId,
+    /// The length of the input
+    pub size: usize,
+    /// The execution time, in microseconds
+    pub exec_time_us: Option<u64>,
+    /// The depth of the testcase, see [`crate::corpus::Testcase::depth`]
+    pub depth: Option<u64>,
+    /// The fitness of the testcase, see [`crate::corpus::Testcase::fitness`]
+    pub fitness: Option<f64>,
+    /// The number of covered map entries, if the testcase has a [`MapIndexesMetadata`]
+    pub covered_edges: Option<usize>,
+    /// The time the testcase was discovered, in milliseconds since the start of the fuzzer,
+    /// see [`crate::state::HasStartTime::start_time`]
+    pub added_time_ms: Option<u64>,
+}
+
+/// The header line of the CSV format
+const CSV_HEADER: &str = "id,size,exec_time_us,depth,fitness,covered_edges,added_time_ms";
+
+impl TestcaseStats {
+    /// This row in the CSV format, without the line break
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        fn field<T: ToString>(value: Option<T>) -> String {
+            value.map(|value| value.to_string()).unwrap_or_default()
+        }
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.id,
+            self.size,
+            field(self.exec_time_us),
+            field(self.depth),
+            field(self.fitness),
+            field(self.covered_edges),
+            field(self.added_time_ms)
+        )
+    }
+}
+
+/// Saturates durations that do not fit into the `u64` of the table
+fn saturating_u64(value: u128) -> u64 {
+    u64::try_from(value).unwrap_or(u64::MAX)
+}
+
+/// Collects the [`TestcaseStats`] of all enabled testcases of the `corpus`, in insertion order.
+/// The times the testcases were added are relative to the `start_time` of the fuzzer.
+pub fn corpus_stats<C>(corpus: &C, start_time: Duration) -> Result<Vec<TestcaseStats>, Error>
+where
+    C: Corpus,
+    C::Input: HasLen,
+{
+    let mut stats = Vec::with_capacity(corpus.count());
+    for id in corpus.ids() {
+        let mut testcase = corpus.get(id)?.borrow_mut();
+        let size = testcase.load_len(corpus)?;
+        stats.push(TestcaseStats {
+            id,
+            size,
+            exec_time_us: testcase
+                .exec_time()
+                .map(|time| saturating_u64(time.as_micros())),
+            depth: testcase.depth(),
+            fitness: testcase.fitness(),
+            covered_edges: testcase
+                .metadata::<MapIndexesMetadata>()
+                .ok()
+                .map(|meta| meta.list.len()),
+            added_time_ms: testcase
+                .added_time()
+                .map(|time| saturating_u64(time.saturating_sub(start_time).as_millis())),
+        });
+    }
+    Ok(stats)
+}
+
+/// Writes the `stats` to the file at `path`, in the given `format`
+pub fn write_stats<P>(stats: &[TestcaseStats], path: P, format: StatsFormat) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    let bytes = match format {
+        StatsFormat::Csv => {
+            let mut csv = String::from(CSV_HEADER);
+            csv.push('\n');
+            for row in stats {
+                writeln!(csv, "{}", row.to_csv()).unwrap();
+            }
+            csv.into_bytes()
+        }
+        StatsFormat::Json => serde_json::to_vec_pretty(stats)?,
+    };
+    write_file_atomic(path, &bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::time::Duration;
+    use std::fs;
+
+    use crate::{
+        corpus::{
+            stats::{corpus_stats, StatsFormat, TestcaseStats, CSV_HEADER},
+            Corpus, InMemoryCorpus, SchedulerTestcaseMetadata, Testcase,
+        },
+        feedbacks::MapIndexesMetadata,
+        inputs::BytesInput,
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_export_stats() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapIndexesMetadata::register();
+        }
+
+        let mut corpus = InMemoryCorpus::<BytesInput>::new();
+        let mut testcase = Testcase::new(BytesInput::new(vec![0; 4]));
+        testcase.set_exec_time(Duration::from_micros(1500));
+        testcase.set_scheduler_metadata(SchedulerTestcaseMetadata::new(2));
+        testcase.set_fitness(0.75);
+        // Added times are absolute, like the start time
+        let start_time = Duration::from_secs(1_700_000_000);
+        testcase.set_added_time(start_time + Duration::from_millis(12_345));
+        testcase.add_metadata(MapIndexesMetadata::new(vec![1, 5, 9]));
+        corpus.add(testcase).unwrap();
+        // A testcase we know nothing about, but its size
+        corpus
+            .add(Testcase::new(BytesInput::new(vec![0; 16])))
+            .unwrap();
+
+        let expected = corpus_stats(&corpus, start_time).unwrap();
+        assert_eq!(expected[0].exec_time_us, Some(1500));
+        assert_eq!(expected[0].added_time_ms, Some(12_345));
+        assert_eq!(expected[0].covered_edges, Some(3));
+        assert_eq!(expected[1].size, 16);
+        assert_eq!(expected[1].fitness, None);
+
+        fs::create_dir_all("target/.test").unwrap();
+
+        let csv_path = "target/.test/corpus_stats.csv";
+        corpus
+            .export_stats(csv_path, StatsFormat::Csv, start_time)
+            .unwrap();
+        let csv = fs::read_to_string(csv_path).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        assert_eq!(
+            rows,
+            [
+                vec!["0", "4", "1500", "2", "0.75", "3", "12345"],
+                vec!["1", "16", "", "", "", "", ""]
+            ]
+        );
+        fs::remove_file(csv_path).unwrap();
+
+        let json_path = "target/.test/corpus_stats.json";
+        corpus
+            .export_stats(json_path, StatsFormat::Json, start_time)
+            .unwrap();
+        let parsed: Vec<TestcaseStats> =
+            serde_json::from_slice(&fs::read(json_path).unwrap()).unwrap();
+        assert_eq!(parsed, expected);
+        fs::remove_file(json_path).unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+//! The [`TeeCorpus`] writes each new [`Testcase`] to two corpora, for example a fast in-memory one,
+//! and a durable on-disk one.
+
+use core::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::{Corpus, CorpusId, HasTestcase, Testcase},
+    inputs::UsesInput,
+    Error,
+};
+
+/// A corpus that adds, replaces, and removes [`Testcase`]s in both a `primary` and a `secondary` corpus.
+/// All reads, including the scheduling position, are served by the `primary` corpus.
+///
+/// Both corpora need to assign the same ids, so they should both be empty when the [`TeeCorpus`] gets created.
+/// Changes to a [`Testcase`] after it was added, such as new metadata, only reach the `primary` corpus.
+#[derive(Default, Serialize, Deserialize, Clone, Debug)]
+pub struct TeeCorpus<A, B> {
+    primary: A,
+    secondary: B,
+}
+
+impl<A, B> UsesInput for TeeCorpus<A, B>
+where
+    A: UsesInput,
+{
+    type Input = A::Input;
+}
+
+impl<A, B> TeeCorpus<A, B>
+where
+    A: Corpus,
+    B: Corpus<Input = A::Input>,
+{
+    /// Creates a new [`TeeCorpus`], serving reads from `primary`
+    pub fn new(primary: A, secondary: B) -> Result<Self, Error> {
+        if primary.count_all() != 0 || secondary.count_all() != 0 {
+            return Err(Error::illegal_argument(
+                "The corpora of a TeeCorpus must be empty on creation",
+            ));
+        }
+        Ok(Self { primary, secondary })
+    }
+
+    /// The primary corpus, serving all reads
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    /// The secondary corpus, only receiving writes
+    pub fn secondary(&self) -> &B {
+        &self.secondary
+    }
+
+    /// Makes sure both corpora assigned the same id
+    fn check_ids(primary: CorpusId, secondary: CorpusId) -> Result<CorpusId, Error> {
+        if primary == secondary {
+            Ok(primary)
+        } else {
+            Err(Error::illegal_state(format!(
+                "TeeCorpus out of sync: the primary corpus added {primary}, the secondary {secondary}"
+            )))
+        }
+    }
+}
+
+impl<A, B> Corpus for TeeCorpus<A, B>
+where
+    A: Corpus,
+    B: Corpus<Input = A::Input>,
+{
+    /// Returns the number of all enabled entries
+    #[inline]
+    fn count(&self) -> usize {
+        self.primary.count()
+    }
+
+    /// Returns the number of all disabled entries
+    #[inline]
+    fn count_disabled(&self) -> usize {
+        self.primary.count_disabled()
+    }
+
+    /// Returns the number of elements including disabled entries
+    #[inline]
+    fn count_all(&self) -> usize {
+        self.primary.count_all()
+    }
+
+    /// Add an enabled testcase to both corpora and return its index
+    fn add(&mut self, testcase: Testcase<Self::Input>) -> Result<CorpusId, Error> {
+        let secondary = self.secondary.add(testcase.clone())?;
+        let primary = self.primary.add(testcase)?;
+        Self::check_ids(primary, secondary)
+    }
+
+    /// Add a disabled testcase to both corpora and return its index
+    fn add_disabled(&mut self, testcase: Testcase<Self::Input>) -> Result<CorpusId, Error> {
+        let secondary = self.secondary.add_disabled(testcase.clone())?;
+        let primary = self.primary.add_disabled(testcase)?;
+        Self::check_ids(primary, secondary)
+    }
+
+    /// Replaces the testcase at the given idx in both corpora, returning the previous one of the primary corpus
+    fn replace(
+        &mut self,
+        idx: CorpusId,
+        testcase: Testcase<Self::Input>,
+    ) -> Result<Testcase<Self::Input>, Error> {
+        self.secondary.replace(idx, testcase.clone())?;
+        self.primary.replace(idx, testcase)
+    }
+
+    /// Removes an entry from both corpora, returning the one of the primary corpus
+    fn remove(&mut self, idx: CorpusId) -> Result<Testcase<Self::Input>, Error> {
+        self.secondary.remove(idx)?;
+        self.primary.remove(idx)
+    }
+
+    /// Get by id; considers only enabled testcases
+    #[inline]
+    fn get(&self, idx: CorpusId) -> Result<&RefCell<Testcase<Self::Input>>, Error> {
+        self.primary.get(idx)
+    }
+
+    /// Get by id; considers both enabled and disabled testcases
+    #[inline]
+    fn get_from_all(&self, idx: CorpusId) -> Result<&RefCell<Testcase<Self::Input>>, Error> {
+        self.primary.get_from_all(idx)
+    }
+
+    /// Current testcase scheduled
+    #[inline]
+    fn current(&self) -> &Option<CorpusId> {
+        self.primary.current()
+    }
+
+    /// Current testcase scheduled (mutable)
+    #[inline]
+    fn current_mut(&mut self) -> &mut Option<CorpusId> {
+        self.primary.current_mut()
+    }
+
+    #[inline]
+    fn next(&self, idx: CorpusId) -> Option<CorpusId> {
+        self.primary.next(idx)
+    }
+
+    #[inline]
+    fn prev(&self, idx: CorpusId) -> Option<CorpusId> {
+        self.primary.prev(idx)
+    }
+
+    #[inline]
+    fn first(&self) -> Option<CorpusId> {
+        self.primary.first()
+    }
+
+    #[inline]
+    fn last(&self) -> Option<CorpusId> {
+        self.primary.last()
+    }
+
+    /// Get the nth corpus id; considers both enabled and disabled testcases
+    #[inline]
+    fn nth_from_all(&self, nth: usize) -> CorpusId {
+        self.primary.nth_from_all(nth)
+    }
+
+    #[inline]
+    fn load_input_into(&self, testcase: &mut Testcase<Self::Input>) -> Result<(), Error> {
+        self.primary.load_input_into(testcase)
+    }
+
+    #[inline]
+    fn store_input_from(&self, testcase: &Testcase<Self::Input>) -> Result<(), Error> {
+        self.primary.store_input_from(testcase)
+    }
+
+    /// Flushes both corpora
+    #[inline]
+    fn flush(&mut self) -> Result<(), Error> {
+        self.primary.flush()?;
+        self.secondary.flush()
+    }
+
+    #[inline]
+    fn prefetch_count(&self) -> usize {
+        self.primary.prefetch_count()
+    }
+
+    #[inline]
+    fn prefetch(&self, ids: &[CorpusId]) {
+        self.primary.prefetch(ids);
+    }
+}
+
+impl<A, B> HasTestcase for TeeCorpus<A, B>
+where
+    A: Corpus,
+    B: Corpus<Input = A::Input>,
+{
+    fn testcase(&self, id: CorpusId) -> Result<core::cell::Ref<Testcase<Self::Input>>, Error> {
+        Ok(self.get(id)?.borrow())
+    }
+
+    fn testcase_mut(
+        &self,
+        id: CorpusId,
+    ) -> Result<core::cell::RefMut<Testcase<Self::Input>>, Error> {
+        Ok(self.get(id)?.borrow_mut())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use crate::{
+        corpus::{tee::TeeCorpus, Corpus, InMemoryCorpus, OnDiskCorpus, Testcase},
+        inputs::BytesInput,
+    };
+
+    #[test]
+    fn test_tee_corpus() {
+        let dir = PathBuf::from("target/.test/tee_corpus");
+        let mut corpus = TeeCorpus::new(
+            InMemoryCorpus::<BytesInput>::new(),
+            OnDiskCorpus::<BytesInput>::no_meta(&dir).unwrap(),
+        )
+        .unwrap();
+
+        let id = corpus
+            .add(Testcase::new(BytesInput::new(b"tee".to_vec())))
+            .unwrap();
+        assert_eq!(corpus.count(), 1);
+        assert_eq!(
+            corpus.primary().cloned_input_for_id(id).unwrap(),
+            BytesInput::new(b"tee".to_vec())
+        );
+        assert_eq!(
+            corpus.secondary().cloned_input_for_id(id).unwrap(),
+            BytesInput::new(b"tee".to_vec())
+        );
+        let file = corpus
+            .secondary()
+            .get(id)
+            .unwrap()
+            .borrow()
+            .file_path()
+            .clone()
+            .unwrap();
+        assert!(file.exists());
+
+        // Removal reaches both corpora
+        corpus.remove(id).unwrap();
+        assert_eq!(corpus.primary().count(), 0);
+        assert_eq!(corpus.secondary().count(), 0);
+        assert!(!file.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,20 +1,28 @@
 //! The testcase is a struct embedded in each corpus.
 //! It will contain a respective input, and metadata.
 
-use alloc::string::String;
+use alloc::{boxed::Box, string::String, vec::Vec};
 use core::{
+    any::{type_name, Any},
     cell::{Ref, RefMut},
     time::Duration,
 };
 #[cfg(feature = "std")]
 use std::path::PathBuf;
 
-use libafl_bolts::{serdeany::SerdeAnyMap, HasLen};
+use libafl_bolts::{
+    serdeany::{SerdeAny, SerdeAnyMap},
+    ClientId, HasLen,
+};
 use serde::{Deserialize, Serialize};
 
 use super::Corpus;
+#[cfg(feature = "std")]
+use crate::corpus::ondisk::OnDiskMetadataFormat;
 use crate::{
     corpus::CorpusId,
+    executors::ExitKind,
+    feedbacks::MapNoveltiesMetadata,
     inputs::{Input, UsesInput},
     Error, HasMetadata,
 };
@@ -34,7 +42,64 @@ pub trait HasTestcase: UsesInput {
     ) -> Result<RefMut<Testcase<<Self as UsesInput>::Input>>, Error>;
 }
 
+/// The kind of a solution, so triage can tell crashes, timeouts, and other objectives apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ObjectiveKind {
+    /// The target crashed
+    Crash,
+    /// The target timed out
+    Timeout,
+    /// The target ran out of memory
+    Oom,
+    /// The target exited normally, but the objective still flagged the run, e.g., on a failed logic assertion
+    Assertion,
+    /// The exit kinds of the executors of a [`crate::executors::DiffExecutor`] differed
+    Diff,
+}
+
+impl From<ExitKind> for ObjectiveKind {
+    fn from(exit_kind: ExitKind) -> Self {
+        match exit_kind {
+            ExitKind::Crash => Self::Crash,
+            ExitKind::Timeout => Self::Timeout,
+            ExitKind::Oom => Self::Oom,
+            ExitKind::Ok => Self::Assertion,
+            ExitKind::Diff { .. } => Self::Diff,
+        }
+    }
+}
+
+/// A [`postcard`] flavor that only counts the serialized bytes, without storing them
+#[derive(Debug, Default)]
+struct ByteCounter {
+    count: usize,
+}
+
+impl postcard::ser_flavors::Flavor for ByteCounter {
+    type Output = usize;
+
+    #[inline]
+    fn try_push(&mut self, _data: u8) -> postcard::Result<()> {
+        self.count += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn try_extend(&mut self, data: &[u8]) -> postcard::Result<()> {
+        self.count += data.len();
+        Ok(())
+    }
+
+    #[inline]
+    fn finalize(self) -> postcard::Result<usize> {
+        Ok(self.count)
+    }
+}
+
 /// An entry in the Testcase Corpus
+///
+/// Adding fields changes the serialized layout, breaking testcases and states serialized with `postcard` by older versions,
+/// note them in the migration guide.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(bound = "I: serde::de::DeserializeOwned")]
 pub struct Testcase<I>
@@ -65,6 +130,26 @@ where
     parent_id: Option<CorpusId>,
     /// If the testcase is "disabled"
     disabled: bool,
+    /// If the testcase was scheduled for fuzzing at least once
+    fuzzed: bool,
+    /// The time this testcase was added to the corpus, if known
+    added_time: Option<Duration>,
+    /// The client that found this testcase, `ClientId(0)` for testcases found locally
+    sender_id: ClientId,
+    /// The metadata of power schedules, inlined instead of boxed in the metadata map, since nearly every testcase has one
+    scheduler_metadata: Option<SchedulerTestcaseMetadata>,
+    /// The map indexes this testcase was the first to cover, inlined like the `scheduler_metadata`
+    novelties: Option<Vec<usize>>,
+    /// The scalar score of this testcase, as computed by a scoring feedback, such as the [`crate::feedbacks::WeightedFeedback`]
+    fitness: Option<f64>,
+    /// The kind of solution, `None` for testcases in the main corpus
+    objective_kind: Option<ObjectiveKind>,
+    /// Number of times this testcase was fuzzed since it last produced an interesting child
+    dry_streak: usize,
+    /// Number of consecutive times fuzzing this testcase failed with an error
+    error_streak: usize,
+    /// If the testcase is quarantined, and should no longer be scheduled
+    quarantined: bool,
 }
 
 impl<I> HasMetadata for Testcase<I>
@@ -82,6 +167,85 @@ where
     fn metadata_map_mut(&mut self) -> &mut SerdeAnyMap {
         &mut self.metadata
     }
+
+    /// Add a metadata to the metadata map.
+    /// A [`SchedulerTestcaseMetadata`] is stored inline instead, see [`Testcase::set_scheduler_metadata`].
+    #[inline]
+    fn add_metadata<M>(&mut self, meta: M)
+    where
+        M: SerdeAny,
+    {
+        let mut meta = Some(meta);
+        match (&mut meta as &mut dyn Any).downcast_mut::<Option<SchedulerTestcaseMetadata>>() {
+            Some(scheduler_meta) => self.scheduler_metadata = scheduler_meta.take(),
+            None => self.metadata.insert(meta.unwrap()),
+        }
+    }
+
+    /// Gets metadata, or inserts it using the given construction function `default`.
+    /// A [`SchedulerTestcaseMetadata`] is stored inline instead, see [`Testcase::set_scheduler_metadata`].
+    #[inline]
+    fn metadata_or_insert_with<M>(&mut self, default: impl FnOnce() -> M) -> &mut M
+    where
+        M: SerdeAny,
+    {
+        match (&mut self.scheduler_metadata as &mut dyn Any).downcast_mut::<Option<M>>() {
+            Some(scheduler_meta) => scheduler_meta.get_or_insert_with(default),
+            None => self.metadata.get_or_insert_with::<M>(default),
+        }
+    }
+
+    /// Remove a metadata, including the inlined [`SchedulerTestcaseMetadata`]
+    #[inline]
+    fn remove_metadata<M>(&mut self) -> Option<Box<M>>
+    where
+        M: SerdeAny,
+    {
+        match (&mut self.scheduler_metadata as &mut dyn Any).downcast_mut::<Option<M>>() {
+            Some(scheduler_meta) => scheduler_meta.take().map(Box::new),
+            None => self.metadata.remove::<M>(),
+        }
+    }
+
+    /// Check for a metadata, including the inlined [`SchedulerTestcaseMetadata`]
+    #[inline]
+    fn has_metadata<M>(&self) -> bool
+    where
+        M: SerdeAny,
+    {
+        self.metadata::<M>().is_ok()
+    }
+
+    /// To get metadata, including the inlined [`SchedulerTestcaseMetadata`]
+    #[inline]
+    fn metadata<M>(&self) -> Result<&M, Error>
+    where
+        M: SerdeAny,
+    {
+        self.scheduler_metadata
+            .as_ref()
+            .and_then(|meta| (meta as &dyn Any).downcast_ref::<M>())
+            .or_else(|| self.metadata.get::<M>())
+            .ok_or_else(|| Error::key_not_found(format!("{} not found", type_name::<M>())))
+    }
+
+    /// To get mutable metadata, including the inlined [`SchedulerTestcaseMetadata`]
+    #[inline]
+    fn metadata_mut<M>(&mut self) -> Result<&mut M, Error>
+    where
+        M: SerdeAny,
+    {
+        if let Some(meta) = self
+            .scheduler_metadata
+            .as_mut()
+            .and_then(|meta| (meta as &mut dyn Any).downcast_mut::<M>())
+        {
+            return Ok(meta);
+        }
+        self.metadata
+            .get_mut::<M>()
+            .ok_or_else(|| Error::key_not_found(format!("{} not found", type_name::<M>())))
+    }
 }
 
 /// Impl of a testcase
@@ -155,6 +319,34 @@ where
         &mut self.metadata_path
     }
 
+    /// Removes all metadata of this testcase, for example before recycling it during a corpus replacement.
+    /// This includes the inlined [`SchedulerTestcaseMetadata`] and novelties.
+    ///
+    /// Use [`HasMetadata::remove_metadata`] to remove a single metadata by type.
+    #[inline]
+    pub fn clear_metadata(&mut self) {
+        self.metadata.clear();
+        self.scheduler_metadata = None;
+        self.novelties = None;
+    }
+
+    /// The number of bytes the input and metadata of this testcase take up, when serialized.
+    /// This includes the inlined [`SchedulerTestcaseMetadata`] and novelties.
+    ///
+    /// The input needs to be loaded, see [`Testcase::load_input`].
+    pub fn disk_size(&self) -> Result<usize, Error> {
+        let input = self
+            .input
+            .as_ref()
+            .ok_or_else(|| Error::empty("The input of this testcase is not loaded"))?;
+        let input_size = postcard::serialize_with_flavor(input, ByteCounter::default())?;
+        let metadata_size = postcard::serialize_with_flavor(
+            &(&self.metadata, &self.scheduler_metadata, &self.novelties),
+            ByteCounter::default(),
+        )?;
+        Ok(input_size + metadata_size)
+    }
+
     /// Get the execution time of the testcase
     #[inline]
     pub fn exec_time(&self) -> &Option<Duration> {
@@ -209,6 +401,169 @@ where
         self.disabled = disabled;
     }
 
+    /// If this testcase was never scheduled for fuzzing
+    #[inline]
+    #[must_use]
+    pub fn never_fuzzed(&self) -> bool {
+        !self.fuzzed
+    }
+
+    /// Marks whether this testcase was scheduled for fuzzing
+    #[inline]
+    pub fn set_fuzzed(&mut self, fuzzed: bool) {
+        self.fuzzed = fuzzed;
+    }
+
+    /// The time this testcase was added to the corpus, if known
+    #[inline]
+    #[must_use]
+    pub fn added_time(&self) -> Option<Duration> {
+        self.added_time
+    }
+
+    /// Sets the time this testcase was added to the corpus
+    #[inline]
+    pub fn set_added_time(&mut self, added_time: Duration) {
+        self.added_time = Some(added_time);
+    }
+
+    /// The client that found this testcase.
+    /// Testcases found locally carry `ClientId(0)`, imported ones the id of the peer that found them.
+    #[inline]
+    #[must_use]
+    pub fn sender_id(&self) -> ClientId {
+        self.sender_id
+    }
+
+    /// Sets the client that found this testcase
+    #[inline]
+    pub fn set_sender_id(&mut self, sender_id: ClientId) {
+        self.sender_id = sender_id;
+    }
+
+    /// The path depth of this testcase, if known
+    #[inline]
+    #[must_use]
+    pub fn depth(&self) -> Option<u64> {
+        self.scheduler_metadata()
+            .ok()
+            .map(SchedulerTestcaseMetadata::depth)
+    }
+
+    /// The [`SchedulerTestcaseMetadata`] of this testcase.
+    ///
+    /// Returns the inlined one, see [`Testcase::set_scheduler_metadata`],
+    /// else falls back to the metadata map, for testcases from older versions.
+    /// Same as `testcase.metadata::<SchedulerTestcaseMetadata>()`.
+    #[inline]
+    pub fn scheduler_metadata(&self) -> Result<&SchedulerTestcaseMetadata, Error> {
+        self.metadata::<SchedulerTestcaseMetadata>()
+    }
+
+    /// The [`SchedulerTestcaseMetadata`] of this testcase (mutable), see [`Testcase::scheduler_metadata`]
+    #[inline]
+    pub fn scheduler_metadata_mut(&mut self) -> Result<&mut SchedulerTestcaseMetadata, Error> {
+        self.metadata_mut::<SchedulerTestcaseMetadata>()
+    }
+
+    /// Stores the [`SchedulerTestcaseMetadata`] of this testcase inline, without an allocation in the metadata map.
+    ///
+    /// [`HasMetadata::add_metadata`], [`HasMetadata::metadata`] and [`HasMetadata::metadata_mut`] use the inlined one as well,
+    /// but it is not part of the [`HasMetadata::metadata_map`].
+    #[inline]
+    pub fn set_scheduler_metadata(&mut self, meta: SchedulerTestcaseMetadata) {
+        self.scheduler_metadata = Some(meta);
+    }
+
+    /// The map indexes this testcase was the first to cover, if known.
+    ///
+    /// Returns the inlined novelties, see [`Testcase::set_novelties`],
+    /// else falls back to the [`MapNoveltiesMetadata`] in the metadata map.
+    #[inline]
+    #[must_use]
+    pub fn novelties(&self) -> Option<&[usize]> {
+        match &self.novelties {
+            Some(novelties) => Some(novelties),
+            None => self
+                .metadata
+                .get::<MapNoveltiesMetadata>()
+                .map(|meta| meta.list.as_slice()),
+        }
+    }
+
+    /// Stores the novelties of this testcase inline, without an allocation in the metadata map
+    #[inline]
+    pub fn set_novelties(&mut self, novelties: Vec<usize>) {
+        self.novelties = Some(novelties);
+    }
+
+    /// The scalar score of this testcase, if a scoring feedback computed one
+    #[inline]
+    #[must_use]
+    pub fn fitness(&self) -> Option<f64> {
+        self.fitness
+    }
+
+    /// Sets the scalar score of this testcase
+    #[inline]
+    pub fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = Some(fitness);
+    }
+
+    /// The kind of solution, if this testcase is a solution
+    #[inline]
+    #[must_use]
+    pub fn objective_kind(&self) -> Option<ObjectiveKind> {
+        self.objective_kind
+    }
+
+    /// Sets the kind of solution, when this testcase is routed to the solutions
+    #[inline]
+    pub fn set_objective_kind(&mut self, objective_kind: ObjectiveKind) {
+        self.objective_kind = Some(objective_kind);
+    }
+
+    /// The number of times this testcase was fuzzed since it last produced an interesting child, updated in `perform_mutational`.
+    ///
+    /// A long dry streak hints at a stagnating testcase, schedulers may deprioritize it.
+    #[inline]
+    #[must_use]
+    pub fn dry_streak(&self) -> usize {
+        self.dry_streak
+    }
+
+    /// Sets the dry streak of this testcase
+    #[inline]
+    pub fn set_dry_streak(&mut self, dry_streak: usize) {
+        self.dry_streak = dry_streak;
+    }
+
+    /// The number of consecutive times fuzzing this testcase failed with an error, updated by the [`crate::StdFuzzer`]
+    #[inline]
+    #[must_use]
+    pub fn error_streak(&self) -> usize {
+        self.error_streak
+    }
+
+    /// Sets the error streak of this testcase
+    #[inline]
+    pub fn set_error_streak(&mut self, error_streak: usize) {
+        self.error_streak = error_streak;
+    }
+
+    /// If this testcase is quarantined, i.e., disabled by the [`crate::schedulers::QuarantineScheduler`]
+    #[inline]
+    #[must_use]
+    pub fn quarantined(&self) -> bool {
+        self.quarantined
+    }
+
+    /// Quarantines this testcase, or lifts the quarantine
+    #[inline]
+    pub fn set_quarantined(&mut self, quarantined: bool) {
+        self.quarantined = quarantined;
+    }
+
     /// Create a new Testcase instance given an input
     #[inline]
     pub fn new(mut input: I) -> Self {
@@ -227,6 +582,16 @@ where
             scheduled_count: 0,
             parent_id: None,
             disabled: false,
+            fuzzed: false,
+            added_time: None,
+            sender_id: ClientId(0),
+            scheduler_metadata: None,
+            novelties: None,
+            fitness: None,
+            objective_kind: None,
+            dry_streak: 0,
+            error_streak: 0,
+            quarantined: false,
         }
     }
 
@@ -248,6 +613,16 @@ where
             scheduled_count: 0,
             parent_id: Some(parent_id),
             disabled: false,
+            fuzzed: false,
+            added_time: None,
+            sender_id: ClientId(0),
+            scheduler_metadata: None,
+            novelties: None,
+            fitness: None,
+            objective_kind: None,
+            dry_streak: 0,
+            error_streak: 0,
+            quarantined: false,
         }
     }
 
@@ -269,6 +644,16 @@ where
             scheduled_count: 0,
             parent_id: None,
             disabled: false,
+            fuzzed: false,
+            added_time: None,
+            sender_id: ClientId(0),
+            scheduler_metadata: None,
+            novelties: None,
+            fitness: None,
+            objective_kind: None,
+            dry_streak: 0,
+            error_streak: 0,
+            quarantined: false,
         }
     }
 
@@ -290,6 +675,16 @@ where
             scheduled_count: 0,
             parent_id: None,
             disabled: false,
+            fuzzed: false,
+            added_time: None,
+            sender_id: ClientId(0),
+            scheduler_metadata: None,
+            novelties: None,
+            fitness: None,
+            objective_kind: None,
+            dry_streak: 0,
+            error_streak: 0,
+            quarantined: false,
         }
     }
 
@@ -331,6 +726,16 @@ where
             #[cfg(feature = "std")]
             metadata_path: None,
             disabled: false,
+            fuzzed: false,
+            added_time: None,
+            sender_id: ClientId(0),
+            scheduler_metadata: None,
+            novelties: None,
+            fitness: None,
+            objective_kind: None,
+            dry_streak: 0,
+            error_streak: 0,
+            quarantined: false,
         }
     }
 }
@@ -367,6 +772,22 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+impl<I> Testcase<I>
+where
+    I: Input,
+{
+    /// Serializes this [`Testcase`], including its input and metadata, in the given `format`
+    pub fn to_bytes(&self, format: OnDiskMetadataFormat) -> Result<Vec<u8>, Error> {
+        format.serialize(self)
+    }
+
+    /// Deserializes a [`Testcase`] from `bytes` in the given `format`
+    pub fn from_bytes(bytes: &[u8], format: OnDiskMetadataFormat) -> Result<Self, Error> {
+        format.deserialize(bytes)
+    }
+}
+
 /// Create a testcase from an input
 impl<I> From<I> for Testcase<I>
 where
@@ -434,7 +855,9 @@ impl SchedulerTestcaseMetadata {
         self.bitmap_size = val;
     }
 
-    /// Get the handicap
+    /// Get the handicap, the number of queue cycles that had elapsed when this entry got calibrated.
+    /// As in AFL, entries discovered late get more energy in [`crate::schedulers::testcase_score::CorpusPowerTestcaseScore`]
+    /// while the handicap is set, which decays each time the entry is scheduled.
     #[inline]
     #[must_use]
     pub fn handicap(&self) -> u64 {
@@ -503,3 +926,194 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use crate::corpus::ondisk::OnDiskMetadataFormat;
+    use crate::{
+        corpus::{SchedulerTestcaseMetadata, Testcase},
+        feedbacks::{MapIndexesMetadata, MapNoveltiesMetadata},
+        inputs::BytesInput,
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_testcase_metadata() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapIndexesMetadata::register();
+            MapNoveltiesMetadata::register();
+        }
+
+        let mut testcase = Testcase::new(BytesInput::new(vec![0x41]));
+        testcase.add_metadata(MapIndexesMetadata::new(vec![1, 2]));
+        testcase.add_metadata(MapNoveltiesMetadata::new(vec![3]));
+
+        let removed = testcase.remove_metadata::<MapIndexesMetadata>().unwrap();
+        assert_eq!(removed.list, vec![1, 2]);
+        assert!(!testcase.has_metadata::<MapIndexesMetadata>());
+        assert_eq!(
+            testcase.metadata::<MapNoveltiesMetadata>().unwrap().list,
+            vec![3]
+        );
+
+        testcase.set_scheduler_metadata(SchedulerTestcaseMetadata::new(1));
+        testcase.set_novelties(vec![4]);
+        testcase.clear_metadata();
+        assert!(testcase.metadata_map().is_empty());
+        assert!(!testcase.has_metadata::<SchedulerTestcaseMetadata>());
+        assert_eq!(testcase.novelties(), None);
+    }
+
+    #[test]
+    fn test_testcase_inline_metadata() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapIndexesMetadata::register();
+            MapNoveltiesMetadata::register();
+        }
+
+        let mut testcase = Testcase::new(BytesInput::new(vec![0x41]));
+        assert_eq!(testcase.depth(), None);
+        assert_eq!(testcase.novelties(), None);
+
+        // Without inlined novelties, the metadata map is used
+        testcase.add_metadata(MapNoveltiesMetadata::new(vec![1]));
+        assert_eq!(testcase.novelties(), Some([1].as_slice()));
+
+        // Adding it as metadata inlines it as well
+        testcase.add_metadata(SchedulerTestcaseMetadata::new(2));
+        assert_eq!(testcase.depth(), Some(2));
+        assert!(testcase
+            .metadata_map()
+            .get::<SchedulerTestcaseMetadata>()
+            .is_none());
+
+        // The other metadata accessors use the inlined metadata as well
+        testcase
+            .metadata_or_insert_with(|| SchedulerTestcaseMetadata::new(4))
+            .set_handicap(1);
+        assert_eq!(testcase.scheduler_metadata().unwrap().handicap(), 1);
+        assert_eq!(
+            testcase
+                .remove_metadata::<SchedulerTestcaseMetadata>()
+                .unwrap()
+                .depth(),
+            2
+        );
+        assert_eq!(testcase.depth(), None);
+        assert_eq!(
+            testcase
+                .metadata_or_insert_with(|| SchedulerTestcaseMetadata::new(4))
+                .depth(),
+            4
+        );
+        assert!(testcase
+            .metadata_map()
+            .get::<SchedulerTestcaseMetadata>()
+            .is_none());
+
+        testcase.set_scheduler_metadata(SchedulerTestcaseMetadata::new(3));
+        testcase.set_novelties(vec![4, 5]);
+        testcase.add_metadata(MapIndexesMetadata::new(vec![6, 7]));
+
+        let restored: Testcase<BytesInput> =
+            postcard::from_bytes(&postcard::to_allocvec(&testcase).unwrap()).unwrap();
+        assert_eq!(restored.depth(), Some(3));
+        assert_eq!(restored.novelties(), Some([4, 5].as_slice()));
+        // The inlined metadata is still found through the metadata accessors, but not in the map
+        assert_eq!(
+            restored
+                .metadata::<SchedulerTestcaseMetadata>()
+                .unwrap()
+                .depth(),
+            3
+        );
+        assert!(restored
+            .metadata_map()
+            .get::<SchedulerTestcaseMetadata>()
+            .is_none());
+        // Rare types still go to the metadata map
+        assert_eq!(
+            restored.metadata::<MapIndexesMetadata>().unwrap().list,
+            vec![6, 7]
+        );
+    }
+
+    #[test]
+    fn test_testcase_disk_size() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapIndexesMetadata::register();
+        }
+
+        let mut testcase = Testcase::new(BytesInput::new(vec![0x41; 300]));
+        testcase.add_metadata(MapIndexesMetadata::new(vec![1, 2, 3]));
+        testcase.set_scheduler_metadata(SchedulerTestcaseMetadata::new(2));
+        testcase.set_novelties(vec![4, 5]);
+
+        let expected = postcard::to_allocvec(testcase.input().as_ref().unwrap())
+            .unwrap()
+            .len()
+            + postcard::to_allocvec(testcase.metadata_map())
+                .unwrap()
+                .len()
+            + postcard::to_allocvec(&Some(testcase.scheduler_metadata().unwrap()))
+                .unwrap()
+                .len()
+            + postcard::to_allocvec(&Some(testcase.novelties().unwrap()))
+                .unwrap()
+                .len();
+        assert_eq!(testcase.disk_size().unwrap(), expected);
+
+        *testcase.input_mut() = None;
+        assert!(testcase.disk_size().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_testcase_formats() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapIndexesMetadata::register();
+        }
+
+        let mut testcase = Testcase::new(BytesInput::new(vec![0x41, 0x00, 0xff]));
+        testcase.add_metadata(MapIndexesMetadata::new(vec![1, 2, 3]));
+        *testcase.executions_mut() = 7;
+
+        for format in [
+            OnDiskMetadataFormat::Postcard,
+            OnDiskMetadataFormat::Json,
+            OnDiskMetadataFormat::JsonPretty,
+            #[cfg(feature = "gzip")]
+            OnDiskMetadataFormat::JsonGzip,
+        ] {
+            let bytes = testcase.to_bytes(format).unwrap();
+            let restored = Testcase::<BytesInput>::from_bytes(&bytes, format).unwrap();
+            assert_eq!(restored.input(), testcase.input());
+            assert_eq!(restored.executions(), testcase.executions());
+            assert_eq!(
+                restored.metadata::<MapIndexesMetadata>().unwrap().list,
+                vec![1, 2, 3]
+            );
+        }
+
+        assert!(
+            testcase
+                .to_bytes(OnDiskMetadataFormat::Postcard)
+                .unwrap()
+                .len()
+                < testcase.to_bytes(OnDiskMetadataFormat::Json).unwrap().len()
+        );
+    }
+}
@@ -28,9 +28,10 @@ use crate::events::llmp::COMPRESS_THRESHOLD;
 use crate::state::HasScalabilityMonitor;
 use crate::{
     events::{
-        AdaptiveSerializer, BrokerEventResult, CustomBufEventResult, Event, EventConfig,
-        EventFirer, EventManager, EventManagerId, EventProcessor, EventRestarter,
-        HasCustomBufHandlers, HasEventManagerId, LogSeverity, ProgressReporter,
+        map_checksum_matches, AdaptiveSerializer, BrokerEventResult, CustomBufEventResult, Event,
+        EventConfig, EventFirer, EventManager, EventManagerId, EventProcessor, EventRestarter,
+        HasCustomBufHandlers, HasEventManagerId, LogSeverity, NewTestcaseSenderMetadata,
+        ProgressReporter,
     },
     executors::{Executor, HasObservers},
     fuzzer::{EvaluatorObservers, ExecutionProcessor},
@@ -203,6 +204,7 @@ where
                 time: _,
                 executions: _,
                 forward_id: _,
+                map_checksum: _,
             } => Ok(BrokerEventResult::Forward),
             _ => Ok(BrokerEventResult::Handled),
         }
@@ -296,6 +298,7 @@ where
                     executions: _,
                     observers_buf: _,
                     forward_id,
+                    map_checksum: _,
                 } => {
                     *forward_id = Some(ClientId(self.inner.mgr_id().0 as u32));
                     is_tc = true;
@@ -355,6 +358,10 @@ where
     fn configuration(&self) -> EventConfig {
         self.inner.configuration()
     }
+
+    fn map_checksum(&self) -> Option<u64> {
+        self.inner.map_checksum()
+    }
 }
 
 impl<EM, SP> EventRestarter for CentralizedEventManager<EM, SP>
@@ -643,38 +650,47 @@ where
                 time,
                 executions,
                 forward_id,
+                map_checksum,
             } => {
                 log::info!("Received new Testcase from {client_id:?} ({client_config:?}, forward {forward_id:?})");
 
-                let res =
-                    if client_config.match_with(&self.configuration()) && observers_buf.is_some() {
-                        let observers: E::Observers =
-                            postcard::from_bytes(observers_buf.as_ref().unwrap())?;
-                        #[cfg(feature = "scalability_introspection")]
-                        {
-                            state.scalability_monitor_mut().testcase_with_observers += 1;
-                        }
-                        fuzzer.execute_and_process(
-                            state,
-                            self,
-                            input.clone(),
-                            &observers,
-                            &exit_kind,
-                            false,
-                        )?
-                    } else {
-                        #[cfg(feature = "scalability_introspection")]
+                let res = NewTestcaseSenderMetadata::with_sender(
+                    state,
+                    forward_id.unwrap_or(client_id),
+                    |state| {
+                        if client_config.match_with(&self.configuration())
+                            && observers_buf.is_some()
+                            && map_checksum_matches(self.map_checksum(), map_checksum)
                         {
-                            state.scalability_monitor_mut().testcase_without_observers += 1;
+                            let observers: E::Observers =
+                                postcard::from_bytes(observers_buf.as_ref().unwrap())?;
+                            #[cfg(feature = "scalability_introspection")]
+                            {
+                                state.scalability_monitor_mut().testcase_with_observers += 1;
+                            }
+                            fuzzer.execute_and_process(
+                                state,
+                                self,
+                                input.clone(),
+                                &observers,
+                                &exit_kind,
+                                false,
+                            )
+                        } else {
+                            #[cfg(feature = "scalability_introspection")]
+                            {
+                                state.scalability_monitor_mut().testcase_without_observers += 1;
+                            }
+                            fuzzer.evaluate_input_with_observers::<E, Self>(
+                                state,
+                                executor,
+                                self,
+                                input.clone(),
+                                false,
+                            )
                         }
-                        fuzzer.evaluate_input_with_observers::<E, Self>(
-                            state,
-                            executor,
-                            self,
-                            input.clone(),
-                            false,
-                        )?
-                    };
+                    },
+                )?;
 
                 if let Some(item) = res.1 {
                     if res.1.is_some() {
@@ -689,6 +705,7 @@ where
                                 time,
                                 executions,
                                 forward_id,
+                                map_checksum,
                             },
                         )?;
                     }
@@ -3,6 +3,9 @@
 //! other clients
 use libafl_bolts::ClientId;
 
+pub mod shard;
+pub use shard::CorpusShardHook;
+
 use crate::{events::Event, state::State, Error};
 
 /// The hooks that are run before and after the event manager calls `handle_in_client`
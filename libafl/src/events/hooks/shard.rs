@@ -0,0 +1,218 @@
+//! Partitions the testcases received from other clients into shards, so that not every client re-executes every testcase.
+//!
+//! Each client owns the testcases sent by the clients that fall into its shard, and adds only those to its corpus.
+//! Testcases from other shards are still evaluated, to record their coverage, but not added to the corpus.
+
+use libafl_bolts::ClientId;
+
+use crate::{
+    events::{
+        hooks::EventManagerHook, map_checksum_matches, CoverageOnlyImportMetadata, Event,
+        EventConfig,
+    },
+    state::State,
+    Error, HasMetadata,
+};
+
+/// An [`EventManagerHook`] that shards the testcases received from other clients by their sender.
+///
+/// A testcase sent by a client whose id falls into the shard of this client is processed as usual.
+/// Other testcases are evaluated with a [`CoverageOnlyImportMetadata`], so the fuzzer records their coverage,
+/// but does not add them to the corpus.
+/// Testcases that come with observers this client can re-use, i.e., with a matching [`EventConfig`]
+/// and map checksum, are always processed as usual, since the target does not run again for them.
+#[derive(Debug, Clone)]
+pub struct CorpusShardHook {
+    /// The shard owned by this client
+    shard: usize,
+    /// The total number of shards, usually the number of clients
+    num_shards: usize,
+    /// The configuration of this client, to check if the observers of a testcase can be re-used
+    configuration: EventConfig,
+    /// The map checksum of this client, see [`crate::events::map_layout_checksum`]
+    map_checksum: Option<u64>,
+    /// The number of received testcases that were only evaluated for coverage, since they belong to another shard
+    skipped: u64,
+}
+
+impl CorpusShardHook {
+    /// Creates a new [`CorpusShardHook`] for the client owning the shard `shard` out of `num_shards`.
+    pub fn new(shard: usize, num_shards: usize, configuration: EventConfig) -> Result<Self, Error> {
+        if shard >= num_shards {
+            return Err(Error::illegal_argument(format!(
+                "Shard {shard} does not exist, only {num_shards} shards are available"
+            )));
+        }
+        Ok(Self {
+            shard,
+            num_shards,
+            configuration,
+            map_checksum: None,
+            skipped: 0,
+        })
+    }
+
+    /// Sets the map checksum of this client, the same as in the event manager
+    #[must_use]
+    pub fn with_map_checksum(mut self, map_checksum: Option<u64>) -> Self {
+        self.map_checksum = map_checksum;
+        self
+    }
+
+    /// The shard the testcases sent by the given client belong to
+    #[must_use]
+    pub fn shard_of(&self, sender_id: ClientId) -> usize {
+        sender_id.0 as usize % self.num_shards
+    }
+
+    /// If this client owns the testcases sent by the given client
+    #[must_use]
+    pub fn owns(&self, sender_id: ClientId) -> bool {
+        self.shard_of(sender_id) == self.shard
+    }
+
+    /// The number of received testcases that were only evaluated for coverage, since they belong to another shard
+    #[must_use]
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+}
+
+impl<S> EventManagerHook<S> for CorpusShardHook
+where
+    S: State + HasMetadata,
+{
+    fn pre_exec(
+        &mut self,
+        state: &mut S,
+        client_id: ClientId,
+        event: &Event<S::Input>,
+    ) -> Result<bool, Error> {
+        // Do not leak the marker of a failed evaluation into the next one
+        state.remove_metadata::<CoverageOnlyImportMetadata>();
+
+        let Event::NewTestcase {
+            client_config,
+            observers_buf,
+            forward_id,
+            map_checksum,
+            ..
+        } = event
+        else {
+            return Ok(true);
+        };
+
+        if observers_buf.is_some()
+            && client_config.match_with(&self.configuration)
+            && map_checksum_matches(self.map_checksum, *map_checksum)
+        {
+            // Re-uses the observers, no need to run the target
+            return Ok(true);
+        }
+
+        if !self.owns(forward_id.unwrap_or(client_id)) {
+            self.skipped += 1;
+            state.add_metadata(CoverageOnlyImportMetadata);
+        }
+        Ok(true)
+    }
+
+    fn post_exec(&mut self, state: &mut S, _client_id: ClientId) -> Result<bool, Error> {
+        state.remove_metadata::<CoverageOnlyImportMetadata>();
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use libafl_bolts::{current_time, ClientId};
+
+    use crate::{
+        events::{
+            hooks::{shard::CorpusShardHook, EventManagerHook},
+            CoverageOnlyImportMetadata, Event, EventConfig,
+        },
+        executors::ExitKind,
+        inputs::BytesInput,
+        state::NopState,
+        HasMetadata,
+    };
+
+    fn new_testcase(
+        observers_buf: Option<Vec<u8>>,
+        forward_id: Option<ClientId>,
+        map_checksum: Option<u64>,
+    ) -> Event<BytesInput> {
+        Event::NewTestcase {
+            input: BytesInput::new(vec![0x41]),
+            observers_buf,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 0,
+            client_config: EventConfig::from_name("test"),
+            time: current_time(),
+            executions: 0,
+            forward_id,
+            map_checksum,
+        }
+    }
+
+    #[test]
+    fn test_corpus_shards() {
+        let mut state = NopState::<BytesInput>::new();
+        let mut clients = [
+            CorpusShardHook::new(0, 2, EventConfig::from_name("test")).unwrap(),
+            CorpusShardHook::new(1, 2, EventConfig::from_name("test"))
+                .unwrap()
+                .with_map_checksum(Some(1)),
+        ];
+
+        for sender in 0..64 {
+            let event = new_testcase(None, None, None);
+            let mut owners = 0;
+            for client in &mut clients {
+                // Every client evaluates the testcase
+                assert!(client
+                    .pre_exec(&mut state, ClientId(sender), &event)
+                    .unwrap());
+                if !state.has_metadata::<CoverageOnlyImportMetadata>() {
+                    owners += 1;
+                }
+                assert!(client.post_exec(&mut state, ClientId(sender)).unwrap());
+                assert!(!state.has_metadata::<CoverageOnlyImportMetadata>());
+            }
+            // Exactly one client adds it to the corpus
+            assert_eq!(owners, 1);
+        }
+        assert_eq!(clients[0].skipped(), 32);
+        assert_eq!(clients[1].skipped(), 32);
+
+        // A forwarded testcase belongs to the shard of the client that found it
+        let event = new_testcase(None, Some(ClientId(1)), None);
+        clients[0]
+            .pre_exec(&mut state, ClientId(0), &event)
+            .unwrap();
+        assert!(state.has_metadata::<CoverageOnlyImportMetadata>());
+        clients[1]
+            .pre_exec(&mut state, ClientId(0), &event)
+            .unwrap();
+        assert!(!state.has_metadata::<CoverageOnlyImportMetadata>());
+
+        // Testcases with re-usable observers are added by every client
+        let event = new_testcase(Some(vec![]), None, Some(1));
+        for client in &mut clients {
+            client.pre_exec(&mut state, ClientId(3), &event).unwrap();
+            assert!(!state.has_metadata::<CoverageOnlyImportMetadata>());
+        }
+
+        // Unless the map checksum does not match
+        let event = new_testcase(Some(vec![]), None, Some(2));
+        clients[1]
+            .pre_exec(&mut state, ClientId(0), &event)
+            .unwrap();
+        assert!(state.has_metadata::<CoverageOnlyImportMetadata>());
+
+        assert!(CorpusShardHook::new(2, 2, EventConfig::from_name("test")).is_err());
+    }
+}
@@ -51,8 +51,9 @@ use crate::events::AdaptiveSerializer;
 use crate::events::EVENTMGR_SIGHANDLER_STATE;
 use crate::{
     events::{
-        BrokerEventResult, Event, EventConfig, EventFirer, EventManager, EventManagerId,
-        EventProcessor, EventRestarter, HasCustomBufHandlers, HasEventManagerId, ProgressReporter,
+        map_checksum_matches, BrokerEventResult, Event, EventConfig, EventFirer, EventManager,
+        EventManagerId, EventProcessor, EventRestarter, HasCustomBufHandlers, HasEventManagerId,
+        NewTestcaseSenderMetadata, ProgressReporter,
     },
     executors::{Executor, HasObservers},
     fuzzer::{EvaluatorObservers, ExecutionProcessor},
@@ -239,6 +240,7 @@ where
                 time,
                 executions,
                 forward_id,
+                map_checksum: _,
             } => {
                 let id = if let Some(id) = *forward_id {
                     id
@@ -329,6 +331,20 @@ where
                 log::log!((*severity_level).into(), "{message}");
                 Ok(BrokerEventResult::Handled)
             }
+            Event::Checkpoint {
+                id,
+                executions,
+                time,
+            } => {
+                log::info!(
+                    "Client {client_id:?} reached checkpoint {id} after {executions} executions"
+                );
+                monitor.client_stats_insert(client_id);
+                monitor
+                    .client_stats_mut_for(client_id)
+                    .update_executions(*executions, *time);
+                Ok(BrokerEventResult::Handled)
+            }
             Event::CustomBuf { .. } => Ok(BrokerEventResult::Forward),
             //_ => Ok(BrokerEventResult::Forward),
         }
@@ -353,6 +369,9 @@ where
     /// A node will not re-use the observer values sent over LLMP
     /// from nodes with other configurations.
     configuration: EventConfig,
+    /// The checksum of the local coverage map layout.
+    /// If set, only observer values sent with the same checksum are re-used.
+    map_checksum: Option<u64>,
     #[cfg(feature = "adaptive_serialization")]
     serialization_time: Duration,
     #[cfg(feature = "adaptive_serialization")]
@@ -410,6 +429,7 @@ where
         let debug = debug.field("compressor", &self.compressor);
         debug
             .field("configuration", &self.configuration)
+            .field("map_checksum", &self.map_checksum)
             .field("phantom", &self.phantom)
             .finish_non_exhaustive()
     }
@@ -439,6 +459,7 @@ where
             #[cfg(feature = "llmp_compression")]
             compressor: GzipCompressor::new(COMPRESS_THRESHOLD),
             configuration,
+            map_checksum: None,
             #[cfg(feature = "adaptive_serialization")]
             serialization_time: Duration::ZERO,
             #[cfg(feature = "adaptive_serialization")]
@@ -538,6 +559,7 @@ where
             #[cfg(feature = "llmp_compression")]
             compressor: GzipCompressor::new(COMPRESS_THRESHOLD),
             configuration,
+            map_checksum: None,
             #[cfg(feature = "adaptive_serialization")]
             serialization_time: Duration::ZERO,
             #[cfg(feature = "adaptive_serialization")]
@@ -603,6 +625,12 @@ where
     pub fn to_env(&self, env_name: &str) {
         self.llmp.to_env(env_name).unwrap();
     }
+
+    /// Set the checksum of the local coverage map layout, see [`crate::events::map_layout_checksum`].
+    /// Received testcases with a different checksum are re-evaluated locally.
+    pub fn set_map_checksum(&mut self, map_checksum: Option<u64>) {
+        self.map_checksum = map_checksum;
+    }
 }
 
 impl<EMH, S, SP> LlmpEventManager<EMH, S, SP>
@@ -639,34 +667,45 @@ where
                 time: _,
                 executions: _,
                 forward_id,
+                map_checksum,
             } => {
                 log::info!("Received new Testcase from {client_id:?} ({client_config:?}, forward {forward_id:?})");
 
-                let res = if client_config.match_with(&self.configuration)
-                    && observers_buf.is_some()
-                {
-                    #[cfg(feature = "adaptive_serialization")]
-                    let start = current_time();
-                    let observers: E::Observers =
-                        postcard::from_bytes(observers_buf.as_ref().unwrap())?;
-                    #[cfg(feature = "adaptive_serialization")]
-                    {
-                        self.deserialization_time = current_time() - start;
-                    }
-                    #[cfg(feature = "scalability_introspection")]
-                    {
-                        state.scalability_monitor_mut().testcase_with_observers += 1;
-                    }
-                    fuzzer.execute_and_process(state, self, input, &observers, &exit_kind, false)?
-                } else {
-                    #[cfg(feature = "scalability_introspection")]
-                    {
-                        state.scalability_monitor_mut().testcase_without_observers += 1;
-                    }
-                    fuzzer.evaluate_input_with_observers::<E, Self>(
-                        state, executor, self, input, false,
-                    )?
-                };
+                let res = NewTestcaseSenderMetadata::with_sender(
+                    state,
+                    forward_id.unwrap_or(client_id),
+                    |state| {
+                        if client_config.match_with(&self.configuration)
+                            && observers_buf.is_some()
+                            && map_checksum_matches(self.map_checksum, map_checksum)
+                        {
+                            #[cfg(feature = "adaptive_serialization")]
+                            let start = current_time();
+                            let observers: E::Observers =
+                                postcard::from_bytes(observers_buf.as_ref().unwrap())?;
+                            #[cfg(feature = "adaptive_serialization")]
+                            {
+                                self.deserialization_time = current_time() - start;
+                            }
+                            #[cfg(feature = "scalability_introspection")]
+                            {
+                                state.scalability_monitor_mut().testcase_with_observers += 1;
+                            }
+                            fuzzer.execute_and_process(
+                                state, self, input, &observers, &exit_kind, false,
+                            )
+                        } else {
+                            #[cfg(feature = "scalability_introspection")]
+                            {
+                                state.scalability_monitor_mut().testcase_without_observers += 1;
+                            }
+                            fuzzer.evaluate_input_with_observers::<E, Self>(
+                                state, executor, self, input, false,
+                            )
+                        }
+                    },
+                )?;
+
                 if let Some(item) = res.1 {
                     log::info!("Added received Testcase as item #{item}");
                 }
@@ -772,6 +811,10 @@ where
     fn configuration(&self) -> EventConfig {
         self.configuration
     }
+
+    fn map_checksum(&self) -> Option<u64> {
+        self.map_checksum
+    }
 }
 
 impl<EMH, S, SP> EventRestarter for LlmpEventManager<EMH, S, SP>
@@ -1013,6 +1056,10 @@ where
     fn configuration(&self) -> EventConfig {
         self.llmp_mgr.configuration()
     }
+
+    fn map_checksum(&self) -> Option<u64> {
+        self.llmp_mgr.map_checksum()
+    }
 }
 
 #[cfg(feature = "std")]
@@ -1142,6 +1189,11 @@ where
         &mut self.staterestorer
     }
 
+    /// Set the checksum of the local coverage map layout, see [`LlmpEventManager::set_map_checksum`]
+    pub fn set_map_checksum(&mut self, map_checksum: Option<u64>) {
+        self.llmp_mgr.set_map_checksum(map_checksum);
+    }
+
     /// Save LLMP state and empty state in staterestorer
     pub fn intermediate_save(&mut self) -> Result<(), Error> {
         // First, reset the page to 0 so the next iteration can read read from the beginning of this page
@@ -1652,6 +1704,7 @@ where
                 time: _,
                 executions: _,
                 forward_id,
+                map_checksum: _,
             } => {
                 log::info!("Received new Testcase to convert from {client_id:?} (forward {forward_id:?}, forward {forward_id:?})");
 
@@ -1774,6 +1827,7 @@ where
                 time,
                 executions,
                 forward_id,
+                map_checksum,
             } => Event::NewTestcase {
                 input: self.converter.as_mut().unwrap().convert(input)?,
                 client_config,
@@ -1783,6 +1837,7 @@ where
                 time,
                 executions,
                 forward_id,
+                map_checksum,
             },
             Event::CustomBuf { buf, tag } => Event::CustomBuf { buf, tag },
             _ => {
@@ -1828,6 +1883,7 @@ where
                 time,
                 executions,
                 forward_id,
+                map_checksum,
             } => Event::NewTestcase {
                 input: self.converter.as_mut().unwrap().convert(input)?,
                 client_config,
@@ -1837,6 +1893,7 @@ where
                 time,
                 executions,
                 forward_id,
+                map_checksum,
             },
             Event::CustomBuf { buf, tag } => Event::CustomBuf { buf, tag },
             _ => {
@@ -1852,9 +1909,13 @@ where
 #[cfg(test)]
 #[cfg(feature = "std")]
 mod tests {
-    use core::sync::atomic::{compiler_fence, Ordering};
+    use core::{
+        ptr::addr_of_mut,
+        sync::atomic::{compiler_fence, Ordering},
+    };
 
     use libafl_bolts::{
+        current_time,
         llmp::{LlmpClient, LlmpSharedMap},
         rands::StdRand,
         shmem::{ShMemProvider, StdShMemProvider},
@@ -1866,16 +1927,21 @@ mod tests {
 
     use crate::{
         corpus::{Corpus, InMemoryCorpus, Testcase},
-        events::llmp::{LlmpEventManager, _ENV_FUZZER_SENDER},
+        events::{
+            hooks::CorpusShardHook,
+            llmp::{LlmpEventManager, _ENV_FUZZER_SENDER},
+            map_layout_checksum, CoverageOnlyImportMetadata, Event, NewTestcaseSenderMetadata,
+        },
         executors::{ExitKind, InProcessExecutor},
-        feedbacks::ConstFeedback,
+        feedbacks::{ConstFeedback, MapFeedbackMetadata, MaxMapFeedback},
         fuzzer::Fuzzer,
-        inputs::BytesInput,
+        inputs::{BytesInput, HasBytesVec},
         mutators::BitFlipMutator,
+        observers::StdMapObserver,
         schedulers::RandScheduler,
         stages::StdMutationalStage,
-        state::StdState,
-        StdFuzzer,
+        state::{test::test_std_state_with, HasCorpus, HasExecutions, StdState},
+        HasMetadata, StdFuzzer,
     };
 
     #[test]
@@ -1970,4 +2036,195 @@ mod tests {
                 .unwrap();
         }
     }
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    fn test_map_checksum_mismatch() {
+        let mut feedback = ConstFeedback::new(true);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = test_std_state_with(&mut feedback, &mut objective);
+
+        let mut shmem_provider = StdShMemProvider::new().unwrap();
+        let mut llmp_client = LlmpClient::new(
+            shmem_provider.clone(),
+            LlmpSharedMap::new(ClientId(0), shmem_provider.new_shmem(1024).unwrap()),
+            ClientId(0),
+        )
+        .unwrap();
+        // A little hack for CI. Don't do that in a real-world scenario.
+        unsafe {
+            llmp_client.mark_safe_to_unmap();
+        }
+        let mut llmp_mgr = LlmpEventManager::new(llmp_client, "fuzzer".into()).unwrap();
+        let local_checksum = map_layout_checksum(65536, b"local build");
+        llmp_mgr.set_map_checksum(Some(local_checksum));
+
+        let mut fuzzer = StdFuzzer::new(RandScheduler::new(), feedback, objective);
+        let mut harness = |_buf: &BytesInput| ExitKind::Ok;
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut llmp_mgr,
+        )
+        .unwrap();
+
+        let new_testcase = |byte: u8, map_checksum: u64| Event::NewTestcase {
+            input: BytesInput::new(vec![byte]),
+            observers_buf: Some(postcard::to_allocvec(&()).unwrap()),
+            exit_kind: ExitKind::Ok,
+            corpus_size: 1,
+            client_config: "fuzzer".into(),
+            time: current_time(),
+            executions: 1,
+            forward_id: None,
+            map_checksum: Some(map_checksum),
+        };
+
+        // A snapshot from another build is not merged, but the input is run locally
+        let remote_checksum = map_layout_checksum(65536, b"remote build");
+        llmp_mgr
+            .handle_in_client(
+                &mut fuzzer,
+                &mut executor,
+                &mut state,
+                ClientId(1),
+                new_testcase(1, remote_checksum),
+            )
+            .unwrap();
+        assert_eq!(*state.executions(), 1);
+        assert_eq!(state.corpus().count(), 1);
+
+        // A snapshot from the same build is merged without running the target
+        llmp_mgr
+            .handle_in_client(
+                &mut fuzzer,
+                &mut executor,
+                &mut state,
+                ClientId(1),
+                new_testcase(2, local_checksum),
+            )
+            .unwrap();
+        assert_eq!(*state.executions(), 1);
+        assert_eq!(state.corpus().count(), 2);
+    }
+
+    static mut SHARD_MAP: [u8; 4] = [0; 4];
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    fn test_corpus_shard_hook() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(not(feature = "serdeany_autoreg"))]
+        unsafe {
+            MapFeedbackMetadata::<u8>::register();
+            NewTestcaseSenderMetadata::register();
+            CoverageOnlyImportMetadata::register();
+        }
+
+        // Each testcase covers the edge of its first byte
+        let mut harness = |input: &BytesInput| {
+            unsafe {
+                SHARD_MAP[input.bytes()[0] as usize] = 1;
+            }
+            ExitKind::Ok
+        };
+        let new_testcase = |byte: u8| Event::NewTestcase {
+            input: BytesInput::new(vec![byte]),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 1,
+            client_config: "fuzzer".into(),
+            time: current_time(),
+            executions: 1,
+            forward_id: None,
+            map_checksum: None,
+        };
+
+        for shard in 0..2 {
+            let observer = unsafe {
+                StdMapObserver::from_mut_ptr("map", addr_of_mut!(SHARD_MAP) as *mut u8, 4)
+            };
+            let mut feedback = MaxMapFeedback::new(&observer);
+            let mut objective = ConstFeedback::new(false);
+            let mut state = test_std_state_with(&mut feedback, &mut objective);
+
+            let mut shmem_provider = StdShMemProvider::new().unwrap();
+            let mut llmp_client = LlmpClient::new(
+                shmem_provider.clone(),
+                LlmpSharedMap::new(ClientId(0), shmem_provider.new_shmem(1024).unwrap()),
+                ClientId(0),
+            )
+            .unwrap();
+            // A little hack for CI. Don't do that in a real-world scenario.
+            unsafe {
+                llmp_client.mark_safe_to_unmap();
+            }
+            let mut llmp_mgr = LlmpEventManager::with_hooks(
+                llmp_client,
+                "fuzzer".into(),
+                tuple_list!(CorpusShardHook::new(shard, 2, "fuzzer".into()).unwrap()),
+            )
+            .unwrap();
+
+            let mut fuzzer = StdFuzzer::new(RandScheduler::new(), feedback, objective);
+            let mut executor = InProcessExecutor::new(
+                &mut harness,
+                tuple_list!(observer),
+                &mut fuzzer,
+                &mut state,
+                &mut llmp_mgr,
+            )
+            .unwrap();
+
+            // The clients 2 and 3 belong to the shards 0 and 1
+            let (owned, other) = (ClientId(2 + shard as u32), ClientId(3 - shard as u32));
+            llmp_mgr
+                .handle_in_client(
+                    &mut fuzzer,
+                    &mut executor,
+                    &mut state,
+                    owned,
+                    new_testcase(0),
+                )
+                .unwrap();
+            llmp_mgr
+                .handle_in_client(
+                    &mut fuzzer,
+                    &mut executor,
+                    &mut state,
+                    other,
+                    new_testcase(1),
+                )
+                .unwrap();
+            // Both testcases ran, but only the owned one was added to the corpus
+            assert_eq!(*state.executions(), 2);
+            assert_eq!(state.corpus().count(), 1);
+            let idx = state.corpus().first().unwrap();
+            assert_eq!(state.corpus().get(idx).unwrap().borrow().sender_id(), owned);
+            assert!(!state.has_metadata::<CoverageOnlyImportMetadata>());
+
+            // The coverage of the other testcase was recorded, so it is not interesting anymore
+            llmp_mgr
+                .handle_in_client(
+                    &mut fuzzer,
+                    &mut executor,
+                    &mut state,
+                    owned,
+                    new_testcase(1),
+                )
+                .unwrap();
+            assert_eq!(*state.executions(), 3);
+            assert_eq!(state.corpus().count(), 1);
+            assert_eq!(llmp_mgr.hooks.0.skipped(), 1);
+
+            unsafe {
+                SHARD_MAP = [0; 4];
+            }
+        }
+    }
 }
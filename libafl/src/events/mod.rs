@@ -5,6 +5,8 @@ pub mod hooks;
 
 pub mod simple;
 pub use simple::*;
+pub mod throttled;
+pub use throttled::ThrottledEventManager;
 #[cfg(all(unix, feature = "std"))]
 pub mod centralized;
 #[cfg(all(unix, feature = "std"))]
@@ -34,11 +36,13 @@ use ahash::RandomState;
 pub use launcher::*;
 #[cfg(all(unix, feature = "std"))]
 use libafl_bolts::os::unix_signals::{siginfo_t, ucontext_t, Handler, Signal};
-use libafl_bolts::{current_time, ClientId};
+use libafl_bolts::ClientId;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "std")]
 use uuid::Uuid;
 
+#[cfg(all(unix, feature = "std"))]
+use crate::fuzzer::FuzzLoopStopHandle;
 #[cfg(feature = "introspection")]
 use crate::state::HasClientPerfMonitor;
 use crate::{
@@ -46,7 +50,7 @@ use crate::{
     inputs::Input,
     monitors::UserStats,
     observers::ObserversTuple,
-    state::{HasExecutions, HasLastReportTime, State},
+    state::{HasClock, HasExecutions, HasLastReportTime, State},
     Error, HasMetadata,
 };
 #[cfg(feature = "scalability_introspection")]
@@ -60,6 +64,7 @@ use crate::{
 pub static mut EVENTMGR_SIGHANDLER_STATE: ShutdownSignalData = ShutdownSignalData {
     shutting_down: false,
     exit_from_main: false,
+    stop_handle: None,
 };
 
 /// A signal handler for releasing `StateRestore` `ShMem`
@@ -69,6 +74,7 @@ pub static mut EVENTMGR_SIGHANDLER_STATE: ShutdownSignalData = ShutdownSignalDat
 pub struct ShutdownSignalData {
     shutting_down: bool,
     exit_from_main: bool,
+    stop_handle: Option<FuzzLoopStopHandle>,
 }
 
 #[cfg(all(unix, feature = "std"))]
@@ -79,6 +85,12 @@ impl ShutdownSignalData {
             core::ptr::write_volatile(core::ptr::addr_of_mut!(self.exit_from_main), true);
         }
     }
+
+    /// Stop the fuzz loop of this process gracefully on a shutdown signal, instead of exiting right away,
+    /// see [`FuzzLoopStopHandle::stop_on_shutdown_signal`]
+    pub fn set_stop_handle(&mut self, stop_handle: FuzzLoopStopHandle) {
+        self.stop_handle = Some(stop_handle);
+    }
 }
 
 /// Shutdown handler. `SigTerm`, `SigInterrupt`, `SigQuit` call this
@@ -98,6 +110,12 @@ impl Handler for ShutdownSignalData {
             std::process::id()
         );
         */
+        // if this process fuzzes with a stop handle, let the fuzz loop finish and persist its state
+        if let Some(stop_handle) = &self.stop_handle {
+            stop_handle.request_stop();
+            return;
+        }
+
         // if this process has not allocated any shmem. then simply exit()
         if !self.exit_from_main {
             unsafe {
@@ -120,6 +138,50 @@ impl Handler for ShutdownSignalData {
     }
 }
 
+/// The client that sent the [`Event::NewTestcase`] that is currently being evaluated.
+///
+/// Event managers evaluate a received testcase in [`NewTestcaseSenderMetadata::with_sender`], so that the fuzzer can record
+/// the sender in [`crate::corpus::Testcase::sender_id`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct NewTestcaseSenderMetadata {
+    /// The client that found the testcase
+    pub sender_id: ClientId,
+}
+
+libafl_bolts::impl_serdeany!(NewTestcaseSenderMetadata);
+
+impl NewTestcaseSenderMetadata {
+    /// Runs `f`, usually evaluating a received testcase, with the `sender_id` set in the `state`.
+    /// Removes it afterwards, even if `f` failed, so it does not stick to testcases found later.
+    pub fn with_sender<S, T, F>(state: &mut S, sender_id: ClientId, f: F) -> Result<T, Error>
+    where
+        S: HasMetadata,
+        F: FnOnce(&mut S) -> Result<T, Error>,
+    {
+        state.add_metadata(Self { sender_id });
+        let res = f(state);
+        state.remove_metadata::<Self>();
+        res
+    }
+}
+
+/// Marks the [`Event::NewTestcase`] that is currently being evaluated as coverage-only.
+///
+/// The fuzzer records the coverage of such a testcase, but does not add it to the corpus,
+/// since another client owns it, see [`hooks::CorpusShardHook`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct CoverageOnlyImportMetadata;
+
+libafl_bolts::impl_serdeany!(CoverageOnlyImportMetadata);
+
 /// A per-fuzzer unique `ID`, usually starting with `0` and increasing
 /// by `1` in multiprocessed [`EventManager`]s, such as [`self::llmp::LlmpEventManager`].
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -259,6 +321,26 @@ impl From<String> for EventConfig {
     }
 }
 
+/// The checksum of a coverage map layout, from the map size and an id of the target build,
+/// such as [`libafl_bolts::build_id::get`].
+///
+/// Coverage snapshots of [`Event::NewTestcase`] are only meaningful to clients with the same map layout.
+#[must_use]
+pub fn map_layout_checksum(map_size: usize, build_id: &[u8]) -> u64 {
+    let mut hasher = RandomState::with_seeds(0, 0, 0, 0).build_hasher();
+    hasher.write_u64(map_size as u64);
+    hasher.write(build_id);
+    hasher.finish()
+}
+
+/// If a received coverage snapshot with the `remote` map checksum may be used by a client with the `local` one.
+///
+/// Clients without a local checksum accept all snapshots.
+#[must_use]
+pub fn map_checksum_matches(local: Option<u64>, remote: Option<u64>) -> bool {
+    local.map_or(true, |local| remote == Some(local))
+}
+
 /*
 /// A custom event, for own messages, with own handler.
 pub trait CustomEvent<I>: SerdeAny
@@ -302,6 +384,9 @@ where
         executions: u64,
         /// The original sender if, if forwarded
         forward_id: Option<ClientId>,
+        /// The checksum of the coverage map layout of the sender, see [`map_layout_checksum`].
+        /// Receivers only use the `observers_buf` if it matches their own.
+        map_checksum: Option<u64>,
     },
     /// New stats event to monitor.
     UpdateExecStats {
@@ -352,6 +437,16 @@ where
         /// `PhantomData`
         phantom: PhantomData<I>,
     },
+    /// A checkpoint, fired by the fuzzer at a fixed interval, see [`crate::fuzzer::CheckpointInterval`].
+    /// A supervisor may use it to coordinate snapshots of the corpora and states of all clients.
+    Checkpoint {
+        /// The id of this checkpoint, increasing by one with each checkpoint of this client
+        id: u64,
+        /// The executions of this client
+        executions: u64,
+        /// The time of generation of the event
+        time: Duration,
+    },
     /// Sends a custom buffer to other clients
     CustomBuf {
         /// The buffer
@@ -381,6 +476,7 @@ where
                 time: _,
                 executions: _,
                 forward_id: _,
+                map_checksum: _,
             } => "Testcase",
             Event::UpdateExecStats {
                 time: _,
@@ -405,6 +501,7 @@ where
                 message: _,
                 phantom: _,
             } => "Log",
+            Event::Checkpoint { .. } => "Checkpoint",
             Event::CustomBuf { .. } => "CustomBuf",
             /*Event::Custom {
                 sender_id: _, /*custom_event} => custom_event.name()*/
@@ -459,6 +556,11 @@ pub trait EventFirer: UsesState {
     fn configuration(&self) -> EventConfig {
         EventConfig::AlwaysUnique
     }
+
+    /// Get the checksum of the local coverage map layout, see [`map_layout_checksum`]
+    fn map_checksum(&self) -> Option<u64> {
+        None
+    }
 }
 
 /// [`ProgressReporter`] report progress to the broker.
@@ -473,13 +575,16 @@ where
         &mut self,
         state: &mut Self::State,
         monitor_timeout: Duration,
-    ) -> Result<(), Error> {
+    ) -> Result<(), Error>
+    where
+        Self::State: HasClock,
+    {
         let Some(last_report_time) = state.last_report_time() else {
             // this is the first time we execute, no need to report progress just yet.
-            *state.last_report_time_mut() = Some(current_time());
+            *state.last_report_time_mut() = Some(state.now());
             return Ok(());
         };
-        let cur = current_time();
+        let cur = state.now();
         // default to 0 here to avoid crashes on clock skew
         if cur.checked_sub(*last_report_time).unwrap_or_default() > monitor_timeout {
             // report_progress sets a new `last_report_time` internally.
@@ -490,9 +595,12 @@ where
 
     /// Send off an info/monitor/heartbeat message to the broker.
     /// Will return an [`crate::Error`], if the stats could not be sent.
-    fn report_progress(&mut self, state: &mut Self::State) -> Result<(), Error> {
+    fn report_progress(&mut self, state: &mut Self::State) -> Result<(), Error>
+    where
+        Self::State: HasClock,
+    {
         let executions = *state.executions();
-        let cur = current_time();
+        let cur = state.now();
 
         // Default no introspection implmentation
         #[cfg(not(feature = "introspection"))]
@@ -761,6 +869,10 @@ where
     fn configuration(&self) -> EventConfig {
         self.inner.configuration()
     }
+
+    fn map_checksum(&self) -> Option<u64> {
+        self.inner.map_checksum()
+    }
 }
 
 impl<EM, M> EventRestarter for MonitorTypedEventManager<EM, M>
@@ -832,12 +944,18 @@ where
         &mut self,
         state: &mut Self::State,
         monitor_timeout: Duration,
-    ) -> Result<(), Error> {
+    ) -> Result<(), Error>
+    where
+        Self::State: HasClock,
+    {
         self.inner.maybe_report_progress(state, monitor_timeout)
     }
 
     #[inline]
-    fn report_progress(&mut self, state: &mut Self::State) -> Result<(), Error> {
+    fn report_progress(&mut self, state: &mut Self::State) -> Result<(), Error>
+    where
+        Self::State: HasClock,
+    {
         self.inner.report_progress(state)
     }
 }
@@ -909,9 +1027,9 @@ pub trait AdaptiveSerializer {
             || must_ser
             || self.serializations_cnt().trailing_zeros() >= 8
         {
-            let start = current_time();
+            let start = libafl_bolts::current_time();
             let ser = postcard::to_allocvec(observers)?;
-            *self.serialization_time_mut() = current_time() - start;
+            *self.serialization_time_mut() = libafl_bolts::current_time() - start;
 
             *self.serializations_cnt_mut() += 1;
             Ok(Some(ser))
@@ -923,20 +1041,118 @@ pub trait AdaptiveSerializer {
 }
 
 #[cfg(test)]
-mod tests {
-
+pub(crate) mod test {
+    use alloc::vec::Vec;
     use core::ptr::addr_of_mut;
+    #[cfg(feature = "std")]
+    use core::time::Duration;
 
     use libafl_bolts::{current_time, tuples::tuple_list, Named};
     use tuple_list::tuple_list_type;
 
+    #[cfg(feature = "std")]
+    use crate::state::{test::test_std_state, MockClock};
     use crate::{
-        events::{Event, EventConfig},
+        events::{
+            Event, EventConfig, EventFirer, EventProcessor, EventRestarter, ProgressReporter,
+        },
         executors::ExitKind,
-        inputs::bytes::BytesInput,
+        inputs::{bytes::BytesInput, UsesInput},
         observers::StdMapObserver,
+        state::{HasExecutions, HasLastReportTime, State, UsesState},
+        Error, HasMetadata,
     };
 
+    /// An event manager that records the events fired, and how often the state was persisted, see [`EventRestarter`]
+    #[derive(Debug)]
+    pub(crate) struct RecordingEventManager<S>
+    where
+        S: UsesInput,
+    {
+        /// The events fired so far
+        pub(crate) events: Vec<Event<S::Input>>,
+        /// The number of calls to [`EventRestarter::on_restart`]
+        pub(crate) restarts: usize,
+        /// If [`EventRestarter::send_exiting`] was called
+        pub(crate) exiting: bool,
+    }
+
+    impl<S> RecordingEventManager<S>
+    where
+        S: UsesInput,
+    {
+        /// Creates a new [`RecordingEventManager`], that did not record anything yet
+        #[must_use]
+        pub(crate) fn new() -> Self {
+            Self {
+                events: Vec::new(),
+                restarts: 0,
+                exiting: false,
+            }
+        }
+    }
+
+    impl<S> Default for RecordingEventManager<S>
+    where
+        S: UsesInput,
+    {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<S> UsesState for RecordingEventManager<S>
+    where
+        S: State,
+    {
+        type State = S;
+    }
+
+    impl<S> EventFirer for RecordingEventManager<S>
+    where
+        S: State,
+    {
+        fn fire(&mut self, _state: &mut S, event: Event<S::Input>) -> Result<(), Error> {
+            self.events.push(event);
+            Ok(())
+        }
+    }
+
+    impl<S> EventRestarter for RecordingEventManager<S>
+    where
+        S: State,
+    {
+        fn on_restart(&mut self, state: &mut S) -> Result<(), Error> {
+            assert!(!self.exiting, "on_restart after send_exiting");
+            self.restarts += 1;
+            state.on_restart()
+        }
+
+        fn send_exiting(&mut self) -> Result<(), Error> {
+            self.exiting = true;
+            Ok(())
+        }
+    }
+
+    impl<E, S, Z> EventProcessor<E, Z> for RecordingEventManager<S>
+    where
+        S: State,
+    {
+        fn process(
+            &mut self,
+            _fuzzer: &mut Z,
+            _state: &mut S,
+            _executor: &mut E,
+        ) -> Result<usize, Error> {
+            Ok(0)
+        }
+    }
+
+    impl<S> ProgressReporter for RecordingEventManager<S> where
+        S: State + HasExecutions + HasLastReportTime + HasMetadata
+    {
+    }
+
     static mut MAP: [u32; 4] = [0; 4];
 
     #[test]
@@ -957,6 +1173,7 @@ mod tests {
             time: current_time(),
             executions: 0,
             forward_id: None,
+            map_checksum: None,
         };
 
         let serialized = postcard::to_allocvec(&e).unwrap();
@@ -972,6 +1189,7 @@ mod tests {
                 time: _,
                 executions: _,
                 forward_id: _,
+                map_checksum: _,
             } => {
                 let o: tuple_list_type!(StdMapObserver::<u32, false>) =
                     postcard::from_bytes(observers_buf.as_ref().unwrap()).unwrap();
@@ -980,4 +1198,37 @@ mod tests {
             _ => panic!("mistmatch"),
         };
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_report_interval_with_mock_clock() {
+        let mut state = test_std_state::<BytesInput>();
+        let clock = MockClock::new(Duration::from_secs(1000));
+        state.set_clock(clock.clone());
+        let mut mgr = RecordingEventManager::new();
+        let interval = Duration::from_secs(15);
+        let reports = |mgr: &RecordingEventManager<_>| {
+            mgr.events
+                .iter()
+                .filter(|event| matches!(event, Event::UpdateExecStats { .. }))
+                .count()
+        };
+
+        // The first call only starts the interval
+        mgr.maybe_report_progress(&mut state, interval).unwrap();
+        assert_eq!(reports(&mgr), 0);
+        clock.advance(Duration::from_secs(10));
+        mgr.maybe_report_progress(&mut state, interval).unwrap();
+        assert_eq!(reports(&mgr), 0);
+
+        clock.advance(Duration::from_secs(10));
+        mgr.maybe_report_progress(&mut state, interval).unwrap();
+        assert_eq!(reports(&mgr), 1);
+        assert_eq!(*state.last_report_time(), Some(Duration::from_secs(1020)));
+
+        // The next interval starts at the last report
+        clock.advance(Duration::from_secs(10));
+        mgr.maybe_report_progress(&mut state, interval).unwrap();
+        assert_eq!(reports(&mgr), 1);
+    }
 }
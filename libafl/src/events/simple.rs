@@ -209,6 +209,7 @@ where
                 time,
                 executions,
                 forward_id: _,
+                map_checksum: _,
             } => {
                 monitor.client_stats_insert(ClientId(0));
                 monitor
@@ -286,6 +287,18 @@ where
                 log::log!((*severity_level).into(), "{message}");
                 Ok(BrokerEventResult::Handled)
             }
+            Event::Checkpoint {
+                id,
+                executions,
+                time,
+            } => {
+                log::info!("Reached checkpoint {id} after {executions} executions");
+                monitor.client_stats_insert(ClientId(0));
+                monitor
+                    .client_stats_mut_for(ClientId(0))
+                    .update_executions(*executions, *time);
+                Ok(BrokerEventResult::Handled)
+            }
             Event::CustomBuf { .. } => Ok(BrokerEventResult::Forward),
             //_ => Ok(BrokerEventResult::Forward),
         }
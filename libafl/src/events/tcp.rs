@@ -40,9 +40,9 @@ use super::{CustomBufEventResult, CustomBufHandlerFn};
 use crate::events::EVENTMGR_SIGHANDLER_STATE;
 use crate::{
     events::{
-        hooks::EventManagerHooksTuple, BrokerEventResult, Event, EventConfig, EventFirer,
-        EventManager, EventManagerId, EventProcessor, EventRestarter, HasCustomBufHandlers,
-        HasEventManagerId, ProgressReporter,
+        hooks::EventManagerHooksTuple, map_checksum_matches, BrokerEventResult, Event, EventConfig,
+        EventFirer, EventManager, EventManagerId, EventProcessor, EventRestarter,
+        HasCustomBufHandlers, HasEventManagerId, NewTestcaseSenderMetadata, ProgressReporter,
     },
     executors::{Executor, HasObservers},
     fuzzer::{EvaluatorObservers, ExecutionProcessor},
@@ -324,6 +324,7 @@ where
                 time,
                 executions,
                 forward_id,
+                map_checksum: _,
             } => {
                 let id = if let Some(id) = *forward_id {
                     id
@@ -408,6 +409,20 @@ where
                 log::log!((*severity_level).into(), "{message}");
                 Ok(BrokerEventResult::Handled)
             }
+            Event::Checkpoint {
+                id,
+                executions,
+                time,
+            } => {
+                log::info!(
+                    "Client {client_id:?} reached checkpoint {id} after {executions} executions"
+                );
+                monitor.client_stats_insert(client_id);
+                monitor
+                    .client_stats_mut_for(client_id)
+                    .update_executions(*executions, *time);
+                Ok(BrokerEventResult::Handled)
+            }
             Event::CustomBuf { .. } => Ok(BrokerEventResult::Forward),
             //_ => Ok(BrokerEventResult::Forward),
         }
@@ -433,6 +448,9 @@ where
     /// A node will not re-use the observer values sent over TCP
     /// from nodes with other configurations.
     configuration: EventConfig,
+    /// The checksum of the local coverage map layout.
+    /// If set, only observer values sent with the same checksum are re-used.
+    map_checksum: Option<u64>,
     phantom: PhantomData<S>,
 }
 
@@ -449,6 +467,7 @@ where
         let debug = debug.field("compressor", &self.compressor);
         debug
             .field("configuration", &self.configuration)
+            .field("map_checksum", &self.map_checksum)
             .field("phantom", &self.phantom)
             .finish_non_exhaustive()
     }
@@ -553,6 +572,7 @@ where
             #[cfg(feature = "tcp_compression")]
             compressor: GzipCompressor::new(COMPRESS_THRESHOLD),
             configuration,
+            map_checksum: None,
             phantom: PhantomData,
             custom_buf_handlers: vec![],
         })
@@ -611,6 +631,12 @@ where
         env::set_var(env_name, format!("{}", self.client_id.0));
     }
 
+    /// Set the checksum of the local coverage map layout, see [`crate::events::map_layout_checksum`].
+    /// Received testcases with a different checksum are re-evaluated locally.
+    pub fn set_map_checksum(&mut self, map_checksum: Option<u64>) {
+        self.map_checksum = map_checksum;
+    }
+
     // Handle arriving events in the client
     #[allow(clippy::unused_self)]
     fn handle_in_client<E, Z>(
@@ -639,28 +665,39 @@ where
                 time: _,
                 executions: _,
                 forward_id,
+                map_checksum,
             } => {
                 log::info!("Received new Testcase from {client_id:?} ({client_config:?}, forward {forward_id:?})");
 
-                let _res = if client_config.match_with(&self.configuration)
-                    && observers_buf.is_some()
-                {
-                    let observers: E::Observers =
-                        postcard::from_bytes(observers_buf.as_ref().unwrap())?;
-                    #[cfg(feature = "scalability_introspection")]
-                    {
-                        state.scalability_monitor_mut().testcase_with_observers += 1;
-                    }
-                    fuzzer.execute_and_process(state, self, input, &observers, &exit_kind, false)?
-                } else {
-                    #[cfg(feature = "scalability_introspection")]
-                    {
-                        state.scalability_monitor_mut().testcase_without_observers += 1;
-                    }
-                    fuzzer.evaluate_input_with_observers::<E, Self>(
-                        state, executor, self, input, false,
-                    )?
-                };
+                let _res = NewTestcaseSenderMetadata::with_sender(
+                    state,
+                    forward_id.unwrap_or(client_id),
+                    |state| {
+                        if client_config.match_with(&self.configuration)
+                            && observers_buf.is_some()
+                            && map_checksum_matches(self.map_checksum, map_checksum)
+                        {
+                            let observers: E::Observers =
+                                postcard::from_bytes(observers_buf.as_ref().unwrap())?;
+                            #[cfg(feature = "scalability_introspection")]
+                            {
+                                state.scalability_monitor_mut().testcase_with_observers += 1;
+                            }
+                            fuzzer.execute_and_process(
+                                state, self, input, &observers, &exit_kind, false,
+                            )
+                        } else {
+                            #[cfg(feature = "scalability_introspection")]
+                            {
+                                state.scalability_monitor_mut().testcase_without_observers += 1;
+                            }
+                            fuzzer.evaluate_input_with_observers::<E, Self>(
+                                state, executor, self, input, false,
+                            )
+                        }
+                    },
+                )?;
+
                 if let Some(item) = _res.1 {
                     log::info!("Added received Testcase as item #{item}");
                 }
@@ -753,6 +790,10 @@ where
     fn configuration(&self) -> EventConfig {
         self.configuration
     }
+
+    fn map_checksum(&self) -> Option<u64> {
+        self.map_checksum
+    }
 }
 
 impl<EMH, S> EventRestarter for TcpEventManager<EMH, S>
@@ -928,6 +969,10 @@ where
     fn configuration(&self) -> EventConfig {
         self.tcp_mgr.configuration()
     }
+
+    fn map_checksum(&self) -> Option<u64> {
+        self.tcp_mgr.map_checksum()
+    }
 }
 
 #[cfg(feature = "std")]
@@ -1053,6 +1098,11 @@ where
     pub fn staterestorer_mut(&mut self) -> &mut StateRestorer<SP> {
         &mut self.staterestorer
     }
+
+    /// Set the checksum of the local coverage map layout, see [`TcpEventManager::set_map_checksum`]
+    pub fn set_map_checksum(&mut self, map_checksum: Option<u64>) {
+        self.tcp_mgr.set_map_checksum(map_checksum);
+    }
 }
 
 /// The kind of manager we're creating right now
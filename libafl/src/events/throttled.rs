@@ -0,0 +1,374 @@
+//! An [`EventManager`] wrapper that rate-limits stats events.
+//!
+//! With many clients, firing stats on every call can overwhelm the broker.
+//! The [`ThrottledEventManager`] coalesces stats events, so only the latest value per interval gets sent.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::time::Duration;
+
+use serde::Serialize;
+
+use crate::{
+    events::{
+        CustomBufEventResult, Event, EventConfig, EventFirer, EventManager, EventManagerId,
+        EventProcessor, EventRestarter, HasCustomBufHandlers, HasEventManagerId, LogSeverity,
+        ProgressReporter,
+    },
+    inputs::{Input, UsesInput},
+    observers::ObserversTuple,
+    state::{HasClock, HasExecutions, HasLastReportTime, UsesState},
+    Error, HasMetadata,
+};
+
+/// The default interval in which stats are forwarded by a [`ThrottledEventManager`]
+pub const DEFAULT_STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Identifies a kind of stats event; only the latest event for each key is kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum StatsKey {
+    Exec,
+    #[cfg(feature = "introspection")]
+    Perf,
+    User(String),
+}
+
+impl StatsKey {
+    /// Returns the key of this event, or `None` if it's not a stats event
+    fn of<I>(event: &Event<I>) -> Option<Self>
+    where
+        I: Input,
+    {
+        match event {
+            Event::UpdateExecStats { .. } => Some(Self::Exec),
+            #[cfg(feature = "introspection")]
+            Event::UpdatePerfMonitor { .. } => Some(Self::Perf),
+            Event::UpdateUserStats { name, .. } => Some(Self::User(name.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// An [`EventManager`] that wraps another manager, coalescing stats events.
+///
+/// Stats events ([`Event::UpdateExecStats`], [`Event::UpdateUserStats`], ...) are held back
+/// and only forwarded to the inner manager once per `interval`, independent of how often they get fired.
+/// For each kind of stats, only the latest event is forwarded. All other events pass through immediately.
+/// The interval is measured on the [`crate::state::Clock`] of the state.
+///
+/// Hence, the inner manager may receive stats after events that were fired later, such as [`Event::NewTestcase`] or [`Event::Objective`].
+/// Don't rely on the order of stats relative to other events.
+///
+/// Before exiting, use [`ThrottledEventManager::flush_and_send_exiting`], or call [`EventRestarter::on_restart`] first,
+/// since [`EventRestarter::send_exiting`] has no state to fire the held-back stats with.
+#[derive(Debug)]
+pub struct ThrottledEventManager<EM>
+where
+    EM: UsesState,
+{
+    inner: EM,
+    interval: Duration,
+    last_sent: Option<Duration>,
+    pending: Vec<(StatsKey, Event<<EM::State as UsesInput>::Input>)>,
+}
+
+impl<EM> ThrottledEventManager<EM>
+where
+    EM: EventFirer,
+{
+    /// Creates a new [`ThrottledEventManager`], forwarding stats to the `inner` manager at most once per `interval`
+    #[must_use]
+    pub fn new(inner: EM, interval: Duration) -> Self {
+        Self {
+            inner,
+            interval,
+            last_sent: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Creates a new [`ThrottledEventManager`], using the [`DEFAULT_STATS_INTERVAL`]
+    #[must_use]
+    pub fn with_default_interval(inner: EM) -> Self {
+        Self::new(inner, DEFAULT_STATS_INTERVAL)
+    }
+
+    /// The wrapped manager
+    pub fn inner(&self) -> &EM {
+        &self.inner
+    }
+
+    /// The wrapped manager (mutable)
+    pub fn inner_mut(&mut self) -> &mut EM {
+        &mut self.inner
+    }
+
+    /// The number of stats events currently held back
+    #[must_use]
+    pub fn pending_stats(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Forwards all held-back stats events to the inner manager, right away.
+    pub fn flush_stats(&mut self, state: &mut EM::State) -> Result<(), Error>
+    where
+        EM::State: HasClock,
+    {
+        let pending = core::mem::take(&mut self.pending);
+        for (_, event) in pending {
+            self.inner.fire(state, event)?;
+        }
+        self.last_sent = Some(state.now());
+        Ok(())
+    }
+
+    /// Forwards all held-back stats events, then tells the inner manager that this client is exiting,
+    /// see [`EventRestarter::send_exiting`]
+    pub fn flush_and_send_exiting(&mut self, state: &mut EM::State) -> Result<(), Error>
+    where
+        EM: EventRestarter,
+        EM::State: HasClock,
+    {
+        self.flush_stats(state)?;
+        self.inner.send_exiting()
+    }
+
+    /// Forwards the held-back stats events, if the interval passed since the last time
+    pub fn maybe_flush_stats(&mut self, state: &mut EM::State) -> Result<(), Error>
+    where
+        EM::State: HasClock,
+    {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let due = match self.last_sent {
+            None => true,
+            // default to 0 here to avoid crashes on clock skew
+            Some(last_sent) => {
+                state.now().checked_sub(last_sent).unwrap_or_default() >= self.interval
+            }
+        };
+        if due {
+            self.flush_stats(state)?;
+        }
+        Ok(())
+    }
+}
+
+impl<EM> UsesState for ThrottledEventManager<EM>
+where
+    EM: UsesState,
+{
+    type State = EM::State;
+}
+
+impl<EM> EventFirer for ThrottledEventManager<EM>
+where
+    EM: EventFirer,
+    EM::State: HasClock,
+{
+    fn fire(
+        &mut self,
+        state: &mut Self::State,
+        event: Event<<Self::State as UsesInput>::Input>,
+    ) -> Result<(), Error> {
+        let Some(key) = StatsKey::of(&event) else {
+            return self.inner.fire(state, event);
+        };
+
+        if let Some(pending) = self.pending.iter_mut().find(|(k, _)| *k == key) {
+            pending.1 = event;
+        } else {
+            self.pending.push((key, event));
+        }
+
+        self.maybe_flush_stats(state)
+    }
+
+    #[inline]
+    fn log(
+        &mut self,
+        state: &mut Self::State,
+        severity_level: LogSeverity,
+        message: String,
+    ) -> Result<(), Error> {
+        self.inner.log(state, severity_level, message)
+    }
+
+    #[inline]
+    fn serialize_observers<OT>(&mut self, observers: &OT) -> Result<Option<Vec<u8>>, Error>
+    where
+        OT: ObserversTuple<Self::State> + Serialize,
+    {
+        self.inner.serialize_observers(observers)
+    }
+
+    #[inline]
+    fn configuration(&self) -> EventConfig {
+        self.inner.configuration()
+    }
+
+    fn map_checksum(&self) -> Option<u64> {
+        self.inner.map_checksum()
+    }
+}
+
+impl<EM> EventRestarter for ThrottledEventManager<EM>
+where
+    EM: EventFirer + EventRestarter,
+    EM::State: HasClock,
+{
+    #[inline]
+    fn on_restart(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        // Don't lose the latest stats
+        self.flush_stats(state)?;
+        self.inner.on_restart(state)
+    }
+
+    fn send_exiting(&mut self) -> Result<(), Error> {
+        if !self.pending.is_empty() {
+            log::warn!(
+                "Exiting with {} stats events held back, use flush_and_send_exiting or on_restart to send them first",
+                self.pending.len()
+            );
+        }
+        self.inner.send_exiting()
+    }
+
+    #[inline]
+    fn await_restart_safe(&mut self) {
+        self.inner.await_restart_safe();
+    }
+}
+
+impl<E, EM, Z> EventProcessor<E, Z> for ThrottledEventManager<EM>
+where
+    EM: EventFirer + EventProcessor<E, Z>,
+    EM::State: HasClock,
+{
+    #[inline]
+    fn process(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut Self::State,
+        executor: &mut E,
+    ) -> Result<usize, Error> {
+        self.maybe_flush_stats(state)?;
+        self.inner.process(fuzzer, state, executor)
+    }
+}
+
+impl<E, EM, Z> EventManager<E, Z> for ThrottledEventManager<EM>
+where
+    EM: EventManager<E, Z>,
+    EM::State: HasLastReportTime + HasExecutions + HasMetadata + HasClock,
+{
+}
+
+impl<EM> HasCustomBufHandlers for ThrottledEventManager<EM>
+where
+    EM: HasCustomBufHandlers,
+{
+    #[inline]
+    fn add_custom_buf_handler(
+        &mut self,
+        handler: Box<
+            dyn FnMut(&mut Self::State, &str, &[u8]) -> Result<CustomBufEventResult, Error>,
+        >,
+    ) {
+        self.inner.add_custom_buf_handler(handler);
+    }
+}
+
+/// Progress is reported through [`ThrottledEventManager::fire`], so it gets throttled as well
+impl<EM> ProgressReporter for ThrottledEventManager<EM>
+where
+    EM: EventFirer,
+    EM::State: HasLastReportTime + HasExecutions + HasMetadata + HasClock,
+{
+}
+
+impl<EM> HasEventManagerId for ThrottledEventManager<EM>
+where
+    EM: UsesState + HasEventManagerId,
+{
+    #[inline]
+    fn mgr_id(&self) -> EventManagerId {
+        self.inner.mgr_id()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{marker::PhantomData, time::Duration};
+
+    use crate::{
+        events::{
+            test::RecordingEventManager, throttled::ThrottledEventManager, Event, EventFirer,
+        },
+        inputs::BytesInput,
+        state::{HasClock, NopState},
+    };
+
+    #[test]
+    fn test_throttled_stats() {
+        let mut state = NopState::<BytesInput>::new();
+        let mut mgr =
+            ThrottledEventManager::new(RecordingEventManager::new(), Duration::from_secs(3600));
+
+        for executions in 0..100 {
+            let time = state.now();
+            mgr.fire(
+                &mut state,
+                Event::UpdateExecStats {
+                    time,
+                    executions,
+                    phantom: PhantomData,
+                },
+            )
+            .unwrap();
+        }
+
+        // Only the very first update went through so far
+        assert_eq!(mgr.inner().events.len(), 1);
+        assert_eq!(mgr.pending_stats(), 1);
+
+        mgr.flush_stats(&mut state).unwrap();
+
+        let events = &mgr.inner().events;
+        assert!(events.len() < 10);
+        match events.last().unwrap() {
+            Event::UpdateExecStats { executions, .. } => assert_eq!(*executions, 99),
+            _ => panic!("expected stats event"),
+        }
+    }
+
+    #[test]
+    fn test_throttled_stats_flushed_on_exit() {
+        let mut state = NopState::<BytesInput>::new();
+        let mut mgr =
+            ThrottledEventManager::new(RecordingEventManager::new(), Duration::from_secs(3600));
+
+        for executions in [1, 2] {
+            let time = state.now();
+            mgr.fire(
+                &mut state,
+                Event::UpdateExecStats {
+                    time,
+                    executions,
+                    phantom: PhantomData,
+                },
+            )
+            .unwrap();
+        }
+        assert_eq!(mgr.pending_stats(), 1);
+
+        mgr.flush_and_send_exiting(&mut state).unwrap();
+
+        assert_eq!(mgr.pending_stats(), 0);
+        assert!(mgr.inner().exiting);
+        match mgr.inner().events.last().unwrap() {
+            Event::UpdateExecStats { executions, .. } => assert_eq!(*executions, 2),
+            _ => panic!("expected stats event"),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Expose an `Executor` based on a `Forkserver` in order to execute AFL/AFL++ binaries
 
-use alloc::{borrow::ToOwned, string::ToString, vec::Vec};
+use alloc::{borrow::ToOwned, boxed::Box, string::ToString, vec::Vec};
 use core::{
     fmt::{self, Debug, Formatter},
     marker::PhantomData,
@@ -74,6 +74,15 @@ const MAX_INPUT_SIZE_DEFAULT: usize = 1024 * 1024;
 /// The default signal to use to kill child processes
 const KILL_SIGNAL_DEFAULT: Signal = Signal::SIGTERM;
 
+/// The signals that count as a crash of the target by default, see [`ForkserverExecutorBuilder::crash_signals`]
+pub const CRASH_SIGNALS_DEFAULT: [Signal; 5] = [
+    Signal::SIGSEGV,
+    Signal::SIGABRT,
+    Signal::SIGBUS,
+    Signal::SIGILL,
+    Signal::SIGFPE,
+];
+
 /// Configure the target, `limit`, `setsid`, `pipe_stdin`, the code was borrowed from the [`Angora`](https://github.com/AngoraFuzzer/Angora) fuzzer
 pub trait ConfigTarget {
     /// Sets the sid
@@ -479,6 +488,100 @@ impl Forkserver {
             Ok(None)
         }
     }
+
+    /// The initial handshake with a freshly started forkserver.
+    ///
+    /// Reads the hello message, and passes its status to `options`, which picks the options to use, if any.
+    /// Sends those back to the forkserver, and reads the autodict, if it was requested.
+    /// Returns the sent options, and the raw autodict.
+    pub fn handshake<F>(&mut self, options: F) -> Result<(Option<i32>, Option<Vec<u8>>), Error>
+    where
+        F: FnOnce(i32) -> Result<Option<i32>, Error>,
+    {
+        let (rlen, status) = self.read_st()?; // Initial handshake, read 4-bytes hello message from the forkserver.
+        if rlen != 4 {
+            return Err(Error::unknown("Failed to start a forkserver".to_string()));
+        }
+        log::info!("All right - fork server is up.");
+
+        let Some(send_status) = options(status)? else {
+            return Ok((None, None));
+        };
+        if self.write_ctl(send_status)? != 4 {
+            return Err(Error::unknown("Writing to forkserver failed.".to_string()));
+        }
+        if (send_status & FS_OPT_AUTODICT) != FS_OPT_AUTODICT {
+            return Ok((Some(send_status), None));
+        }
+
+        let (read_len, dict_size) = self.read_st()?;
+        if read_len != 4 {
+            return Err(Error::unknown(
+                "Reading from forkserver failed.".to_string(),
+            ));
+        }
+        if !(2..=0xffffff).contains(&dict_size) {
+            return Err(Error::illegal_state(
+                "Dictionary has an illegal size".to_string(),
+            ));
+        }
+        log::info!("Autodict size {dict_size:x}");
+
+        let dict_size = usize::try_from(dict_size)?;
+        let (rlen, buf) = self.read_st_size(dict_size)?;
+        if rlen != dict_size {
+            return Err(Error::unknown("Failed to load autodictionary".to_string()));
+        }
+        Ok((Some(send_status), Some(buf)))
+    }
+}
+
+/// The additional arguments and environment variables of the target, for a single run of a [`ForkserverExecutor`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Invocation {
+    /// The arguments, appended to the ones the executor was built with
+    pub args: Vec<OsString>,
+    /// The environment variables, set in addition to the ones the executor was built with
+    pub envs: Vec<(OsString, OsString)>,
+}
+
+/// Maps an input to the [`Invocation`] of the target running it, see [`ForkserverExecutor::set_invocation_mapper`].
+///
+/// This lets the fuzzer reach the behavior of targets that depends on their command line or environment,
+/// as is common for CLI tools, e.g., by reading them from a dedicated part of the input.
+/// It is implemented for all closures taking the input.
+pub trait InvocationMapper<S> {
+    /// The [`Invocation`] of the target for the given input
+    fn invocation(&mut self, input: &<S as UsesInput>::Input) -> Invocation
+    where
+        S: UsesInput;
+}
+
+impl<F, S> InvocationMapper<S> for F
+where
+    F: FnMut(&S::Input) -> Invocation,
+    S: UsesInput,
+{
+    fn invocation(&mut self, input: &S::Input) -> Invocation
+    where
+        S: UsesInput,
+    {
+        self(input)
+    }
+}
+
+/// The options needed to spawn the [`Forkserver`] again, with another [`Invocation`]
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+struct ForkserverOptions {
+    envs: Vec<(OsString, OsString)>,
+    use_stdin: bool,
+    is_persistent: bool,
+    is_deferred_frksrv: bool,
+    debug_child: bool,
+    kill_signal: Signal,
+    /// The options negotiated with the forkserver in the initial handshake, if any
+    handshake_status: Option<i32>,
 }
 
 /// This [`Executor`] can run binaries compiled for AFL/AFL++ that make use of a forkserver.
@@ -493,11 +596,17 @@ where
     input_file: InputFile,
     uses_shmem_testcase: bool,
     forkserver: Forkserver,
+    forkserver_options: ForkserverOptions,
+    invocation_mapper: Option<Box<dyn InvocationMapper<S>>>,
+    /// The [`Invocation`] the running forkserver was started with
+    invocation: Invocation,
     observers: OT,
     map: Option<SP::ShMem>,
     phantom: PhantomData<S>,
     map_size: Option<usize>,
     timeout: TimeSpec,
+    /// The signals that count as a crash, others are ignored
+    crash_signals: Vec<Signal>,
 }
 
 impl<OT, S, SP> Debug for ForkserverExecutor<OT, S, SP>
@@ -512,6 +621,7 @@ where
             .field("input_file", &self.input_file)
             .field("uses_shmem_testcase", &self.uses_shmem_testcase)
             .field("forkserver", &self.forkserver)
+            .field("invocation", &self.invocation)
             .field("observers", &self.observers)
             .field("map", &self.map)
             .finish_non_exhaustive()
@@ -561,6 +671,57 @@ where
     pub fn coverage_map_size(&self) -> Option<usize> {
         self.map_size
     }
+
+    /// The signals that count as a crash of the target, see [`ForkserverExecutorBuilder::crash_signals`]
+    pub fn crash_signals(&self) -> &[Signal] {
+        &self.crash_signals
+    }
+
+    /// If the raw `signal` the target was terminated with counts as a crash
+    fn is_crash_signal(&self, signal: i32) -> bool {
+        Signal::try_from(signal).is_ok_and(|signal| self.crash_signals.contains(&signal))
+    }
+
+    /// Set the [`InvocationMapper`], deriving additional arguments and environment variables of the target from each input.
+    ///
+    /// The forkserver can only fork the target with the command line and environment it was started with.
+    /// It is restarted whenever the [`Invocation`] for an input differs from the one of the last run,
+    /// so a mapper should only produce a few distinct invocations.
+    pub fn set_invocation_mapper<M>(&mut self, mapper: M)
+    where
+        M: InvocationMapper<S> + 'static,
+    {
+        self.invocation_mapper = Some(Box::new(mapper));
+    }
+
+    /// Restarts the forkserver with the given [`Invocation`]
+    fn restart_forkserver(&mut self, invocation: Invocation) -> Result<(), Error> {
+        let options = &self.forkserver_options;
+        let mut args = self.args.clone();
+        args.extend(invocation.args.iter().cloned());
+        let mut envs = options.envs.clone();
+        envs.extend(invocation.envs.iter().cloned());
+
+        // Replacing the old forkserver kills it
+        self.forkserver = Forkserver::with_kill_signal(
+            self.target.clone(),
+            args,
+            envs,
+            self.input_file.as_raw_fd(),
+            options.use_stdin,
+            0,
+            options.is_persistent,
+            options.is_deferred_frksrv,
+            options.debug_child,
+            options.kill_signal,
+        )?;
+        self.invocation = invocation;
+
+        // Agree on the same options as the first time, the autodict was already parsed then.
+        let handshake_status = self.forkserver_options.handshake_status;
+        self.forkserver.handshake(|_status| Ok(handshake_status))?;
+        Ok(())
+    }
 }
 
 /// The builder for `ForkserverExecutor`
@@ -583,6 +744,7 @@ pub struct ForkserverExecutorBuilder<'a, SP> {
     real_map_size: i32,
     kill_signal: Option<Signal>,
     timeout: Option<Duration>,
+    crash_signals: Option<Vec<Signal>>,
 }
 
 impl<'a, SP> ForkserverExecutorBuilder<'a, SP> {
@@ -599,7 +761,7 @@ impl<'a, SP> ForkserverExecutorBuilder<'a, SP> {
         S::Input: Input + HasTargetBytes,
         SP: ShMemProvider,
     {
-        let (forkserver, input_file, map) = self.build_helper()?;
+        let (forkserver, input_file, map, handshake_status) = self.build_helper()?;
 
         let target = self.program.take().unwrap();
         log::info!(
@@ -626,11 +788,18 @@ impl<'a, SP> ForkserverExecutorBuilder<'a, SP> {
             input_file,
             uses_shmem_testcase: self.uses_shmem_testcase,
             forkserver,
+            forkserver_options: self.forkserver_options(handshake_status),
+            invocation_mapper: None,
+            invocation: Invocation::default(),
             observers,
             map,
             phantom: PhantomData,
             map_size: self.map_size,
             timeout,
+            crash_signals: self
+                .crash_signals
+                .clone()
+                .unwrap_or_else(|| CRASH_SIGNALS_DEFAULT.to_vec()),
         })
     }
 
@@ -649,7 +818,7 @@ impl<'a, SP> ForkserverExecutorBuilder<'a, SP> {
         S::Input: Input + HasTargetBytes,
         SP: ShMemProvider,
     {
-        let (forkserver, input_file, map) = self.build_helper()?;
+        let (forkserver, input_file, map, handshake_status) = self.build_helper()?;
 
         let target = self.program.take().unwrap();
         log::info!(
@@ -683,16 +852,25 @@ impl<'a, SP> ForkserverExecutorBuilder<'a, SP> {
             input_file,
             uses_shmem_testcase: self.uses_shmem_testcase,
             forkserver,
+            forkserver_options: self.forkserver_options(handshake_status),
+            invocation_mapper: None,
+            invocation: Invocation::default(),
             observers,
             map,
             phantom: PhantomData,
             map_size: self.map_size,
             timeout,
+            crash_signals: self
+                .crash_signals
+                .clone()
+                .unwrap_or_else(|| CRASH_SIGNALS_DEFAULT.to_vec()),
         })
     }
 
-    #[allow(clippy::pedantic)]
-    fn build_helper(&mut self) -> Result<(Forkserver, InputFile, Option<SP::ShMem>), Error>
+    #[allow(clippy::pedantic, clippy::type_complexity)]
+    fn build_helper(
+        &mut self,
+    ) -> Result<(Forkserver, InputFile, Option<SP::ShMem>, Option<i32>), Error>
     where
         SP: ShMemProvider,
     {
@@ -739,13 +917,17 @@ impl<'a, SP> ForkserverExecutorBuilder<'a, SP> {
             }
         };
 
-        let (rlen, status) = forkserver.read_st()?; // Initial handshake, read 4-bytes hello message from the forkserver.
-
-        if rlen != 4 {
-            return Err(Error::unknown("Failed to start a forkserver".to_string()));
+        let (handshake_status, autodict) =
+            forkserver.handshake(|status| Ok(self.negotiate_options(status, map.is_some())))?;
+        if let (Some(t), Some(buf)) = (&mut self.autotokens, autodict) {
+            t.parse_autodict(&buf, buf.len());
         }
-        log::info!("All right - fork server is up.");
 
+        Ok((forkserver, input_file, map, handshake_status))
+    }
+
+    /// Picks the options to use from the ones the forkserver offers in its hello `status`, if any
+    fn negotiate_options(&mut self, status: i32, uses_shmem: bool) -> Option<i32> {
         if status & FS_OPT_ENABLED == FS_OPT_ENABLED && status & FS_OPT_MAPSIZE == FS_OPT_MAPSIZE {
             let mut map_size = fs_opt_get_mapsize(status);
             // When 0, we assume that map_size was filled by the user or const
@@ -778,7 +960,7 @@ impl<'a, SP> ForkserverExecutorBuilder<'a, SP> {
         {
             let mut send_status = FS_OPT_ENABLED;
 
-            if (status & FS_OPT_SHDMEM_FUZZ == FS_OPT_SHDMEM_FUZZ) && map.is_some() {
+            if (status & FS_OPT_SHDMEM_FUZZ == FS_OPT_SHDMEM_FUZZ) && uses_shmem {
                 log::info!("Using SHARED MEMORY FUZZING feature.");
                 send_status |= FS_OPT_SHDMEM_FUZZ;
                 self.uses_shmem_testcase = true;
@@ -789,46 +971,29 @@ impl<'a, SP> ForkserverExecutorBuilder<'a, SP> {
                 send_status |= FS_OPT_AUTODICT;
             }
 
+            // if send_status is not changed (Options are available but we didn't use any), then don't send the next write_ctl message.
+            // This is important
             if send_status != FS_OPT_ENABLED {
-                // if send_status is not changed (Options are available but we didn't use any), then don't send the next write_ctl message.
-                // This is important
-
-                let send_len = forkserver.write_ctl(send_status)?;
-                if send_len != 4 {
-                    return Err(Error::unknown("Writing to forkserver failed.".to_string()));
-                }
-
-                if (send_status & FS_OPT_AUTODICT) == FS_OPT_AUTODICT {
-                    let (read_len, dict_size) = forkserver.read_st()?;
-                    if read_len != 4 {
-                        return Err(Error::unknown(
-                            "Reading from forkserver failed.".to_string(),
-                        ));
-                    }
-
-                    if !(2..=0xffffff).contains(&dict_size) {
-                        return Err(Error::illegal_state(
-                            "Dictionary has an illegal size".to_string(),
-                        ));
-                    }
-
-                    log::info!("Autodict size {dict_size:x}");
-
-                    let (rlen, buf) = forkserver.read_st_size(dict_size as usize)?;
-
-                    if rlen != dict_size as usize {
-                        return Err(Error::unknown("Failed to load autodictionary".to_string()));
-                    }
-                    if let Some(t) = &mut self.autotokens {
-                        t.parse_autodict(&buf, dict_size as usize);
-                    }
-                }
+                return Some(send_status);
             }
         } else {
             log::warn!("Forkserver Options are not available.");
         }
 
-        Ok((forkserver, input_file, map))
+        None
+    }
+
+    /// The options to spawn the forkserver again, with another [`Invocation`]
+    fn forkserver_options(&self, handshake_status: Option<i32>) -> ForkserverOptions {
+        ForkserverOptions {
+            envs: self.envs.clone(),
+            use_stdin: self.use_stdin,
+            is_persistent: self.is_persistent,
+            is_deferred_frksrv: self.is_deferred_frksrv,
+            debug_child: self.debug_child,
+            kill_signal: self.kill_signal.unwrap_or(KILL_SIGNAL_DEFAULT),
+            handshake_status,
+        }
     }
 
     /// Use autodict?
@@ -1019,6 +1184,17 @@ impl<'a, SP> ForkserverExecutorBuilder<'a, SP> {
         self.kill_signal = Some(kill_signal);
         self
     }
+
+    /// Sets the signals that count as a crash of the target, defaults to [`CRASH_SIGNALS_DEFAULT`].
+    /// A run terminated by any other signal, such as a `SIGPIPE`, is treated as [`ExitKind::Ok`].
+    #[must_use]
+    pub fn crash_signals<IT>(mut self, signals: IT) -> Self
+    where
+        IT: IntoIterator<Item = Signal>,
+    {
+        self.crash_signals = Some(signals.into_iter().collect());
+        self
+    }
 }
 
 impl<'a> ForkserverExecutorBuilder<'a, UnixShMemProvider> {
@@ -1047,6 +1223,7 @@ impl<'a> ForkserverExecutorBuilder<'a, UnixShMemProvider> {
             max_input_size: MAX_INPUT_SIZE_DEFAULT,
             kill_signal: None,
             timeout: None,
+            crash_signals: None,
         }
     }
 
@@ -1072,6 +1249,7 @@ impl<'a> ForkserverExecutorBuilder<'a, UnixShMemProvider> {
             max_input_size: MAX_INPUT_SIZE_DEFAULT,
             kill_signal: None,
             timeout: None,
+            crash_signals: self.crash_signals,
         }
     }
 }
@@ -1101,6 +1279,16 @@ where
     ) -> Result<ExitKind, Error> {
         *state.executions_mut() += 1;
 
+        let invocation = self
+            .invocation_mapper
+            .as_mut()
+            .map(|mapper| mapper.invocation(input));
+        if let Some(invocation) = invocation {
+            if invocation != self.invocation {
+                self.restart_forkserver(invocation)?;
+            }
+        }
+
         let mut exit_kind = ExitKind::Ok;
 
         let last_run_timed_out = self.forkserver.last_run_timed_out_raw();
@@ -1157,7 +1345,7 @@ where
 
         if let Some(status) = self.forkserver.read_st_timed(&self.timeout)? {
             self.forkserver.set_status(status);
-            if libc::WIFSIGNALED(self.forkserver().status()) {
+            if libc::WIFSIGNALED(status) && self.is_crash_signal(libc::WTERMSIG(status)) {
                 exit_kind = ExitKind::Crash;
                 #[cfg(feature = "regex")]
                 if let Some(asan_observer) = self
@@ -1230,14 +1418,35 @@ mod tests {
         tuples::tuple_list,
         AsMutSlice,
     };
+    use nix::sys::signal::Signal;
     use serial_test::serial;
 
     use crate::{
-        executors::forkserver::ForkserverExecutorBuilder,
+        corpus::Corpus,
+        events::NopEventManager,
+        executors::{
+            forkserver::{ForkserverExecutorBuilder, Invocation},
+            Executor, ExitKind,
+        },
+        feedbacks::{ConstFeedback, CrashFeedback},
+        fuzzer::{test::NopFuzzer, Evaluator},
+        inputs::{BytesInput, HasBytesVec},
         observers::{ConstMapObserver, HitcountsMapObserver},
-        Error,
+        schedulers::QueueScheduler,
+        state::{test::test_std_state_with, HasSolutions, NopState},
+        Error, StdFuzzer,
     };
 
+    /// A fake forkserver, in bash, reporting the wait status `FUZZ_STATUS` for each run
+    const FAKE_FORKSERVER: &str = r#"
+        printf '\000\000\000\000' >&199
+        while [ "$(head -c 4 <&198 | wc -c)" -eq 4 ]; do
+            p=$$
+            printf "$(printf '\\%03o\\%03o\\%03o\\%03o' $((p & 255)) $((p >> 8 & 255)) $((p >> 16 & 255)) $((p >> 24 & 255)))" >&199
+            printf "$(printf '\\%03o' "${FUZZ_STATUS:-0}")\000\000\000" >&199
+        done
+    "#;
+
     #[test]
     #[serial]
     #[cfg_attr(miri, ignore)]
@@ -1274,4 +1483,90 @@ mod tests {
         };
         assert!(result);
     }
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    fn test_forkserver_invocation() {
+        let mut executor = ForkserverExecutorBuilder::new()
+            .program("bash")
+            .arg("-c")
+            .arg(FAKE_FORKSERVER)
+            .build::<_, NopState<BytesInput>>(tuple_list!())
+            .unwrap();
+        executor.set_invocation_mapper(|input: &BytesInput| {
+            let mut invocation = Invocation::default();
+            if input.bytes().starts_with(b"flag") {
+                // Crash with a SIGSEGV
+                invocation.envs.push(("FUZZ_STATUS".into(), "11".into()));
+            }
+            invocation
+        });
+
+        let mut fuzzer = NopFuzzer::new();
+        let mut state = NopState::new();
+        let mut mgr = NopEventManager::new();
+        let mut run = |bytes: &[u8]| {
+            executor
+                .run_target(
+                    &mut fuzzer,
+                    &mut state,
+                    &mut mgr,
+                    &BytesInput::new(bytes.to_vec()),
+                )
+                .unwrap()
+        };
+
+        assert_eq!(run(b"none"), ExitKind::Ok);
+        // Only the input setting the env var reaches the crashing branch
+        assert_eq!(run(b"flag"), ExitKind::Crash);
+        assert_eq!(run(b"flag!"), ExitKind::Crash);
+        assert_eq!(run(b"none"), ExitKind::Ok);
+    }
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    fn test_forkserver_crash_signals() {
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = CrashFeedback::new();
+        let mut state = test_std_state_with(&mut feedback, &mut objective);
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+        let mut mgr = NopEventManager::new();
+
+        // The target dies of a SIGPIPE, which is no crash by default
+        let mut executor = ForkserverExecutorBuilder::new()
+            .program("bash")
+            .arg("-c")
+            .arg(FAKE_FORKSERVER)
+            .env("FUZZ_STATUS", "13")
+            .build(tuple_list!())
+            .unwrap();
+        assert!(!executor.crash_signals().contains(&Signal::SIGPIPE));
+        let input = BytesInput::new(b"pipe".to_vec());
+        assert_eq!(
+            executor
+                .run_target(&mut fuzzer, &mut state, &mut mgr, &input)
+                .unwrap(),
+            ExitKind::Ok
+        );
+        fuzzer
+            .evaluate_input(&mut state, &mut executor, &mut mgr, input.clone())
+            .unwrap();
+        assert_eq!(state.solutions().count(), 0);
+
+        // Unless it is in the allowlist
+        let mut executor = ForkserverExecutorBuilder::new()
+            .program("bash")
+            .arg("-c")
+            .arg(FAKE_FORKSERVER)
+            .env("FUZZ_STATUS", "13")
+            .crash_signals([Signal::SIGPIPE])
+            .build(tuple_list!())
+            .unwrap();
+        fuzzer
+            .evaluate_input(&mut state, &mut executor, &mut mgr, input)
+            .unwrap();
+        assert_eq!(state.solutions().count(), 1);
+    }
 }
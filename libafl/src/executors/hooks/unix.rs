@@ -16,7 +16,7 @@ pub mod unix_signal_handler {
             inprocess::{run_observers_and_save_state, HasInProcessHooks},
             Executor, ExitKind, HasObservers,
         },
-        feedbacks::Feedback,
+        feedbacks::{exploitability::record_crash_info, CrashInfo, Feedback},
         fuzzer::HasObjective,
         inputs::{Input, UsesInput},
         state::{HasCorpus, HasExecutions, HasSolutions},
@@ -221,6 +221,8 @@ pub mod unix_signal_handler {
                 }
             }
 
+            record_crash_info(CrashInfo::from_signal(signal, _info, _context.as_deref()));
+
             run_observers_and_save_state::<E, EM, OF, Z>(
                 executor,
                 state,
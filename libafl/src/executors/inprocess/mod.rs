@@ -453,6 +453,7 @@ pub fn run_observers_and_save_state<E, EM, OF, Z>(
         let executions = *state.executions();
         let mut new_testcase = Testcase::with_executions(input.clone(), executions);
         new_testcase.add_metadata(exitkind);
+        new_testcase.set_objective_kind(exitkind.into());
         new_testcase.set_parent_id_optional(*state.corpus().current());
         fuzzer
             .objective_mut()
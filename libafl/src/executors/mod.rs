@@ -15,6 +15,10 @@ pub use inprocess::InProcessExecutor;
 pub use inprocess_fork::InProcessForkExecutor;
 #[cfg(unix)]
 use libafl_bolts::os::unix_signals::Signal;
+#[cfg(all(feature = "std", unix))]
+pub use pipe::PipeExecutor;
+pub use recording::RecordingExecutor;
+pub use retry::RetryExecutor;
 use serde::{Deserialize, Serialize};
 pub use shadow::ShadowExecutor;
 pub use with_observers::WithObservers;
@@ -37,6 +41,10 @@ pub mod inprocess;
 #[cfg(all(feature = "std", unix))]
 pub mod inprocess_fork;
 
+#[cfg(all(feature = "std", unix))]
+pub mod pipe;
+pub mod recording;
+pub mod retry;
 pub mod shadow;
 
 pub mod with_observers;
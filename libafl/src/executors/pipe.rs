@@ -0,0 +1,444 @@
+//! The [`PipeExecutor`] drives an external tool through a pair of named pipes (FIFOs).
+//!
+//! For each run, the executor writes the input to the input pipe, framed by its length as a little-endian `u32`,
+//! and reads back a single status line from the status pipe: `ok`, `crash`, `timeout`, or `oom`.
+//! This lets tools that are neither forkservers nor in-process harnesses, for example analysis tools, consume the inputs.
+
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    time::Duration,
+};
+use std::{
+    fs::{File, OpenOptions},
+    io::{ErrorKind, Read, Write},
+    os::unix::{fs::OpenOptionsExt, io::AsRawFd},
+    path::{Path, PathBuf},
+    thread,
+    time::Instant,
+};
+
+use libafl_bolts::AsSlice;
+
+use crate::{
+    executors::{Executor, ExitKind, HasObservers},
+    inputs::HasTargetBytes,
+    observers::{ObserversTuple, UsesObservers},
+    state::{HasExecutions, State, UsesState},
+    Error,
+};
+
+/// How long to wait between two attempts to connect to a consumer that is not ready, yet
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Parses a status line of the consumer of a [`PipeExecutor`] into an [`ExitKind`]
+pub fn parse_pipe_status(line: &str) -> Result<ExitKind, Error> {
+    match line.trim() {
+        "ok" => Ok(ExitKind::Ok),
+        "crash" => Ok(ExitKind::Crash),
+        "timeout" => Ok(ExitKind::Timeout),
+        "oom" => Ok(ExitKind::Oom),
+        other => Err(Error::illegal_state(format!(
+            "Unknown status from the pipe consumer: {other}"
+        ))),
+    }
+}
+
+/// The open ends of the two pipes
+#[derive(Debug)]
+struct PipeConnection {
+    input: File,
+    status: File,
+    /// Bytes read from the status pipe, that are not part of a complete line, yet
+    pending: Vec<u8>,
+}
+
+/// An [`Executor`] writing each input to a named pipe, and reading its [`ExitKind`] from another named pipe.
+///
+/// The executor connects lazily on the first run, and reconnects after the consumer went away.
+/// If no consumer opened the input pipe within the timeout, the run fails with an error.
+/// If the consumer does not read the input, or does not answer, within the timeout, the run is a [`ExitKind::Timeout`],
+/// and the executor reconnects in the next run.
+pub struct PipeExecutor<OT, S> {
+    input_path: PathBuf,
+    status_path: PathBuf,
+    timeout: Duration,
+    connection: Option<PipeConnection>,
+    observers: OT,
+    phantom: PhantomData<S>,
+}
+
+impl<OT, S> PipeExecutor<OT, S>
+where
+    OT: ObserversTuple<S>,
+    S: State,
+{
+    /// Creates a new [`PipeExecutor`], writing inputs to the FIFO at `input_path`
+    /// and reading status lines from the FIFO at `status_path`.
+    ///
+    /// The `timeout` applies both to waiting for the consumer to connect, and to each run,
+    /// from writing the input until reading its status line.
+    pub fn new<P, Q>(input_path: P, status_path: Q, timeout: Duration, observers: OT) -> Self
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        Self {
+            input_path: input_path.as_ref().into(),
+            status_path: status_path.as_ref().into(),
+            timeout,
+            connection: None,
+            observers,
+            phantom: PhantomData,
+        }
+    }
+
+    /// If the executor is currently connected to a consumer
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    /// Opens both pipes, waiting up to the timeout for the consumer to open the input pipe
+    fn connect(&self) -> Result<PipeConnection, Error> {
+        let start = Instant::now();
+        let input = loop {
+            // Without a reader, a non-blocking open fails with `ENXIO`, instead of blocking forever
+            match OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&self.input_path)
+            {
+                Ok(input) => break input,
+                Err(err) if err.raw_os_error() == Some(libc::ENXIO) => {
+                    if start.elapsed() >= self.timeout {
+                        return Err(Error::illegal_state(format!(
+                            "No consumer opened the pipe {} in time",
+                            self.input_path.display()
+                        )));
+                    }
+                    thread::sleep(CONNECT_RETRY_INTERVAL);
+                }
+                Err(err) => return Err(err.into()),
+            }
+        };
+        // The input stays non-blocking, so that a consumer that stops reading can't stall a run forever.
+        // The consumer is there, it opens the status pipe next
+        let status = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&self.status_path)?;
+        Ok(PipeConnection {
+            input,
+            status,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Writes the whole `frame` to the input pipe, or returns `false` if the consumer did not read it before the deadline
+    fn write_input(
+        &self,
+        connection: &mut PipeConnection,
+        frame: &[u8],
+        start: Instant,
+    ) -> Result<bool, Error> {
+        let mut written = 0;
+        while written < frame.len() {
+            match connection.input.write(&frame[written..]) {
+                Ok(len) => written += len,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    let Some(remaining) = self.timeout.checked_sub(start.elapsed()) else {
+                        return Ok(false);
+                    };
+                    poll(&connection.input, libc::POLLOUT, remaining)?;
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Reads the next status line, or returns `None` if the consumer did not answer before the deadline
+    fn read_status(
+        &self,
+        connection: &mut PipeConnection,
+        start: Instant,
+    ) -> Result<Option<ExitKind>, Error> {
+        loop {
+            if let Some(end) = connection.pending.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = connection.pending.drain(..=end).collect();
+                return parse_pipe_status(&String::from_utf8_lossy(&line)).map(Some);
+            }
+
+            let Some(remaining) = self.timeout.checked_sub(start.elapsed()) else {
+                return Ok(None);
+            };
+            let revents = poll(&connection.status, libc::POLLIN, remaining)?;
+
+            let mut buf = [0; 64];
+            match connection.status.read(&mut buf) {
+                // The consumer did not open its end, yet, or closed it
+                Ok(0) if revents & libc::POLLHUP != 0 => {
+                    return Err(Error::illegal_state("The pipe consumer went away"));
+                }
+                Ok(read) => connection.pending.extend_from_slice(&buf[..read]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+/// Waits up to `timeout` for the `events` on the file, returns the events that occurred.
+/// An interrupted wait returns no events.
+fn poll(file: &File, events: libc::c_short, timeout: Duration) -> Result<libc::c_short, Error> {
+    let mut pollfd = libc::pollfd {
+        fd: file.as_raw_fd(),
+        events,
+        revents: 0,
+    };
+    let millis = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+    // # Safety
+    // The pollfd is valid for the duration of the call
+    if unsafe { libc::poll(&mut pollfd, 1, millis.max(1)) } < 0 {
+        let err = std::io::Error::last_os_error();
+        if err.kind() == ErrorKind::Interrupted {
+            return Ok(0);
+        }
+        return Err(err.into());
+    }
+    Ok(pollfd.revents)
+}
+
+impl<OT, S> Debug for PipeExecutor<OT, S>
+where
+    OT: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipeExecutor")
+            .field("input_path", &self.input_path)
+            .field("status_path", &self.status_path)
+            .field("timeout", &self.timeout)
+            .field("connected", &self.connection.is_some())
+            .field("observers", &self.observers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<EM, OT, S, Z> Executor<EM, Z> for PipeExecutor<OT, S>
+where
+    EM: UsesState<State = S>,
+    S: State + HasExecutions,
+    S::Input: HasTargetBytes,
+    OT: ObserversTuple<S>,
+    Z: UsesState<State = S>,
+{
+    fn run_target(
+        &mut self,
+        _fuzzer: &mut Z,
+        state: &mut Self::State,
+        _mgr: &mut EM,
+        input: &Self::Input,
+    ) -> Result<ExitKind, Error> {
+        *state.executions_mut() += 1;
+
+        let mut connection = match self.connection.take() {
+            Some(connection) => connection,
+            None => self.connect()?,
+        };
+
+        let bytes = input.target_bytes();
+        let len = u32::try_from(bytes.as_slice().len())
+            .map_err(|_| Error::illegal_argument("Input too large for the pipe protocol"))?;
+        let mut frame = Vec::with_capacity(4 + bytes.as_slice().len());
+        frame.extend_from_slice(&len.to_le_bytes());
+        frame.extend_from_slice(bytes.as_slice());
+        // On errors and timeouts, the connection is dropped, and re-established in the next run
+        let start = Instant::now();
+        if !self.write_input(&mut connection, &frame, start)? {
+            return Ok(ExitKind::Timeout);
+        }
+
+        match self.read_status(&mut connection, start)? {
+            Some(exit_kind) => {
+                self.connection = Some(connection);
+                Ok(exit_kind)
+            }
+            // The answer may still arrive later, so the connection is out of sync
+            None => Ok(ExitKind::Timeout),
+        }
+    }
+}
+
+impl<OT, S> UsesState for PipeExecutor<OT, S>
+where
+    S: State,
+{
+    type State = S;
+}
+
+impl<OT, S> UsesObservers for PipeExecutor<OT, S>
+where
+    OT: ObserversTuple<S>,
+    S: State,
+{
+    type Observers = OT;
+}
+
+impl<OT, S> HasObservers for PipeExecutor<OT, S>
+where
+    OT: ObserversTuple<S>,
+    S: State,
+{
+    fn observers(&self) -> &OT {
+        &self.observers
+    }
+
+    fn observers_mut(&mut self) -> &mut OT {
+        &mut self.observers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::time::Duration;
+    use std::{
+        ffi::CString,
+        fs::{self, File, OpenOptions},
+        io::{Read, Write},
+        os::unix::ffi::OsStrExt,
+        path::Path,
+        sync::mpsc::channel,
+        thread,
+    };
+
+    use super::PipeExecutor;
+    use crate::{
+        events::NopEventManager,
+        executors::{Executor, ExitKind},
+        fuzzer::test::NopFuzzer,
+        inputs::BytesInput,
+        state::NopState,
+    };
+
+    fn mkfifo(path: &Path) {
+        let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+        // # Safety
+        // The path is a valid, nul-terminated string
+        assert_eq!(unsafe { libc::mkfifo(path.as_ptr(), 0o600) }, 0);
+    }
+
+    #[test]
+    fn test_pipe_executor() {
+        let dir = Path::new("target/.test/pipe_executor");
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        let input_path = dir.join("input");
+        let status_path = dir.join("status");
+        mkfifo(&input_path);
+        mkfifo(&status_path);
+
+        let mut executor =
+            PipeExecutor::new(&input_path, &status_path, Duration::from_millis(100), ());
+        let mut fuzzer = NopFuzzer::new();
+        let mut state = NopState::<BytesInput>::new();
+        let mut mgr = NopEventManager::new();
+
+        // Without a consumer, the run fails
+        assert!(executor
+            .run_target(
+                &mut fuzzer,
+                &mut state,
+                &mut mgr,
+                &BytesInput::new(b"hello".to_vec())
+            )
+            .is_err());
+        assert!(!executor.is_connected());
+
+        // A mock consumer, crashing on `crash`
+        let consumer = {
+            let (input_path, status_path) = (input_path.clone(), status_path.clone());
+            thread::spawn(move || {
+                let mut input = File::open(input_path).unwrap();
+                let mut status = OpenOptions::new().write(true).open(status_path).unwrap();
+                let mut received = Vec::new();
+                for _ in 0..2 {
+                    let mut len = [0; 4];
+                    input.read_exact(&mut len).unwrap();
+                    let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+                    input.read_exact(&mut bytes).unwrap();
+                    let answer = if bytes == b"crash" { "crash\n" } else { "ok\n" };
+                    status.write_all(answer.as_bytes()).unwrap();
+                    received.push(bytes);
+                }
+                received
+            })
+        };
+
+        let mut executor =
+            PipeExecutor::new(&input_path, &status_path, Duration::from_secs(10), ());
+        for (input, expected) in [(&b"hello"[..], ExitKind::Ok), (b"crash", ExitKind::Crash)] {
+            let exit_kind = executor
+                .run_target(
+                    &mut fuzzer,
+                    &mut state,
+                    &mut mgr,
+                    &BytesInput::new(input.to_vec()),
+                )
+                .unwrap();
+            assert_eq!(exit_kind, expected);
+        }
+        assert_eq!(
+            consumer.join().unwrap(),
+            vec![b"hello".to_vec(), b"crash".to_vec()]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_pipe_executor_write_timeout() {
+        let dir = Path::new("target/.test/pipe_executor_write_timeout");
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        let input_path = dir.join("input");
+        let status_path = dir.join("status");
+        mkfifo(&input_path);
+        mkfifo(&status_path);
+
+        // A hanging consumer, opening the input pipe but never reading from it
+        let (done, wait_done) = channel::<()>();
+        let consumer = {
+            let input_path = input_path.clone();
+            thread::spawn(move || {
+                let _input = File::open(input_path).unwrap();
+                wait_done.recv().unwrap();
+            })
+        };
+
+        let mut executor =
+            PipeExecutor::new(&input_path, &status_path, Duration::from_millis(100), ());
+        let mut fuzzer = NopFuzzer::new();
+        let mut state = NopState::<BytesInput>::new();
+        let mut mgr = NopEventManager::new();
+
+        // The input does not fit into the buffer of the pipe
+        let exit_kind = executor
+            .run_target(
+                &mut fuzzer,
+                &mut state,
+                &mut mgr,
+                &BytesInput::new(vec![0; 1 << 20]),
+            )
+            .unwrap();
+        assert_eq!(exit_kind, ExitKind::Timeout);
+        assert!(!executor.is_connected());
+
+        done.send(()).unwrap();
+        consumer.join().unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
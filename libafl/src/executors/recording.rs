@@ -0,0 +1,411 @@
+//! A [`RecordingExecutor`] wraps an executor and records every run, so that a sequence of runs can be replayed later.
+//!
+//! This helps to reproduce bugs that depend on the exact sequence of executions, for example in the fuzzer itself.
+
+use alloc::{collections::VecDeque, vec::Vec};
+use core::{cell::UnsafeCell, fmt::Debug, ptr};
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
+use libafl_bolts::{ownedref::OwnedMutPtr, tuples::MatchName};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    executors::{Executor, ExitKind, HasObservers},
+    inputs::UsesInput,
+    observers::{ObserversTuple, UsesObservers},
+    state::UsesState,
+    Error,
+};
+
+/// The default number of runs a [`RecordingExecutor`] keeps, see [`RecordingExecutor::with_trace_limit`]
+pub const DEFAULT_TRACE_LIMIT: usize = 1024;
+
+/// A single recorded run of a [`RecordingExecutor`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRecord<I> {
+    /// The input that was executed
+    pub input: I,
+    /// How the run finished
+    pub exit_kind: ExitKind,
+    /// The serialized observers, after their `post_exec`
+    pub observers: Vec<u8>,
+}
+
+/// Proxy the observers of the executor wrapped by a [`RecordingExecutor`].
+///
+/// Completes the record of the last run once the observers ran their `post_exec`,
+/// so that the recorded observers are the ones the feedbacks see, for example after hitcount classification.
+/// Serializes to the wrapped observers.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(bound(
+    serialize = "OT: Serialize",
+    deserialize = "OT: serde::de::DeserializeOwned"
+))]
+#[serde(transparent)]
+pub struct RecordingObserversTuple<OT, I> {
+    observers: OwnedMutPtr<OT>,
+    /// The input and [`ExitKind`] of the last run, waiting for the observers
+    #[serde(skip)]
+    pending: Option<(I, ExitKind)>,
+    /// The last runs recorded so far, the oldest first
+    #[serde(skip)]
+    trace: VecDeque<RunRecord<I>>,
+    /// The maximum number of runs in the `trace`
+    #[serde(skip)]
+    limit: usize,
+}
+
+impl<OT, I, S> ObserversTuple<S> for RecordingObserversTuple<OT, I>
+where
+    OT: ObserversTuple<S> + Serialize,
+    S: UsesInput<Input = I>,
+{
+    fn pre_exec_all(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
+        self.observers.as_mut().pre_exec_all(state, input)
+    }
+
+    fn post_exec_all(
+        &mut self,
+        state: &mut S,
+        input: &S::Input,
+        exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.observers
+            .as_mut()
+            .post_exec_all(state, input, exit_kind)?;
+        if let Some((input, exit_kind)) = self.pending.take() {
+            let observers = postcard::to_allocvec(self.observers.as_ref())?;
+            if self.trace.len() == self.limit {
+                self.trace.pop_front();
+            }
+            self.trace.push_back(RunRecord {
+                input,
+                exit_kind,
+                observers,
+            });
+        }
+        Ok(())
+    }
+
+    fn pre_exec_child_all(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
+        self.observers.as_mut().pre_exec_child_all(state, input)
+    }
+
+    fn post_exec_child_all(
+        &mut self,
+        state: &mut S,
+        input: &S::Input,
+        exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.observers
+            .as_mut()
+            .post_exec_child_all(state, input, exit_kind)
+    }
+
+    #[inline]
+    fn observes_stdout(&self) -> bool {
+        self.observers.as_ref().observes_stdout()
+    }
+
+    #[inline]
+    fn observes_stderr(&self) -> bool {
+        self.observers.as_ref().observes_stderr()
+    }
+
+    fn observe_stdout(&mut self, stdout: &[u8]) {
+        self.observers.as_mut().observe_stdout(stdout);
+    }
+
+    fn observe_stderr(&mut self, stderr: &[u8]) {
+        self.observers.as_mut().observe_stderr(stderr);
+    }
+}
+
+impl<OT, I> MatchName for RecordingObserversTuple<OT, I>
+where
+    OT: MatchName,
+{
+    fn match_name<T>(&self, name: &str) -> Option<&T> {
+        self.observers.as_ref().match_name::<T>(name)
+    }
+
+    fn match_name_mut<T>(&mut self, name: &str) -> Option<&mut T> {
+        self.observers.as_mut().match_name_mut::<T>(name)
+    }
+}
+
+impl<OT, I> RecordingObserversTuple<OT, I> {
+    fn set(&mut self, observers: &OT) {
+        self.observers = OwnedMutPtr::Ptr(ptr::from_ref(observers) as *mut OT);
+    }
+}
+
+/// A [`RecordingExecutor`] wraps an executor and records the input, [`ExitKind`],
+/// and the state of all observers for each run.
+///
+/// The observers are recorded after their `post_exec`, when the fuzzer (or whoever runs the executor)
+/// calls [`ObserversTuple::post_exec_all`] on the [`HasObservers::observers_mut`] of this executor.
+/// Runs without a `post_exec` of the observers are not recorded.
+/// Only the last [`DEFAULT_TRACE_LIMIT`] runs are kept, see [`RecordingExecutor::with_trace_limit`].
+///
+/// Use [`RecordingExecutor::replay`] to feed a recorded trace through the executor again.
+#[derive(Debug)]
+pub struct RecordingExecutor<E>
+where
+    E: HasObservers,
+{
+    /// The wrapped executor
+    executor: E,
+    /// The proxy of the observers of the wrapped executor, holding the trace
+    observers: UnsafeCell<RecordingObserversTuple<E::Observers, <E::State as UsesInput>::Input>>,
+}
+
+impl<E> RecordingExecutor<E>
+where
+    E: HasObservers,
+    E::Observers: Serialize,
+{
+    /// Create a new `RecordingExecutor`, wrapping the given `executor`.
+    pub fn new(executor: E) -> Self {
+        Self {
+            executor,
+            observers: UnsafeCell::new(RecordingObserversTuple {
+                observers: OwnedMutPtr::Ptr(ptr::null_mut()),
+                pending: None,
+                trace: VecDeque::new(),
+                limit: DEFAULT_TRACE_LIMIT,
+            }),
+        }
+    }
+
+    /// Keeps only the last `limit` runs, but at least one, dropping the oldest ones.
+    /// Each run holds the input and the serialized observers, so large maps need a lower limit.
+    #[must_use]
+    pub fn with_trace_limit(mut self, limit: usize) -> Self {
+        let observers = self.observers.get_mut();
+        observers.limit = limit.max(1);
+        let excess = observers.trace.len().saturating_sub(observers.limit);
+        observers.trace.drain(..excess);
+        self
+    }
+
+    /// The wrapped executor
+    #[inline]
+    pub fn executor(&self) -> &E {
+        &self.executor
+    }
+
+    /// The wrapped executor (mutable)
+    #[inline]
+    pub fn executor_mut(&mut self) -> &mut E {
+        &mut self.executor
+    }
+
+    /// The last runs recorded so far, the oldest first
+    #[inline]
+    pub fn trace(&self) -> &VecDeque<RunRecord<<E::State as UsesInput>::Input>> {
+        // # Safety
+        // The trace is only changed through `&mut self`
+        unsafe { &(*self.observers.get()).trace }
+    }
+
+    /// Takes the last runs recorded so far, the oldest first, starting a new trace
+    #[inline]
+    pub fn take_trace(&mut self) -> Vec<RunRecord<<E::State as UsesInput>::Input>> {
+        core::mem::take(&mut self.observers.get_mut().trace).into()
+    }
+
+    /// Runs the inputs of a recorded `trace` again, in order.
+    /// Returns the new records, which can be compared to the given `trace`.
+    /// The replayed runs are not added to the trace of this executor.
+    pub fn replay<EM, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut E::State,
+        mgr: &mut EM,
+        trace: &[RunRecord<<E::State as UsesInput>::Input>],
+    ) -> Result<Vec<RunRecord<<E::State as UsesInput>::Input>>, Error>
+    where
+        E: Executor<EM, Z>,
+        EM: UsesState<State = E::State>,
+        Z: UsesState<State = E::State>,
+    {
+        let recorded = core::mem::take(&mut self.observers.get_mut().trace);
+        let mut replayed = Vec::with_capacity(trace.len());
+        for record in trace {
+            self.observers_mut().pre_exec_all(state, &record.input)?;
+            let exit_kind = self.run_target(fuzzer, state, mgr, &record.input)?;
+            self.observers_mut()
+                .post_exec_all(state, &record.input, &exit_kind)?;
+            replayed.extend(self.observers.get_mut().trace.drain(..));
+        }
+        self.observers.get_mut().trace = recorded;
+        Ok(replayed)
+    }
+
+    /// Writes the last runs recorded so far to the given file
+    #[cfg(feature = "std")]
+    pub fn save_trace<P>(&self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        fs::write(path, postcard::to_allocvec(self.trace())?)?;
+        Ok(())
+    }
+
+    /// Loads a trace, previously written using [`RecordingExecutor::save_trace`]
+    #[cfg(feature = "std")]
+    pub fn load_trace<P>(path: P) -> Result<Vec<RunRecord<<E::State as UsesInput>::Input>>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(postcard::from_bytes(&fs::read(path)?)?)
+    }
+}
+
+impl<E, EM, Z> Executor<EM, Z> for RecordingExecutor<E>
+where
+    E: Executor<EM, Z> + HasObservers,
+    E::Observers: Serialize,
+    EM: UsesState<State = E::State>,
+    Z: UsesState<State = E::State>,
+{
+    fn run_target(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut Self::State,
+        mgr: &mut EM,
+        input: &Self::Input,
+    ) -> Result<ExitKind, Error> {
+        let exit_kind = self.executor.run_target(fuzzer, state, mgr, input)?;
+        // Recorded once the observers are done, see `RecordingObserversTuple::post_exec_all`
+        self.observers.get_mut().pending = Some((input.clone(), exit_kind));
+        Ok(exit_kind)
+    }
+}
+
+impl<E> UsesState for RecordingExecutor<E>
+where
+    E: HasObservers,
+{
+    type State = E::State;
+}
+
+impl<E> UsesObservers for RecordingExecutor<E>
+where
+    E: HasObservers,
+    E::Observers: Serialize,
+{
+    type Observers = RecordingObserversTuple<E::Observers, <E::State as UsesInput>::Input>;
+}
+
+impl<E> HasObservers for RecordingExecutor<E>
+where
+    E: HasObservers,
+    E::Observers: Serialize,
+{
+    #[inline]
+    fn observers(&self) -> &Self::Observers {
+        unsafe {
+            self.observers
+                .get()
+                .as_mut()
+                .unwrap()
+                .set(self.executor.observers());
+            self.observers.get().as_ref().unwrap()
+        }
+    }
+
+    #[inline]
+    fn observers_mut(&mut self) -> &mut Self::Observers {
+        let observers = self.observers.get_mut();
+        observers.set(self.executor.observers());
+        observers
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use core::ptr::addr_of_mut;
+
+    use libafl_bolts::{ownedref::OwnedMutSlice, tuples::tuple_list};
+
+    use crate::{
+        executors::{recording::RecordingExecutor, Executor, ExitKind, HasObservers},
+        feedbacks::ConstFeedback,
+        fuzzer::test::test_fuzzer,
+        inputs::{BytesInput, HasBytesVec},
+        observers::{HitcountsMapObserver, ObserversTuple, StdMapObserver},
+        schedulers::QueueScheduler,
+    };
+
+    static mut MAP: [u8; 4] = [0; 4];
+
+    #[test]
+    fn test_record_replay() {
+        let observer = HitcountsMapObserver::new(unsafe {
+            StdMapObserver::from_ownedref(
+                "map",
+                OwnedMutSlice::from_raw_parts_mut(addr_of_mut!(MAP) as *mut u8, 4),
+            )
+        });
+        let mut harness = |input: &BytesInput| {
+            for byte in input.bytes() {
+                unsafe {
+                    (*addr_of_mut!(MAP))[*byte as usize % 4] += 1;
+                }
+            }
+            ExitKind::Ok
+        };
+        let (mut fuzzer, executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(observer),
+        );
+        // Only the last three runs are kept
+        let mut executor = RecordingExecutor::new(executor).with_trace_limit(3);
+
+        for input in [vec![1], vec![0, 1], vec![2, 2, 2], vec![3, 0, 1, 3]] {
+            let input = BytesInput::new(input);
+            executor
+                .observers_mut()
+                .pre_exec_all(&mut state, &input)
+                .unwrap();
+            let exit_kind = executor
+                .run_target(&mut fuzzer, &mut state, &mut mgr, &input)
+                .unwrap();
+            // Not recorded before the observers are done
+            assert!(executor.trace().back().map_or(true, |r| r.input != input));
+            executor
+                .observers_mut()
+                .post_exec_all(&mut state, &input, &exit_kind)
+                .unwrap();
+
+            if input.bytes() == [2, 2, 2] {
+                // The three hits got classified into the bucket of 4, before recording
+                assert_eq!(unsafe { (*addr_of_mut!(MAP))[2] }, 4);
+            }
+            let record = executor.trace().back().unwrap();
+            assert_eq!(record.input, input);
+            assert_eq!(
+                record.observers,
+                postcard::to_allocvec(executor.observers()).unwrap()
+            );
+        }
+
+        let trace = executor.take_trace();
+        assert_eq!(trace.len(), 3);
+        assert_eq!(trace[0].input.bytes(), [0, 1]);
+
+        let replayed = executor
+            .replay(&mut fuzzer, &mut state, &mut mgr, &trace)
+            .unwrap();
+        assert_eq!(trace, replayed);
+        assert!(executor.trace().is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+//! A [`RetryExecutor`] wraps an executor and retries runs that failed with a transient error.
+//!
+//! Some targets intermittently fail to start, for example due to a lack of resources.
+//! Genuine crashes are reported as [`ExitKind`] by the inner executor and are never retried.
+
+use crate::{
+    executors::{Executor, ExitKind, HasObservers},
+    observers::{ObserversTuple, UsesObservers},
+    state::UsesState,
+    Error,
+};
+
+/// The default number of retries of a [`RetryExecutor`]
+pub const DEFAULT_MAX_RETRIES: usize = 3;
+
+/// The default check, if an [`Error`] is transient, i.e., if it makes sense to retry the run.
+///
+/// Only OS errors, for example a target that failed to start for a lack of resources, are considered transient.
+/// Use [`RetryExecutor::with_transient_check`] to retry other errors, too.
+#[must_use]
+pub fn is_transient_error(error: &Error) -> bool {
+    #[cfg(feature = "std")]
+    if matches!(error, Error::OsError(..)) {
+        return true;
+    }
+    #[cfg(not(feature = "std"))]
+    let _ = error;
+    false
+}
+
+/// An executor that retries `run_target` on the wrapped executor, if it fails with a transient error.
+///
+/// Only errors are retried: any [`ExitKind`], including [`ExitKind::Crash`], is returned as-is.
+/// The observers are reset with `pre_exec_all` before each retry, so that nothing of the failed run leaks into the next one.
+#[derive(Debug)]
+pub struct RetryExecutor<E> {
+    /// The wrapped executor
+    executor: E,
+    /// The maximum number of retries, after the first try
+    max_retries: usize,
+    /// Decides, if an error is transient
+    is_transient: fn(&Error) -> bool,
+    /// The number of retries so far
+    retries: u64,
+}
+
+impl<E> RetryExecutor<E> {
+    /// Create a new [`RetryExecutor`], retrying each run up to [`DEFAULT_MAX_RETRIES`] times
+    pub fn new(executor: E) -> Self {
+        Self::with_max_retries(executor, DEFAULT_MAX_RETRIES)
+    }
+
+    /// Create a new [`RetryExecutor`], retrying each run up to `max_retries` times
+    pub fn with_max_retries(executor: E, max_retries: usize) -> Self {
+        Self {
+            executor,
+            max_retries,
+            is_transient: is_transient_error,
+            retries: 0,
+        }
+    }
+
+    /// Use a custom check to decide, if an [`Error`] is transient, instead of [`is_transient_error`]
+    #[must_use]
+    pub fn with_transient_check(mut self, is_transient: fn(&Error) -> bool) -> Self {
+        self.is_transient = is_transient;
+        self
+    }
+
+    /// The wrapped executor
+    #[inline]
+    pub fn executor(&self) -> &E {
+        &self.executor
+    }
+
+    /// The wrapped executor (mutable)
+    #[inline]
+    pub fn executor_mut(&mut self) -> &mut E {
+        &mut self.executor
+    }
+
+    /// The number of retries done so far, over all runs
+    #[must_use]
+    #[inline]
+    pub fn retries(&self) -> u64 {
+        self.retries
+    }
+}
+
+impl<E, EM, Z> Executor<EM, Z> for RetryExecutor<E>
+where
+    E: Executor<EM, Z> + HasObservers,
+    EM: UsesState<State = E::State>,
+    Z: UsesState<State = E::State>,
+{
+    fn run_target(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut Self::State,
+        mgr: &mut EM,
+        input: &Self::Input,
+    ) -> Result<ExitKind, Error> {
+        let mut tries = 0;
+        loop {
+            match self.executor.run_target(fuzzer, state, mgr, input) {
+                Err(err) if tries < self.max_retries && (self.is_transient)(&err) => {
+                    log::warn!("Retrying run after a transient error: {err:?}");
+                    tries += 1;
+                    self.retries += 1;
+                    self.executor.observers_mut().pre_exec_all(state, input)?;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+impl<E> UsesState for RetryExecutor<E>
+where
+    E: UsesState,
+{
+    type State = E::State;
+}
+
+impl<E> UsesObservers for RetryExecutor<E>
+where
+    E: UsesObservers,
+{
+    type Observers = E::Observers;
+}
+
+impl<E> HasObservers for RetryExecutor<E>
+where
+    E: HasObservers,
+{
+    #[inline]
+    fn observers(&self) -> &Self::Observers {
+        self.executor.observers()
+    }
+
+    #[inline]
+    fn observers_mut(&mut self) -> &mut Self::Observers {
+        self.executor.observers_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::marker::PhantomData;
+    #[cfg(feature = "std")]
+    use std::io;
+
+    use libafl_bolts::{tuples::tuple_list, Named};
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        events::NopEventManager,
+        executors::{retry::RetryExecutor, Executor, ExitKind, HasObservers},
+        fuzzer::test::NopFuzzer,
+        inputs::{BytesInput, UsesInput},
+        observers::{Observer, UsesObservers},
+        state::{NopState, State, UsesState},
+        Error,
+    };
+
+    /// Counts the runs it observed
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    struct PreExecCounter {
+        count: usize,
+    }
+
+    impl Named for PreExecCounter {
+        fn name(&self) -> &str {
+            "pre_exec_counter"
+        }
+    }
+
+    impl<S> Observer<S> for PreExecCounter
+    where
+        S: UsesInput,
+    {
+        fn pre_exec(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
+            self.count += 1;
+            Ok(())
+        }
+    }
+
+    /// Returns the given results, one per run
+    struct ScriptedExecutor<S> {
+        results: Vec<Result<ExitKind, Error>>,
+        runs: usize,
+        observers: (PreExecCounter, ()),
+        phantom: PhantomData<S>,
+    }
+
+    impl<S> ScriptedExecutor<S> {
+        fn new(mut results: Vec<Result<ExitKind, Error>>) -> Self {
+            results.reverse();
+            Self {
+                results,
+                runs: 0,
+                observers: tuple_list!(PreExecCounter::default()),
+                phantom: PhantomData,
+            }
+        }
+    }
+
+    impl<S> UsesState for ScriptedExecutor<S>
+    where
+        S: State,
+    {
+        type State = S;
+    }
+
+    impl<S> UsesObservers for ScriptedExecutor<S>
+    where
+        S: State,
+    {
+        type Observers = (PreExecCounter, ());
+    }
+
+    impl<S> HasObservers for ScriptedExecutor<S>
+    where
+        S: State,
+    {
+        fn observers(&self) -> &Self::Observers {
+            &self.observers
+        }
+
+        fn observers_mut(&mut self) -> &mut Self::Observers {
+            &mut self.observers
+        }
+    }
+
+    impl<EM, S, Z> Executor<EM, Z> for ScriptedExecutor<S>
+    where
+        EM: UsesState<State = S>,
+        S: State,
+        Z: UsesState<State = S>,
+    {
+        fn run_target(
+            &mut self,
+            _fuzzer: &mut Z,
+            _state: &mut S,
+            _mgr: &mut EM,
+            _input: &S::Input,
+        ) -> Result<ExitKind, Error> {
+            self.runs += 1;
+            self.results.pop().unwrap()
+        }
+    }
+
+    fn run(
+        executor: &mut RetryExecutor<ScriptedExecutor<NopState<BytesInput>>>,
+    ) -> Result<ExitKind, Error> {
+        executor.run_target(
+            &mut NopFuzzer::new(),
+            &mut NopState::new(),
+            &mut NopEventManager::new(),
+            &BytesInput::new(vec![0x41]),
+        )
+    }
+
+    #[cfg(feature = "std")]
+    fn start_failure() -> Error {
+        Error::os_error(
+            io::Error::from(io::ErrorKind::WouldBlock),
+            "Target did not start",
+        )
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_retry_executor() {
+        // Fails to start twice, then runs
+        let mut executor = RetryExecutor::new(ScriptedExecutor::new(vec![
+            Err(start_failure()),
+            Err(start_failure()),
+            Ok(ExitKind::Ok),
+        ]));
+        assert_eq!(run(&mut executor).unwrap(), ExitKind::Ok);
+        assert_eq!(executor.executor().runs, 3);
+        assert_eq!(executor.retries(), 2);
+        // The observers got reset before each retry
+        assert_eq!(executor.observers().0.count, 2);
+
+        // Crashes are not retried
+        let mut executor = RetryExecutor::new(ScriptedExecutor::new(vec![
+            Ok(ExitKind::Crash),
+            Ok(ExitKind::Ok),
+        ]));
+        assert_eq!(run(&mut executor).unwrap(), ExitKind::Crash);
+        assert_eq!(executor.executor().runs, 1);
+
+        // Neither are other errors, by default
+        let mut executor = RetryExecutor::new(ScriptedExecutor::new(vec![
+            Err(Error::unknown("Target did not start")),
+            Ok(ExitKind::Ok),
+        ]));
+        assert!(run(&mut executor).is_err());
+        assert_eq!(executor.executor().runs, 1);
+
+        // Unless they are considered transient, too
+        let mut executor = RetryExecutor::new(ScriptedExecutor::new(vec![
+            Err(Error::unknown("Target did not start")),
+            Ok(ExitKind::Ok),
+        ]))
+        .with_transient_check(|err| matches!(err, Error::Unknown(..)));
+        assert_eq!(run(&mut executor).unwrap(), ExitKind::Ok);
+        assert_eq!(executor.executor().runs, 2);
+
+        // Give up after the maximum number of retries
+        let mut executor = RetryExecutor::with_max_retries(
+            ScriptedExecutor::new(vec![
+                Err(start_failure()),
+                Err(start_failure()),
+                Ok(ExitKind::Ok),
+            ]),
+            1,
+        );
+        assert!(run(&mut executor).is_err());
+        assert_eq!(executor.executor().runs, 2);
+    }
+}
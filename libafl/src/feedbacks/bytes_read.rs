@@ -0,0 +1,186 @@
+//! The [`BytesReadFeedback`] records how many bytes of a new testcase the harness actually consumed,
+//! as observed by a [`BytesReadObserver`].
+
+use alloc::string::{String, ToString};
+
+use libafl_bolts::{impl_serdeany, HasLen, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::Testcase,
+    events::EventFirer,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverName},
+    observers::{BytesReadObserver, ObserversTuple},
+    state::State,
+    Error, HasMetadata,
+};
+
+/// The number of input bytes the harness consumed, attached to a testcase by the [`BytesReadFeedback`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct BytesReadMetadata {
+    /// The number of bytes the harness read
+    pub bytes_read: usize,
+    /// The length of the input
+    pub input_len: usize,
+}
+
+impl_serdeany!(BytesReadMetadata);
+
+impl BytesReadMetadata {
+    /// The number of input bytes the harness did not read, which could be trimmed
+    #[must_use]
+    pub fn unread(&self) -> usize {
+        self.input_len.saturating_sub(self.bytes_read)
+    }
+}
+
+/// Nop feedback that annotates new testcases with the [`BytesReadMetadata`] of their run, if the harness reported it.
+/// For this feedback, the testcase is never interesting (use with an OR).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BytesReadFeedback {
+    observer_name: String,
+    /// The metadata of the last run
+    last: Option<BytesReadMetadata>,
+}
+
+impl BytesReadFeedback {
+    /// Creates a new [`BytesReadFeedback`] for the given [`BytesReadObserver`]
+    #[must_use]
+    pub fn new(observer: &BytesReadObserver) -> Self {
+        Self {
+            observer_name: observer.name().to_string(),
+            last: None,
+        }
+    }
+}
+
+impl<S> Feedback<S> for BytesReadFeedback
+where
+    S: State,
+    S::Input: HasLen,
+{
+    #[allow(clippy::wrong_self_convention)]
+    fn is_interesting<EM, OT>(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        input: &S::Input,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let observer = observers
+            .match_name::<BytesReadObserver>(&self.observer_name)
+            .ok_or(Error::illegal_state("BytesReadObserver is missing"))?;
+        self.last = observer
+            .last_bytes_read()
+            .map(|bytes_read| BytesReadMetadata {
+                bytes_read,
+                input_len: input.len(),
+            });
+        Ok(false)
+    }
+
+    fn append_metadata<EM, OT>(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        testcase: &mut Testcase<S::Input>,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<S>,
+        EM: EventFirer<State = S>,
+    {
+        if let Some(meta) = self.last.take() {
+            testcase.add_metadata(meta);
+        }
+        Ok(())
+    }
+
+    fn discard_metadata(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
+        self.last = None;
+        Ok(())
+    }
+}
+
+impl Named for BytesReadFeedback {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.observer_name
+    }
+}
+
+impl HasObserverName for BytesReadFeedback {
+    #[inline]
+    fn observer_name(&self) -> &str {
+        &self.observer_name
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use libafl_bolts::tuples::tuple_list;
+
+    use crate::{
+        corpus::Corpus,
+        executors::ExitKind,
+        feedback_or,
+        feedbacks::{BytesReadFeedback, BytesReadMetadata, ConstFeedback},
+        fuzzer::test::test_fuzzer,
+        inputs::BytesInput,
+        observers::{report_bytes_read, BytesReadObserver},
+        schedulers::QueueScheduler,
+        state::HasCorpus,
+        Evaluator, HasMetadata,
+    };
+
+    #[test]
+    fn test_bytes_read_feedback() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            BytesReadMetadata::register();
+        }
+
+        // The harness only looks at a 4 byte header
+        let mut harness = |_input: &BytesInput| {
+            report_bytes_read(4);
+            ExitKind::Ok
+        };
+        let observer = BytesReadObserver::new("bytes_read");
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            feedback_or!(ConstFeedback::new(true), BytesReadFeedback::new(&observer)),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(observer),
+        );
+
+        let (_, id) = fuzzer
+            .evaluate_input(
+                &mut state,
+                &mut executor,
+                &mut mgr,
+                BytesInput::new(vec![0; 100]),
+            )
+            .unwrap();
+
+        let testcase = state.corpus().get(id.unwrap()).unwrap().borrow();
+        let meta = testcase.metadata::<BytesReadMetadata>().unwrap();
+        assert_eq!(meta.bytes_read, 4);
+        assert_eq!(meta.input_len, 100);
+        assert_eq!(meta.unread(), 96);
+    }
+}
@@ -0,0 +1,327 @@
+//! Rough exploitability classification of crashes, to help with triage.
+//!
+//! The crash handler records a [`CrashInfo`] for the last crash, using [`record_crash_info`].
+//! The [`ExploitabilityFeedback`] then picks it up and attaches an [`ExploitabilityMetadata`] to the solution.
+
+use core::ptr::addr_of_mut;
+
+#[cfg(unix)]
+use libafl_bolts::os::unix_signals::{siginfo_t, ucontext_t, Signal};
+use libafl_bolts::{impl_serdeany, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::Testcase, events::EventFirer, executors::ExitKind, feedbacks::Feedback,
+    observers::ObserversTuple, state::State, Error, HasMetadata,
+};
+
+/// Faulting addresses below this value are considered to be a (near) null pointer dereference
+pub const NEAR_NULL_THRESHOLD: usize = 0x1_0000;
+
+/// The last crash, as reported by the crash handler
+static mut LAST_CRASH_INFO: Option<CrashInfo> = None;
+
+/// The kind of memory access that led to a crash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MemoryAccess {
+    /// The target crashed reading memory
+    Read,
+    /// The target crashed writing memory
+    Write,
+    /// The target crashed executing memory
+    Execute,
+    /// The kind of access is not known (or not reported on this platform)
+    Unknown,
+}
+
+/// A rough exploitability rating of a crash.
+/// The ratings are ordered, a higher rating is more likely to be exploitable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Exploitability {
+    /// The crash is most likely not exploitable, for example a null pointer read
+    ProbablyNotExploitable,
+    /// Not enough information to decide
+    Unknown,
+    /// The crash may be exploitable, for example a wild read
+    ProbablyExploitable,
+    /// The crash is likely exploitable, for example a wild write
+    Exploitable,
+}
+
+/// Information about a crash, as reported by the crash handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashInfo {
+    /// The signal (or exception) number of the crash
+    pub signal: i32,
+    /// The faulting address, if the crash was caused by an invalid memory access
+    pub fault_addr: Option<usize>,
+    /// The kind of memory access that caused the crash
+    pub access: MemoryAccess,
+}
+
+impl CrashInfo {
+    /// Creates a new [`CrashInfo`]
+    #[must_use]
+    pub fn new(signal: i32, fault_addr: Option<usize>, access: MemoryAccess) -> Self {
+        Self {
+            signal,
+            fault_addr,
+            access,
+        }
+    }
+
+    /// Creates a new [`CrashInfo`] from the arguments passed to a unix signal handler.
+    /// The kind of memory access can only be inferred on `x86_64` Linux, for now.
+    #[cfg(unix)]
+    #[must_use]
+    pub fn from_signal(signal: Signal, info: &siginfo_t, context: Option<&ucontext_t>) -> Self {
+        let fault_addr = match signal {
+            Signal::SigSegmentationFault | Signal::SigBus => {
+                #[cfg(target_os = "android")]
+                let si_addr = (info._pad[0] as usize) | ((info._pad[1] as usize) << 32);
+                #[cfg(not(target_os = "android"))]
+                let si_addr = unsafe { info.si_addr() as usize };
+                Some(si_addr)
+            }
+            _ => None,
+        };
+
+        let access = match signal {
+            Signal::SigIllegalInstruction => MemoryAccess::Execute,
+            Signal::SigSegmentationFault | Signal::SigBus => Self::access_from_context(context),
+            _ => MemoryAccess::Unknown,
+        };
+
+        Self::new(signal as i32, fault_addr, access)
+    }
+
+    /// Reads the page fault error code from the context to find the kind of access
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn access_from_context(context: Option<&ucontext_t>) -> MemoryAccess {
+        /// The page fault trap number
+        const PAGE_FAULT: i64 = 14;
+
+        let Some(context) = context else {
+            return MemoryAccess::Unknown;
+        };
+        let gregs = &context.uc_mcontext.gregs;
+        if gregs[libc::REG_TRAPNO as usize] != PAGE_FAULT {
+            return MemoryAccess::Unknown;
+        }
+
+        let err = gregs[libc::REG_ERR as usize];
+        if err & 0x10 != 0 {
+            MemoryAccess::Execute
+        } else if err & 0x2 != 0 {
+            MemoryAccess::Write
+        } else {
+            MemoryAccess::Read
+        }
+    }
+
+    /// Reads the page fault error code from the context to find the kind of access
+    #[cfg(all(unix, not(all(target_os = "linux", target_arch = "x86_64"))))]
+    fn access_from_context(_context: Option<&ucontext_t>) -> MemoryAccess {
+        MemoryAccess::Unknown
+    }
+
+    /// If the faulting address is close to null
+    #[must_use]
+    pub fn is_near_null(&self) -> bool {
+        matches!(self.fault_addr, Some(addr) if addr < NEAR_NULL_THRESHOLD)
+    }
+
+    /// Classifies this crash into a rough [`Exploitability`] rating
+    #[must_use]
+    pub fn classify(&self) -> Exploitability {
+        if self.fault_addr.is_none() {
+            // Aborts, illegal instructions, ...
+            return match self.access {
+                MemoryAccess::Execute => Exploitability::ProbablyExploitable,
+                _ => Exploitability::Unknown,
+            };
+        }
+
+        if self.is_near_null() {
+            return match self.access {
+                MemoryAccess::Execute => Exploitability::Unknown,
+                _ => Exploitability::ProbablyNotExploitable,
+            };
+        }
+
+        match self.access {
+            MemoryAccess::Write | MemoryAccess::Execute => Exploitability::Exploitable,
+            MemoryAccess::Read => Exploitability::ProbablyExploitable,
+            MemoryAccess::Unknown => Exploitability::Unknown,
+        }
+    }
+}
+
+/// Records the [`CrashInfo`] of the current crash, to be picked up by the [`ExploitabilityFeedback`].
+/// This is meant to be called from crash handlers.
+pub fn record_crash_info(info: CrashInfo) {
+    // # Safety
+    // Crashes are handled one at a time, from the thread that runs the target
+    unsafe {
+        *addr_of_mut!(LAST_CRASH_INFO) = Some(info);
+    }
+}
+
+/// Takes the [`CrashInfo`] recorded by the last crash handler, if any
+#[must_use]
+pub fn take_crash_info() -> Option<CrashInfo> {
+    // # Safety
+    // Crashes are handled one at a time, from the thread that runs the target
+    unsafe { (*addr_of_mut!(LAST_CRASH_INFO)).take() }
+}
+
+/// The exploitability rating of a solution, attached by the [`ExploitabilityFeedback`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct ExploitabilityMetadata {
+    /// The crash, as reported by the crash handler
+    pub crash_info: CrashInfo,
+    /// The rough exploitability rating of this crash
+    pub exploitability: Exploitability,
+}
+
+impl_serdeany!(ExploitabilityMetadata);
+
+impl ExploitabilityMetadata {
+    /// Creates a new [`ExploitabilityMetadata`], classifying the given crash
+    #[must_use]
+    pub fn new(crash_info: CrashInfo) -> Self {
+        Self {
+            crash_info,
+            exploitability: crash_info.classify(),
+        }
+    }
+}
+
+/// Nop feedback that annotates solutions with an [`ExploitabilityMetadata`],
+/// based on the [`CrashInfo`] reported by the crash handler.
+/// The testcase is never interesting, use it with an eager OR (i.e. [`crate::feedback_or`])
+/// next to a [`crate::feedbacks::CrashFeedback`] in the objective.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ExploitabilityFeedback {}
+
+impl<S> Feedback<S> for ExploitabilityFeedback
+where
+    S: State,
+{
+    #[allow(clippy::wrong_self_convention)]
+    #[inline]
+    fn is_interesting<EM, OT>(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &S::Input,
+        _observers: &OT,
+        exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        if *exit_kind != ExitKind::Crash {
+            // Don't attach stale information to timeouts and the like
+            let _ = take_crash_info();
+        }
+        Ok(false)
+    }
+
+    /// Append the exploitability rating of the last crash, if any, to the testcase
+    #[inline]
+    fn append_metadata<EM, OT>(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        testcase: &mut Testcase<S::Input>,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<S>,
+        EM: EventFirer<State = S>,
+    {
+        if let Some(crash_info) = take_crash_info() {
+            testcase.add_metadata(ExploitabilityMetadata::new(crash_info));
+        }
+        Ok(())
+    }
+
+    /// Discard the recorded crash in case that the testcase is not added to the corpus
+    #[inline]
+    fn discard_metadata(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
+        let _ = take_crash_info();
+        Ok(())
+    }
+}
+
+impl Named for ExploitabilityFeedback {
+    #[inline]
+    fn name(&self) -> &str {
+        "ExploitabilityFeedback"
+    }
+}
+
+impl ExploitabilityFeedback {
+    /// Creates a new [`ExploitabilityFeedback`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        corpus::Testcase,
+        events::NopEventManager,
+        feedbacks::{
+            exploitability::{
+                record_crash_info, CrashInfo, Exploitability, ExploitabilityFeedback,
+                ExploitabilityMetadata, MemoryAccess,
+            },
+            Feedback,
+        },
+        inputs::BytesInput,
+        state::NopState,
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_exploitability_ordering() {
+        let wild_write = CrashInfo::new(11, Some(0x4141_4141), MemoryAccess::Write);
+        let null_read = CrashInfo::new(11, Some(0x8), MemoryAccess::Read);
+
+        assert_eq!(wild_write.classify(), Exploitability::Exploitable);
+        assert_eq!(null_read.classify(), Exploitability::ProbablyNotExploitable);
+        assert!(wild_write.classify() > null_read.classify());
+    }
+
+    #[test]
+    fn test_exploitability_feedback() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            ExploitabilityMetadata::register();
+        }
+
+        let mut state = NopState::<BytesInput>::new();
+        let mut mgr = NopEventManager::new();
+        let mut feedback = ExploitabilityFeedback::new();
+        let mut testcase = Testcase::new(BytesInput::new(vec![0x41]));
+
+        record_crash_info(CrashInfo::new(11, Some(0x4141_4141), MemoryAccess::Write));
+        feedback
+            .append_metadata(&mut state, &mut mgr, &(), &mut testcase)
+            .unwrap();
+
+        let meta = testcase.metadata::<ExploitabilityMetadata>().unwrap();
+        assert_eq!(meta.exploitability, Exploitability::Exploitable);
+    }
+}
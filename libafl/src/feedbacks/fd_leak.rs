@@ -0,0 +1,180 @@
+//! The [`FdLeakFeedback`] reports inputs that make the harness leak file descriptors,
+//! as counted by a [`FdCountObserver`].
+
+use alloc::string::{String, ToString};
+
+use libafl_bolts::{impl_serdeany, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::Testcase,
+    events::EventFirer,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverName},
+    observers::{FdCountObserver, ObserversTuple},
+    state::State,
+    Error, HasMetadata,
+};
+
+/// The number of leaked file descriptors, attached to a testcase found by the [`FdLeakFeedback`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct FdLeakMetadata {
+    /// The number of file descriptors the run opened, but did not close
+    pub leaked: usize,
+}
+
+impl_serdeany!(FdLeakMetadata);
+
+/// A [`Feedback`] that deems an input interesting, if the run leaked more than `threshold` file descriptors.
+///
+/// Use it as objective, to collect resource leaks as solutions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FdLeakFeedback {
+    name: String,
+    observer_name: String,
+    /// The number of leaked file descriptors that are still tolerated
+    threshold: usize,
+    /// The number of leaked file descriptors of the last interesting run
+    last_leaked: Option<usize>,
+}
+
+impl FdLeakFeedback {
+    /// Creates a new [`FdLeakFeedback`], reporting every run that leaks at least one file descriptor
+    #[must_use]
+    pub fn new(observer: &FdCountObserver) -> Self {
+        Self::with_threshold(observer, 0)
+    }
+
+    /// Creates a new [`FdLeakFeedback`], reporting runs that leak more than `threshold` file descriptors
+    #[must_use]
+    pub fn with_threshold(observer: &FdCountObserver, threshold: usize) -> Self {
+        Self {
+            name: format!("FdLeakFeedback_{}", observer.name()),
+            observer_name: observer.name().to_string(),
+            threshold,
+            last_leaked: None,
+        }
+    }
+}
+
+impl<S> Feedback<S> for FdLeakFeedback
+where
+    S: State,
+{
+    #[allow(clippy::wrong_self_convention)]
+    fn is_interesting<EM, OT>(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &S::Input,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let observer = observers
+            .match_name::<FdCountObserver>(&self.observer_name)
+            .ok_or(Error::illegal_state("FdCountObserver is missing"))?;
+        let leaked = observer.leaked();
+        if leaked > self.threshold {
+            self.last_leaked = Some(leaked);
+            Ok(true)
+        } else {
+            self.last_leaked = None;
+            Ok(false)
+        }
+    }
+
+    fn append_metadata<EM, OT>(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        testcase: &mut Testcase<S::Input>,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<S>,
+        EM: EventFirer<State = S>,
+    {
+        if let Some(leaked) = self.last_leaked.take() {
+            testcase.add_metadata(FdLeakMetadata { leaked });
+        }
+        Ok(())
+    }
+
+    fn discard_metadata(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
+        self.last_leaked = None;
+        Ok(())
+    }
+}
+
+impl Named for FdLeakFeedback {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl HasObserverName for FdLeakFeedback {
+    #[inline]
+    fn observer_name(&self) -> &str {
+        &self.observer_name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use std::fs::File;
+
+    use libafl_bolts::tuples::tuple_list;
+
+    use crate::{
+        events::NopEventManager,
+        executors::ExitKind,
+        feedbacks::{fd_leak::FdLeakFeedback, Feedback},
+        inputs::{BytesInput, HasBytesVec},
+        observers::{FdCountObserver, ObserversTuple},
+        state::test::test_std_state_with,
+    };
+
+    #[test]
+    fn test_fd_leak_feedback() {
+        let observer = FdCountObserver::new("fds");
+        // Some slack for other tests opening files at the same time
+        let mut feedback = FdLeakFeedback::with_threshold(&observer, 8);
+        let mut state = test_std_state_with(&mut feedback, &mut ());
+        let mut mgr = NopEventManager::new();
+        let mut observers = tuple_list!(observer);
+
+        let mut leaked = Vec::new();
+        let mut run = |input: &BytesInput| {
+            observers.pre_exec_all(&mut state, input).unwrap();
+            // Leaks 32 file descriptors for inputs starting with `L`
+            let files: Vec<_> = (0..32)
+                .map(|_| File::open("/proc/self/cmdline").unwrap())
+                .collect();
+            if input.bytes()[0] == b'L' {
+                leaked.extend(files);
+            } else {
+                drop(files);
+            }
+            observers
+                .post_exec_all(&mut state, input, &ExitKind::Ok)
+                .unwrap();
+            feedback
+                .is_interesting(&mut state, &mut mgr, input, &observers, &ExitKind::Ok)
+                .unwrap()
+        };
+
+        assert!(!run(&BytesInput::new(b"ok".to_vec())));
+        assert!(run(&BytesInput::new(b"Leak".to_vec())));
+        assert!(!run(&BytesInput::new(b"ok again".to_vec())));
+    }
+}
@@ -0,0 +1,215 @@
+//! The [`NewFunctionFeedback`] reports each function of the target the first time it is entered.
+//!
+//! Reaching a new function is a coarser, but more meaningful, event than reaching a new edge:
+//! it often means the fuzzer reached a new feature of the target.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::marker::PhantomData;
+
+use hashbrown::HashSet;
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::{EventFirer, LogSeverity},
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverName},
+    observers::{FunctionEntryObserver, MapObserver, ObserversTuple},
+    state::State,
+    Error, HasNamedMetadata,
+};
+
+/// The map indexes of all functions a [`NewFunctionFeedback`] has seen entered so far
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct FunctionEntryMetadata {
+    /// The map indexes of the entered functions
+    pub entered: HashSet<usize>,
+}
+
+libafl_bolts::impl_serdeany!(FunctionEntryMetadata);
+
+impl FunctionEntryMetadata {
+    /// Creates a new, empty [`FunctionEntryMetadata`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// A [`Feedback`] that deems an input interesting, if it entered a function that was never entered before,
+/// as observed by a [`FunctionEntryObserver`].
+///
+/// Each newly reached function is logged once, by its symbol name, as an [`LogSeverity::Info`] event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewFunctionFeedback<M> {
+    name: String,
+    observer_name: String,
+    phantom: PhantomData<M>,
+}
+
+impl<M> NewFunctionFeedback<M>
+where
+    M: MapObserver,
+{
+    /// Creates a new [`NewFunctionFeedback`], looking at the given [`FunctionEntryObserver`]
+    #[must_use]
+    pub fn new(observer: &FunctionEntryObserver<M>) -> Self {
+        Self {
+            name: format!("NewFunctionFeedback_{}", observer.name()),
+            observer_name: observer.name().to_string(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<M, S> Feedback<S> for NewFunctionFeedback<M>
+where
+    M: MapObserver,
+    S: State + HasNamedMetadata,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        state.add_named_metadata(&self.name, FunctionEntryMetadata::new());
+        Ok(())
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn is_interesting<EM, OT>(
+        &mut self,
+        state: &mut S,
+        manager: &mut EM,
+        _input: &S::Input,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let observer = observers
+            .match_name::<FunctionEntryObserver<M>>(&self.observer_name)
+            .ok_or(Error::illegal_state("FunctionEntryObserver is missing"))?;
+
+        let metadata = state
+            .named_metadata_map_mut()
+            .get_mut::<FunctionEntryMetadata>(&self.name)
+            .ok_or(Error::illegal_state(
+                "FunctionEntryMetadata is missing, init_state was not called",
+            ))?;
+        let new_functions: Vec<usize> = observer
+            .entered()
+            .into_iter()
+            .filter(|idx| metadata.entered.insert(*idx))
+            .collect();
+
+        for idx in &new_functions {
+            let message = match observer.symbol(*idx) {
+                Some(symbol) => format!("New function reached: {symbol}"),
+                None => format!("New function reached: #{idx}"),
+            };
+            manager.log(state, LogSeverity::Info, message)?;
+        }
+        Ok(!new_functions.is_empty())
+    }
+}
+
+impl<M> Named for NewFunctionFeedback<M> {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<M> HasObserverName for NewFunctionFeedback<M> {
+    #[inline]
+    fn observer_name(&self) -> &str {
+        &self.observer_name
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use alloc::vec::Vec;
+
+    use libafl_bolts::{tuples::tuple_list, AsMutSlice};
+
+    use crate::{
+        events::{test::RecordingEventManager, Event},
+        executors::ExitKind,
+        feedbacks::{function_entry::NewFunctionFeedback, Feedback},
+        inputs::{BytesInput, UsesInput},
+        observers::{FunctionEntryObserver, StdMapObserver},
+        state::test::test_std_state_with,
+    };
+
+    /// The logged messages
+    fn messages<S>(mgr: &RecordingEventManager<S>) -> Vec<&str>
+    where
+        S: UsesInput,
+    {
+        mgr.events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Log { message, .. } => Some(message.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_new_function_feedback() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            crate::feedbacks::function_entry::FunctionEntryMetadata::register();
+        }
+
+        let symbols = vec!["main".into(), "parse_header".into(), "parse_body".into()];
+        let mut observer =
+            FunctionEntryObserver::new(StdMapObserver::owned("functions", vec![0_u8; 3]), symbols);
+        let mut feedback = NewFunctionFeedback::new(&observer);
+
+        let mut state = test_std_state_with(&mut feedback, &mut ());
+        let mut mgr = RecordingEventManager::new();
+        let input = BytesInput::new(vec![0x41]);
+
+        let mut run = |entered: [u8; 3], mgr: &mut RecordingEventManager<_>| {
+            observer.base_mut().as_mut_slice().copy_from_slice(&entered);
+            let observers = tuple_list!(observer.clone());
+            feedback
+                .is_interesting(&mut state, mgr, &input, &observers, &ExitKind::Ok)
+                .unwrap()
+        };
+
+        assert!(run([1, 1, 0], &mut mgr));
+        assert_eq!(
+            messages(&mgr),
+            [
+                "New function reached: main",
+                "New function reached: parse_header"
+            ]
+        );
+        // Entering the same functions again is not reported
+        assert!(!run([1, 1, 0], &mut mgr));
+        assert!(!run([1, 0, 0], &mut mgr));
+        assert_eq!(messages(&mgr).len(), 2);
+
+        assert!(run([1, 1, 1], &mut mgr));
+        assert_eq!(
+            messages(&mgr),
+            [
+                "New function reached: main",
+                "New function reached: parse_header",
+                "New function reached: parse_body"
+            ]
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! The [`NewLogMessageFeedback`] looks at the captured output of the target and keeps inputs
+//! that emit error or warning messages that were never seen before.
+//!
+//! This is useful to catch regressions that only show up as new assertions or log output,
+//! without crashing the target.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use hashbrown::HashSet;
+use libafl_bolts::{hash_std, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::EventFirer,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverName},
+    observers::{ObserversTuple, StdErrObserver, StdOutObserver},
+    state::State,
+    Error, HasNamedMetadata,
+};
+
+/// The keywords a line needs to contain (case-insensitive) to count as an error or warning message
+pub const DEFAULT_LOG_KEYWORDS: &[&str] = &["error", "warning", "assert", "fatal", "panic"];
+
+/// The normalized hashes of all log messages a [`NewLogMessageFeedback`] has seen so far
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct LogMessageFeedbackMetadata {
+    /// The hashes of the normalized messages
+    pub hashes: HashSet<u64>,
+}
+
+libafl_bolts::impl_serdeany!(LogMessageFeedbackMetadata);
+
+impl LogMessageFeedbackMetadata {
+    /// Creates a new, empty [`LogMessageFeedbackMetadata`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// A [`Feedback`] that deems an input interesting, if the output captured by a [`StdErrObserver`]
+/// (or [`StdOutObserver`]) contains an error or warning message that was never seen before.
+///
+/// Messages are normalized before hashing: they are lowercased and every number (including hex numbers,
+/// like addresses) is replaced by a placeholder, so `error at 0x1234` and `error at 0x5678` are the same message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewLogMessageFeedback {
+    name: String,
+    observer_name: String,
+    /// If the observer is a [`StdOutObserver`] instead of a [`StdErrObserver`]
+    stdout: bool,
+    keywords: Vec<String>,
+}
+
+impl NewLogMessageFeedback {
+    fn with_observer_name(observer_name: &str, stdout: bool) -> Self {
+        Self {
+            name: format!("NewLogMessageFeedback_{observer_name}"),
+            observer_name: observer_name.to_string(),
+            stdout,
+            keywords: DEFAULT_LOG_KEYWORDS
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+
+    /// Creates a new [`NewLogMessageFeedback`], looking at the output of the given [`StdErrObserver`]
+    #[must_use]
+    pub fn with_stderr(observer: &StdErrObserver) -> Self {
+        Self::with_observer_name(observer.name(), false)
+    }
+
+    /// Creates a new [`NewLogMessageFeedback`], looking at the output of the given [`StdOutObserver`]
+    #[must_use]
+    pub fn with_stdout(observer: &StdOutObserver) -> Self {
+        Self::with_observer_name(observer.name(), true)
+    }
+
+    /// Replaces the keywords that mark a line as error or warning message.
+    /// Keywords are matched case-insensitively.
+    #[must_use]
+    pub fn with_keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = keywords.iter().map(|k| k.to_lowercase()).collect();
+        self
+    }
+
+    /// Normalizes a log message, lowercasing it and replacing all (hex) numbers with `#`
+    #[must_use]
+    pub fn normalize(line: &str) -> String {
+        let mut normalized = String::with_capacity(line.len());
+        let is_number_part = |c: &char| c.is_ascii_hexdigit() || *c == 'x' || *c == 'X';
+        let mut chars = line.trim().chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_ascii_digit() {
+                while chars.next_if(is_number_part).is_some() {}
+                normalized.push('#');
+            } else {
+                normalized.extend(c.to_lowercase());
+            }
+        }
+        normalized
+    }
+
+    /// The hashes of all normalized error or warning messages in the given output
+    #[must_use]
+    pub fn message_hashes(&self, output: &[u8]) -> Vec<u64> {
+        String::from_utf8_lossy(output)
+            .lines()
+            .map(Self::normalize)
+            .filter(|line| self.keywords.iter().any(|k| line.contains(k.as_str())))
+            .map(|line| hash_std(line.as_bytes()))
+            .collect()
+    }
+
+    fn output<'a, S, OT>(&self, observers: &'a OT) -> Result<Option<&'a [u8]>, Error>
+    where
+        S: State,
+        OT: ObserversTuple<S>,
+    {
+        let output = if self.stdout {
+            observers
+                .match_name::<StdOutObserver>(&self.observer_name)
+                .ok_or(Error::illegal_state("StdOutObserver is missing"))?
+                .stdout
+                .as_deref()
+        } else {
+            observers
+                .match_name::<StdErrObserver>(&self.observer_name)
+                .ok_or(Error::illegal_state("StdErrObserver is missing"))?
+                .stderr
+                .as_deref()
+        };
+        Ok(output)
+    }
+}
+
+impl<S> Feedback<S> for NewLogMessageFeedback
+where
+    S: State + HasNamedMetadata,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        state.add_named_metadata(&self.name, LogMessageFeedbackMetadata::new());
+        Ok(())
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn is_interesting<EM, OT>(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _input: &S::Input,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let Some(output) = self.output::<S, OT>(observers)? else {
+            return Ok(false);
+        };
+        let hashes = self.message_hashes(output);
+        if hashes.is_empty() {
+            return Ok(false);
+        }
+
+        let metadata = state
+            .named_metadata_map_mut()
+            .get_mut::<LogMessageFeedbackMetadata>(&self.name)
+            .ok_or(Error::illegal_state(
+                "LogMessageFeedbackMetadata is missing, init_state was not called",
+            ))?;
+        let mut interesting = false;
+        for hash in hashes {
+            interesting |= metadata.hashes.insert(hash);
+        }
+        Ok(interesting)
+    }
+}
+
+impl Named for NewLogMessageFeedback {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl HasObserverName for NewLogMessageFeedback {
+    #[inline]
+    fn observer_name(&self) -> &str {
+        &self.observer_name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::{tuples::tuple_list, Named};
+
+    use crate::{
+        events::NopEventManager,
+        executors::ExitKind,
+        feedbacks::{
+            log_message::{LogMessageFeedbackMetadata, NewLogMessageFeedback},
+            Feedback,
+        },
+        inputs::BytesInput,
+        observers::StdErrObserver,
+        state::test::test_std_state_with,
+        HasNamedMetadata,
+    };
+
+    #[test]
+    fn test_new_log_message_feedback() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            LogMessageFeedbackMetadata::register();
+        }
+
+        let mut observer = StdErrObserver::new("stderr".into());
+        let mut feedback = NewLogMessageFeedback::with_stderr(&observer);
+        let mut state = test_std_state_with(&mut feedback, &mut ());
+        let mut mgr = NopEventManager::new();
+        let input = BytesInput::new(vec![0x41]);
+
+        let mut run = |stderr: &str, observer: &mut StdErrObserver| {
+            observer.stderr = Some(stderr.as_bytes().to_vec());
+            let observers = tuple_list!(observer.clone());
+            feedback
+                .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+                .unwrap()
+        };
+
+        assert!(run(
+            "starting\nWARNING: buffer at 0x7ffd1234 is too small\n",
+            &mut observer
+        ));
+        // Same warning, different address
+        assert!(!run(
+            "starting\nwarning: buffer at 0x7ffd5678 is too small\n",
+            &mut observer
+        ));
+        // No error or warning at all
+        assert!(!run("all good\n", &mut observer));
+        // A new message
+        assert!(run("error: assertion `len > 0` failed\n", &mut observer));
+
+        let metadata = state
+            .named_metadata::<LogMessageFeedbackMetadata>(feedback.name())
+            .unwrap();
+        assert_eq!(metadata.hashes.len(), 2);
+    }
+}
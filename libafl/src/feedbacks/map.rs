@@ -12,18 +12,24 @@ use core::{
     ops::{BitAnd, BitOr},
 };
 
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
+#[cfg(feature = "std")]
+use libafl_bolts::fs::write_file_atomic;
 use libafl_bolts::{AsIter, AsMutSlice, AsSlice, HasRefCnt, Named};
 use num_traits::PrimInt;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
     corpus::Testcase,
-    events::{Event, EventFirer},
+    events::{Event, EventFirer, LogSeverity},
     executors::ExitKind,
     feedbacks::{Feedback, HasObserverName},
     inputs::UsesInput,
     monitors::{AggregatorOps, UserStats, UserStatsValue},
     observers::{CanTrack, MapObserver, Observer, ObserversTuple, UsesObserver},
+    schedulers::SchedulerMetadata,
     state::State,
     Error, HasMetadata, HasNamedMetadata,
 };
@@ -379,6 +385,35 @@ where
         self.num_covered_map_indexes = 0;
         Ok(())
     }
+
+    /// Merges the history of `other` into this one, keeping the maximum of each entry,
+    /// as for maps reduced with a [`MaxReducer`] starting at the default value.
+    ///
+    /// Both histories must come from maps of the same size.
+    pub fn merge(&mut self, other: &Self) -> Result<(), Error>
+    where
+        T: PartialOrd,
+    {
+        if self.history_map.len() != other.history_map.len() {
+            return Err(Error::illegal_argument(format!(
+                "Cannot merge coverage histories of different sizes ({} and {})",
+                self.history_map.len(),
+                other.history_map.len()
+            )));
+        }
+        for (entry, other) in self.history_map.iter_mut().zip(&other.history_map) {
+            if *other > *entry {
+                *entry = *other;
+            }
+        }
+        let initial = T::default();
+        self.num_covered_map_indexes = self
+            .history_map
+            .iter()
+            .filter(|entry| **entry != initial)
+            .count();
+        Ok(())
+    }
 }
 
 /// The most common AFL-like feedback type
@@ -392,6 +427,16 @@ pub struct MapFeedback<C, N, O, R, S, T> {
     observer_name: String,
     /// Name of the feedback as shown in the `UserStats`
     stats_name: String,
+    /// Clear the history whenever the scheduler completes a queue cycle
+    reset_on_cycle: bool,
+    /// The last queue cycle seen by this feedback
+    last_cycle: Option<u64>,
+    /// Warn, once the share of covered map entries exceeds this threshold
+    density_warning: Option<f64>,
+    /// If the density warning was already sent
+    density_warned: bool,
+    /// Store the novelties inline in the testcase, instead of as [`MapNoveltiesMetadata`]
+    inline_novelties: bool,
     /// Phantom Data of Reducer
     phantom: PhantomData<(C, N, O, R, S, T)>,
 }
@@ -409,7 +454,7 @@ where
     N: IsNovel<T>,
     O: MapObserver<Entry = T> + for<'it> AsIter<'it, Item = T>,
     R: Reducer<T>,
-    S: State + HasNamedMetadata,
+    S: State + HasNamedMetadata + HasMetadata,
     T: Default + Copy + Serialize + for<'de> Deserialize<'de> + PartialEq + Debug + 'static,
     C: CanTrack + AsRef<O>,
 {
@@ -433,6 +478,7 @@ where
         EM: EventFirer<State = S>,
         OT: ObserversTuple<S>,
     {
+        self.reset_history_on_cycle(state, observers)?;
         Ok(self.is_interesting_default(state, manager, input, observers, exit_kind))
     }
 
@@ -449,6 +495,7 @@ where
         EM: EventFirer<State = S>,
         OT: ObserversTuple<S>,
     {
+        self.reset_history_on_cycle(state, observers)?;
         Ok(self.is_interesting_default(state, manager, input, observers, exit_kind))
     }
 
@@ -464,8 +511,11 @@ where
         EM: EventFirer<State = S>,
     {
         if let Some(novelties) = self.novelties.as_mut().map(core::mem::take) {
-            let meta = MapNoveltiesMetadata::new(novelties);
-            testcase.add_metadata(meta);
+            if self.inline_novelties {
+                testcase.set_novelties(novelties);
+            } else {
+                testcase.add_metadata(MapNoveltiesMetadata::new(novelties));
+            }
         }
         let observer = observers
             .match_name::<C>(&self.observer_name)
@@ -543,6 +593,23 @@ where
             },
         )?;
 
+        if let Some(threshold) = self.density_warning {
+            #[allow(clippy::cast_precision_loss)]
+            let density = covered as f64 / len as f64;
+            if !self.density_warned && density > threshold {
+                self.density_warned = true;
+                manager.log(
+                    state,
+                    LogSeverity::Warn,
+                    format!(
+                        "The map of {} is {:.0}% full ({covered} of {len} entries), collisions are likely. Consider using a larger map.",
+                        self.name,
+                        density * 100.0
+                    ),
+                )?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -553,7 +620,7 @@ impl<C, O, S> Feedback<S> for MapFeedback<C, DifferentIsNovel, O, MaxReducer, S,
 where
     O: MapObserver<Entry = u8> + AsSlice<Entry = u8>,
     for<'it> O: AsIter<'it, Item = u8>,
-    S: State + HasNamedMetadata,
+    S: State + HasNamedMetadata + HasMetadata,
     C: CanTrack + AsRef<O>,
 {
     #[allow(clippy::wrong_self_convention)]
@@ -573,6 +640,8 @@ where
         // 128 bits vectors
         type VectorType = core::simd::u8x16;
 
+        self.reset_history_on_cycle(state, observers)?;
+
         let mut interesting = false;
         // TODO Replace with match_name_type when stable
         let observer = observers
@@ -687,6 +756,9 @@ where
     }
 }
 
+/// A reasonable threshold for [`MapFeedback::with_density_warning`]
+pub const DEFAULT_MAP_DENSITY_WARNING: f64 = 0.7;
+
 fn create_stats_name(name: &str) -> String {
     name.to_lowercase()
 }
@@ -710,6 +782,11 @@ where
             name: map_observer.name().to_string(),
             observer_name: map_observer.name().to_string(),
             stats_name: create_stats_name(map_observer.name()),
+            reset_on_cycle: false,
+            last_cycle: None,
+            density_warning: None,
+            density_warned: false,
+            inline_novelties: false,
             phantom: PhantomData,
         }
     }
@@ -725,10 +802,124 @@ where
             name: name.to_string(),
             observer_name: map_observer.name().to_string(),
             stats_name: create_stats_name(name),
+            reset_on_cycle: false,
+            last_cycle: None,
+            density_warning: None,
+            density_warned: false,
+            inline_novelties: false,
             phantom: PhantomData,
         }
     }
 
+    /// Clear the history of this feedback whenever the scheduler completes a queue cycle,
+    /// so that all entries are novel again. The corpus is kept.
+    ///
+    /// This needs a scheduler that counts the queue cycles in its [`SchedulerMetadata`], that is
+    /// the [`crate::schedulers::PowerQueueScheduler`] or the [`crate::schedulers::WeightedScheduler`].
+    /// With other schedulers, such as the [`crate::schedulers::QueueScheduler`], evaluating an input fails.
+    #[must_use]
+    pub fn with_reset_on_cycle(mut self, reset_on_cycle: bool) -> Self {
+        self.reset_on_cycle = reset_on_cycle;
+        self
+    }
+
+    /// Log a warning (once), when more than `threshold` (for example, [`DEFAULT_MAP_DENSITY_WARNING`]) of the map entries are covered.
+    /// A full map causes collisions, so the map should be made larger.
+    #[must_use]
+    pub fn with_density_warning(mut self, threshold: f64) -> Self {
+        self.density_warning = Some(threshold);
+        self
+    }
+
+    /// Store the novelties of new testcases inline, see [`Testcase::set_novelties`],
+    /// instead of boxing a [`MapNoveltiesMetadata`] into the metadata map of each testcase.
+    /// Readers should use [`Testcase::novelties`], which handles both.
+    #[must_use]
+    pub fn with_inline_novelties(mut self, inline_novelties: bool) -> Self {
+        self.inline_novelties = inline_novelties;
+        self
+    }
+
+    /// Stores the history map of this feedback to the file at `path`, to warm-start a later run
+    /// from it, using [`MapFeedback::load_history`].
+    #[cfg(feature = "std")]
+    pub fn save_history<P>(&self, state: &S, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        let map_state = state.named_metadata::<MapFeedbackMetadata<T>>(&self.name)?;
+        write_file_atomic(path, &postcard::to_allocvec(map_state)?)
+    }
+
+    /// Loads a history map, stored by [`MapFeedback::save_history`] in a previous run, into the `state`,
+    /// so that the map entries already discovered back then are no longer novel.
+    /// This replaces the current history of this feedback, so call it after creating the state,
+    /// which initializes an empty history, but before loading or evaluating any inputs.
+    ///
+    /// Fails with [`Error::IllegalArgument`] if the history does not have the size of the map of the observer,
+    /// for example because it was saved for a different build of the target.
+    #[cfg(feature = "std")]
+    pub fn load_history<OT, P>(&self, state: &mut S, observers: &OT, path: P) -> Result<(), Error>
+    where
+        OT: ObserversTuple<S>,
+        P: AsRef<Path>,
+    {
+        let map_state: MapFeedbackMetadata<T> = postcard::from_bytes(&fs::read(path)?)?;
+        let len = observers
+            .match_name::<C>(&self.observer_name)
+            .ok_or_else(|| {
+                Error::key_not_found(format!("Observer {} not found", self.observer_name))
+            })?
+            .as_ref()
+            .len();
+        // An empty history was saved before anything ran, it grows with the map
+        if !map_state.history_map.is_empty() && map_state.history_map.len() != len {
+            return Err(Error::illegal_argument(format!(
+                "The history of {} has {} entries, but the map has {len}",
+                self.name,
+                map_state.history_map.len()
+            )));
+        }
+        state.add_named_metadata(&self.name, map_state);
+        Ok(())
+    }
+
+    /// Clears the history if enabled, and a new queue cycle started since the last call
+    fn reset_history_on_cycle<OT>(&mut self, state: &mut S, observers: &OT) -> Result<(), Error>
+    where
+        S: HasMetadata,
+        OT: ObserversTuple<S>,
+    {
+        if !self.reset_on_cycle {
+            return Ok(());
+        }
+        let cycles = state
+            .metadata::<SchedulerMetadata>()
+            .map_err(|_| {
+                Error::illegal_state(format!(
+                    "{} resets on queue cycles, but the scheduler does not count them in a SchedulerMetadata",
+                    self.name
+                ))
+            })?
+            .queue_cycles();
+        let cycle_done = matches!(self.last_cycle, Some(last_cycle) if cycles > last_cycle);
+        self.last_cycle = Some(cycles);
+        if !cycle_done {
+            return Ok(());
+        }
+
+        let initial = observers
+            .match_name::<C>(&self.observer_name)
+            .unwrap()
+            .as_ref()
+            .initial();
+        state
+            .named_metadata_map_mut()
+            .get_mut::<MapFeedbackMetadata<T>>(&self.name)
+            .unwrap()
+            .reset_with_value(initial)
+    }
+
     #[allow(clippy::wrong_self_convention)]
     #[allow(clippy::needless_range_loop)]
     #[allow(clippy::trivially_copy_pass_by_ref)]
@@ -801,7 +992,39 @@ where
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "std")]
+    use core::ptr::addr_of_mut;
+    #[cfg(feature = "std")]
+    use std::fs;
+
+    #[cfg(feature = "std")]
+    use libafl_bolts::tuples::tuple_list;
+
     use crate::feedbacks::{AllIsNovel, IsNovel, NextPow2IsNovel};
+    #[cfg(feature = "std")]
+    use crate::{
+        corpus::Testcase,
+        events::{test::RecordingEventManager, Event, LogSeverity, NopEventManager},
+        executors::ExitKind,
+        feedbacks::{Feedback, MapFeedbackMetadata, MaxMapFeedback},
+        inputs::BytesInput,
+        observers::{StdMapObserver, WindowedMapObserver},
+        schedulers::SchedulerMetadata,
+        state::test::{test_std_state, test_std_state_with},
+        Error, HasMetadata, HasNamedMetadata,
+    };
+
+    #[cfg(feature = "std")]
+    static mut MAP: [u8; 4] = [0; 4];
+
+    #[cfg(feature = "std")]
+    static mut WINDOW_MAP: [u8; 8] = [0; 8];
+
+    #[cfg(feature = "std")]
+    static mut DENSITY_MAP: [u8; 4] = [0; 4];
+
+    #[cfg(feature = "std")]
+    static mut HISTORY_MAP: [u8; 4] = [0; 4];
 
     #[test]
     fn test_map_is_novel() {
@@ -822,4 +1045,226 @@ mod tests {
         assert!(NextPow2IsNovel::is_novel(254_u8, 255));
         assert!(!NextPow2IsNovel::is_novel(255_u8, 255));
     }
+
+    /// Runs the same input before and after a queue cycle, returns if it was interesting after the cycle
+    #[cfg(feature = "std")]
+    fn interesting_after_cycle(reset_on_cycle: bool) -> bool {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapFeedbackMetadata::<u8>::register();
+            SchedulerMetadata::register();
+        }
+
+        let observer =
+            unsafe { StdMapObserver::from_mut_ptr("map", addr_of_mut!(MAP) as *mut u8, 4) };
+        let mut feedback = MaxMapFeedback::new(&observer).with_reset_on_cycle(reset_on_cycle);
+        let observers = tuple_list!(observer);
+
+        let mut state = test_std_state_with(&mut feedback, &mut ());
+        state.add_metadata(SchedulerMetadata::new(None));
+        let mut mgr = NopEventManager::new();
+        let input = BytesInput::new(vec![0x41]);
+
+        unsafe {
+            (*addr_of_mut!(MAP))[1] = 1;
+        }
+        assert!(feedback
+            .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+            .unwrap());
+        let mut testcase = Testcase::new(input.clone());
+        feedback
+            .append_metadata(&mut state, &mut mgr, &observers, &mut testcase)
+            .unwrap();
+        assert!(!feedback
+            .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+            .unwrap());
+
+        // The scheduler completes a cycle
+        let psmeta = state.metadata_mut::<SchedulerMetadata>().unwrap();
+        psmeta.set_queue_cycles(psmeta.queue_cycles() + 1);
+
+        feedback
+            .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+            .unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_map_reset_on_cycle() {
+        assert!(!interesting_after_cycle(false));
+        assert!(interesting_after_cycle(true));
+
+        // Without a scheduler counting the cycles, the reset can't work
+        let observer = StdMapObserver::owned("map", vec![0_u8; 4]);
+        let mut feedback = MaxMapFeedback::new(&observer).with_reset_on_cycle(true);
+        let observers = tuple_list!(observer);
+        let mut state = test_std_state_with(&mut feedback, &mut ());
+        assert!(matches!(
+            feedback.is_interesting(
+                &mut state,
+                &mut NopEventManager::new(),
+                &BytesInput::new(vec![0x41]),
+                &observers,
+                &ExitKind::Ok
+            ),
+            Err(Error::IllegalState(..))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_map_density_warning() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapFeedbackMetadata::<u8>::register();
+        }
+
+        let observer = unsafe {
+            StdMapObserver::from_mut_ptr("density", addr_of_mut!(DENSITY_MAP) as *mut u8, 4)
+        };
+        let mut feedback = MaxMapFeedback::new(&observer).with_density_warning(0.7);
+        let observers = tuple_list!(observer);
+
+        let mut state = test_std_state::<BytesInput>();
+        feedback.init_state(&mut state).unwrap();
+        let mut mgr = RecordingEventManager::new();
+        let input = BytesInput::new(vec![0x41]);
+
+        // Fill the map, one entry per run
+        for (i, warnings) in [0, 0, 1, 1].into_iter().enumerate() {
+            unsafe {
+                (*addr_of_mut!(DENSITY_MAP))[i] = 1;
+            }
+            assert!(feedback
+                .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+                .unwrap());
+            let mut testcase = Testcase::new(input.clone());
+            feedback
+                .append_metadata(&mut state, &mut mgr, &observers, &mut testcase)
+                .unwrap();
+            let logged = mgr
+                .events
+                .iter()
+                .filter(|event| {
+                    matches!(
+                        event,
+                        Event::Log {
+                            severity_level: LogSeverity::Warn,
+                            ..
+                        }
+                    )
+                })
+                .count();
+            assert_eq!(logged, warnings);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_map_history_warm_start() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapFeedbackMetadata::<u8>::register();
+        }
+
+        let observer = unsafe {
+            StdMapObserver::from_mut_ptr("history", addr_of_mut!(HISTORY_MAP) as *mut u8, 4)
+        };
+        let mut previous_feedback = MaxMapFeedback::new(&observer);
+        let mut feedback = MaxMapFeedback::new(&observer);
+        let observers = tuple_list!(observer);
+        let mut mgr = NopEventManager::new();
+        let input = BytesInput::new(vec![0x41]);
+        let path = "target/.test/map_history";
+        fs::create_dir_all("target/.test").unwrap();
+
+        unsafe {
+            (*addr_of_mut!(HISTORY_MAP))[2] = 1;
+        }
+
+        // The previous run discovers the entry
+        let mut state = test_std_state_with(&mut previous_feedback, &mut ());
+        assert!(previous_feedback
+            .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+            .unwrap());
+        let mut testcase = Testcase::new(input.clone());
+        previous_feedback
+            .append_metadata(&mut state, &mut mgr, &observers, &mut testcase)
+            .unwrap();
+        previous_feedback.save_history(&state, path).unwrap();
+
+        // A fresh run, warm-started from the previous history
+        let mut state = test_std_state_with(&mut feedback, &mut ());
+        // A history of a different map size is refused
+        let other_observers = tuple_list!(StdMapObserver::owned("history", vec![0_u8; 8]));
+        assert!(matches!(
+            feedback.load_history(&mut state, &other_observers, path),
+            Err(Error::IllegalArgument(..))
+        ));
+        feedback.load_history(&mut state, &observers, path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(
+            state
+                .named_metadata::<MapFeedbackMetadata<u8>>("history")
+                .unwrap()
+                .num_covered_map_indexes,
+            1
+        );
+        assert!(!feedback
+            .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+            .unwrap());
+
+        // New entries still count
+        unsafe {
+            (*addr_of_mut!(HISTORY_MAP))[3] = 1;
+        }
+        assert!(feedback
+            .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+            .unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_map_window() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapFeedbackMetadata::<u8>::register();
+        }
+
+        let base = unsafe {
+            StdMapObserver::from_mut_ptr("window", addr_of_mut!(WINDOW_MAP) as *mut u8, 8)
+        };
+        // Only the upper half of the map belongs to the target module
+        let observer = WindowedMapObserver::new(base, 4, 4);
+        let mut feedback = MaxMapFeedback::new(&observer);
+        let observers = tuple_list!(observer);
+
+        let mut state = test_std_state_with(&mut feedback, &mut ());
+        let mut mgr = NopEventManager::new();
+        let input = BytesInput::new(vec![0x41]);
+
+        // Coverage outside of the window is ignored
+        unsafe {
+            (*addr_of_mut!(WINDOW_MAP))[1] = 1;
+        }
+        assert!(!feedback
+            .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+            .unwrap());
+
+        unsafe {
+            (*addr_of_mut!(WINDOW_MAP))[5] = 1;
+        }
+        assert!(feedback
+            .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+            .unwrap());
+    }
 }
@@ -7,8 +7,25 @@
 pub mod map;
 pub use map::*;
 
+pub mod bytes_read;
+pub use bytes_read::{BytesReadFeedback, BytesReadMetadata};
+
 pub mod differential;
 pub use differential::DiffFeedback;
+
+pub mod function_entry;
+pub use function_entry::{FunctionEntryMetadata, NewFunctionFeedback};
+
+pub mod msan;
+pub use msan::{MsanReportMetadata, MsanSitesMetadata, NewMsanSiteFeedback};
+
+pub mod stack_depth;
+pub use stack_depth::{MaxStackDepthMetadata, StackDepthFeedback, StackDepthMetadata};
+
+pub mod exploitability;
+pub use exploitability::{
+    CrashInfo, Exploitability, ExploitabilityFeedback, ExploitabilityMetadata, MemoryAccess,
+};
 #[cfg(feature = "std")]
 pub mod concolic;
 #[cfg(feature = "std")]
@@ -21,11 +38,23 @@ pub use new_hash_feedback::NewHashFeedback;
 #[cfg(feature = "std")]
 pub use new_hash_feedback::NewHashFeedbackMetadata;
 
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod fd_leak;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use fd_leak::{FdLeakFeedback, FdLeakMetadata};
+
+#[cfg(feature = "std")]
+pub mod log_message;
+#[cfg(feature = "std")]
+pub use log_message::NewLogMessageFeedback;
+
 #[cfg(feature = "nautilus")]
 pub mod nautilus;
 #[cfg(feature = "std")]
 pub mod stdio;
 pub mod transferred;
+pub mod weighted;
+pub use weighted::{recompute_fitness, HasFitness, WeightedFeedback, WeightedVerdictsMetadata};
 
 /// The module for list feedback
 pub mod list;
@@ -133,6 +162,26 @@ where
     fn discard_metadata(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
         Ok(())
     }
+
+    /// Returns `true`, if the last call to [`Feedback::is_interesting`] could not decide yet, and needs another run of the input,
+    /// for example for statistical feedbacks over timings.
+    /// The [`crate::StdFuzzer`] then runs the input again, and asks for the verdict again,
+    /// up to [`crate::fuzzer::MAX_FEEDBACK_RERUNS`] times. Feedbacks should bound their reruns well below that.
+    ///
+    /// Only the feedback (or objective) requesting the rerun is asked again,
+    /// the verdict of the other one is kept.
+    /// Feedbacks requesting reruns have to reset the request in each [`Feedback::is_interesting`] call,
+    /// and in [`Feedback::reset_rerun`].
+    #[inline]
+    fn needs_rerun(&self) -> bool {
+        false
+    }
+
+    /// Drops a pending rerun request, see [`Feedback::needs_rerun`].
+    /// Called by [`CombinedFeedback`] before each evaluation, as fast logics may skip a feedback,
+    /// which would otherwise keep the request of an earlier input.
+    #[inline]
+    fn reset_rerun(&mut self) {}
 }
 
 /// Has an associated observer name (mostly used to retrieve the observer with `MatchName` from an `ObserverTuple`)
@@ -215,6 +264,7 @@ where
         EM: EventFirer<State = S>,
         OT: ObserversTuple<S>,
     {
+        self.reset_rerun();
         FL::is_pair_interesting(
             &mut self.first,
             &mut self.second,
@@ -240,6 +290,7 @@ where
         EM: EventFirer<State = S>,
         OT: ObserversTuple<S>,
     {
+        self.reset_rerun();
         FL::is_pair_interesting_introspection(
             &mut self.first,
             &mut self.second,
@@ -269,6 +320,17 @@ where
             .append_metadata(state, manager, observers, testcase)
     }
 
+    #[inline]
+    fn needs_rerun(&self) -> bool {
+        self.first.needs_rerun() || self.second.needs_rerun()
+    }
+
+    #[inline]
+    fn reset_rerun(&mut self) {
+        self.first.reset_rerun();
+        self.second.reset_rerun();
+    }
+
     #[inline]
     fn discard_metadata(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
         self.first.discard_metadata(state, input)?;
@@ -689,6 +751,16 @@ where
     fn discard_metadata(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
         self.first.discard_metadata(state, input)
     }
+
+    #[inline]
+    fn needs_rerun(&self) -> bool {
+        self.first.needs_rerun()
+    }
+
+    #[inline]
+    fn reset_rerun(&mut self) {
+        self.first.reset_rerun();
+    }
 }
 
 impl<A, S> Named for NotFeedback<A, S>
@@ -845,6 +917,26 @@ impl Default for CrashFeedback {
 /// A feedback factory for crash feedbacks
 pub type CrashFeedbackFactory = DefaultFeedbackFactory<CrashFeedback>;
 
+/// A [`NotCrashFeedback`] reports as interesting if the target did not crash.
+pub type NotCrashFeedback<S> = NotFeedback<CrashFeedback, S>;
+
+/// A preset for the discovery corpus: only keeps inputs that did not crash, and are interesting to the given feedback,
+/// usually a map feedback for new coverage. Crashes are left to the objective.
+///
+/// Since the feedbacks are combined with a fast AND, the map feedback does not even see crashing runs,
+/// so their coverage does not end up in its history either.
+pub type DiscoveryFeedback<F, S> = FastAndFeedback<NotCrashFeedback<S>, F, S>;
+
+/// Creates a new [`DiscoveryFeedback`], keeping non-crashing inputs that are interesting to the given `feedback`
+#[must_use]
+pub fn discovery_feedback<F, S>(feedback: F) -> DiscoveryFeedback<F, S>
+where
+    F: Feedback<S>,
+    S: State,
+{
+    DiscoveryFeedback::new(NotFeedback::new(CrashFeedback::new()), feedback)
+}
+
 /// A [`TimeoutFeedback`] reduces the timeout value of a run.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TimeoutFeedback {}
@@ -1036,3 +1128,52 @@ impl From<bool> for ConstFeedback {
         }
     }
 }
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use core::ptr::addr_of_mut;
+
+    use libafl_bolts::tuples::tuple_list;
+
+    use crate::{
+        events::NopEventManager,
+        executors::ExitKind,
+        feedbacks::{discovery_feedback, Feedback, MaxMapFeedback},
+        inputs::BytesInput,
+        observers::StdMapObserver,
+        state::test::test_std_state_with,
+    };
+
+    static mut MAP: [u8; 4] = [0; 4];
+
+    #[test]
+    fn test_discovery_feedback() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            crate::feedbacks::MapFeedbackMetadata::<u8>::register();
+        }
+
+        let observer =
+            unsafe { StdMapObserver::from_mut_ptr("map", addr_of_mut!(MAP) as *mut u8, 4) };
+        let mut feedback = discovery_feedback(MaxMapFeedback::new(&observer));
+        let observers = tuple_list!(observer);
+
+        let mut state = test_std_state_with(&mut feedback, &mut ());
+        let mut mgr = NopEventManager::new();
+        let input = BytesInput::new(vec![0x41]);
+
+        // Both runs cover a new map entry, but only the clean one is kept
+        unsafe {
+            (*addr_of_mut!(MAP))[1] = 1;
+        }
+        assert!(!feedback
+            .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Crash)
+            .unwrap());
+        assert!(feedback
+            .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+            .unwrap());
+    }
+}
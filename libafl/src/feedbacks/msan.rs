@@ -0,0 +1,237 @@
+//! The [`NewMsanSiteFeedback`] reports an input as a solution, if it triggered an MSAN report at a new site.
+//!
+//! Like the coverage feedbacks, it only cares about sites never seen before,
+//! so each uninitialized read is reported once, not for every input reaching it.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::marker::PhantomData;
+
+use hashbrown::HashSet;
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::Testcase,
+    events::EventFirer,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverName},
+    observers::{MapObserver, MsanReportObserver, ObserversTuple},
+    state::State,
+    Error, HasMetadata, HasNamedMetadata,
+};
+
+/// The report sites a [`NewMsanSiteFeedback`] has seen so far
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct MsanSitesMetadata {
+    /// The map indexes of the report sites
+    pub sites: HashSet<usize>,
+}
+
+libafl_bolts::impl_serdeany!(MsanSitesMetadata);
+
+impl MsanSitesMetadata {
+    /// Creates a new, empty [`MsanSitesMetadata`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// The new MSAN report sites of a solution, added by the [`NewMsanSiteFeedback`]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct MsanReportMetadata {
+    /// The map indexes of the report sites this testcase reached first
+    pub new_sites: Vec<usize>,
+}
+
+libafl_bolts::impl_serdeany!(MsanReportMetadata);
+
+/// A [`Feedback`], usually used as objective, that deems an input interesting,
+/// if it triggered an MSAN report at a site that never reported before, as observed by a [`MsanReportObserver`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewMsanSiteFeedback<M> {
+    name: String,
+    observer_name: String,
+    /// The new report sites of the last run
+    new_sites: Vec<usize>,
+    phantom: PhantomData<M>,
+}
+
+impl<M> NewMsanSiteFeedback<M>
+where
+    M: MapObserver,
+{
+    /// Creates a new [`NewMsanSiteFeedback`], looking at the given [`MsanReportObserver`]
+    #[must_use]
+    pub fn new(observer: &MsanReportObserver<M>) -> Self {
+        Self {
+            name: format!("NewMsanSiteFeedback_{}", observer.name()),
+            observer_name: observer.name().to_string(),
+            new_sites: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<M, S> Feedback<S> for NewMsanSiteFeedback<M>
+where
+    M: MapObserver,
+    S: State + HasNamedMetadata,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        state.add_named_metadata(&self.name, MsanSitesMetadata::new());
+        Ok(())
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn is_interesting<EM, OT>(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _input: &S::Input,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let observer = observers
+            .match_name::<MsanReportObserver<M>>(&self.observer_name)
+            .ok_or(Error::illegal_state("MsanReportObserver is missing"))?;
+
+        let metadata = state
+            .named_metadata_map_mut()
+            .get_mut::<MsanSitesMetadata>(&self.name)
+            .ok_or(Error::illegal_state(
+                "MsanSitesMetadata is missing, init_state was not called",
+            ))?;
+        self.new_sites = observer
+            .sites()
+            .into_iter()
+            .filter(|idx| metadata.sites.insert(*idx))
+            .collect();
+        Ok(!self.new_sites.is_empty())
+    }
+
+    fn append_metadata<EM, OT>(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        testcase: &mut Testcase<S::Input>,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<S>,
+        EM: EventFirer<State = S>,
+    {
+        testcase.add_metadata(MsanReportMetadata {
+            new_sites: core::mem::take(&mut self.new_sites),
+        });
+        Ok(())
+    }
+
+    fn discard_metadata(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
+        self.new_sites.clear();
+        Ok(())
+    }
+}
+
+impl<M> Named for NewMsanSiteFeedback<M> {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<M> HasObserverName for NewMsanSiteFeedback<M> {
+    #[inline]
+    fn observer_name(&self) -> &str {
+        &self.observer_name
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use core::ptr::addr_of_mut;
+
+    use libafl_bolts::tuples::tuple_list;
+
+    use crate::{
+        corpus::Corpus,
+        executors::ExitKind,
+        feedbacks::{
+            msan::{MsanReportMetadata, NewMsanSiteFeedback},
+            ConstFeedback,
+        },
+        fuzzer::{test::test_fuzzer, Evaluator},
+        inputs::{BytesInput, HasBytesVec},
+        observers::{MsanReportObserver, StdMapObserver},
+        schedulers::QueueScheduler,
+        state::HasSolutions,
+        HasMetadata,
+    };
+
+    static mut MSAN_MAP: [u8; 4] = [0; 4];
+
+    #[test]
+    fn test_new_msan_site_feedback() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            crate::feedbacks::msan::MsanSitesMetadata::register();
+            MsanReportMetadata::register();
+        }
+
+        // A synthetic report at the site the first byte maps to
+        let mut harness = |input: &BytesInput| {
+            unsafe {
+                (*addr_of_mut!(MSAN_MAP))[input.bytes()[0] as usize % 4] += 1;
+            }
+            ExitKind::Ok
+        };
+        let observer = MsanReportObserver::new(unsafe {
+            StdMapObserver::from_mut_ptr("msan", addr_of_mut!(MSAN_MAP) as *mut u8, 4)
+        });
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            NewMsanSiteFeedback::new(&observer),
+            (),
+            &mut harness,
+            tuple_list!(observer),
+        );
+
+        for (byte, solutions) in [(0, 1), (0, 1), (1, 2), (4, 2), (3, 3)] {
+            fuzzer
+                .evaluate_input(
+                    &mut state,
+                    &mut executor,
+                    &mut mgr,
+                    BytesInput::new(vec![byte]),
+                )
+                .unwrap();
+            assert_eq!(state.solutions().count(), solutions, "input {byte}");
+        }
+
+        let first = state.solutions().first().unwrap();
+        let testcase = state.solutions().get(first).unwrap().borrow();
+        assert_eq!(
+            testcase.metadata::<MsanReportMetadata>().unwrap().new_sites,
+            [0]
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! The [`StackDepthFeedback`] deems inputs interesting that reach a new maximum stack depth,
+//! as observed by a [`StackDepthObserver`].
+//!
+//! Keeping these inputs guides the fuzzer towards deeply nested structures,
+//! and, used as objective with a limit, reports the ones nesting deep enough to exhaust the stack.
+
+use alloc::string::{String, ToString};
+
+use libafl_bolts::{impl_serdeany, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::Testcase,
+    events::EventFirer,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverName},
+    observers::{ObserversTuple, StackDepthObserver},
+    state::State,
+    Error, HasMetadata, HasNamedMetadata,
+};
+
+/// The maximum stack depth a [`StackDepthFeedback`] has seen so far
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct MaxStackDepthMetadata {
+    /// The maximum stack depth
+    pub max_depth: usize,
+}
+
+impl_serdeany!(MaxStackDepthMetadata);
+
+/// The stack depth a testcase reached, attached by the [`StackDepthFeedback`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct StackDepthMetadata {
+    /// The maximum stack depth of the run
+    pub depth: usize,
+}
+
+impl_serdeany!(StackDepthMetadata);
+
+/// A [`Feedback`] that deems an input interesting, if it reached a stack depth deeper than any input before.
+///
+/// Created with [`StackDepthFeedback::with_limit`], it only deems inputs interesting that also exceed the limit,
+/// and should be used as objective.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackDepthFeedback {
+    name: String,
+    observer_name: String,
+    /// Only depths above this limit are interesting
+    limit: Option<usize>,
+    /// The depth of the last run
+    last_depth: usize,
+}
+
+impl StackDepthFeedback {
+    /// Creates a new [`StackDepthFeedback`], deeming each new maximum stack depth interesting
+    #[must_use]
+    pub fn new(observer: &StackDepthObserver) -> Self {
+        Self {
+            name: format!("StackDepthFeedback_{}", observer.name()),
+            observer_name: observer.name().to_string(),
+            limit: None,
+            last_depth: 0,
+        }
+    }
+
+    /// Creates a new [`StackDepthFeedback`], deeming each new maximum stack depth above `limit` interesting
+    #[must_use]
+    pub fn with_limit(observer: &StackDepthObserver, limit: usize) -> Self {
+        Self {
+            name: format!("StackDepthFeedback_{}_{limit}", observer.name()),
+            observer_name: observer.name().to_string(),
+            limit: Some(limit),
+            last_depth: 0,
+        }
+    }
+
+    /// The depth limit, if any
+    #[must_use]
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+}
+
+impl<S> Feedback<S> for StackDepthFeedback
+where
+    S: State + HasNamedMetadata,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        state.add_named_metadata(&self.name, MaxStackDepthMetadata::default());
+        Ok(())
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn is_interesting<EM, OT>(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _input: &S::Input,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let observer = observers
+            .match_name::<StackDepthObserver>(&self.observer_name)
+            .ok_or(Error::illegal_state("StackDepthObserver is missing"))?;
+        self.last_depth = observer.max_depth();
+
+        let metadata = state
+            .named_metadata_map_mut()
+            .get_mut::<MaxStackDepthMetadata>(&self.name)
+            .ok_or(Error::illegal_state(
+                "MaxStackDepthMetadata is missing, init_state was not called",
+            ))?;
+        if self.last_depth <= metadata.max_depth {
+            return Ok(false);
+        }
+        metadata.max_depth = self.last_depth;
+        Ok(self.limit.map_or(true, |limit| self.last_depth > limit))
+    }
+
+    fn append_metadata<EM, OT>(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        testcase: &mut Testcase<S::Input>,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<S>,
+        EM: EventFirer<State = S>,
+    {
+        testcase.add_metadata(StackDepthMetadata {
+            depth: self.last_depth,
+        });
+        Ok(())
+    }
+
+    fn discard_metadata(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
+        self.last_depth = 0;
+        Ok(())
+    }
+}
+
+impl Named for StackDepthFeedback {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl HasObserverName for StackDepthFeedback {
+    #[inline]
+    fn observer_name(&self) -> &str {
+        &self.observer_name
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use libafl_bolts::tuples::tuple_list;
+
+    use crate::{
+        corpus::Corpus,
+        executors::ExitKind,
+        feedbacks::{StackDepthFeedback, StackDepthMetadata},
+        fuzzer::test::test_fuzzer,
+        inputs::{BytesInput, HasBytesVec},
+        observers::{report_stack_depth, StackDepthObserver},
+        schedulers::QueueScheduler,
+        state::{HasCorpus, HasSolutions},
+        Evaluator, HasMetadata,
+    };
+
+    #[test]
+    fn test_stack_depth_feedback() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            crate::feedbacks::MaxStackDepthMetadata::register();
+            StackDepthMetadata::register();
+        }
+
+        // The harness recurses as deep as the first byte says
+        let mut harness = |input: &BytesInput| {
+            for depth in 0..=input.bytes()[0] {
+                report_stack_depth(depth.into());
+            }
+            ExitKind::Ok
+        };
+        let observer = StackDepthObserver::new("stack_depth");
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            StackDepthFeedback::new(&observer),
+            StackDepthFeedback::with_limit(&observer, 200),
+            (),
+            &mut harness,
+            tuple_list!(observer),
+        );
+
+        for (depth, corpus, solutions) in [
+            (10, 1, 0),
+            (5, 1, 0),
+            (20, 2, 0),
+            (20, 2, 0),
+            (15, 2, 0),
+            (30, 3, 0),
+            (250, 3, 1),
+        ] {
+            fuzzer
+                .evaluate_input(
+                    &mut state,
+                    &mut executor,
+                    &mut mgr,
+                    BytesInput::new(vec![depth]),
+                )
+                .unwrap();
+            assert_eq!(state.corpus().count(), corpus, "depth {depth}");
+            assert_eq!(state.solutions().count(), solutions, "depth {depth}");
+        }
+
+        let last = state.corpus().last().unwrap();
+        let testcase = state.corpus().get(last).unwrap().borrow();
+        assert_eq!(testcase.metadata::<StackDepthMetadata>().unwrap().depth, 30);
+    }
+}
@@ -0,0 +1,420 @@
+//! A feedback that combines two [`Feedback`]s using a weighted sum, instead of a plain boolean logic.
+//!
+//! Each feedback that deems the input interesting contributes its weight to the score.
+//! The input is interesting if the score reaches the threshold.
+//! The score is stored as the fitness of new testcases, and [`recompute_fitness`] updates it after the weights change.
+
+use alloc::string::String;
+use core::marker::PhantomData;
+
+use hashbrown::HashMap;
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::{Corpus, Testcase},
+    events::EventFirer,
+    executors::ExitKind,
+    feedbacks::Feedback,
+    fuzzer::HasFeedback,
+    inputs::Input,
+    observers::ObserversTuple,
+    state::{HasCorpus, State},
+    Error, HasMetadata,
+};
+
+/// The default weight of each feedback in a [`WeightedFeedback`]
+pub const DEFAULT_FEEDBACK_WEIGHT: f64 = 1.0;
+
+/// The default threshold of a [`WeightedFeedback`].
+/// With the default weights, this behaves like an eager OR.
+pub const DEFAULT_WEIGHTED_THRESHOLD: f64 = 1.0;
+
+/// Combines two [`Feedback`]s into a weighted score.
+///
+/// Both feedbacks are always evaluated. Each one that is interesting adds its weight to the score,
+/// and the input is interesting if the score is at least the threshold.
+/// Use [`WeightedFeedback::with_weights`] and [`WeightedFeedback::with_threshold`]
+/// to control what matters for the "interesting" decision.
+#[derive(Debug)]
+pub struct WeightedFeedback<A, B, S>
+where
+    A: Feedback<S>,
+    B: Feedback<S>,
+    S: State,
+{
+    /// First [`Feedback`]
+    pub first: A,
+    /// Second [`Feedback`]
+    pub second: B,
+    first_weight: f64,
+    second_weight: f64,
+    threshold: f64,
+    /// If the first and the second feedback deemed the last input interesting
+    last_verdicts: Option<(bool, bool)>,
+    name: String,
+    phantom: PhantomData<S>,
+}
+
+impl<A, B, S> WeightedFeedback<A, B, S>
+where
+    A: Feedback<S>,
+    B: Feedback<S>,
+    S: State,
+{
+    /// Creates a new [`WeightedFeedback`], using the [`DEFAULT_FEEDBACK_WEIGHT`] for both feedbacks
+    /// and the [`DEFAULT_WEIGHTED_THRESHOLD`].
+    pub fn new(first: A, second: B) -> Self {
+        let name = format!("Weighted ({},{})", first.name(), second.name());
+        Self {
+            first,
+            second,
+            first_weight: DEFAULT_FEEDBACK_WEIGHT,
+            second_weight: DEFAULT_FEEDBACK_WEIGHT,
+            threshold: DEFAULT_WEIGHTED_THRESHOLD,
+            last_verdicts: None,
+            name,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the weights of the first and second feedback
+    #[must_use]
+    pub fn with_weights(mut self, first_weight: f64, second_weight: f64) -> Self {
+        self.first_weight = first_weight;
+        self.second_weight = second_weight;
+        self
+    }
+
+    /// Changes the weights of the first and second feedback, for example while tuning a running campaign.
+    /// Use [`recompute_fitness`] to update the fitness of the testcases already in the corpus.
+    pub fn set_weights(&mut self, first_weight: f64, second_weight: f64) {
+        self.first_weight = first_weight;
+        self.second_weight = second_weight;
+    }
+
+    /// Sets the score an input needs to reach to be interesting
+    #[must_use]
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// The weights of the first and second feedback
+    #[must_use]
+    pub fn weights(&self) -> (f64, f64) {
+        (self.first_weight, self.second_weight)
+    }
+
+    /// The score an input needs to reach to be interesting
+    #[must_use]
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// The score of the verdicts of the first and second feedback, with the current weights
+    fn score(&self, (first, second): (bool, bool)) -> f64 {
+        let mut score = 0.0;
+        if first {
+            score += self.first_weight;
+        }
+        if second {
+            score += self.second_weight;
+        }
+        score
+    }
+}
+
+impl<A, B, S> Named for WeightedFeedback<A, B, S>
+where
+    A: Feedback<S>,
+    B: Feedback<S>,
+    S: State,
+{
+    fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+}
+
+impl<A, B, S> Feedback<S> for WeightedFeedback<A, B, S>
+where
+    A: Feedback<S>,
+    B: Feedback<S>,
+    S: State,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        self.first.init_state(state)?;
+        self.second.init_state(state)?;
+        Ok(())
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn is_interesting<EM, OT>(
+        &mut self,
+        state: &mut S,
+        manager: &mut EM,
+        input: &S::Input,
+        observers: &OT,
+        exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let verdicts = (
+            self.first
+                .is_interesting(state, manager, input, observers, exit_kind)?,
+            self.second
+                .is_interesting(state, manager, input, observers, exit_kind)?,
+        );
+        self.last_verdicts = Some(verdicts);
+        Ok(self.score(verdicts) >= self.threshold)
+    }
+
+    #[cfg(feature = "introspection")]
+    #[allow(clippy::wrong_self_convention)]
+    fn is_interesting_introspection<EM, OT>(
+        &mut self,
+        state: &mut S,
+        manager: &mut EM,
+        input: &S::Input,
+        observers: &OT,
+        exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let verdicts = (
+            self.first
+                .is_interesting_introspection(state, manager, input, observers, exit_kind)?,
+            self.second
+                .is_interesting_introspection(state, manager, input, observers, exit_kind)?,
+        );
+        self.last_verdicts = Some(verdicts);
+        Ok(self.score(verdicts) >= self.threshold)
+    }
+
+    #[inline]
+    fn append_metadata<EM, OT>(
+        &mut self,
+        state: &mut S,
+        manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<S::Input>,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<S>,
+        EM: EventFirer<State = S>,
+    {
+        if let Some(verdicts) = self.last_verdicts.take() {
+            testcase.set_fitness(self.score(verdicts));
+            testcase
+                .metadata_or_insert_with(WeightedVerdictsMetadata::default)
+                .verdicts
+                .insert(self.name.clone(), verdicts);
+        }
+        self.first
+            .append_metadata(state, manager, observers, testcase)?;
+        self.second
+            .append_metadata(state, manager, observers, testcase)
+    }
+
+    #[inline]
+    fn discard_metadata(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
+        self.last_verdicts = None;
+        self.first.discard_metadata(state, input)?;
+        self.second.discard_metadata(state, input)
+    }
+
+    #[inline]
+    fn needs_rerun(&self) -> bool {
+        self.first.needs_rerun() || self.second.needs_rerun()
+    }
+
+    #[inline]
+    fn reset_rerun(&mut self) {
+        self.first.reset_rerun();
+        self.second.reset_rerun();
+    }
+}
+
+/// The verdicts of the feedbacks of each [`WeightedFeedback`] that evaluated a testcase, when it was added to the corpus.
+///
+/// Used by [`recompute_fitness`] to score the testcase with new weights, without evaluating it again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct WeightedVerdictsMetadata {
+    /// If the first and the second feedback deemed the testcase interesting, by the name of the [`WeightedFeedback`]
+    pub verdicts: HashMap<String, (bool, bool)>,
+}
+
+libafl_bolts::impl_serdeany!(WeightedVerdictsMetadata);
+
+/// A [`Feedback`] that computes a scalar score, the fitness, for each input it evaluates
+pub trait HasFitness {
+    /// The fitness of the last evaluated input, if any
+    fn last_fitness(&self) -> Option<f64>;
+
+    /// The fitness of a `testcase` in the corpus with the current configuration of the feedback,
+    /// if the feedback evaluated it when it was added
+    fn fitness_of<I>(&self, testcase: &Testcase<I>) -> Option<f64>
+    where
+        I: Input;
+}
+
+impl<A, B, S> HasFitness for WeightedFeedback<A, B, S>
+where
+    A: Feedback<S>,
+    B: Feedback<S>,
+    S: State,
+{
+    #[inline]
+    fn last_fitness(&self) -> Option<f64> {
+        self.last_verdicts.map(|verdicts| self.score(verdicts))
+    }
+
+    fn fitness_of<I>(&self, testcase: &Testcase<I>) -> Option<f64>
+    where
+        I: Input,
+    {
+        let verdicts = testcase
+            .metadata::<WeightedVerdictsMetadata>()
+            .ok()?
+            .verdicts
+            .get(&self.name)?;
+        Some(self.score(*verdicts))
+    }
+}
+
+/// Updates the fitness of each testcase of the corpus to the score the fuzzer's feedback gives it now.
+///
+/// Use this after tuning the feedback, for example with [`WeightedFeedback::set_weights`],
+/// since the fitness stored in the corpus is not updated automatically.
+/// The testcases are not executed again: feedbacks with state, such as the map history, already know their coverage,
+/// so the verdicts recorded in the [`WeightedVerdictsMetadata`] of each testcase are reweighted instead.
+/// Testcases the feedback did not evaluate, like seeds added to the corpus directly, keep their fitness.
+pub fn recompute_fitness<Z>(fuzzer: &Z, state: &Z::State) -> Result<(), Error>
+where
+    Z: HasFeedback,
+    Z::Feedback: HasFitness,
+    Z::State: HasCorpus,
+{
+    for id in state.corpus().ids() {
+        let mut testcase = state.corpus().get(id)?.borrow_mut();
+        if let Some(fitness) = fuzzer.feedback().fitness_of(&testcase) {
+            testcase.set_fitness(fitness);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use core::ptr::addr_of_mut;
+
+    #[cfg(feature = "std")]
+    use libafl_bolts::tuples::tuple_list;
+
+    #[cfg(all(feature = "std", any(not(feature = "serdeany_autoreg"), miri)))]
+    use crate::feedbacks::{weighted::WeightedVerdictsMetadata, MapFeedbackMetadata};
+    #[cfg(feature = "std")]
+    use crate::{
+        corpus::Corpus,
+        feedbacks::{
+            weighted::{recompute_fitness, DEFAULT_FEEDBACK_WEIGHT},
+            MaxMapFeedback,
+        },
+        fuzzer::{test::test_fuzzer, HasFeedback},
+        observers::StdMapObserver,
+        schedulers::QueueScheduler,
+        state::HasCorpus,
+        Evaluator,
+    };
+    use crate::{
+        events::NopEventManager,
+        executors::ExitKind,
+        feedbacks::{weighted::WeightedFeedback, ConstFeedback, Feedback},
+        inputs::BytesInput,
+        state::NopState,
+    };
+
+    #[test]
+    fn test_weighted_feedback() {
+        let mut state = NopState::<BytesInput>::new();
+        let mut mgr = NopEventManager::new();
+        let input = BytesInput::new(vec![0x41]);
+
+        // Only the first feedback is interesting
+        let mut feedback =
+            WeightedFeedback::new(ConstFeedback::new(true), ConstFeedback::new(false))
+                .with_threshold(1.5);
+        assert!(!feedback
+            .is_interesting(&mut state, &mut mgr, &input, &(), &ExitKind::Ok)
+            .unwrap());
+
+        let mut feedback = feedback.with_weights(2.0, 1.0);
+        assert!(feedback
+            .is_interesting(&mut state, &mut mgr, &input, &(), &ExitKind::Ok)
+            .unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_recompute_fitness() {
+        static mut MAP: [u8; 4] = [0; 4];
+
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapFeedbackMetadata::<u8>::register();
+            WeightedVerdictsMetadata::register();
+        }
+
+        let observer =
+            unsafe { StdMapObserver::from_mut_ptr("map", addr_of_mut!(MAP) as *mut u8, 4) };
+        // The map history already holds the coverage of the testcase, once it is in the corpus
+        let feedback =
+            WeightedFeedback::new(MaxMapFeedback::new(&observer), ConstFeedback::new(false));
+        let mut harness = |_input: &BytesInput| {
+            unsafe {
+                (*addr_of_mut!(MAP))[0] = 1;
+            }
+            ExitKind::Ok
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            feedback,
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(observer),
+        );
+
+        let (_, id) = fuzzer
+            .evaluate_input(
+                &mut state,
+                &mut executor,
+                &mut mgr,
+                BytesInput::new(vec![0]),
+            )
+            .unwrap();
+        let id = id.unwrap();
+        assert_eq!(
+            state.corpus().get(id).unwrap().borrow().fitness(),
+            Some(DEFAULT_FEEDBACK_WEIGHT)
+        );
+
+        fuzzer.feedback_mut().set_weights(3.0, 1.0);
+        recompute_fitness(&fuzzer, &state).unwrap();
+        assert_eq!(
+            state.corpus().get(id).unwrap().borrow().fitness(),
+            Some(3.0)
+        );
+    }
+}
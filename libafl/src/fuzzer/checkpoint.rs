@@ -0,0 +1,149 @@
+//! Checkpoints of the [`super::StdFuzzer`], fired as [`Event::Checkpoint`] at a fixed interval, see [`CheckpointInterval`].
+//!
+//! An external supervisor watching the events can use them to coordinate atomic snapshots
+//! of the corpora and states of a fleet of fuzzers, for example by pausing all of them at the same checkpoint id.
+
+use core::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::{Event, EventFirer},
+    fuzzer::FuzzerHook,
+    state::{HasClock, HasExecutions, State},
+    Error, HasMetadata,
+};
+
+/// The checkpoint progress of a fuzzer, stored in the state, so that the ids keep increasing across restarts
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct CheckpointMetadata {
+    /// The id of the next checkpoint
+    pub next_id: u64,
+    /// The time of the last checkpoint, or when the interval started
+    pub last_time: Duration,
+}
+
+libafl_bolts::impl_serdeany!(CheckpointMetadata);
+
+/// Fires an [`Event::Checkpoint`], if `interval` passed since the last one.
+/// The first call only starts the interval.
+/// Returns the id of the fired checkpoint, if any.
+pub fn maybe_checkpoint<EM, S>(
+    state: &mut S,
+    manager: &mut EM,
+    interval: Duration,
+) -> Result<Option<u64>, Error>
+where
+    EM: EventFirer<State = S>,
+    S: State + HasMetadata + HasExecutions + HasClock,
+{
+    let now = state.now();
+    let Some(metadata) = state.metadata_map_mut().get_mut::<CheckpointMetadata>() else {
+        state.add_metadata(CheckpointMetadata {
+            next_id: 0,
+            last_time: now,
+        });
+        return Ok(None);
+    };
+    if now.saturating_sub(metadata.last_time) < interval {
+        return Ok(None);
+    }
+
+    let id = metadata.next_id;
+    metadata.next_id += 1;
+    metadata.last_time = now;
+    let executions = *state.executions();
+    manager.fire(
+        state,
+        Event::Checkpoint {
+            id,
+            executions,
+            time: now,
+        },
+    )?;
+    Ok(Some(id))
+}
+
+/// A [`FuzzerHook`] that fires an [`Event::Checkpoint`] with an increasing id every `interval`,
+/// checked after each [`super::Fuzzer::fuzz_one`], see [`maybe_checkpoint`]
+#[derive(Debug, Clone, Copy)]
+pub struct CheckpointInterval {
+    interval: Duration,
+}
+
+impl CheckpointInterval {
+    /// Creates a new [`CheckpointInterval`], firing a checkpoint every `interval`
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Self { interval }
+    }
+
+    /// The interval between two checkpoints
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+impl<S> FuzzerHook<S> for CheckpointInterval
+where
+    S: State + HasMetadata + HasExecutions + HasClock,
+{
+    fn post_fuzz_one<EM>(&mut self, state: &mut S, manager: &mut EM) -> Result<(), Error>
+    where
+        EM: EventFirer<State = S>,
+    {
+        maybe_checkpoint(state, manager, self.interval)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    use crate::{
+        events::{test::RecordingEventManager, Event},
+        fuzzer::checkpoint::maybe_checkpoint,
+        inputs::BytesInput,
+        state::{test::test_std_state, MockClock},
+    };
+
+    #[test]
+    fn test_checkpoint_interval() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            crate::fuzzer::checkpoint::CheckpointMetadata::register();
+        }
+
+        let mut state = test_std_state::<BytesInput>();
+        let clock = MockClock::new(Duration::from_secs(100));
+        state.set_clock(clock.clone());
+        let mut mgr = RecordingEventManager::new();
+        let interval = Duration::from_secs(60);
+
+        let mut fired = Vec::new();
+        for step in [0, 30, 30, 59, 1, 120] {
+            clock.advance(Duration::from_secs(step));
+            fired.push(maybe_checkpoint(&mut state, &mut mgr, interval).unwrap());
+        }
+        assert_eq!(fired, [None, None, Some(0), None, Some(1), Some(2)]);
+        let ids: Vec<u64> = mgr
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Checkpoint { id, .. } => Some(*id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ids, [0, 1, 2]);
+    }
+}
@@ -0,0 +1,84 @@
+//! An [`InputCollector`] records the inputs the [`super::StdFuzzer`] executes,
+//! to see what the stages actually tried, or to build a replay corpus.
+
+use alloc::vec::Vec;
+
+use crate::{executors::ExitKind, fuzzer::FuzzerHook, inputs::UsesInput, state::State, Error};
+
+/// Collects copies of up to `max_inputs` executed inputs, in execution order.
+///
+/// Further inputs are only counted, see [`InputCollector::dropped`].
+/// As a [`FuzzerHook`], it records each input run through the fuzzer, for example using [`super::Evaluator::evaluate_input`].
+/// Stages calling the executor directly, like the calibration stage, are not recorded.
+#[derive(Debug, Clone)]
+pub struct InputCollector<I> {
+    inputs: Vec<I>,
+    max_inputs: usize,
+    dropped: usize,
+}
+
+impl<I> InputCollector<I>
+where
+    I: Clone,
+{
+    /// Creates a new [`InputCollector`], keeping up to `max_inputs` inputs
+    #[must_use]
+    pub fn new(max_inputs: usize) -> Self {
+        Self {
+            inputs: Vec::new(),
+            max_inputs,
+            dropped: 0,
+        }
+    }
+
+    /// Records an executed input
+    pub fn push(&mut self, input: &I) {
+        if self.inputs.len() < self.max_inputs {
+            self.inputs.push(input.clone());
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    /// Drops the collected inputs and the count of dropped inputs, for example before each [`super::Fuzzer::fuzz_one`]
+    pub fn clear(&mut self) {
+        self.inputs.clear();
+        self.dropped = 0;
+    }
+
+    /// The collected inputs
+    #[must_use]
+    pub fn inputs(&self) -> &[I] {
+        &self.inputs
+    }
+
+    /// Consumes the collector, returning the collected inputs
+    #[must_use]
+    pub fn into_inputs(self) -> Vec<I> {
+        self.inputs
+    }
+
+    /// The number of executed inputs that did not fit into the collector
+    #[must_use]
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// The number of executed inputs, collected or not
+    #[must_use]
+    pub fn executed(&self) -> usize {
+        self.inputs.len() + self.dropped
+    }
+}
+
+impl<I, S> FuzzerHook<S> for InputCollector<I>
+where
+    I: Clone,
+    S: State + UsesInput<Input = I>,
+{
+    fn post_exec(&mut self, _state: &mut S, input: &I, res: &Result<ExitKind, Error>) {
+        if res.is_ok() {
+            self.push(input);
+        }
+    }
+}
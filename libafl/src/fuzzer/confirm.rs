@@ -0,0 +1,195 @@
+//! A [`CrashConfirmation`] re-runs new solutions of the [`super::StdFuzzer`] before saving them,
+//! to tell reproducible crashes from flaky ones.
+//!
+//! Flaky crashes, depending on timing, threads, or leftover state of the target, are hard to triage and fix.
+//! Knowing which solutions reproduce reliably helps to prioritize.
+
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::Testcase, events::EventFirer, executors::ExitKind, feedbacks::Feedback,
+    observers::ObserversTuple, state::State, Error, HasMetadata,
+};
+
+/// What happens to solutions that do not reproduce in every confirmation run, see [`CrashConfirmation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlakyPolicy {
+    /// The solution is saved, with a [`FlakySolutionMetadata`]
+    Mark,
+    /// The solution is not saved
+    Discard,
+}
+
+/// Wraps an objective, and re-runs each new solution a number of times, before it is saved.
+///
+/// The confirmation runs are requested using [`Feedback::needs_rerun`],
+/// so the fuzzer has to allow at least `runs` reruns, see [`super::MAX_FEEDBACK_RERUNS`].
+/// A run reproduces the solution, if it ends with the same [`ExitKind`] as the original run.
+/// The saved solution keeps the objective kind of the original run,
+/// but the wrapped objective adds its metadata from the observers of the last confirmation run.
+/// A discarded solution is evaluated by the feedback instead, like any other input that is no solution.
+///
+/// Only solutions evaluated by the fuzzer, like timeouts or crashes reported as an exit kind, are confirmed.
+/// Crashes caught by the signal handlers of the in-process executors are saved right away,
+/// as the process does not survive them.
+#[derive(Debug, Clone)]
+pub struct CrashConfirmation<A> {
+    objective: A,
+    runs: usize,
+    policy: FlakyPolicy,
+    /// The exit kind of the original run, while confirming a solution
+    original: Option<ExitKind>,
+    confirmed: usize,
+    reproduced: usize,
+}
+
+impl<A> CrashConfirmation<A> {
+    /// Creates a new [`CrashConfirmation`] of the solutions of the `objective`, re-running each solution `runs` times,
+    /// and applying the `policy` if it did not reproduce in all of them
+    #[must_use]
+    pub fn new(objective: A, runs: usize, policy: FlakyPolicy) -> Self {
+        Self {
+            objective,
+            runs,
+            policy,
+            original: None,
+            confirmed: 0,
+            reproduced: 0,
+        }
+    }
+
+    /// The number of confirmation runs
+    #[must_use]
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    /// The policy for flaky solutions
+    #[must_use]
+    pub fn policy(&self) -> FlakyPolicy {
+        self.policy
+    }
+
+    /// If a solution is being confirmed
+    fn is_confirming(&self) -> bool {
+        self.original.is_some() && self.confirmed < self.runs
+    }
+
+    fn reset(&mut self) {
+        self.original = None;
+        self.confirmed = 0;
+        self.reproduced = 0;
+    }
+}
+
+impl<A> Named for CrashConfirmation<A>
+where
+    A: Named,
+{
+    #[inline]
+    fn name(&self) -> &str {
+        self.objective.name()
+    }
+}
+
+impl<A, S> Feedback<S> for CrashConfirmation<A>
+where
+    A: Feedback<S>,
+    S: State,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        self.objective.init_state(state)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn is_interesting<EM, OT>(
+        &mut self,
+        state: &mut S,
+        manager: &mut EM,
+        input: &S::Input,
+        observers: &OT,
+        exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        if let Some(original) = self.original.filter(|_| self.is_confirming()) {
+            self.confirmed += 1;
+            if *exit_kind == original {
+                self.reproduced += 1;
+            }
+            return Ok(self.is_confirming()
+                || self.reproduced == self.runs
+                || self.policy == FlakyPolicy::Mark);
+        }
+
+        self.reset();
+        let is_solution = self
+            .objective
+            .is_interesting(state, manager, input, observers, exit_kind)?;
+        if is_solution && !self.objective.needs_rerun() {
+            self.original = Some(*exit_kind);
+        }
+        Ok(is_solution)
+    }
+
+    #[inline]
+    fn append_metadata<EM, OT>(
+        &mut self,
+        state: &mut S,
+        manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<S::Input>,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<S>,
+        EM: EventFirer<State = S>,
+    {
+        if let Some(original) = self.original {
+            testcase.set_objective_kind(original.into());
+            if self.confirmed == self.runs && self.reproduced < self.runs {
+                testcase.add_metadata(FlakySolutionMetadata {
+                    runs: self.runs,
+                    reproduced: self.reproduced,
+                });
+            }
+        }
+        self.reset();
+        self.objective
+            .append_metadata(state, manager, observers, testcase)
+    }
+
+    #[inline]
+    fn discard_metadata(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
+        self.reset();
+        self.objective.discard_metadata(state, input)
+    }
+
+    #[inline]
+    fn needs_rerun(&self) -> bool {
+        self.is_confirming() || self.objective.needs_rerun()
+    }
+
+    #[inline]
+    fn reset_rerun(&mut self) {
+        self.reset();
+        self.objective.reset_rerun();
+    }
+}
+
+/// Marks a solution that did not reproduce in all confirmation runs, see [`CrashConfirmation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct FlakySolutionMetadata {
+    /// The number of confirmation runs
+    pub runs: usize,
+    /// The number of confirmation runs that reproduced the solution
+    pub reproduced: usize,
+}
+
+libafl_bolts::impl_serdeany!(FlakySolutionMetadata);
@@ -0,0 +1,168 @@
+//! A [`CorpusGate`] keeps the [`super::StdFuzzer`] from running its stages, until the corpus is good enough.
+//!
+//! For targets with a weak seed corpus, havoc and friends waste a lot of time.
+//! With a gate, the fuzzer only imports testcases (for example from other nodes) until the corpus
+//! has enough entries, or covers enough edges, and only then switches to the stages.
+
+use alloc::string::{String, ToString};
+
+use crate::{
+    corpus::Corpus,
+    feedbacks::MapFeedbackMetadata,
+    fuzzer::FuzzerHook,
+    state::{HasCorpus, State},
+    HasNamedMetadata,
+};
+
+/// A gate that only opens once the corpus has at least `min_entries` entries
+/// and covers at least `min_edges` edges.
+///
+/// Once opened, the gate stays open.
+/// As a [`FuzzerHook`], it skips the stages while closed,
+/// so [`super::Fuzzer::fuzz_one`] only processes incoming events, i.e., imports new testcases.
+#[derive(Debug, Clone, Default)]
+pub struct CorpusGate {
+    /// The minimum number of entries in the corpus
+    min_entries: usize,
+    /// The minimum number of covered edges
+    min_edges: usize,
+    /// The name of the map feedback that keeps track of the covered edges
+    edges_name: Option<String>,
+    /// If the gate is already open
+    open: bool,
+}
+
+impl CorpusGate {
+    /// Creates a new [`CorpusGate`] that opens once the corpus contains at least `min_entries` entries
+    #[must_use]
+    pub fn new(min_entries: usize) -> Self {
+        Self {
+            min_entries,
+            ..Self::default()
+        }
+    }
+
+    /// Additionally require at least `min_edges` covered edges, as tracked by the
+    /// [`MapFeedbackMetadata`] of the (`u8`) map feedback called `edges_name`
+    #[must_use]
+    pub fn with_min_edges(mut self, edges_name: &str, min_edges: usize) -> Self {
+        self.edges_name = Some(edges_name.to_string());
+        self.min_edges = min_edges;
+        self
+    }
+
+    /// The number of edges covered so far, or `0`, if no map feedback is known
+    pub fn covered_edges<S>(&self, state: &S) -> usize
+    where
+        S: HasNamedMetadata,
+    {
+        self.edges_name
+            .as_ref()
+            .and_then(|name| state.named_metadata::<MapFeedbackMetadata<u8>>(name).ok())
+            .map_or(0, |meta| meta.num_covered_map_indexes)
+    }
+
+    /// If the gate is open, i.e., the fuzzer should run its stages.
+    /// Checks the current corpus, if the gate is still closed.
+    pub fn is_open<S>(&mut self, state: &S) -> bool
+    where
+        S: HasCorpus + HasNamedMetadata,
+    {
+        if !self.open
+            && state.corpus().count() >= self.min_entries
+            && self.covered_edges(state) >= self.min_edges
+        {
+            log::info!(
+                "Corpus gate opened with {} entries, switching to the stages",
+                state.corpus().count()
+            );
+            self.open = true;
+        }
+        self.open
+    }
+}
+
+impl<S> FuzzerHook<S> for CorpusGate
+where
+    S: State + HasCorpus + HasNamedMetadata,
+{
+    fn pre_stages(&mut self, state: &mut S) -> bool {
+        self.is_open(state)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use libafl_bolts::tuples::tuple_list;
+
+    #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+    use crate::stages::ExecutionCountRestartHelperMetadata;
+    use crate::{
+        corpus::{Corpus, Testcase},
+        executors::ExitKind,
+        feedbacks::{ConstFeedback, MapFeedbackMetadata},
+        fuzzer::{test::test_fuzzer, CorpusGate, Fuzzer},
+        inputs::BytesInput,
+        mutators::{havoc_mutations, StdScheduledMutator},
+        schedulers::QueueScheduler,
+        stages::StdMutationalStage,
+        state::{HasCorpus, HasExecutions},
+        HasNamedMetadata,
+    };
+
+    #[test]
+    fn test_corpus_gate() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            ExecutionCountRestartHelperMetadata::register();
+            MapFeedbackMetadata::<u8>::register();
+        }
+
+        let mut harness = |_input: &BytesInput| ExitKind::Ok;
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            tuple_list!(CorpusGate::new(3)),
+            &mut harness,
+            tuple_list!(),
+        );
+        state
+            .corpus_mut()
+            .add(Testcase::new(vec![0; 4].into()))
+            .unwrap();
+
+        let mutator = StdScheduledMutator::new(havoc_mutations());
+        let mut stages = tuple_list!(StdMutationalStage::with_max_iterations(mutator, 4));
+
+        // Not enough entries, the mutational stage never runs
+        fuzzer
+            .fuzz_loop_for(&mut stages, &mut executor, &mut state, &mut mgr, 5)
+            .unwrap();
+        assert_eq!(*state.executions(), 0);
+
+        for i in 1..3 {
+            state
+                .corpus_mut()
+                .add(Testcase::new(vec![i; 4].into()))
+                .unwrap();
+        }
+        fuzzer
+            .fuzz_loop_for(&mut stages, &mut executor, &mut state, &mut mgr, 5)
+            .unwrap();
+        assert!(*state.executions() > 0);
+
+        // Edges are counted using the map feedback metadata
+        let mut gate = CorpusGate::new(0).with_min_edges("edges", 2);
+        assert!(!gate.is_open(&state));
+        state.add_named_metadata(
+            "edges",
+            MapFeedbackMetadata::with_history_map(vec![1_u8, 0, 1, 0], 0),
+        );
+        assert_eq!(gate.covered_edges(&state), 2);
+        assert!(gate.is_open(&state));
+    }
+}
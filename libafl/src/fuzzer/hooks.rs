@@ -0,0 +1,193 @@
+//! Hooks for the [`super::StdFuzzer`].
+//! These run around the stages of each [`super::Fuzzer::fuzz_one`], after each execution,
+//! and right before an interesting input is added to the corpus.
+
+use crate::{
+    corpus::CorpusId, events::EventFirer, executors::ExitKind, inputs::UsesInput, state::State,
+    Error,
+};
+
+/// A hook of the [`super::StdFuzzer`], for optional policies on top of the core fuzzing logic.
+///
+/// All methods default to doing nothing, so a hook only implements the points it needs.
+pub trait FuzzerHook<S>
+where
+    S: State,
+{
+    /// The hook that runs before the stages of a [`super::Fuzzer::fuzz_one`].
+    /// Returns `false` to skip the stages, the fuzzer then only processes incoming events.
+    fn pre_stages(&mut self, _state: &mut S) -> bool {
+        true
+    }
+
+    /// The hook that runs after the stages fuzzed the testcase `idx`, with their result.
+    /// Returns the result to pass on, for example to swallow errors.
+    fn post_stages(
+        &mut self,
+        _state: &mut S,
+        _idx: CorpusId,
+        res: Result<(), Error>,
+    ) -> Result<(), Error> {
+        res
+    }
+
+    /// The hook that runs at the end of each [`super::Fuzzer::fuzz_one`]
+    fn post_fuzz_one<EM>(&mut self, _state: &mut S, _manager: &mut EM) -> Result<(), Error>
+    where
+        EM: EventFirer<State = S>,
+    {
+        Ok(())
+    }
+
+    /// The hook that runs after each execution of an input through the fuzzer
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &<S as UsesInput>::Input,
+        _res: &Result<ExitKind, Error>,
+    ) {
+    }
+
+    /// The hook that runs before an interesting input is added to the corpus.
+    /// Returns `false` to keep the input out of the corpus.
+    fn admit_corpus_input(&mut self, _state: &mut S, _input: &<S as UsesInput>::Input) -> bool {
+        true
+    }
+
+    /// The hook that may change an interesting input before it is evaluated for the last time.
+    /// Returns `true`, if the input changed, so the fuzzer executes and evaluates it again.
+    fn trim_corpus_input(&mut self, _state: &mut S, _input: &mut <S as UsesInput>::Input) -> bool {
+        false
+    }
+}
+
+/// The hooks of the [`super::StdFuzzer`], see [`FuzzerHook`]
+pub trait FuzzerHooksTuple<S>
+where
+    S: State,
+{
+    /// The hooks that run before the stages, returns `false` if any hook skips them
+    fn pre_stages_all(&mut self, state: &mut S) -> bool;
+    /// The hooks that run after the stages
+    fn post_stages_all(
+        &mut self,
+        state: &mut S,
+        idx: CorpusId,
+        res: Result<(), Error>,
+    ) -> Result<(), Error>;
+    /// The hooks that run at the end of each [`super::Fuzzer::fuzz_one`]
+    fn post_fuzz_one_all<EM>(&mut self, state: &mut S, manager: &mut EM) -> Result<(), Error>
+    where
+        EM: EventFirer<State = S>;
+    /// The hooks that run after each execution
+    fn post_exec_all(
+        &mut self,
+        state: &mut S,
+        input: &<S as UsesInput>::Input,
+        res: &Result<ExitKind, Error>,
+    );
+    /// The hooks that run before an input is added to the corpus, returns `false` if any hook rejects it
+    fn admit_corpus_input_all(&mut self, state: &mut S, input: &<S as UsesInput>::Input) -> bool;
+    /// The hooks that may change an interesting input, returns `true` if any hook changed it
+    fn trim_corpus_input_all(&mut self, state: &mut S, input: &mut <S as UsesInput>::Input)
+        -> bool;
+}
+
+impl<S> FuzzerHooksTuple<S> for ()
+where
+    S: State,
+{
+    fn pre_stages_all(&mut self, _state: &mut S) -> bool {
+        true
+    }
+
+    fn post_stages_all(
+        &mut self,
+        _state: &mut S,
+        _idx: CorpusId,
+        res: Result<(), Error>,
+    ) -> Result<(), Error> {
+        res
+    }
+
+    fn post_fuzz_one_all<EM>(&mut self, _state: &mut S, _manager: &mut EM) -> Result<(), Error>
+    where
+        EM: EventFirer<State = S>,
+    {
+        Ok(())
+    }
+
+    fn post_exec_all(
+        &mut self,
+        _state: &mut S,
+        _input: &<S as UsesInput>::Input,
+        _res: &Result<ExitKind, Error>,
+    ) {
+    }
+
+    fn admit_corpus_input_all(&mut self, _state: &mut S, _input: &<S as UsesInput>::Input) -> bool {
+        true
+    }
+
+    fn trim_corpus_input_all(
+        &mut self,
+        _state: &mut S,
+        _input: &mut <S as UsesInput>::Input,
+    ) -> bool {
+        false
+    }
+}
+
+impl<Head, Tail, S> FuzzerHooksTuple<S> for (Head, Tail)
+where
+    S: State,
+    Head: FuzzerHook<S>,
+    Tail: FuzzerHooksTuple<S>,
+{
+    fn pre_stages_all(&mut self, state: &mut S) -> bool {
+        // Every hook sees every iteration, even if an earlier one skips the stages
+        let run = self.0.pre_stages(state);
+        self.1.pre_stages_all(state) && run
+    }
+
+    fn post_stages_all(
+        &mut self,
+        state: &mut S,
+        idx: CorpusId,
+        res: Result<(), Error>,
+    ) -> Result<(), Error> {
+        let res = self.0.post_stages(state, idx, res);
+        self.1.post_stages_all(state, idx, res)
+    }
+
+    fn post_fuzz_one_all<EM>(&mut self, state: &mut S, manager: &mut EM) -> Result<(), Error>
+    where
+        EM: EventFirer<State = S>,
+    {
+        self.0.post_fuzz_one(state, manager)?;
+        self.1.post_fuzz_one_all(state, manager)
+    }
+
+    fn post_exec_all(
+        &mut self,
+        state: &mut S,
+        input: &<S as UsesInput>::Input,
+        res: &Result<ExitKind, Error>,
+    ) {
+        self.0.post_exec(state, input, res);
+        self.1.post_exec_all(state, input, res);
+    }
+
+    fn admit_corpus_input_all(&mut self, state: &mut S, input: &<S as UsesInput>::Input) -> bool {
+        self.0.admit_corpus_input(state, input) && self.1.admit_corpus_input_all(state, input)
+    }
+
+    fn trim_corpus_input_all(
+        &mut self,
+        state: &mut S,
+        input: &mut <S as UsesInput>::Input,
+    ) -> bool {
+        let trimmed = self.0.trim_corpus_input(state, input);
+        self.1.trim_corpus_input_all(state, input) || trimmed
+    }
+}
@@ -0,0 +1,120 @@
+//! A [`CorpusLengthLimit`] keeps overly long inputs out of the corpus of the [`super::StdFuzzer`].
+//!
+//! Long inputs slow down every later execution of their children, and many targets only look at a prefix anyway.
+
+use crate::{
+    fuzzer::FuzzerHook,
+    inputs::{HasBytesVec, UsesInput},
+    state::State,
+};
+
+/// What happens to interesting inputs longer than the maximum length, see [`CorpusLengthLimit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthPolicy {
+    /// The input is not added to the corpus
+    Reject,
+    /// The input is truncated to the maximum length, then executed and evaluated again,
+    /// so that it is only added to the corpus, with its own metadata, if it is still interesting.
+    /// Inputs evaluated without an executor, for example from their observers only, can't be run again and are rejected.
+    Trim,
+}
+
+/// A [`FuzzerHook`] that limits the length of the inputs the [`super::StdFuzzer`] adds to its corpus, even if they are interesting.
+/// Solutions are never limited.
+///
+/// The feedbacks discard the coverage of a rejected input, so the same coverage reached by a shorter input later
+/// is still considered new.
+#[derive(Debug, Clone)]
+pub struct CorpusLengthLimit<I> {
+    max_len: usize,
+    policy: LengthPolicy,
+    len: fn(&I) -> usize,
+    truncate: fn(&mut I, usize),
+    rejected: usize,
+    trimmed: usize,
+}
+
+impl<I> CorpusLengthLimit<I>
+where
+    I: HasBytesVec,
+{
+    /// Creates a new [`CorpusLengthLimit`], applying the `policy` to inputs longer than `max_len` bytes
+    #[must_use]
+    pub fn new(max_len: usize, policy: LengthPolicy) -> Self {
+        Self {
+            max_len,
+            policy,
+            len: |input| input.bytes().len(),
+            truncate: |input, len| input.bytes_mut().truncate(len),
+            rejected: 0,
+            trimmed: 0,
+        }
+    }
+}
+
+impl<I> CorpusLengthLimit<I> {
+    /// The maximum length of inputs in the corpus
+    #[must_use]
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// The policy for oversized inputs
+    #[must_use]
+    pub fn policy(&self) -> LengthPolicy {
+        self.policy
+    }
+
+    /// If the `input` is longer than the maximum length
+    #[must_use]
+    pub fn is_oversized(&self, input: &I) -> bool {
+        (self.len)(input) > self.max_len
+    }
+
+    /// Checks an interesting `input` right before it is added to the corpus,
+    /// returns `false`, counting it as rejected, if it is oversized
+    pub fn admit(&mut self, input: &I) -> bool {
+        if self.is_oversized(input) {
+            self.rejected += 1;
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Truncates an oversized, interesting `input` to the maximum length, if the policy is [`LengthPolicy::Trim`].
+    /// Returns `true` if the input got trimmed, so it has to be evaluated again.
+    pub fn trim(&mut self, input: &mut I) -> bool {
+        if self.policy != LengthPolicy::Trim || !self.is_oversized(input) {
+            return false;
+        }
+        (self.truncate)(input, self.max_len);
+        self.trimmed += 1;
+        true
+    }
+
+    /// The number of interesting inputs rejected so far
+    #[must_use]
+    pub fn rejected(&self) -> usize {
+        self.rejected
+    }
+
+    /// The number of interesting inputs trimmed so far
+    #[must_use]
+    pub fn trimmed(&self) -> usize {
+        self.trimmed
+    }
+}
+
+impl<I, S> FuzzerHook<S> for CorpusLengthLimit<I>
+where
+    S: State + UsesInput<Input = I>,
+{
+    fn admit_corpus_input(&mut self, _state: &mut S, input: &I) -> bool {
+        self.admit(input)
+    }
+
+    fn trim_corpus_input(&mut self, _state: &mut S, input: &mut I) -> bool {
+        self.trim(input)
+    }
+}
@@ -1,24 +1,58 @@
 //! The `Fuzzer` is the main struct for a fuzz campaign.
 
+pub mod checkpoint;
+pub use checkpoint::{maybe_checkpoint, CheckpointInterval, CheckpointMetadata};
+pub mod collector;
+pub use collector::InputCollector;
+pub mod confirm;
+pub use confirm::{CrashConfirmation, FlakyPolicy, FlakySolutionMetadata};
+pub mod gate;
+pub use gate::CorpusGate;
+pub mod hooks;
+pub use hooks::{FuzzerHook, FuzzerHooksTuple};
+pub mod length;
+pub use length::{CorpusLengthLimit, LengthPolicy};
+pub mod quarantine;
+pub use quarantine::Quarantine;
+#[cfg(feature = "std")]
+pub mod pause;
+#[cfg(feature = "std")]
+pub use pause::FuzzLoopPauseHandle;
+#[cfg(feature = "std")]
+pub mod stop;
+#[cfg(feature = "std")]
+pub use stop::FuzzLoopStopHandle;
+
 use alloc::string::ToString;
 use core::{fmt::Debug, marker::PhantomData, time::Duration};
+#[cfg(feature = "std")]
+use std::path::Path;
 
-use libafl_bolts::current_time;
+use libafl_bolts::{rands::Rand, tuples::MatchFirstType};
 use serde::{de::DeserializeOwned, Serialize};
 
+#[cfg(feature = "std")]
+use crate::mutators::{MutatorsTuple, ReproductionRecipe};
 use crate::{
     corpus::{Corpus, CorpusId, HasCurrentCorpusIdx, HasTestcase, Testcase},
-    events::{Event, EventConfig, EventFirer, EventProcessor, ProgressReporter},
+    events::{
+        CoverageOnlyImportMetadata, Event, EventConfig, EventFirer, EventProcessor, EventRestarter,
+        NewTestcaseSenderMetadata, ProgressReporter,
+    },
     executors::{Executor, ExitKind, HasObservers},
     feedbacks::Feedback,
     inputs::UsesInput,
     mark_feature_time,
+    monitors::{AggregatorOps, UserStats, UserStatsValue},
     observers::ObserversTuple,
     schedulers::Scheduler,
     stages::{HasCurrentStage, StagesTuple},
     start_timer,
-    state::{HasCorpus, HasExecutions, HasImported, HasLastReportTime, HasSolutions, UsesState},
-    Error, HasMetadata,
+    state::{
+        HasClock, HasCorpus, HasExecutions, HasFirstFindTimes, HasImported, HasLastReportTime,
+        HasRand, HasSolutions, HasStartTime, State, UsesState,
+    },
+    Error, HasMetadata, HasNamedMetadata,
 };
 #[cfg(feature = "introspection")]
 use crate::{monitors::PerfFeature, state::HasClientPerfMonitor};
@@ -26,6 +60,10 @@ use crate::{monitors::PerfFeature, state::HasClientPerfMonitor};
 /// Send a monitor update all 15 (or more) seconds
 const STATS_TIMEOUT_DEFAULT: Duration = Duration::from_secs(15);
 
+/// The maximum number of reruns of an input, requested by [`Feedback::needs_rerun`].
+/// Feedbacks bound their own reruns, this only stops feedbacks that never decide.
+pub const MAX_FEEDBACK_RERUNS: usize = 64;
+
 /// Holds a scheduler
 pub trait HasScheduler: UsesState
 where
@@ -205,20 +243,100 @@ where
         manager: &mut EM,
     ) -> Result<CorpusId, Error>;
 
+    /// The handle that pauses the [`Fuzzer::fuzz_loop`] of this fuzzer, if any
+    #[cfg(feature = "std")]
+    fn pause_handle(&self) -> Option<&FuzzLoopPauseHandle> {
+        None
+    }
+
+    /// The handle that stops the [`Fuzzer::fuzz_loop`] of this fuzzer, if any
+    #[cfg(feature = "std")]
+    fn stop_handle(&self) -> Option<&FuzzLoopStopHandle> {
+        None
+    }
+
     /// Fuzz forever (or until stopped)
+    ///
+    /// With the `std` feature, the loop stops after the current iteration once the [`Fuzzer::stop_handle`] requested it,
+    /// for example on Ctrl-C, see [`FuzzLoopStopHandle::stop_on_shutdown_signal`].
+    /// It then reports its progress, and returns `Ok(())`.
+    /// Use [`Fuzzer::fuzz_loop_graceful`] to also persist the state.
+    ///
+    /// While the [`Fuzzer::pause_handle`] is paused, the loop sleeps after the current iteration.
     fn fuzz_loop(
         &mut self,
         stages: &mut ST,
         executor: &mut E,
         state: &mut EM::State,
         manager: &mut EM,
-    ) -> Result<(), Error> {
+    ) -> Result<(), Error>
+    where
+        Self::State: HasClock,
+    {
         let monitor_timeout = STATS_TIMEOUT_DEFAULT;
+        #[cfg(feature = "std")]
+        let pause_handle = self.pause_handle().cloned();
+        #[cfg(feature = "std")]
+        let stop_handle = self.stop_handle().cloned();
         loop {
             // log::info!("Starting another fuzz_loop");
             manager.maybe_report_progress(state, monitor_timeout)?;
             self.fuzz_one(stages, executor, state, manager)?;
+
+            #[cfg(feature = "std")]
+            if let Some(pause_handle) = &pause_handle {
+                while pause_handle.is_paused()
+                    && !stop_handle
+                        .as_ref()
+                        .is_some_and(FuzzLoopStopHandle::is_stop_requested)
+                {
+                    manager.maybe_report_progress(state, monitor_timeout)?;
+                    std::thread::sleep(pause::FUZZ_LOOP_PAUSE_POLL_INTERVAL);
+                }
+            }
+
+            #[cfg(feature = "std")]
+            if stop_handle
+                .as_ref()
+                .is_some_and(FuzzLoopStopHandle::take_stop_request)
+            {
+                log::info!("Stop requested, exiting the fuzz loop");
+                manager.report_progress(state)?;
+                return Ok(());
+            }
+        }
+    }
+
+    /// Like [`Fuzzer::fuzz_loop`], but once stopped, or if fuzzing failed, persists the state:
+    /// The corpus and the solutions get flushed to disk, see [`Corpus::flush`],
+    /// the event manager stores the state using [`EventRestarter::on_restart`],
+    /// and tells the restarter not to respawn this client, see [`EventRestarter::send_exiting`].
+    ///
+    /// Returns the error of the fuzz loop, if any, else the error of persisting the state.
+    fn fuzz_loop_graceful(
+        &mut self,
+        stages: &mut ST,
+        executor: &mut E,
+        state: &mut EM::State,
+        manager: &mut EM,
+    ) -> Result<(), Error>
+    where
+        Self::State: HasClock + HasCorpus + HasSolutions,
+        EM: EventRestarter,
+    {
+        let res = self.fuzz_loop(stages, executor, state, manager);
+        let persisted = state
+            .corpus_mut()
+            .flush()
+            .and_then(|()| state.solutions_mut().flush())
+            .and_then(|()| manager.on_restart(state))
+            .and_then(|()| manager.send_exiting());
+        if let (Err(err), Err(persist_err)) = (&res, &persisted) {
+            log::error!(
+                "Could not persist the state after the fuzz loop failed with {err}: {persist_err}"
+            );
         }
+        res.and(persisted)
     }
 
     /// Fuzz for n iterations.
@@ -237,7 +355,10 @@ where
         state: &mut EM::State,
         manager: &mut EM,
         iters: u64,
-    ) -> Result<CorpusId, Error> {
+    ) -> Result<CorpusId, Error>
+    where
+        Self::State: HasClock,
+    {
         if iters == 0 {
             return Err(Error::illegal_argument(
                 "Cannot fuzz for 0 iterations!".to_string(),
@@ -262,6 +383,47 @@ where
 
         Ok(ret.unwrap())
     }
+
+    /// Fuzz for n iterations in a deterministic, "known answer" mode.
+    /// Returns the index of the last fuzzed corpus item.
+    ///
+    /// Before each iteration, the [`libafl_bolts::rands::Rand`] of the state gets reseeded,
+    /// based on the given `seed` and the iteration number.
+    /// The outcome of an iteration therefore only depends on `seed` and the current corpus,
+    /// and not on randomness consumed elsewhere, for example while processing events.
+    /// With a deterministic executor, two runs with the same `seed` end up with identical corpora.
+    ///
+    /// Progress is never reported in this mode, as it depends on the wall-clock time.
+    /// This is mostly useful to test stages and mutators end-to-end.
+    fn fuzz_loop_for_deterministic(
+        &mut self,
+        stages: &mut ST,
+        executor: &mut E,
+        state: &mut EM::State,
+        manager: &mut EM,
+        seed: u64,
+        iters: u64,
+    ) -> Result<CorpusId, Error>
+    where
+        EM::State: HasRand,
+    {
+        if iters == 0 {
+            return Err(Error::illegal_argument(
+                "Cannot fuzz for 0 iterations!".to_string(),
+            ));
+        }
+
+        let mut ret = None;
+
+        for iter in 0..iters {
+            state
+                .rand_mut()
+                .set_seed(seed ^ iter.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            ret = Some(self.fuzz_one(stages, executor, state, manager)?);
+        }
+
+        Ok(ret.unwrap())
+    }
 }
 
 /// The corpus this input should be added to
@@ -276,8 +438,10 @@ pub enum ExecuteInputResult {
 }
 
 /// Your default fuzzer instance, for everyday use.
+///
+/// Optional policies, like a [`CorpusGate`] or a [`Quarantine`], are added as [`FuzzerHook`]s, see [`StdFuzzer::with_hooks`].
 #[derive(Debug)]
-pub struct StdFuzzer<CS, F, OF, OT>
+pub struct StdFuzzer<CS, F, OF, OT, H = ()>
 where
     CS: Scheduler,
     F: Feedback<CS::State>,
@@ -287,10 +451,11 @@ where
     scheduler: CS,
     feedback: F,
     objective: OF,
+    hooks: H,
     phantom: PhantomData<OT>,
 }
 
-impl<CS, F, OF, OT> UsesState for StdFuzzer<CS, F, OF, OT>
+impl<CS, F, OF, OT, H> UsesState for StdFuzzer<CS, F, OF, OT, H>
 where
     CS: Scheduler,
     F: Feedback<CS::State>,
@@ -300,7 +465,7 @@ where
     type State = CS::State;
 }
 
-impl<CS, F, OF, OT> HasScheduler for StdFuzzer<CS, F, OF, OT>
+impl<CS, F, OF, OT, H> HasScheduler for StdFuzzer<CS, F, OF, OT, H>
 where
     CS: Scheduler,
     F: Feedback<CS::State>,
@@ -318,7 +483,7 @@ where
     }
 }
 
-impl<CS, F, OF, OT> HasFeedback for StdFuzzer<CS, F, OF, OT>
+impl<CS, F, OF, OT, H> HasFeedback for StdFuzzer<CS, F, OF, OT, H>
 where
     CS: Scheduler,
     F: Feedback<CS::State>,
@@ -336,7 +501,7 @@ where
     }
 }
 
-impl<CS, F, OF, OT> HasObjective for StdFuzzer<CS, F, OF, OT>
+impl<CS, F, OF, OT, H> HasObjective for StdFuzzer<CS, F, OF, OT, H>
 where
     CS: Scheduler,
     F: Feedback<CS::State>,
@@ -354,13 +519,21 @@ where
     }
 }
 
-impl<CS, F, OF, OT> ExecutionProcessor<OT> for StdFuzzer<CS, F, OF, OT>
+impl<CS, F, OF, OT, H> ExecutionProcessor<OT> for StdFuzzer<CS, F, OF, OT, H>
 where
     CS: Scheduler,
     F: Feedback<CS::State>,
     OF: Feedback<CS::State>,
+    H: FuzzerHooksTuple<CS::State>,
     OT: ObserversTuple<CS::State> + Serialize + DeserializeOwned,
-    CS::State: HasCorpus + HasSolutions + HasExecutions + HasCorpus + HasImported,
+    CS::State: HasCorpus
+        + HasSolutions
+        + HasExecutions
+        + HasCorpus
+        + HasImported
+        + HasMetadata
+        + HasFirstFindTimes
+        + HasClock,
 {
     fn execute_no_process<EM>(
         &mut self,
@@ -454,14 +627,42 @@ where
                 // Not a solution
                 self.objective_mut().discard_metadata(state, &input)?;
 
+                if !send_events && state.has_metadata::<CoverageOnlyImportMetadata>() {
+                    // Another client owns this testcase, only record its coverage
+                    self.feedback_mut().append_metadata(
+                        state,
+                        manager,
+                        observers,
+                        &mut Testcase::new(input),
+                    )?;
+                    return Ok(None);
+                }
+
+                if !self.hooks.admit_corpus_input_all(state, &input) {
+                    self.feedback_mut().discard_metadata(state, &input)?;
+                    return Ok(None);
+                }
+
                 // Add the input to the main corpus
                 let mut testcase = Testcase::with_executions(input.clone(), *state.executions());
+                testcase.set_added_time(state.now());
+                if !send_events {
+                    // This testcase is from the other fuzzers, remember who found it
+                    if let Ok(meta) = state.metadata::<NewTestcaseSenderMetadata>() {
+                        testcase.set_sender_id(meta.sender_id);
+                    }
+                }
                 self.feedback_mut()
                     .append_metadata(state, manager, observers, &mut testcase)?;
                 let idx = state.corpus_mut().add(testcase)?;
                 self.scheduler_mut().on_add(state, idx)?;
 
                 if send_events {
+                    if state.time_to_first_corpus().is_none() {
+                        let elapsed = state.now().saturating_sub(*state.start_time());
+                        *state.time_to_first_corpus_mut() = Some(elapsed);
+                        fire_first_find_time(state, manager, "time_to_first_corpus", elapsed)?;
+                    }
                     // TODO set None for fast targets
                     let observers_buf = if manager.configuration() == EventConfig::AlwaysUnique {
                         None
@@ -476,9 +677,10 @@ where
                             exit_kind: *exit_kind,
                             corpus_size: state.corpus().count(),
                             client_config: manager.configuration(),
-                            time: current_time(),
+                            time: state.now(),
                             executions: *state.executions(),
                             forward_id: None,
+                            map_checksum: manager.map_checksum(),
                         },
                     )?;
                 } else {
@@ -495,17 +697,24 @@ where
                 // The input is a solution, add it to the respective corpus
                 let mut testcase = Testcase::with_executions(input, executions);
                 testcase.set_parent_id_optional(*state.corpus().current());
+                testcase.set_added_time(state.now());
+                testcase.set_objective_kind((*exit_kind).into());
                 self.objective_mut()
                     .append_metadata(state, manager, observers, &mut testcase)?;
                 state.solutions_mut().add(testcase)?;
 
                 if send_events {
+                    if state.time_to_first_solution().is_none() {
+                        let elapsed = state.now().saturating_sub(*state.start_time());
+                        *state.time_to_first_solution_mut() = Some(elapsed);
+                        fire_first_find_time(state, manager, "time_to_first_solution", elapsed)?;
+                    }
                     manager.fire(
                         state,
                         Event::Objective {
                             objective_size: state.solutions().count(),
                             executions,
-                            time: current_time(),
+                            time: state.now(),
                         },
                     )?;
                 }
@@ -516,13 +725,44 @@ where
     }
 }
 
-impl<CS, F, OF, OT> EvaluatorObservers<OT> for StdFuzzer<CS, F, OF, OT>
+/// Reports the time it took to make the first find of a kind, in seconds, as user stats
+fn fire_first_find_time<EM, S>(
+    state: &mut S,
+    manager: &mut EM,
+    name: &str,
+    elapsed: Duration,
+) -> Result<(), Error>
+where
+    EM: EventFirer<State = S>,
+    S: State,
+{
+    manager.fire(
+        state,
+        Event::UpdateUserStats {
+            name: name.into(),
+            value: UserStats::new(
+                UserStatsValue::Number(elapsed.as_secs()),
+                AggregatorOps::Min,
+            ),
+            phantom: PhantomData,
+        },
+    )
+}
+
+impl<CS, F, OF, OT, H> EvaluatorObservers<OT> for StdFuzzer<CS, F, OF, OT, H>
 where
     CS: Scheduler,
     OT: ObserversTuple<CS::State> + Serialize + DeserializeOwned,
     F: Feedback<CS::State>,
     OF: Feedback<CS::State>,
-    CS::State: HasCorpus + HasSolutions + HasExecutions + HasImported,
+    H: FuzzerHooksTuple<CS::State>,
+    CS::State: HasCorpus
+        + HasSolutions
+        + HasExecutions
+        + HasImported
+        + HasMetadata
+        + HasFirstFindTimes
+        + HasClock,
 {
     /// Process one input, adding to the respective corpora if needed and firing the right events
     #[inline]
@@ -538,24 +778,159 @@ where
         E: Executor<EM, Self> + HasObservers<Observers = OT, State = Self::State>,
         EM: EventFirer<State = Self::State>,
     {
+        // Drop rerun requests left over from evaluations without reruns
+        self.feedback.reset_rerun();
+        self.objective.reset_rerun();
+
         let exit_kind = self.execute_input(state, executor, manager, &input)?;
-        let observers = executor.observers();
 
-        self.scheduler.on_evaluation(state, &input, observers)?;
+        self.scheduler
+            .on_evaluation(state, &input, executor.observers())?;
+
+        let exec_res =
+            self.execute_no_process(state, manager, &input, executor.observers(), &exit_kind)?;
+        let (mut exec_res, mut exit_kind) =
+            self.rerun_while_requested(state, executor, manager, &input, exec_res, exit_kind)?;
+
+        // An input changed by the hooks, for example trimmed, is evaluated again, so that its metadata matches
+        let mut input = input;
+        if exec_res == ExecuteInputResult::Corpus
+            && self.hooks.trim_corpus_input_all(state, &mut input)
+        {
+            self.feedback.discard_metadata(state, &input)?;
+            self.objective.discard_metadata(state, &input)?;
+            exit_kind = self.execute_input(state, executor, manager, &input)?;
+            exec_res =
+                self.execute_no_process(state, manager, &input, executor.observers(), &exit_kind)?;
+            (exec_res, exit_kind) =
+                self.rerun_while_requested(state, executor, manager, &input, exec_res, exit_kind)?;
+        }
 
-        self.execute_and_process(state, manager, input, observers, &exit_kind, send_events)
+        let corpus_idx = self.process_execution(
+            state,
+            manager,
+            input,
+            &exec_res,
+            executor.observers(),
+            &exit_kind,
+            send_events,
+        )?;
+        Ok((exec_res, corpus_idx))
     }
 }
 
-impl<CS, E, EM, F, OF, OT> Evaluator<E, EM> for StdFuzzer<CS, F, OF, OT>
+impl<CS, F, OF, OT, H> StdFuzzer<CS, F, OF, OT, H>
+where
+    CS: Scheduler,
+    OT: ObserversTuple<CS::State> + Serialize + DeserializeOwned,
+    F: Feedback<CS::State>,
+    OF: Feedback<CS::State>,
+    H: FuzzerHooksTuple<CS::State>,
+    CS::State: HasCorpus
+        + HasSolutions
+        + HasExecutions
+        + HasImported
+        + HasMetadata
+        + HasFirstFindTimes
+        + HasClock,
+{
+    /// Runs the `input` again, as long as the feedback or the objective request it using [`Feedback::needs_rerun`],
+    /// at most [`MAX_FEEDBACK_RERUNS`] times. Returns the final verdict, and the exit kind of the last run.
+    ///
+    /// Only the feedback that requested the rerun is asked again,
+    /// so stateful feedbacks do not lose their verdict for the same input.
+    fn rerun_while_requested<E, EM>(
+        &mut self,
+        state: &mut CS::State,
+        executor: &mut E,
+        manager: &mut EM,
+        input: &<CS::State as UsesInput>::Input,
+        mut exec_res: ExecuteInputResult,
+        mut exit_kind: ExitKind,
+    ) -> Result<(ExecuteInputResult, ExitKind), Error>
+    where
+        E: Executor<EM, Self> + HasObservers<Observers = OT, State = CS::State>,
+        EM: EventFirer<State = CS::State>,
+    {
+        // The feedback is only asked, if the input is no solution
+        let mut feedback_asked = exec_res != ExecuteInputResult::Solution;
+        for _ in 0..MAX_FEEDBACK_RERUNS {
+            let rerun_objective = self.objective.needs_rerun();
+            let rerun_feedback = feedback_asked
+                && exec_res != ExecuteInputResult::Solution
+                && self.feedback.needs_rerun();
+            if !rerun_objective && !rerun_feedback {
+                return Ok((exec_res, exit_kind));
+            }
+            exit_kind = self.execute_input(state, executor, manager, input)?;
+            let observers = executor.observers();
+
+            if rerun_objective {
+                #[cfg(not(feature = "introspection"))]
+                let is_solution = self
+                    .objective
+                    .is_interesting(state, manager, input, observers, &exit_kind)?;
+                #[cfg(feature = "introspection")]
+                let is_solution = self
+                    .objective
+                    .is_interesting_introspection(state, manager, input, observers, &exit_kind)?;
+
+                if is_solution {
+                    exec_res = ExecuteInputResult::Solution;
+                    continue;
+                }
+                if exec_res == ExecuteInputResult::Solution {
+                    exec_res = ExecuteInputResult::None;
+                }
+                if feedback_asked && !rerun_feedback {
+                    // Keep the verdict the feedback gave before
+                    continue;
+                }
+            }
+
+            #[cfg(not(feature = "introspection"))]
+            let corpus_worthy = self
+                .feedback
+                .is_interesting(state, manager, input, observers, &exit_kind)?;
+            #[cfg(feature = "introspection")]
+            let corpus_worthy = self
+                .feedback
+                .is_interesting_introspection(state, manager, input, observers, &exit_kind)?;
+            feedback_asked = true;
+            exec_res = if corpus_worthy {
+                ExecuteInputResult::Corpus
+            } else {
+                ExecuteInputResult::None
+            };
+        }
+
+        if self.objective.needs_rerun() || self.feedback.needs_rerun() {
+            log::warn!(
+                "Feedbacks still request reruns after {MAX_FEEDBACK_RERUNS} reruns, keeping the last verdict"
+            );
+            self.feedback.reset_rerun();
+            self.objective.reset_rerun();
+        }
+        Ok((exec_res, exit_kind))
+    }
+}
+
+impl<CS, E, EM, F, OF, OT, H> Evaluator<E, EM> for StdFuzzer<CS, F, OF, OT, H>
 where
     CS: Scheduler,
     E: HasObservers<State = CS::State, Observers = OT> + Executor<EM, Self>,
     EM: EventFirer<State = CS::State>,
     F: Feedback<CS::State>,
     OF: Feedback<CS::State>,
+    H: FuzzerHooksTuple<CS::State>,
     OT: ObserversTuple<CS::State> + Serialize + DeserializeOwned,
-    CS::State: HasCorpus + HasSolutions + HasExecutions + HasImported,
+    CS::State: HasCorpus
+        + HasSolutions
+        + HasExecutions
+        + HasImported
+        + HasMetadata
+        + HasFirstFindTimes
+        + HasClock,
 {
     /// Process one input, adding to the respective corpora if needed and firing the right events
     #[inline]
@@ -576,6 +951,7 @@ where
     ) -> Result<CorpusId, Error> {
         let mut testcase = Testcase::with_executions(input.clone(), *state.executions());
         testcase.set_disabled(true);
+        testcase.set_added_time(state.now());
         // Add the disabled input to the main corpus
         let idx = state.corpus_mut().add_disabled(testcase)?;
         Ok(idx)
@@ -592,6 +968,7 @@ where
         let observers = executor.observers();
         // Always consider this to be "interesting"
         let mut testcase = Testcase::with_executions(input.clone(), *state.executions());
+        testcase.set_added_time(state.now());
 
         // Maybe a solution
         #[cfg(not(feature = "introspection"))]
@@ -605,6 +982,7 @@ where
             .is_interesting_introspection(state, manager, &input, observers, &exit_kind)?;
 
         if is_solution {
+            testcase.set_objective_kind(exit_kind.into());
             self.objective_mut()
                 .append_metadata(state, manager, observers, &mut testcase)?;
             let idx = state.solutions_mut().add(testcase)?;
@@ -615,7 +993,7 @@ where
                 Event::Objective {
                     objective_size: state.solutions().count(),
                     executions,
-                    time: current_time(),
+                    time: state.now(),
                 },
             )?;
             return Ok(idx);
@@ -655,32 +1033,46 @@ where
                 exit_kind,
                 corpus_size: state.corpus().count(),
                 client_config: manager.configuration(),
-                time: current_time(),
+                time: state.now(),
                 executions: *state.executions(),
                 forward_id: None,
+                map_checksum: manager.map_checksum(),
             },
         )?;
         Ok(idx)
     }
 }
 
-impl<CS, E, EM, F, OF, OT, ST> Fuzzer<E, EM, ST> for StdFuzzer<CS, F, OF, OT>
+impl<CS, E, EM, F, OF, OT, H, ST> Fuzzer<E, EM, ST> for StdFuzzer<CS, F, OF, OT, H>
 where
     CS: Scheduler,
     E: UsesState<State = CS::State>,
     EM: ProgressReporter + EventProcessor<E, Self, State = CS::State>,
     F: Feedback<CS::State>,
     OF: Feedback<CS::State>,
+    H: FuzzerHooksTuple<CS::State> + MatchFirstType,
     CS::State: HasExecutions
         + HasMetadata
         + HasCorpus
         + HasTestcase
         + HasImported
         + HasLastReportTime
+        + HasClock
         + HasCurrentCorpusIdx
+        + HasNamedMetadata
         + HasCurrentStage,
     ST: StagesTuple<E, EM, CS::State, Self>,
 {
+    #[cfg(feature = "std")]
+    fn pause_handle(&self) -> Option<&FuzzLoopPauseHandle> {
+        self.hook()
+    }
+
+    #[cfg(feature = "std")]
+    fn stop_handle(&self) -> Option<&FuzzLoopStopHandle> {
+        self.hook()
+    }
+
     fn fuzz_one(
         &mut self,
         stages: &mut ST,
@@ -698,6 +1090,13 @@ where
         } else {
             let idx = self.scheduler.next(state)?;
             state.set_corpus_idx(idx)?; // set up for resume
+
+            // Let the corpus load the inputs the scheduler likely picks next in the background
+            let prefetch_count = state.corpus().prefetch_count();
+            if prefetch_count > 0 {
+                let upcoming = self.scheduler.upcoming(state, prefetch_count);
+                state.corpus().prefetch(&upcoming);
+            }
             idx
         };
 
@@ -709,8 +1108,11 @@ where
         #[cfg(feature = "introspection")]
         state.introspection_monitor_mut().reset_stage_index();
 
-        // Execute all stages
-        stages.perform_all(self, executor, state, manager)?;
+        // Execute all stages, unless a hook skips them
+        if self.hooks.pre_stages_all(state) {
+            let res = stages.perform_all_by_priority(self, executor, state, manager);
+            self.hooks.post_stages_all(state, idx, res)?;
+        }
 
         // Init timer for manager
         #[cfg(feature = "introspection")]
@@ -733,6 +1135,8 @@ where
 
         state.clear_corpus_idx()?;
 
+        self.hooks.post_fuzz_one_all(state, manager)?;
+
         Ok(idx)
     }
 }
@@ -750,9 +1154,95 @@ where
             scheduler,
             feedback,
             objective,
+            hooks: (),
             phantom: PhantomData,
         }
     }
+}
+
+impl<CS, F, OF, OT, H> StdFuzzer<CS, F, OF, OT, H>
+where
+    CS: Scheduler,
+    F: Feedback<CS::State>,
+    OF: Feedback<CS::State>,
+    CS::State: UsesInput + HasExecutions + HasCorpus,
+{
+    /// Replaces the hooks of this fuzzer, see [`FuzzerHook`]. Pass several hooks as a `tuple_list!`.
+    ///
+    /// Add the hooks before creating the executor, as the in-process executors keep the type of the fuzzer.
+    #[must_use]
+    pub fn with_hooks<H2>(self, hooks: H2) -> StdFuzzer<CS, F, OF, OT, H2> {
+        StdFuzzer {
+            scheduler: self.scheduler,
+            feedback: self.feedback,
+            objective: self.objective,
+            hooks,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The hooks of this fuzzer
+    #[must_use]
+    pub fn hooks(&self) -> &H {
+        &self.hooks
+    }
+
+    /// The hooks of this fuzzer (mutable)
+    pub fn hooks_mut(&mut self) -> &mut H {
+        &mut self.hooks
+    }
+
+    /// The first hook of type `T`, if any
+    #[must_use]
+    pub fn hook<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+        H: MatchFirstType,
+    {
+        self.hooks.match_first_type()
+    }
+
+    /// The first hook of type `T`, if any (mutable)
+    pub fn hook_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: 'static,
+        H: MatchFirstType,
+    {
+        self.hooks.match_first_type_mut()
+    }
+}
+
+impl<CS, F, OF, OT, H> StdFuzzer<CS, F, OF, OT, H>
+where
+    CS: Scheduler,
+    F: Feedback<CS::State>,
+    OF: Feedback<CS::State>,
+    H: FuzzerHooksTuple<CS::State>,
+    CS::State: UsesInput + HasExecutions + HasCorpus,
+{
+    /// Re-runs the `input` `runs` times, returns in how many of them it ended with the given `exit_kind` again
+    pub fn verify_deterministic<E, EM>(
+        &mut self,
+        state: &mut CS::State,
+        executor: &mut E,
+        manager: &mut EM,
+        input: &<CS::State as UsesInput>::Input,
+        exit_kind: &ExitKind,
+        runs: usize,
+    ) -> Result<usize, Error>
+    where
+        E: Executor<EM, Self> + HasObservers<Observers = OT, State = CS::State>,
+        EM: UsesState<State = CS::State>,
+        OT: ObserversTuple<CS::State>,
+    {
+        let mut reproduced = 0;
+        for _ in 0..runs {
+            if self.execute_input(state, executor, manager, input)? == *exit_kind {
+                reproduced += 1;
+            }
+        }
+        Ok(reproduced)
+    }
 
     /// Runs the input and triggers observers and feedback
     pub fn execute_input<E, EM>(
@@ -772,9 +1262,12 @@ where
         mark_feature_time!(state, PerfFeature::PreExecObservers);
 
         start_timer!(state);
-        let exit_kind = executor.run_target(self, state, event_mgr, input)?;
+        let res = executor.run_target(self, state, event_mgr, input);
         mark_feature_time!(state, PerfFeature::TargetExecution);
 
+        self.hooks.post_exec_all(state, input, &res);
+        let exit_kind = res?;
+
         start_timer!(state);
         executor
             .observers_mut()
@@ -783,6 +1276,33 @@ where
 
         Ok(exit_kind)
     }
+
+    /// Reproduces a single child input from a [`ReproductionRecipe`] file, and runs it once.
+    ///
+    /// The recipe must have been recorded with the same `mutations`,
+    /// for example from [`crate::mutators::StdScheduledMutator::last_recipe`] of a mutator with recipe recording.
+    /// The child is neither evaluated by the feedbacks nor added to a corpus.
+    #[cfg(feature = "std")]
+    pub fn reproduce_from<E, EM, MT, P>(
+        &mut self,
+        state: &mut CS::State,
+        executor: &mut E,
+        event_mgr: &mut EM,
+        mutations: &mut MT,
+        path: P,
+    ) -> Result<ExitKind, Error>
+    where
+        E: Executor<EM, Self> + HasObservers<Observers = OT, State = CS::State>,
+        EM: UsesState<State = CS::State>,
+        OT: ObserversTuple<CS::State>,
+        MT: MutatorsTuple<<CS::State as UsesInput>::Input, CS::State>,
+        CS::State: HasRand,
+        P: AsRef<Path>,
+    {
+        let recipe = ReproductionRecipe::<<CS::State as UsesInput>::Input>::from_file(path)?;
+        let child = recipe.reconstruct(mutations, state)?;
+        self.execute_input(state, executor, event_mgr, &child)
+    }
 }
 
 /// Structs with this trait will execute an input
@@ -801,11 +1321,12 @@ where
     ) -> Result<ExitKind, Error>;
 }
 
-impl<CS, E, EM, F, OF> ExecutesInput<E, EM> for StdFuzzer<CS, F, OF, E::Observers>
+impl<CS, E, EM, F, OF, H> ExecutesInput<E, EM> for StdFuzzer<CS, F, OF, E::Observers, H>
 where
     CS: Scheduler,
     F: Feedback<CS::State>,
     OF: Feedback<CS::State>,
+    H: FuzzerHooksTuple<CS::State>,
     E: Executor<EM, Self> + HasObservers<State = CS::State>,
     EM: UsesState<State = CS::State>,
     CS::State: UsesInput + HasExecutions + HasCorpus,
@@ -818,29 +1339,31 @@ where
         event_mgr: &mut EM,
         input: &<CS::State as UsesInput>::Input,
     ) -> Result<ExitKind, Error> {
-        start_timer!(state);
-        executor.observers_mut().pre_exec_all(state, input)?;
-        mark_feature_time!(state, PerfFeature::PreExecObservers);
-
-        start_timer!(state);
-        let exit_kind = executor.run_target(self, state, event_mgr, input)?;
-        mark_feature_time!(state, PerfFeature::TargetExecution);
-
-        start_timer!(state);
-        executor
-            .observers_mut()
-            .post_exec_all(state, input, &exit_kind)?;
-        mark_feature_time!(state, PerfFeature::PostExecObservers);
-
-        Ok(exit_kind)
+        // Calls the inherent method
+        StdFuzzer::execute_input(self, state, executor, event_mgr, input)
     }
 }
 
 #[cfg(test)]
 pub mod test {
+    #[cfg(feature = "std")]
+    use alloc::{sync::Arc, vec::Vec};
     use core::marker::PhantomData;
+    #[cfg(feature = "std")]
+    use core::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    };
+    #[cfg(feature = "std")]
+    use std::{ptr::addr_of_mut, thread, time::Instant};
 
     use libafl_bolts::Error;
+    #[cfg(feature = "std")]
+    use libafl_bolts::{
+        rands::{Rand, StdRand},
+        tuples::tuple_list,
+        ClientId, Named,
+    };
 
     use crate::{
         corpus::CorpusId,
@@ -849,6 +1372,42 @@ pub mod test {
         state::{HasExecutions, HasLastReportTime, State, UsesState},
         Fuzzer, HasMetadata,
     };
+    #[cfg(feature = "std")]
+    use crate::{
+        corpus::{
+            Corpus, HasCurrentCorpusIdx, InMemoryCorpus, InMemoryOnDiskCorpus, ObjectiveKind,
+            OnDiskMetadataFormat, Testcase,
+        },
+        events::{
+            test::RecordingEventManager, EventFirer, NewTestcaseSenderMetadata, NopEventManager,
+            ThrottledEventManager,
+        },
+        executors::{Executor, ExitKind, HasObservers, InProcessExecutor},
+        feedbacks::{
+            ConstFeedback, CrashFeedback, Feedback, MapIndexesMetadata, MaxMapFeedback,
+            TimeoutFeedback,
+        },
+        fuzzer::{
+            CorpusLengthLimit, CrashConfirmation, ExecuteInputResult, ExecutesInput, FlakyPolicy,
+            FlakySolutionMetadata, FuzzLoopPauseHandle, FuzzLoopStopHandle, HasFeedback,
+            InputCollector, LengthPolicy, Quarantine,
+        },
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{
+            havoc_mutations, ByteIncMutator, ComposedByMutations, MutationResult, Mutator,
+            ReproductionRecipe, StdScheduledMutator,
+        },
+        observers::{CanTrack, ObserversTuple, StdMapObserver, UsesObservers},
+        schedulers::{QuarantineScheduler, QueueScheduler, RandScheduler, Scheduler},
+        stages::{Stage, StdMutationalStage},
+        state::{
+            test::{test_std_state, test_std_state_with, TestStdState},
+            HasCorpus, HasFirstFindTimes, HasRand, HasSolutions, StdState,
+        },
+        Evaluator, EvaluatorObservers, StdFuzzer,
+    };
+    #[cfg(all(feature = "std", any(not(feature = "serdeany_autoreg"), miri)))]
+    use crate::{feedbacks::MapFeedbackMetadata, stages::ExecutionCountRestartHelperMetadata};
 
     #[derive(Clone, Debug)]
     pub struct NopFuzzer<S> {
@@ -894,4 +1453,941 @@ pub mod test {
             unimplemented!()
         }
     }
+
+    /// The parts of a fuzzer for the tests, see [`test_fuzzer`]
+    #[cfg(feature = "std")]
+    pub type TestFuzzer<'a, CS, F, OF, OT, HK, H> = (
+        StdFuzzer<CS, F, OF, OT, HK>,
+        InProcessExecutor<'a, H, OT, TestStdState<BytesInput>>,
+        TestStdState<BytesInput>,
+        NopEventManager<TestStdState<BytesInput>>,
+    );
+
+    /// Sets up a [`StdFuzzer`] with the `scheduler`, the `feedback`, the `objective` and the `hooks`,
+    /// and an [`InProcessExecutor`] running the `harness` with the `observers`,
+    /// on a [`test_std_state_with`] both feedbacks
+    #[cfg(feature = "std")]
+    pub fn test_fuzzer<CS, F, OF, OT, HK, H>(
+        scheduler: CS,
+        mut feedback: F,
+        mut objective: OF,
+        hooks: HK,
+        harness: &mut H,
+        observers: OT,
+    ) -> TestFuzzer<'_, CS, F, OF, OT, HK, H>
+    where
+        CS: Scheduler<State = TestStdState<BytesInput>>,
+        F: Feedback<TestStdState<BytesInput>>,
+        OF: Feedback<TestStdState<BytesInput>>,
+        OT: ObserversTuple<TestStdState<BytesInput>>,
+        H: FnMut(&BytesInput) -> ExitKind,
+    {
+        let mut state = test_std_state_with(&mut feedback, &mut objective);
+        let mut mgr = NopEventManager::new();
+        let mut fuzzer = StdFuzzer::new(scheduler, feedback, objective).with_hooks(hooks);
+        let executor =
+            InProcessExecutor::new(harness, observers, &mut fuzzer, &mut state, &mut mgr)
+                .expect("couldn't instantiate the test executor");
+        (fuzzer, executor, state, mgr)
+    }
+
+    #[cfg(feature = "std")]
+    fn run_deterministic(state_seed: u64, seed: u64) -> Vec<BytesInput> {
+        let mut harness = |_buf: &BytesInput| ExitKind::Ok;
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            ConstFeedback::new(true),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+        state.rand_mut().set_seed(state_seed);
+        state
+            .corpus_mut()
+            .add(Testcase::new(vec![0; 8].into()))
+            .unwrap();
+
+        let mutator = StdScheduledMutator::new(havoc_mutations());
+        let mut stages = tuple_list!(StdMutationalStage::with_max_iterations(mutator, 8));
+
+        fuzzer
+            .fuzz_loop_for_deterministic(&mut stages, &mut executor, &mut state, &mut mgr, seed, 5)
+            .unwrap();
+
+        state
+            .corpus()
+            .ids()
+            .map(|id| state.corpus().cloned_input_for_id(id).unwrap())
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_fuzz_loop_for_deterministic() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            ExecutionCountRestartHelperMetadata::register();
+        }
+
+        // The initial state of the rand must not matter, only the seed
+        let first = run_deterministic(0, 1337);
+        let second = run_deterministic(42, 1337);
+
+        assert!(first.len() > 1);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_testcase_sender() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            NewTestcaseSenderMetadata::register();
+        }
+
+        let mut harness = |_buf: &BytesInput| ExitKind::Ok;
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            ConstFeedback::new(true),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+
+        // Found locally
+        let (_, local) = fuzzer
+            .evaluate_input(&mut state, &mut executor, &mut mgr, vec![0x41].into())
+            .unwrap();
+
+        // Imported from a peer, like the event managers do it
+        let (_, imported) =
+            NewTestcaseSenderMetadata::with_sender(&mut state, ClientId(3), |state| {
+                fuzzer.evaluate_input_with_observers(
+                    state,
+                    &mut executor,
+                    &mut mgr,
+                    vec![0x42].into(),
+                    false,
+                )
+            })
+            .unwrap();
+        assert!(!state.has_metadata::<NewTestcaseSenderMetadata>());
+
+        let local = state.corpus().get(local.unwrap()).unwrap().borrow();
+        assert_eq!(local.sender_id(), ClientId(0));
+        assert!(local.added_time().is_some());
+
+        let imported = state.corpus().get(imported.unwrap()).unwrap().borrow();
+        assert_eq!(imported.sender_id(), ClientId(3));
+        assert!(imported.added_time() >= local.added_time());
+    }
+
+    /// Runs [`Fuzzer::fuzz_loop_graceful`] on a corpus on disk, with a testcase starting with `first_byte`,
+    /// stopping during the first execution.
+    /// Returns the result, the event manager, and the exec time stored on disk.
+    #[cfg(feature = "std")]
+    #[allow(clippy::type_complexity)]
+    fn run_graceful(
+        dir: &str,
+        first_byte: u8,
+    ) -> (
+        Result<(), Error>,
+        ThrottledEventManager<
+            RecordingEventManager<
+                StdState<
+                    BytesInput,
+                    InMemoryOnDiskCorpus<BytesInput>,
+                    StdRand,
+                    InMemoryCorpus<BytesInput>,
+                >,
+            >,
+        >,
+        serde_json::Value,
+    ) {
+        drop(std::fs::remove_dir_all(dir));
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+
+        let mut corpus =
+            InMemoryOnDiskCorpus::with_meta_format(dir, Some(OnDiskMetadataFormat::Json)).unwrap();
+        let id = corpus
+            .add(Testcase::new(vec![first_byte; 8].into()))
+            .unwrap();
+
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            corpus,
+            InMemoryCorpus::new(),
+            &mut feedback,
+            &mut objective,
+        )
+        .unwrap();
+        // Only known in memory, until the corpus gets flushed
+        state
+            .corpus()
+            .get(id)
+            .unwrap()
+            .borrow_mut()
+            .set_exec_time(Duration::from_millis(42));
+
+        // Holds back stats until it is flushed on restart
+        let mut mgr =
+            ThrottledEventManager::new(RecordingEventManager::new(), Duration::from_secs(3600));
+        let stop = FuzzLoopStopHandle::new();
+        let mut fuzzer = StdFuzzer::new(RandScheduler::new(), feedback, objective)
+            .with_hooks(tuple_list!(stop.clone()));
+
+        // Simulates a Ctrl-C during the first execution
+        let mut harness = |_buf: &BytesInput| {
+            stop.request_stop();
+            ExitKind::Ok
+        };
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut mgr,
+        )
+        .unwrap();
+
+        let mut stages = tuple_list!(FailingStage {
+            phantom: PhantomData
+        });
+
+        let res = fuzzer.fuzz_loop_graceful(&mut stages, &mut executor, &mut state, &mut mgr);
+
+        let metadata_path = state
+            .corpus()
+            .get(id)
+            .unwrap()
+            .borrow()
+            .metadata_path()
+            .clone()
+            .unwrap();
+        let metadata = serde_json::from_slice(&std::fs::read(metadata_path).unwrap()).unwrap();
+        (res, mgr, metadata)
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_fuzz_loop_graceful_stop() {
+        let (res, mgr, metadata) = run_graceful("target/.test/fuzz_loop_graceful_stop", 0);
+        res.unwrap();
+
+        // The event manager was flushed, the corpus and the state persisted, and the restarter told not to respawn us
+        assert_eq!(mgr.pending_stats(), 0);
+        assert!(!mgr.inner().events.is_empty());
+        assert_eq!(mgr.inner().restarts, 1);
+        assert!(mgr.inner().exiting);
+        assert_eq!(metadata["exec_time"]["nanos"], 42_000_000);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_fuzz_loop_graceful_error() {
+        // The stage fails for this testcase, before stopping
+        let (res, mgr, metadata) = run_graceful("target/.test/fuzz_loop_graceful_error", 1);
+        assert!(matches!(res, Err(Error::IllegalArgument(..))));
+
+        // The state gets persisted anyways
+        assert_eq!(mgr.inner().restarts, 1);
+        assert!(mgr.inner().exiting);
+        assert_eq!(metadata["exec_time"]["nanos"], 42_000_000);
+    }
+
+    /// Waits up to a few seconds for the number of executions to exceed `min`
+    #[cfg(feature = "std")]
+    fn wait_for_executions(executions: &AtomicU64, min: u64) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(10) {
+            if executions.load(Ordering::SeqCst) > min {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        false
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_fuzz_loop_pause() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            ExecutionCountRestartHelperMetadata::register();
+        }
+
+        let pause = FuzzLoopPauseHandle::new();
+        let stop = FuzzLoopStopHandle::new();
+        let executions = Arc::new(AtomicU64::new(0));
+
+        let fuzz_thread = {
+            let pause = pause.clone();
+            let executions = executions.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut harness = |_buf: &BytesInput| {
+                    executions.fetch_add(1, Ordering::SeqCst);
+                    ExitKind::Ok
+                };
+                let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+                    RandScheduler::new(),
+                    ConstFeedback::new(false),
+                    ConstFeedback::new(false),
+                    tuple_list!(pause, stop),
+                    &mut harness,
+                    tuple_list!(),
+                );
+                state
+                    .corpus_mut()
+                    .add(Testcase::new(vec![0; 8].into()))
+                    .unwrap();
+
+                let mutator = StdScheduledMutator::new(havoc_mutations());
+                let mut stages = tuple_list!(StdMutationalStage::with_max_iterations(mutator, 8));
+
+                fuzzer
+                    .fuzz_loop(&mut stages, &mut executor, &mut state, &mut mgr)
+                    .is_ok()
+            })
+        };
+
+        assert!(wait_for_executions(&executions, 0));
+
+        // Once the current iteration is done, nothing runs anymore
+        pause.pause();
+        thread::sleep(Duration::from_millis(100));
+        let paused = executions.load(Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(executions.load(Ordering::SeqCst), paused);
+
+        pause.resume();
+        assert!(wait_for_executions(&executions, paused));
+
+        // A stop is not held up by the pause
+        pause.pause();
+        stop.request_stop();
+        assert!(fuzz_thread.join().unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_reproduce_from() {
+        // Record the recipe of a child
+        let parent = BytesInput::new(b"AAAAAAAA".to_vec());
+        let mut mutator = StdScheduledMutator::new(tuple_list!(ByteIncMutator::new()))
+            .with_recipe_recording(true);
+        let mut child = parent.clone();
+        assert_eq!(
+            mutator
+                .mutate(&mut test_std_state::<BytesInput>(), &mut child)
+                .unwrap(),
+            MutationResult::Mutated
+        );
+        let recipe = mutator.last_recipe().clone();
+
+        let path = "target/.test/reproduce_from.recipe";
+        std::fs::create_dir_all("target/.test").unwrap();
+        ReproductionRecipe::new(parent, recipe)
+            .to_file(path)
+            .unwrap();
+
+        // Only the child crashes
+        let mut harness = |input: &BytesInput| {
+            if *input == child {
+                ExitKind::Crash
+            } else {
+                ExitKind::Ok
+            }
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+
+        let exit_kind = fuzzer
+            .reproduce_from(
+                &mut state,
+                &mut executor,
+                &mut mgr,
+                mutator.mutations_mut(),
+                path,
+            )
+            .unwrap();
+        assert_eq!(exit_kind, ExitKind::Crash);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_input_collector() {
+        let mut harness = |_buf: &BytesInput| ExitKind::Ok;
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            tuple_list!(InputCollector::<BytesInput>::new(1000)),
+            &mut harness,
+            tuple_list!(),
+        );
+        state
+            .corpus_mut()
+            .add(Testcase::new(vec![0; 8].into()))
+            .unwrap();
+
+        let mutator = StdScheduledMutator::new(havoc_mutations());
+        let mut stages = tuple_list!(StdMutationalStage::with_max_iterations(mutator, 64));
+
+        let executions = *state.executions();
+        fuzzer
+            .fuzz_one(&mut stages, &mut executor, &mut state, &mut mgr)
+            .unwrap();
+        let executed = usize::try_from(*state.executions() - executions).unwrap();
+        assert!(executed > 0);
+        let collector = fuzzer.hook::<InputCollector<BytesInput>>().unwrap();
+        assert_eq!(collector.inputs().len(), executed);
+        assert_eq!(collector.dropped(), 0);
+
+        // The collector is bounded
+        *fuzzer.hook_mut::<InputCollector<BytesInput>>().unwrap() = InputCollector::new(0);
+        let executions = *state.executions();
+        fuzzer
+            .fuzz_one(&mut stages, &mut executor, &mut state, &mut mgr)
+            .unwrap();
+        let collector = fuzzer.hook::<InputCollector<BytesInput>>().unwrap();
+        assert!(collector.inputs().is_empty());
+        assert_eq!(
+            collector.executed(),
+            usize::try_from(*state.executions() - executions).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_time_to_first_solution() {
+        // The target takes a while, then crashes
+        let mut harness = |_buf: &BytesInput| {
+            thread::sleep(Duration::from_millis(20));
+            ExitKind::Crash
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            ConstFeedback::new(false),
+            CrashFeedback::new(),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+
+        assert_eq!(state.time_to_first_solution(), None);
+        fuzzer
+            .evaluate_input(&mut state, &mut executor, &mut mgr, vec![0].into())
+            .unwrap();
+        assert_eq!(state.solutions().count(), 1);
+        let first = state.time_to_first_solution().unwrap();
+        assert!(first >= Duration::from_millis(20));
+        assert!(first < Duration::from_secs(60));
+        assert_eq!(state.time_to_first_corpus(), None);
+
+        // Only the first solution is timed
+        fuzzer
+            .evaluate_input(&mut state, &mut executor, &mut mgr, vec![1].into())
+            .unwrap();
+        assert_eq!(state.solutions().count(), 2);
+        assert_eq!(state.time_to_first_solution(), Some(first));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_objective_kind() {
+        let mut harness = |input: &BytesInput| {
+            if input.bytes() == b"slow" {
+                ExitKind::Timeout
+            } else {
+                ExitKind::Ok
+            }
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            ConstFeedback::new(true),
+            TimeoutFeedback::new(),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+
+        fuzzer
+            .evaluate_input(&mut state, &mut executor, &mut mgr, b"fast".to_vec().into())
+            .unwrap();
+        fuzzer
+            .evaluate_input(&mut state, &mut executor, &mut mgr, b"slow".to_vec().into())
+            .unwrap();
+
+        let entry = state.corpus().first().unwrap();
+        assert_eq!(
+            state.corpus().get(entry).unwrap().borrow().objective_kind(),
+            None
+        );
+        let solution = state.solutions().first().unwrap();
+        assert_eq!(
+            state
+                .solutions()
+                .get(solution)
+                .unwrap()
+                .borrow()
+                .objective_kind(),
+            Some(ObjectiveKind::Timeout)
+        );
+    }
+
+    /// Evaluates the always interesting `inputs`, returns the resulting corpus and the length limit
+    #[cfg(feature = "std")]
+    fn corpus_with_length_limit(
+        policy: LengthPolicy,
+        inputs: &[&[u8]],
+    ) -> (Vec<Vec<u8>>, CorpusLengthLimit<BytesInput>) {
+        let mut harness = |_input: &BytesInput| ExitKind::Ok;
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            ConstFeedback::new(true),
+            ConstFeedback::new(false),
+            tuple_list!(CorpusLengthLimit::new(4, policy)),
+            &mut harness,
+            tuple_list!(),
+        );
+        for input in inputs {
+            fuzzer
+                .evaluate_input(&mut state, &mut executor, &mut mgr, input.to_vec().into())
+                .unwrap();
+        }
+
+        let corpus = state
+            .corpus()
+            .ids()
+            .map(|id| {
+                state
+                    .corpus()
+                    .cloned_input_for_id(id)
+                    .unwrap()
+                    .bytes()
+                    .to_vec()
+            })
+            .collect();
+        let length_limit = fuzzer.hook::<CorpusLengthLimit<BytesInput>>().unwrap();
+        (corpus, length_limit.clone())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_length_limit() {
+        let (corpus, limit) =
+            corpus_with_length_limit(LengthPolicy::Reject, &[b"short", b"tiny", b"oversized"]);
+        assert_eq!(corpus, [b"tiny".to_vec()]);
+        assert_eq!((limit.rejected(), limit.trimmed()), (2, 0));
+
+        let (corpus, limit) =
+            corpus_with_length_limit(LengthPolicy::Trim, &[b"tiny", b"oversized"]);
+        assert_eq!(corpus, [b"tiny".to_vec(), b"over".to_vec()]);
+        assert_eq!((limit.rejected(), limit.trimmed()), (0, 1));
+    }
+
+    #[cfg(feature = "std")]
+    static mut LENGTH_MAP: [u8; 4] = [0; 4];
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_length_limit_trim_reevaluates() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapFeedbackMetadata::<u8>::register();
+            MapIndexesMetadata::register();
+        }
+
+        let observer =
+            unsafe { StdMapObserver::from_mut_ptr("map", addr_of_mut!(LENGTH_MAP) as *mut u8, 4) }
+                .track_indices();
+        let feedback = MaxMapFeedback::new(&observer);
+
+        // Only inputs longer than the limit reach the second edge
+        let mut harness = |input: &BytesInput| {
+            unsafe {
+                LENGTH_MAP[0] = 1;
+                if input.bytes().len() > 4 {
+                    LENGTH_MAP[1] = 1;
+                }
+            }
+            ExitKind::Ok
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            feedback,
+            ConstFeedback::new(false),
+            tuple_list!(CorpusLengthLimit::new(4, LengthPolicy::Trim)),
+            &mut harness,
+            tuple_list!(observer),
+        );
+
+        let (_, idx) = fuzzer
+            .evaluate_input(
+                &mut state,
+                &mut executor,
+                &mut mgr,
+                b"oversized".to_vec().into(),
+            )
+            .unwrap();
+        let testcase = state.corpus().get(idx.unwrap()).unwrap().borrow();
+        assert_eq!(testcase.input().as_ref().unwrap().bytes(), b"over");
+        // The metadata is the one of the trimmed run
+        assert_eq!(
+            testcase.metadata::<MapIndexesMetadata>().unwrap().list,
+            vec![0]
+        );
+    }
+
+    /// Evaluates a deterministic and a flaky crash, confirming each in four more runs,
+    /// returns the flakiness of the saved solutions
+    #[cfg(feature = "std")]
+    fn confirmed_solutions(policy: FlakyPolicy) -> Vec<Option<FlakySolutionMetadata>> {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            FlakySolutionMetadata::register();
+        }
+
+        // The flaky input crashes in one of three runs, starting with the first one
+        let mut flaky_runs = 0;
+        let mut harness = |input: &BytesInput| match input.bytes() {
+            b"crash" => ExitKind::Crash,
+            b"flaky" => {
+                flaky_runs += 1;
+                if flaky_runs % 3 == 1 {
+                    ExitKind::Crash
+                } else {
+                    ExitKind::Ok
+                }
+            }
+            _ => ExitKind::Ok,
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            ConstFeedback::new(false),
+            CrashConfirmation::new(CrashFeedback::new(), 4, policy),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+        for input in [b"crash", b"flaky"] {
+            fuzzer
+                .evaluate_input(&mut state, &mut executor, &mut mgr, input.to_vec().into())
+                .unwrap();
+        }
+
+        state
+            .solutions()
+            .ids()
+            .map(|id| {
+                let testcase = state.solutions().get(id).unwrap().borrow();
+                // Even if the last confirmation run did not crash
+                assert_eq!(testcase.objective_kind(), Some(ObjectiveKind::Crash));
+                testcase.metadata::<FlakySolutionMetadata>().ok().copied()
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_crash_confirmation() {
+        // Runs 2 to 5 of the flaky input only crash in run 4
+        assert_eq!(
+            confirmed_solutions(FlakyPolicy::Mark),
+            [
+                None,
+                Some(FlakySolutionMetadata {
+                    runs: 4,
+                    reproduced: 1
+                })
+            ]
+        );
+        assert_eq!(confirmed_solutions(FlakyPolicy::Discard), [None]);
+    }
+
+    /// Wraps an executor, failing with an error for inputs starting with a `0` byte
+    #[cfg(feature = "std")]
+    #[derive(Debug)]
+    struct FailingExecutor<E> {
+        inner: E,
+    }
+
+    #[cfg(feature = "std")]
+    impl<E> UsesState for FailingExecutor<E>
+    where
+        E: UsesState,
+    {
+        type State = E::State;
+    }
+
+    #[cfg(feature = "std")]
+    impl<E> UsesObservers for FailingExecutor<E>
+    where
+        E: HasObservers,
+    {
+        type Observers = E::Observers;
+    }
+
+    #[cfg(feature = "std")]
+    impl<E> HasObservers for FailingExecutor<E>
+    where
+        E: HasObservers,
+    {
+        fn observers(&self) -> &E::Observers {
+            self.inner.observers()
+        }
+
+        fn observers_mut(&mut self) -> &mut E::Observers {
+            self.inner.observers_mut()
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<E, EM, Z> Executor<EM, Z> for FailingExecutor<E>
+    where
+        E: Executor<EM, Z>,
+        E::Input: HasBytesVec,
+        EM: UsesState<State = E::State>,
+        Z: UsesState<State = E::State>,
+    {
+        fn run_target(
+            &mut self,
+            fuzzer: &mut Z,
+            state: &mut E::State,
+            mgr: &mut EM,
+            input: &E::Input,
+        ) -> Result<ExitKind, Error> {
+            if input.bytes()[0] == 0 {
+                Err(Error::illegal_state("The harness failed"))
+            } else {
+                self.inner.run_target(fuzzer, state, mgr, input)
+            }
+        }
+    }
+
+    /// Executes the current testcase once, or fails on its own, for testcases starting with a `1` byte
+    #[cfg(feature = "std")]
+    struct FailingStage<S> {
+        phantom: PhantomData<S>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<S> UsesState for FailingStage<S>
+    where
+        S: State,
+    {
+        type State = S;
+    }
+
+    #[cfg(feature = "std")]
+    impl<E, EM, S, Z> Stage<E, EM, Z> for FailingStage<S>
+    where
+        E: UsesState<State = S>,
+        EM: UsesState<State = S>,
+        S: State + HasCorpus + HasCurrentCorpusIdx,
+        S::Input: HasBytesVec,
+        Z: ExecutesInput<E, EM, State = S>,
+    {
+        fn perform(
+            &mut self,
+            fuzzer: &mut Z,
+            executor: &mut E,
+            state: &mut S,
+            manager: &mut EM,
+        ) -> Result<(), Error> {
+            let idx = state.current_corpus_idx()?.unwrap();
+            let input = state.corpus().cloned_input_for_id(idx)?;
+            if input.bytes()[0] == 1 {
+                return Err(Error::illegal_argument("The stage failed"));
+            }
+            fuzzer.execute_input(state, executor, manager, &input)?;
+            Ok(())
+        }
+
+        fn restart_progress_should_run(&mut self, _state: &mut S) -> Result<bool, Error> {
+            Ok(true)
+        }
+
+        fn clear_restart_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_quarantine() {
+        let mut harness = |_input: &BytesInput| ExitKind::Ok;
+        let (mut fuzzer, executor, mut state, mut mgr) = test_fuzzer(
+            QuarantineScheduler::new(QueueScheduler::new()),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            tuple_list!(Quarantine::new(3)),
+            &mut harness,
+            tuple_list!(),
+        );
+        let mut executor = FailingExecutor { inner: executor };
+        let failing = state
+            .corpus_mut()
+            .add(Testcase::new(vec![0].into()))
+            .unwrap();
+        let working = state
+            .corpus_mut()
+            .add(Testcase::new(vec![2].into()))
+            .unwrap();
+        let mut stages = tuple_list!(FailingStage {
+            phantom: PhantomData
+        });
+
+        // The queue alternates, until the third error quarantines the failing testcase
+        for expected in [failing, working, failing, working, failing] {
+            let idx = fuzzer
+                .fuzz_one(&mut stages, &mut executor, &mut state, &mut mgr)
+                .unwrap();
+            assert_eq!(idx, expected);
+        }
+        let testcase = state.corpus().get(failing).unwrap().borrow();
+        assert_eq!(testcase.error_streak(), 3);
+        assert!(testcase.quarantined());
+        drop(testcase);
+        assert_eq!(
+            state.corpus().get(working).unwrap().borrow().error_streak(),
+            0
+        );
+
+        // The quarantined testcase gets disabled, and is no longer scheduled
+        for _ in 0..4 {
+            let idx = fuzzer
+                .fuzz_one(&mut stages, &mut executor, &mut state, &mut mgr)
+                .unwrap();
+            assert_eq!(idx, working);
+        }
+        assert_eq!(state.corpus().count(), 1);
+        assert_eq!(state.corpus().count_disabled(), 1);
+
+        // Errors not caused by the executor are not swallowed
+        state
+            .corpus_mut()
+            .add(Testcase::new(vec![1].into()))
+            .unwrap();
+        assert!((0..2).any(|_| fuzzer
+            .fuzz_one(&mut stages, &mut executor, &mut state, &mut mgr)
+            .is_err()));
+    }
+
+    /// Needs two runs to decide, and only deems the input interesting in the second one
+    #[cfg(feature = "std")]
+    #[derive(Debug, Default)]
+    struct TwoRunFeedback {
+        runs: usize,
+    }
+
+    #[cfg(feature = "std")]
+    impl Named for TwoRunFeedback {
+        fn name(&self) -> &str {
+            "TwoRunFeedback"
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<S> Feedback<S> for TwoRunFeedback
+    where
+        S: State,
+    {
+        fn is_interesting<EM, OT>(
+            &mut self,
+            _state: &mut S,
+            _manager: &mut EM,
+            _input: &S::Input,
+            _observers: &OT,
+            _exit_kind: &ExitKind,
+        ) -> Result<bool, Error>
+        where
+            EM: EventFirer<State = S>,
+            OT: ObserversTuple<S>,
+        {
+            self.runs += 1;
+            Ok(self.runs == 2)
+        }
+
+        fn needs_rerun(&self) -> bool {
+            self.runs == 1
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_feedback_rerun() {
+        let mut harness = |_input: &BytesInput| ExitKind::Ok;
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            TwoRunFeedback::default(),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+
+        let (res, id) = fuzzer
+            .evaluate_input(
+                &mut state,
+                &mut executor,
+                &mut mgr,
+                BytesInput::new(vec![0]),
+            )
+            .unwrap();
+        assert_eq!(res, ExecuteInputResult::Corpus);
+        assert!(id.is_some());
+        assert_eq!(*state.executions(), 2);
+        assert_eq!(fuzzer.feedback().runs, 2);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_objective_rerun_keeps_feedback() {
+        let mut harness = |_input: &BytesInput| ExitKind::Ok;
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            TwoRunFeedback::default(),
+            TwoRunFeedback::default(),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+
+        let (res, _) = fuzzer
+            .evaluate_input(
+                &mut state,
+                &mut executor,
+                &mut mgr,
+                BytesInput::new(vec![0]),
+            )
+            .unwrap();
+        // Only the objective asked for the rerun, the feedback is not queried again
+        assert_eq!(res, ExecuteInputResult::Solution);
+        assert_eq!(*state.executions(), 2);
+        assert_eq!(fuzzer.objective.runs, 2);
+        assert_eq!(fuzzer.feedback().runs, 1);
+    }
 }
@@ -0,0 +1,57 @@
+//! A [`FuzzLoopPauseHandle`] pauses and resumes a running [`super::Fuzzer::fuzz_loop`], for example to inspect the state.
+
+use alloc::sync::Arc;
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use crate::{fuzzer::FuzzerHook, state::State};
+
+/// How often a paused [`super::Fuzzer::fuzz_loop`] checks if it got resumed
+pub const FUZZ_LOOP_PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A handle to pause and resume the [`super::Fuzzer::fuzz_loop`] of a [`super::StdFuzzer`],
+/// when added to its hooks, see [`super::StdFuzzer::with_hooks`].
+///
+/// The handle can be cloned and sent to other threads. All clones toggle the same flag.
+/// While paused, the fuzz loop finishes its current iteration and then sleeps,
+/// only reporting its progress, until the handle is resumed, or a stop got requested.
+#[derive(Debug, Clone, Default)]
+pub struct FuzzLoopPauseHandle {
+    paused: Arc<AtomicBool>,
+}
+
+impl FuzzLoopPauseHandle {
+    /// Creates a new [`FuzzLoopPauseHandle`], not paused
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pauses the fuzz loop after its current iteration
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resumes the fuzz loop
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Pauses the fuzz loop, if it is running, or resumes it, if it is paused.
+    /// Returns `true`, if the fuzz loop is paused now.
+    #[must_use]
+    pub fn toggle(&self) -> bool {
+        !self.paused.fetch_xor(true, Ordering::SeqCst)
+    }
+
+    /// If the fuzz loop is (or will be, after its current iteration) paused
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+}
+
+/// Found by the [`super::StdFuzzer`] among its hooks, the handle itself hooks into nothing
+impl<S> FuzzerHook<S> for FuzzLoopPauseHandle where S: State {}
@@ -0,0 +1,88 @@
+//! A [`Quarantine`] flags testcases the [`super::StdFuzzer`] keeps failing on, see [`crate::corpus::Testcase::quarantined`].
+//!
+//! Wrap the scheduler in a [`crate::schedulers::QuarantineScheduler`] to disable quarantined testcases.
+
+use crate::{
+    corpus::{CorpusId, HasTestcase},
+    executors::ExitKind,
+    fuzzer::FuzzerHook,
+    inputs::UsesInput,
+    stages::HasCurrentStage,
+    state::State,
+    Error,
+};
+
+/// A [`FuzzerHook`] that quarantines a testcase once fuzzing it failed with an error `max_errors` times in a row.
+///
+/// Errors of the stages that were caused by a failing executor are logged and swallowed,
+/// except for [`Error::ShuttingDown`]. All other errors are returned as before.
+#[derive(Debug, Clone, Copy)]
+pub struct Quarantine {
+    max_errors: usize,
+    /// If the executor failed since the stages started
+    executor_failed: bool,
+}
+
+impl Quarantine {
+    /// Creates a new [`Quarantine`], flagging testcases after `max_errors` errors in a row
+    #[must_use]
+    pub fn new(max_errors: usize) -> Self {
+        Self {
+            max_errors,
+            executor_failed: false,
+        }
+    }
+
+    /// The number of errors in a row that quarantine a testcase
+    #[must_use]
+    pub fn max_errors(&self) -> usize {
+        self.max_errors
+    }
+}
+
+impl<S> FuzzerHook<S> for Quarantine
+where
+    S: State + HasTestcase + HasCurrentStage,
+{
+    fn pre_stages(&mut self, _state: &mut S) -> bool {
+        self.executor_failed = false;
+        true
+    }
+
+    fn post_stages(
+        &mut self,
+        state: &mut S,
+        idx: CorpusId,
+        res: Result<(), Error>,
+    ) -> Result<(), Error> {
+        match res {
+            Ok(()) => state.testcase_mut(idx)?.set_error_streak(0),
+            Err(err) if self.executor_failed && !matches!(err, Error::ShuttingDown) => {
+                {
+                    let mut testcase = state.testcase_mut(idx)?;
+                    let error_streak = testcase.error_streak() + 1;
+                    testcase.set_error_streak(error_streak);
+                    if error_streak >= self.max_errors {
+                        log::warn!("Quarantining testcase {idx} after {error_streak} errors in a row, the last one: {err}");
+                        testcase.set_quarantined(true);
+                    } else {
+                        log::info!("Fuzzing testcase {idx} failed: {err}");
+                    }
+                }
+                // Do not resume the failed stage with the next testcase
+                state.clear_stage()?;
+            }
+            Err(err) => return Err(err),
+        }
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &<S as UsesInput>::Input,
+        res: &Result<ExitKind, Error>,
+    ) {
+        self.executor_failed |= res.is_err();
+    }
+}
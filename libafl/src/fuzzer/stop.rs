@@ -0,0 +1,68 @@
+//! A [`FuzzLoopStopHandle`] stops a running [`super::Fuzzer::fuzz_loop`] gracefully, for example on Ctrl-C.
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(unix)]
+use libafl_bolts::os::unix_signals::setup_signal_handler;
+
+#[cfg(unix)]
+use crate::{events::EVENTMGR_SIGHANDLER_STATE, Error};
+use crate::{fuzzer::FuzzerHook, state::State};
+
+/// A handle to stop the [`super::Fuzzer::fuzz_loop`] of a [`super::StdFuzzer`],
+/// when added to its hooks, see [`super::StdFuzzer::with_hooks`].
+///
+/// The handle can be cloned and sent to other threads. All clones share the same request.
+/// Once a stop is requested, the fuzz loop finishes its current iteration, reports its progress, and returns `Ok(())`.
+/// Use [`super::Fuzzer::fuzz_loop_graceful`] to also persist the corpus and the state.
+#[derive(Debug, Clone, Default)]
+pub struct FuzzLoopStopHandle {
+    stop_requested: Arc<AtomicBool>,
+}
+
+impl FuzzLoopStopHandle {
+    /// Creates a new [`FuzzLoopStopHandle`], without a pending request
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the fuzz loop to stop after its current iteration.
+    ///
+    /// This is safe to call from a signal handler.
+    pub fn request_stop(&self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+    }
+
+    /// If a stop was requested, and not yet taken by the fuzz loop
+    #[must_use]
+    pub fn is_stop_requested(&self) -> bool {
+        self.stop_requested.load(Ordering::SeqCst)
+    }
+
+    /// Returns `true` if a stop was requested, clearing the request
+    #[must_use]
+    pub fn take_stop_request(&self) -> bool {
+        self.stop_requested.swap(false, Ordering::SeqCst)
+    }
+
+    /// Requests a stop on `SIGINT`, `SIGTERM`, or `SIGQUIT`, using the shutdown handler of the restarting event managers,
+    /// see [`crate::events::ShutdownSignalData`].
+    ///
+    /// Without this, the fuzzing (child) process of a restarting event manager exits right away on these signals.
+    /// Call this once in the fuzzing process, before the fuzz loop starts.
+    #[cfg(unix)]
+    pub fn stop_on_shutdown_signal(&self) -> Result<(), Error> {
+        // # Safety
+        // Only the fuzzing process registers its handle, before any signal gets handled.
+        // The handler itself only touches atomics.
+        unsafe {
+            EVENTMGR_SIGHANDLER_STATE.set_stop_handle(self.clone());
+            setup_signal_handler(core::ptr::addr_of_mut!(EVENTMGR_SIGHANDLER_STATE))
+        }
+    }
+}
+
+/// Found by the [`super::StdFuzzer`] among its hooks, the handle itself hooks into nothing
+impl<S> FuzzerHook<S> for FuzzLoopStopHandle where S: State {}
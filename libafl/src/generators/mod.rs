@@ -7,6 +7,7 @@ use libafl_bolts::rands::Rand;
 
 use crate::{
     inputs::{bytes::BytesInput, Input},
+    mutators::{MutationResult, Mutator},
     state::HasRand,
     Error,
 };
@@ -14,6 +15,9 @@ use crate::{
 pub mod gramatron;
 pub use gramatron::*;
 
+pub mod tlv;
+pub use tlv::{TlvGenerator, TlvTag};
+
 #[cfg(feature = "nautilus")]
 pub mod nautilus;
 #[cfg(feature = "nautilus")]
@@ -166,3 +170,78 @@ where
         }
     }
 }
+
+/// The number of times the [`SeedMutatingGenerator`] retries a mutation that got skipped
+const SEED_MUTATION_ATTEMPTS: usize = 16;
+
+#[derive(Clone, Debug)]
+/// Generates lightly mutated copies of a single base seed, without the need for a corpus.
+///
+/// Useful to smoke-test a harness with inputs that are close to a known-good one.
+pub struct SeedMutatingGenerator<I, M> {
+    seed: I,
+    mutator: M,
+}
+
+impl<I, M, S> Generator<I, S> for SeedMutatingGenerator<I, M>
+where
+    I: Input,
+    M: Mutator<I, S>,
+{
+    /// Returns a copy of the seed, mutated once by the mutator.
+    /// If the mutator keeps skipping, an unmutated copy is returned.
+    fn generate(&mut self, state: &mut S) -> Result<I, Error> {
+        for _ in 0..SEED_MUTATION_ATTEMPTS {
+            let mut input = self.seed.clone();
+            if self.mutator.mutate(state, &mut input)? == MutationResult::Mutated {
+                return Ok(input);
+            }
+        }
+        Ok(self.seed.clone())
+    }
+}
+
+impl<I, M> SeedMutatingGenerator<I, M> {
+    /// Creates a new [`SeedMutatingGenerator`], generating copies of `seed`, mutated by `mutator`.
+    #[must_use]
+    pub fn new(seed: I, mutator: M) -> Self {
+        Self { seed, mutator }
+    }
+
+    /// The base seed
+    #[must_use]
+    pub fn seed(&self) -> &I {
+        &self.seed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Generator, SeedMutatingGenerator};
+    use crate::{
+        inputs::{BytesInput, HasBytesVec},
+        mutators::BitFlipMutator,
+        state::test::test_std_state,
+    };
+
+    #[test]
+    fn test_seed_mutating_generator() {
+        let mut state = test_std_state::<BytesInput>();
+
+        let seed = BytesInput::new(b"GET / HTTP/1.1".to_vec());
+        let mut generator = SeedMutatingGenerator::new(seed.clone(), BitFlipMutator::new());
+        for _ in 0..16 {
+            let input: BytesInput = generator.generate(&mut state).unwrap();
+            assert_ne!(input, seed);
+            // Exactly one bit got flipped
+            assert_eq!(input.bytes().len(), seed.bytes().len());
+            let flipped: u32 = input
+                .bytes()
+                .iter()
+                .zip(seed.bytes())
+                .map(|(a, b)| (a ^ b).count_ones())
+                .sum();
+            assert_eq!(flipped, 1);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Generates structurally-valid tag-length-value messages, as used by many binary protocols.
+//!
+//! Each record is encoded as a one byte tag, followed by the length of the value as a big-endian `u16`,
+//! followed by the value itself. Random bytes rarely get these lengths right,
+//! so the [`TlvGenerator`] gets past the framing checks and into the code parsing the values.
+
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+use libafl_bolts::rands::Rand;
+
+use crate::{generators::Generator, inputs::BytesInput, state::HasRand, Error};
+
+/// The number of bytes of the header of each record, the tag and the length
+pub const TLV_HEADER_LEN: usize = 3;
+
+/// A tag the [`TlvGenerator`] may emit, with the allowed lengths of its value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlvTag {
+    /// The tag byte
+    pub tag: u8,
+    /// The allowed lengths of the value, at most [`u16::MAX`]
+    pub value_len: RangeInclusive<u16>,
+}
+
+impl TlvTag {
+    /// Creates a new [`TlvTag`], with values between `min_len` and `max_len` bytes long
+    #[must_use]
+    pub fn new(tag: u8, min_len: u16, max_len: u16) -> Self {
+        Self {
+            tag,
+            value_len: min_len..=max_len.max(min_len),
+        }
+    }
+}
+
+/// Generates [`BytesInput`]s made of random, but well-formed, tag-length-value records.
+///
+/// The tags, and the lengths of their values, are picked from the schema, the values are random bytes.
+#[derive(Debug, Clone)]
+pub struct TlvGenerator {
+    schema: Vec<TlvTag>,
+    max_records: usize,
+}
+
+impl TlvGenerator {
+    /// Creates a new [`TlvGenerator`], emitting between `1` and `max_records` records of the tags in the `schema`
+    pub fn new(schema: Vec<TlvTag>, max_records: usize) -> Result<Self, Error> {
+        if schema.is_empty() {
+            return Err(Error::illegal_argument(
+                "TlvGenerator needs at least one tag in its schema",
+            ));
+        }
+        Ok(Self {
+            schema,
+            max_records: max_records.max(1),
+        })
+    }
+
+    /// The tags this generator emits
+    #[must_use]
+    pub fn schema(&self) -> &[TlvTag] {
+        &self.schema
+    }
+}
+
+impl<S> Generator<BytesInput, S> for TlvGenerator
+where
+    S: HasRand,
+{
+    #[allow(clippy::cast_possible_truncation)]
+    fn generate(&mut self, state: &mut S) -> Result<BytesInput, Error> {
+        let records = state.rand_mut().between(1, self.max_records as u64);
+        let mut bytes = Vec::new();
+        for _ in 0..records {
+            let tag = state.rand_mut().choose(&self.schema);
+            let len = state.rand_mut().between(
+                u64::from(*tag.value_len.start()),
+                u64::from(*tag.value_len.end()),
+            ) as u16;
+            bytes.push(tag.tag);
+            bytes.extend_from_slice(&len.to_be_bytes());
+            bytes.extend((0..len).map(|_| state.rand_mut().below(256) as u8));
+        }
+        Ok(BytesInput::new(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{TlvGenerator, TlvTag, TLV_HEADER_LEN};
+    use crate::{
+        generators::Generator,
+        inputs::{BytesInput, HasBytesVec},
+        state::test::test_std_state,
+    };
+
+    /// Splits the bytes into `(tag, value)` records, or returns `None`, if they are malformed
+    fn parse(mut bytes: &[u8]) -> Option<Vec<(u8, &[u8])>> {
+        let mut records = Vec::new();
+        while !bytes.is_empty() {
+            if bytes.len() < TLV_HEADER_LEN {
+                return None;
+            }
+            let len = u16::from_be_bytes([bytes[1], bytes[2]]) as usize;
+            let value = bytes.get(TLV_HEADER_LEN..TLV_HEADER_LEN + len)?;
+            records.push((bytes[0], value));
+            bytes = &bytes[TLV_HEADER_LEN + len..];
+        }
+        Some(records)
+    }
+
+    #[test]
+    fn test_tlv_generator() {
+        let mut state = test_std_state::<BytesInput>();
+
+        let schema = vec![
+            TlvTag::new(1, 0, 4),
+            TlvTag::new(2, 8, 8),
+            TlvTag::new(7, 1, 300),
+        ];
+        let mut generator = TlvGenerator::new(schema.clone(), 5).unwrap();
+        for _ in 0..100 {
+            let input = generator.generate(&mut state).unwrap();
+            let records = parse(input.bytes()).unwrap();
+            assert!(!records.is_empty() && records.len() <= 5);
+            for (tag, value) in records {
+                let allowed = schema.iter().find(|allowed| allowed.tag == tag).unwrap();
+                assert!(allowed
+                    .value_len
+                    .contains(&u16::try_from(value.len()).unwrap()));
+            }
+        }
+
+        assert!(TlvGenerator::new(vec![], 5).is_err());
+    }
+}
@@ -15,7 +15,7 @@ use libafl_bolts::{fs::write_file_atomic, Error};
 use libafl_bolts::{ownedref::OwnedSlice, HasLen};
 use serde::{Deserialize, Serialize};
 
-use crate::inputs::{HasBytesVec, HasTargetBytes, Input};
+use crate::inputs::{hexdump, HasBytesVec, HasTargetBytes, Input};
 
 /// A bytes input is the basic input
 #[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
@@ -52,6 +52,11 @@ impl Input for BytesInput {
         hasher.write(self.bytes());
         format!("{:016x}", hasher.finish())
     }
+
+    /// A [`hexdump`] of the bytes
+    fn debug_dump(&self) -> String {
+        hexdump(&self.bytes)
+    }
 }
 
 /// Rc Ref-cell from Input
@@ -112,3 +117,20 @@ impl BytesInput {
         Self { bytes }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::inputs::{BytesInput, Input};
+
+    #[test]
+    fn test_debug_dump() {
+        let input = BytesInput::new(b"Hello, fuzzer!\x00\xff\x7fAB".to_vec());
+        assert_eq!(
+            input.debug_dump(),
+            "00000000  48 65 6c 6c 6f 2c 20 66  75 7a 7a 65 72 21 00 ff  |Hello, fuzzer!..|\n\
+             00000010  7f 41 42                                          |.AB|\n\
+             00000013\n"
+        );
+        assert_eq!(BytesInput::new(vec![]).debug_dump(), "00000000\n");
+    }
+}
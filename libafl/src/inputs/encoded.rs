@@ -209,6 +209,11 @@ impl Input for EncodedInput {
         }
         format!("{:016x}", hasher.finish())
     }
+
+    /// The codes of the tokens
+    fn debug_dump(&self) -> String {
+        format!("{} tokens: {:?}", self.codes.len(), self.codes)
+    }
 }
 
 /// Rc Ref-cell from Input
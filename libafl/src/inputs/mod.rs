@@ -25,7 +25,11 @@ use alloc::{
     string::{String, ToString},
     vec::Vec,
 };
-use core::{clone::Clone, fmt::Debug, marker::PhantomData};
+use core::{
+    clone::Clone,
+    fmt::{Debug, Write},
+    marker::PhantomData,
+};
 #[cfg(feature = "std")]
 use std::{fs::File, hash::Hash, io::Read, path::Path};
 
@@ -54,6 +58,12 @@ pub trait Input: Clone + Serialize + serde::de::DeserializeOwned + Debug {
 
     /// An hook executed if the input is stored as `Testcase`
     fn wrapped_as_testcase(&mut self) {}
+
+    /// A human-readable dump of this input, for triage, for example when logging a crashing testcase.
+    /// By default, a [`hexdump`] of the serialized input.
+    fn debug_dump(&self) -> String {
+        postcard::to_allocvec(self).map_or_else(|err| format!("<{err}>"), |bytes| hexdump(&bytes))
+    }
 }
 
 /// An input for the target
@@ -83,6 +93,40 @@ pub trait Input: Clone + Serialize + serde::de::DeserializeOwned + Debug {
 
     /// An hook executed if the input is stored as `Testcase`
     fn wrapped_as_testcase(&mut self) {}
+
+    /// A human-readable dump of this input, for triage, for example when logging a crashing testcase.
+    /// By default, a [`hexdump`] of the serialized input.
+    fn debug_dump(&self) -> String {
+        postcard::to_allocvec(self).map_or_else(|err| format!("<{err}>"), |bytes| hexdump(&bytes))
+    }
+}
+
+/// Formats `bytes` like `hexdump -C`: one line per 16 bytes, with the offset, the bytes in hex, and the printable ASCII characters
+#[must_use]
+pub fn hexdump(bytes: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, chunk) in bytes.chunks(16).enumerate() {
+        let mut hex = String::new();
+        for (idx, byte) in chunk.iter().enumerate() {
+            if idx == 8 {
+                hex.push(' ');
+            }
+            write!(hex, "{byte:02x} ").unwrap();
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    char::from(*byte)
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(dump, "{:08x}  {hex:<49} |{ascii}|", line * 16).unwrap();
+    }
+    writeln!(dump, "{:08x}", bytes.len()).unwrap();
+    dump
 }
 
 /// Convert between two input types with a state
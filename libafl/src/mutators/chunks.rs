@@ -0,0 +1,273 @@
+//! Block mutations that respect the known chunk boundaries of an input.
+//!
+//! Many formats are made of chunks, such as records, sections, or TLV entries.
+//! If their boundaries are known, for example declared by the user or discovered using cmplog,
+//! they can be stored in the [`ChunkBoundariesMetadata`] of a testcase.
+//! The mutators in this module then delete, copy, and swap whole chunks, keeping the structure intact.
+//! Without the metadata, they behave like their plain counterparts, such as the [`BytesDeleteMutator`].
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use libafl_bolts::{impl_serdeany, rands::Rand, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::HasCurrentCorpusIdx,
+    inputs::HasBytesVec,
+    mutators::{BytesCopyMutator, BytesDeleteMutator, BytesSwapMutator, MutationResult, Mutator},
+    state::{HasCorpus, HasCurrentTestcase, HasMaxSize, HasRand},
+    Error, HasMetadata,
+};
+
+/// The offsets at which new chunks of the input of a testcase start
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct ChunkBoundariesMetadata {
+    /// The start offsets of the chunks. The first chunk always starts at `0`.
+    pub offsets: Vec<usize>,
+}
+
+impl_serdeany!(ChunkBoundariesMetadata);
+
+impl ChunkBoundariesMetadata {
+    /// Creates a new [`ChunkBoundariesMetadata`] from the start offsets of the chunks, in any order
+    #[must_use]
+    pub fn new(mut offsets: Vec<usize>) -> Self {
+        offsets.sort_unstable();
+        offsets.dedup();
+        Self { offsets }
+    }
+
+    /// The chunks of an input of the given length.
+    /// Offsets beyond the length are ignored, so the chunks always cover the whole input.
+    #[must_use]
+    pub fn chunks(&self, len: usize) -> Vec<Range<usize>> {
+        let mut chunks = Vec::with_capacity(self.offsets.len() + 1);
+        let mut start = 0;
+        for offset in &self.offsets {
+            if *offset > start && *offset < len {
+                chunks.push(start..*offset);
+                start = *offset;
+            }
+        }
+        if start < len {
+            chunks.push(start..len);
+        }
+        chunks
+    }
+}
+
+/// The chunks of the input, if the current testcase has [`ChunkBoundariesMetadata`].
+///
+/// The boundaries belong to the input of the testcase. After earlier stacked mutations changed its length,
+/// they may no longer line up exactly.
+fn current_chunks<S>(state: &S, len: usize) -> Option<Vec<Range<usize>>>
+where
+    S: HasCorpus + HasCurrentCorpusIdx,
+{
+    let testcase = state.current_testcase().ok()?;
+    let boundaries = testcase.metadata::<ChunkBoundariesMetadata>().ok()?;
+    Some(boundaries.chunks(len))
+}
+
+/// Picks two different chunks
+fn choose_two<S>(state: &mut S, count: usize) -> (usize, usize)
+where
+    S: HasRand,
+{
+    let first = state.rand_mut().below(count as u64) as usize;
+    let second = (first + 1 + state.rand_mut().below(count as u64 - 1) as usize) % count;
+    (first, second)
+}
+
+/// Deletes a whole chunk, see [`ChunkBoundariesMetadata`]
+#[derive(Default, Debug)]
+pub struct ChunkDeleteMutator;
+
+impl<I, S> Mutator<I, S> for ChunkDeleteMutator
+where
+    S: HasRand + HasCorpus + HasCurrentCorpusIdx,
+    I: HasBytesVec,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let Some(chunks) = current_chunks(state, input.bytes().len()) else {
+            return BytesDeleteMutator.mutate(state, input);
+        };
+        if chunks.len() <= 1 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let chunk = state.rand_mut().choose(chunks);
+        input.bytes_mut().drain(chunk);
+        Ok(MutationResult::Mutated)
+    }
+}
+
+impl Named for ChunkDeleteMutator {
+    fn name(&self) -> &str {
+        "ChunkDeleteMutator"
+    }
+}
+
+impl ChunkDeleteMutator {
+    /// Creates a new [`ChunkDeleteMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Replaces a whole chunk with a copy of another chunk, see [`ChunkBoundariesMetadata`]
+#[derive(Default, Debug)]
+pub struct ChunkCopyMutator;
+
+impl<I, S> Mutator<I, S> for ChunkCopyMutator
+where
+    S: HasRand + HasCorpus + HasCurrentCorpusIdx + HasMaxSize,
+    I: HasBytesVec,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let size = input.bytes().len();
+        let Some(chunks) = current_chunks(state, size) else {
+            return BytesCopyMutator.mutate(state, input);
+        };
+        if chunks.len() <= 1 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let (from, to) = choose_two(state, chunks.len());
+        let (from, to) = (chunks[from].clone(), chunks[to].clone());
+        if size - to.len() + from.len() > state.max_size() {
+            return Ok(MutationResult::Skipped);
+        }
+        let copy = input.bytes()[from].to_vec();
+        input.bytes_mut().splice(to, copy);
+        Ok(MutationResult::Mutated)
+    }
+}
+
+impl Named for ChunkCopyMutator {
+    fn name(&self) -> &str {
+        "ChunkCopyMutator"
+    }
+}
+
+impl ChunkCopyMutator {
+    /// Creates a new [`ChunkCopyMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Swaps two whole chunks, see [`ChunkBoundariesMetadata`]
+#[derive(Default, Debug)]
+pub struct ChunkSwapMutator;
+
+impl<I, S> Mutator<I, S> for ChunkSwapMutator
+where
+    S: HasRand + HasCorpus + HasCurrentCorpusIdx,
+    I: HasBytesVec,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let Some(chunks) = current_chunks(state, input.bytes().len()) else {
+            return BytesSwapMutator::default().mutate(state, input);
+        };
+        if chunks.len() <= 1 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let (first, second) = choose_two(state, chunks.len());
+        let (first, second) = if first < second {
+            (chunks[first].clone(), chunks[second].clone())
+        } else {
+            (chunks[second].clone(), chunks[first].clone())
+        };
+        let bytes = input.bytes();
+        let mut swapped = Vec::with_capacity(second.end - first.start);
+        swapped.extend_from_slice(&bytes[second.clone()]);
+        swapped.extend_from_slice(&bytes[first.end..second.start]);
+        swapped.extend_from_slice(&bytes[first.clone()]);
+        input.bytes_mut().splice(first.start..second.end, swapped);
+        Ok(MutationResult::Mutated)
+    }
+}
+
+impl Named for ChunkSwapMutator {
+    fn name(&self) -> &str {
+        "ChunkSwapMutator"
+    }
+}
+
+impl ChunkSwapMutator {
+    /// Creates a new [`ChunkSwapMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{ChunkBoundariesMetadata, ChunkDeleteMutator, ChunkSwapMutator};
+    use crate::{
+        corpus::{Corpus, HasCurrentCorpusIdx, Testcase},
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{MutationResult, Mutator},
+        state::{test::test_std_state, HasCorpus},
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_chunk_mutators() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            ChunkBoundariesMetadata::register();
+        }
+
+        let original = b"AAAABBBBBBCC".to_vec();
+        let mut testcase = Testcase::new(BytesInput::new(original.clone()));
+        testcase.add_metadata(ChunkBoundariesMetadata::new(vec![10, 4]));
+        let mut state = test_std_state::<BytesInput>();
+        let id = state.corpus_mut().add(testcase).unwrap();
+        state.set_corpus_idx(id).unwrap();
+
+        let chunks: [&[u8]; 3] = [b"AAAA", b"BBBBBB", b"CC"];
+        let mut mutator = ChunkDeleteMutator::new();
+        for _ in 0..20 {
+            let mut input = BytesInput::new(original.clone());
+            assert_eq!(
+                mutator.mutate(&mut state, &mut input).unwrap(),
+                MutationResult::Mutated
+            );
+            // Exactly one chunk is gone, the others are untouched
+            let remaining = (0..3)
+                .map(|deleted| {
+                    chunks
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _)| *idx != deleted)
+                        .flat_map(|(_, chunk)| chunk.iter().copied())
+                        .collect::<Vec<u8>>()
+                })
+                .filter(|remaining| remaining == input.bytes())
+                .count();
+            assert_eq!(remaining, 1);
+        }
+
+        let mut mutator = ChunkSwapMutator::new();
+        let mut input = BytesInput::new(original.clone());
+        mutator.mutate(&mut state, &mut input).unwrap();
+        assert_eq!(input.bytes().len(), original.len());
+        assert_ne!(input.bytes(), &original);
+    }
+}
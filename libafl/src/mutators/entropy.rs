@@ -0,0 +1,216 @@
+//! Biases positional mutations towards the high-entropy regions of an input.
+//!
+//! In binary formats, long runs of zeros or padding rarely influence the parsing,
+//! so mutating them is mostly wasted effort.
+//! With an [`EntropyWeightingMetadata`] in the [`crate::state::StdState`], the positional mutators, such as the [`crate::mutators::BitFlipMutator`],
+//! pick their offsets weighted by the byte entropy of each region of the input, see [`crate::mutators::rand_position`].
+
+use alloc::vec::Vec;
+
+use libafl_bolts::{impl_serdeany, rands::Rand};
+use serde::{Deserialize, Serialize};
+
+/// The default length of the regions the [`EntropyWeightingMetadata`] computes the entropy of
+pub const DEFAULT_ENTROPY_REGION_LEN: usize = 32;
+
+/// The weight of a region without any entropy, so that it still gets mutated from time to time.
+/// The maximum entropy of a region is `8.0` bits per byte.
+pub const DEFAULT_MIN_ENTROPY_WEIGHT: f64 = 0.25;
+
+/// The Shannon entropy of the `bytes`, in bits per byte, between `0.0` and `8.0`
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn byte_entropy(bytes: &[u8]) -> f64 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0_usize; 256];
+    for byte in bytes {
+        counts[*byte as usize] += 1;
+    }
+    let len = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|count| **count != 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * libm::log2(p)
+        })
+        .sum()
+}
+
+/// The [`byte_entropy`] of each consecutive region of `region_len` bytes. The last region may be shorter.
+#[must_use]
+pub fn region_entropies(bytes: &[u8], region_len: usize) -> Vec<f64> {
+    bytes.chunks(region_len.max(1)).map(byte_entropy).collect()
+}
+
+/// Biases the offsets of the positional mutators towards the high-entropy regions of the input,
+/// once added to the state, see [`crate::mutators::rand_position`].
+///
+/// Each region of the input is picked with a probability proportional to its entropy,
+/// then the offset is picked uniformly within that region.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct EntropyWeightingMetadata {
+    region_len: usize,
+    min_weight: f64,
+}
+
+impl_serdeany!(EntropyWeightingMetadata);
+
+impl Default for EntropyWeightingMetadata {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntropyWeightingMetadata {
+    /// Creates a new [`EntropyWeightingMetadata`], with regions of [`DEFAULT_ENTROPY_REGION_LEN`] bytes
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            region_len: DEFAULT_ENTROPY_REGION_LEN,
+            min_weight: DEFAULT_MIN_ENTROPY_WEIGHT,
+        }
+    }
+
+    /// Sets the length of the regions to compute the entropy of
+    #[must_use]
+    pub fn with_region_len(mut self, region_len: usize) -> Self {
+        self.region_len = region_len.max(1);
+        self
+    }
+
+    /// Sets the weight of regions without any entropy, see [`DEFAULT_MIN_ENTROPY_WEIGHT`]
+    #[must_use]
+    pub fn with_min_weight(mut self, min_weight: f64) -> Self {
+        self.min_weight = min_weight;
+        self
+    }
+
+    /// The length of the regions to compute the entropy of
+    #[must_use]
+    pub fn region_len(&self) -> usize {
+        self.region_len
+    }
+
+    /// The weight of each region of the `bytes`: its entropy, but at least the min weight
+    #[must_use]
+    pub fn region_weights(&self, bytes: &[u8]) -> Vec<f64> {
+        region_entropies(bytes, self.region_len)
+            .into_iter()
+            .map(|entropy| entropy.max(self.min_weight))
+            .collect()
+    }
+
+    /// Picks an offset below `upper` into the `bytes`, weighted by the entropy of its region.
+    /// `upper` must be greater than `0`, and at most one past the end of the `bytes`.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    pub fn position<R>(&self, rand: &mut R, bytes: &[u8], upper: usize) -> usize
+    where
+        R: Rand,
+    {
+        if bytes.len() <= self.region_len {
+            return rand.below(upper as u64) as usize;
+        }
+
+        // Only the regions with offsets below `upper`
+        let mut weights = self.region_weights(bytes);
+        weights.truncate(upper.div_ceil(self.region_len));
+        let total: f64 = weights.iter().sum();
+        let threshold = total * (rand.below(1 << 20) as f64 / f64::from(1 << 20));
+        let mut sum = 0.0;
+        let mut region = weights.len() - 1;
+        for (idx, weight) in weights.iter().enumerate() {
+            sum += weight;
+            if sum > threshold {
+                region = idx;
+                break;
+            }
+        }
+
+        let start = region * self.region_len;
+        // The last region also covers the offset right after the end, if it is below `upper`
+        let end = if region + 1 == weights.len() {
+            upper
+        } else {
+            start + self.region_len
+        };
+        start + rand.below((end - start) as u64) as usize
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use alloc::vec::Vec;
+
+    use libafl_bolts::rands::{Rand, StdRand};
+
+    use super::{byte_entropy, region_entropies, EntropyWeightingMetadata};
+    use crate::{
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{BitFlipMutator, BytesInsertMutator, Mutator},
+        state::{test::test_std_state, HasMaxSize},
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_entropy_weighted_positions() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            EntropyWeightingMetadata::register();
+        }
+
+        assert!(byte_entropy(&[0; 64]).abs() < f64::EPSILON);
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        assert!((byte_entropy(&all_bytes) - 8.0).abs() < 1e-9);
+
+        let mut state = test_std_state::<BytesInput>();
+        state.add_metadata(EntropyWeightingMetadata::new().with_region_len(32));
+
+        // Half zeros, half random
+        let mut rand = StdRand::with_seed(1337);
+        let mut original = vec![0_u8; 256];
+        original.extend((0..256).map(|_| rand.below(256) as u8));
+        let entropies = region_entropies(&original, 32);
+        assert!(entropies[..8]
+            .iter()
+            .all(|entropy| entropy.abs() < f64::EPSILON));
+        assert!(entropies[8..].iter().all(|entropy| *entropy > 3.0));
+
+        let mut mutator = BitFlipMutator::new();
+        let mut zero_half = 0;
+        let mut random_half = 0;
+        for _ in 0..1000 {
+            let mut input = BytesInput::new(original.clone());
+            mutator.mutate(&mut state, &mut input).unwrap();
+            let changed = input
+                .bytes()
+                .iter()
+                .zip(&original)
+                .position(|(mutated, original)| mutated != original)
+                .unwrap();
+            if changed < 256 {
+                zero_half += 1;
+            } else {
+                random_half += 1;
+            }
+        }
+        assert!(random_half > 10 * zero_half);
+
+        // Length-changing mutators still respect the max size
+        state.set_max_size(original.len());
+        let mut input = BytesInput::new(original.clone());
+        BytesInsertMutator::new()
+            .mutate(&mut state, &mut input)
+            .unwrap();
+        assert_eq!(input.bytes(), original.as_slice());
+    }
+}
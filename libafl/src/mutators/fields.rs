@@ -0,0 +1,261 @@
+//! Arithmetic mutations on the known integer fields of an input.
+//!
+//! If the location of integer fields is known, for example declared by the user or derived from cmplog,
+//! it can be stored in the [`IntegerFieldsMetadata`] of a testcase.
+//! The [`FieldArithMutator`] then adds small deltas to whole fields, in their actual width and byte order,
+//! instead of blindly adding to random bytes, as the [`crate::mutators::DwordAddMutator`] does.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use libafl_bolts::{impl_serdeany, rands::Rand, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::HasCurrentCorpusIdx,
+    inputs::HasBytesVec,
+    mutators::{MutationResult, Mutator, ARITH_MAX},
+    state::{HasCorpus, HasCurrentTestcase, HasRand},
+    Error, HasMetadata,
+};
+
+/// The location of an integer field in an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegerField {
+    /// The offset of the first byte of the field
+    pub offset: usize,
+    /// The width of the field in bytes, one of `1`, `2`, `4`, or `8`
+    pub width: usize,
+    /// If the field is stored in big-endian byte order
+    pub big_endian: bool,
+}
+
+impl IntegerField {
+    /// Creates a new little-endian [`IntegerField`]
+    #[must_use]
+    pub fn le(offset: usize, width: usize) -> Self {
+        Self {
+            offset,
+            width,
+            big_endian: false,
+        }
+    }
+
+    /// Creates a new big-endian [`IntegerField`]
+    #[must_use]
+    pub fn be(offset: usize, width: usize) -> Self {
+        Self {
+            offset,
+            width,
+            big_endian: true,
+        }
+    }
+
+    /// The bytes of the field
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.width
+    }
+
+    /// If the field has a supported width, and fits into an input of the given length
+    #[must_use]
+    pub fn fits(&self, len: usize) -> bool {
+        matches!(self.width, 1 | 2 | 4 | 8) && self.offset + self.width <= len
+    }
+
+    /// Reads the value of the field from the `bytes`, which must fit the field
+    #[must_use]
+    pub fn read(&self, bytes: &[u8]) -> u64 {
+        let field = &bytes[self.range()];
+        let fold = |val: u64, byte: &u8| (val << 8) | u64::from(*byte);
+        if self.big_endian {
+            field.iter().fold(0, fold)
+        } else {
+            field.iter().rev().fold(0, fold)
+        }
+    }
+
+    /// Writes the `val`, truncated to the width of the field, into the `bytes`, which must fit the field
+    pub fn write(&self, bytes: &mut [u8], val: u64) {
+        let range = self.range();
+        let le_bytes = val.to_le_bytes();
+        let field = &mut bytes[range];
+        field.copy_from_slice(&le_bytes[..self.width]);
+        if self.big_endian {
+            field.reverse();
+        }
+    }
+}
+
+/// The known integer fields of the input of a testcase
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct IntegerFieldsMetadata {
+    /// The integer fields
+    pub fields: Vec<IntegerField>,
+}
+
+impl_serdeany!(IntegerFieldsMetadata);
+
+impl IntegerFieldsMetadata {
+    /// Creates a new [`IntegerFieldsMetadata`] from the given fields
+    #[must_use]
+    pub fn new(fields: Vec<IntegerField>) -> Self {
+        Self { fields }
+    }
+}
+
+/// Adds or subtracts a random value up to [`ARITH_MAX`] to the integer `field` of the `bytes`,
+/// wrapping around in the width of the field, and keeping its byte order.
+///
+/// Skips fields that do not fit into the `bytes`.
+pub fn mutation_field_arith<R>(
+    rand: &mut R,
+    bytes: &mut [u8],
+    field: &IntegerField,
+) -> MutationResult
+where
+    R: Rand,
+{
+    if !field.fits(bytes.len()) {
+        return MutationResult::Skipped;
+    }
+    let val = field.read(bytes);
+    let delta = 1 + rand.below(ARITH_MAX);
+    let new_val = if rand.below(2) == 0 {
+        val.wrapping_add(delta)
+    } else {
+        val.wrapping_sub(delta)
+    };
+    field.write(bytes, new_val);
+    MutationResult::Mutated
+}
+
+/// Adds or subtracts a small value to one of the known integer fields, see [`IntegerFieldsMetadata`]
+/// and [`mutation_field_arith`].
+///
+/// Skips inputs without known fields.
+#[derive(Default, Debug)]
+pub struct FieldArithMutator;
+
+impl<I, S> Mutator<I, S> for FieldArithMutator
+where
+    S: HasRand + HasCorpus + HasCurrentCorpusIdx,
+    I: HasBytesVec,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let len = input.bytes().len();
+        // Earlier stacked mutations may have moved the fields out of the input
+        let fields: Vec<IntegerField> = {
+            let Ok(testcase) = state.current_testcase() else {
+                return Ok(MutationResult::Skipped);
+            };
+            let Ok(metadata) = testcase.metadata::<IntegerFieldsMetadata>() else {
+                return Ok(MutationResult::Skipped);
+            };
+            metadata
+                .fields
+                .iter()
+                .filter(|field| field.fits(len))
+                .copied()
+                .collect()
+        };
+        if fields.is_empty() {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let field = state.rand_mut().choose(fields);
+        Ok(mutation_field_arith(
+            state.rand_mut(),
+            input.bytes_mut(),
+            &field,
+        ))
+    }
+}
+
+impl Named for FieldArithMutator {
+    fn name(&self) -> &str {
+        "FieldArithMutator"
+    }
+}
+
+impl FieldArithMutator {
+    /// Creates a new [`FieldArithMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use libafl_bolts::rands::StdRand;
+
+    use super::{mutation_field_arith, FieldArithMutator, IntegerField, IntegerFieldsMetadata};
+    use crate::{
+        corpus::{Corpus, HasCurrentCorpusIdx, Testcase},
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{MutationResult, Mutator, ARITH_MAX},
+        state::{test::test_std_state, HasCorpus},
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_field_arith_mutator() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            IntegerFieldsMetadata::register();
+        }
+
+        // A 4 byte little-endian field at offset 2, holding 1000
+        let mut original = b"AA\0\0\0\0BB".to_vec();
+        original[2..6].copy_from_slice(&1000_u32.to_le_bytes());
+        let mut testcase = Testcase::new(BytesInput::new(original.clone()));
+        testcase.add_metadata(IntegerFieldsMetadata::new(vec![IntegerField::le(2, 4)]));
+        let mut state = test_std_state::<BytesInput>();
+        let id = state.corpus_mut().add(testcase).unwrap();
+        state.set_corpus_idx(id).unwrap();
+
+        let mut mutator = FieldArithMutator::new();
+        for _ in 0..100 {
+            let mut input = BytesInput::new(original.clone());
+            assert_eq!(
+                mutator.mutate(&mut state, &mut input).unwrap(),
+                MutationResult::Mutated
+            );
+            let bytes = input.bytes();
+            assert_eq!(bytes[..2], original[..2]);
+            assert_eq!(bytes[6..], original[6..]);
+            let val = u32::from_le_bytes(bytes[2..6].try_into().unwrap());
+            assert_ne!(val, 1000);
+            assert!(u64::from(val.abs_diff(1000)) <= ARITH_MAX);
+        }
+    }
+
+    #[test]
+    fn test_mutation_field_arith_big_endian() {
+        let mut rand = StdRand::with_seed(0);
+        let field = IntegerField::be(1, 2);
+        for _ in 0..100 {
+            // Wraps around in the width of the field, without touching its neighbours
+            let mut bytes = [0xff, 0, 0, 0xff];
+            assert_eq!(
+                mutation_field_arith(&mut rand, &mut bytes, &field),
+                MutationResult::Mutated
+            );
+            let val = u16::from_be_bytes([bytes[1], bytes[2]]);
+            assert!(u64::from(val.min(0_u16.wrapping_sub(val))) <= ARITH_MAX);
+            assert_eq!((bytes[0], bytes[3]), (0xff, 0xff));
+        }
+        assert_eq!(
+            mutation_field_arith(&mut rand, &mut [0; 2], &field),
+            MutationResult::Skipped
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! The [`MagicHeaderMutator`] puts well-known file format signatures at the start of an input,
+//! to get past the format detection of a target and reach its format-specific parsers.
+
+use alloc::vec::Vec;
+
+use libafl_bolts::{rands::Rand, Named};
+
+use crate::{
+    inputs::HasBytesVec,
+    mutators::{MutationResult, Mutator},
+    state::{HasMaxSize, HasRand},
+    Error,
+};
+
+/// The magic headers used by [`MagicHeaderMutator::new`]
+pub const DEFAULT_MAGIC_HEADERS: &[&[u8]] = &[
+    // PNG
+    b"\x89PNG\r\n\x1a\n",
+    // JPEG
+    b"\xff\xd8\xff",
+    // GIF
+    b"GIF89a",
+    // ELF
+    b"\x7fELF",
+    // PE
+    b"MZ",
+    // Mach-O (64 bit)
+    b"\xcf\xfa\xed\xfe",
+    // ZIP
+    b"PK\x03\x04",
+    // gzip
+    b"\x1f\x8b",
+    // bzip2
+    b"BZh",
+    // xz
+    b"\xfd7zXZ\x00",
+    // 7z
+    b"7z\xbc\xaf\x27\x1c",
+    // PDF
+    b"%PDF-",
+    // RIFF (WAV, AVI, WebP)
+    b"RIFF",
+    // OGG
+    b"OggS",
+    // SQLite
+    b"SQLite format 3\x00",
+    // WebAssembly
+    b"\x00asm",
+];
+
+/// A [`Mutator`] that either prepends a magic header to the input, or overwrites the start of the input with it.
+///
+/// The header is drawn from a configurable table, see [`MagicHeaderMutator::with_headers`].
+/// If prepending would exceed the max size, the end of the input gets cut off, never the header.
+#[derive(Debug, Clone)]
+pub struct MagicHeaderMutator {
+    headers: Vec<Vec<u8>>,
+}
+
+impl MagicHeaderMutator {
+    /// Creates a new [`MagicHeaderMutator`] using the [`DEFAULT_MAGIC_HEADERS`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_headers(DEFAULT_MAGIC_HEADERS.iter().map(|header| header.to_vec()))
+    }
+
+    /// Creates a new [`MagicHeaderMutator`] using the given magic headers
+    #[must_use]
+    pub fn with_headers<IT>(headers: IT) -> Self
+    where
+        IT: IntoIterator<Item = Vec<u8>>,
+    {
+        Self {
+            headers: headers
+                .into_iter()
+                .filter(|header| !header.is_empty())
+                .collect(),
+        }
+    }
+
+    /// The magic headers of this mutator
+    #[must_use]
+    pub fn headers(&self) -> &[Vec<u8>] {
+        &self.headers
+    }
+}
+
+impl Default for MagicHeaderMutator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, S> Mutator<I, S> for MagicHeaderMutator
+where
+    S: HasRand + HasMaxSize,
+    I: HasBytesVec,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        if self.headers.is_empty() {
+            return Ok(MutationResult::Skipped);
+        }
+        let max_size = state.max_size();
+        let header = state.rand_mut().choose(&self.headers);
+        if header.len() > max_size || input.bytes().starts_with(header) {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let size = input.bytes().len();
+        if state.rand_mut().below(2) == 0 {
+            // Prepend the header
+            let keep = size.min(max_size - header.len());
+            let bytes = input.bytes_mut();
+            bytes.truncate(keep);
+            bytes.splice(0..0, header.iter().copied());
+        } else {
+            // Overwrite the start with the header
+            let bytes = input.bytes_mut();
+            if size < header.len() {
+                bytes.resize(header.len(), 0);
+            }
+            bytes[..header.len()].copy_from_slice(header);
+        }
+
+        Ok(MutationResult::Mutated)
+    }
+}
+
+impl Named for MagicHeaderMutator {
+    fn name(&self) -> &str {
+        "MagicHeaderMutator"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MagicHeaderMutator;
+    use crate::{
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{MutationResult, Mutator},
+        state::{test::test_std_state, HasMaxSize},
+    };
+
+    #[test]
+    fn test_magic_header_mutator() {
+        let mut state = test_std_state::<BytesInput>();
+        state.set_max_size(16);
+
+        let headers = [b"\x7fELF".to_vec(), b"PK\x03\x04".to_vec()];
+        let mut mutator = MagicHeaderMutator::with_headers(headers.clone());
+
+        for _ in 0..64 {
+            let mut input = BytesInput::new(b"0123456789abcdef".to_vec());
+            assert_eq!(
+                mutator.mutate(&mut state, &mut input).unwrap(),
+                MutationResult::Mutated
+            );
+            assert!(input.bytes().len() <= 16);
+            assert!(headers
+                .iter()
+                .any(|header| input.bytes().starts_with(header)));
+        }
+    }
+}
@@ -19,6 +19,18 @@ pub mod grimoire;
 pub use grimoire::*;
 pub mod tuneable;
 pub use tuneable::*;
+pub mod chunks;
+pub use chunks::*;
+pub mod fields;
+pub use fields::*;
+pub mod entropy;
+pub use entropy::*;
+pub mod magic;
+pub use magic::*;
+pub mod pipeline;
+pub use pipeline::*;
+pub mod utf8;
+pub use utf8::*;
 
 #[cfg(feature = "unicode")]
 pub mod string;
@@ -93,6 +105,20 @@ pub trait Mutator<I, S>: Named {
     /// Mutate a given input
     fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error>;
 
+    /// Mutate a given input, calling `on_step` with every intermediate result, if this mutator stacks multiple mutations.
+    /// The final result is not passed to `on_step`.
+    ///
+    /// By default, this simply calls [`Mutator::mutate`], without any intermediate results.
+    #[inline]
+    fn mutate_with_intermediates(
+        &mut self,
+        state: &mut S,
+        input: &mut I,
+        _on_step: &mut dyn FnMut(&mut S, &I) -> Result<(), Error>,
+    ) -> Result<MutationResult, Error> {
+        self.mutate(state, input)
+    }
+
     /// Post-process given the outcome of the execution
     /// `new_corpus_idx` will be `Some` if a new `Testcase` was created this execution.
     #[inline]
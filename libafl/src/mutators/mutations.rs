@@ -3,15 +3,17 @@
 use alloc::{borrow::ToOwned, vec::Vec};
 use core::{cmp::min, marker::PhantomData, mem::size_of, ops::Range};
 
-use libafl_bolts::{rands::Rand, Named};
+use libafl_bolts::{rands::Rand, AsSlice, Named};
 
 use crate::{
-    corpus::Corpus,
+    corpus::{Corpus, HasCurrentCorpusIdx},
     inputs::{HasBytesVec, Input},
     mutators::{MutationResult, Mutator},
+    observers::{CmpValues, CmpValuesMetadata},
     random_corpus_id_with_disabled,
-    state::{HasCorpus, HasMaxSize, HasRand},
-    Error,
+    stages::EffectorMapMetadata,
+    state::{HasCorpus, HasCurrentTestcase, HasMaxSize, HasRand},
+    Error, HasMetadata,
 };
 
 /// Mem move in the own vec
@@ -73,6 +75,20 @@ pub fn rand_range<S: HasRand>(state: &mut S, upper: usize, max_len: usize) -> Ra
     offset1..offset2
 }
 
+/// Picks the offset of a positional mutation of the `bytes`, below `upper`.
+/// `upper` must be greater than `0`, and at most one past the end of the `bytes`.
+///
+/// This is the hook the positional mutators use to choose where to mutate, see [`HasRand::rand_position`].
+/// With an [`crate::mutators::EntropyWeightingMetadata`] in a [`crate::state::StdState`],
+/// offsets in high-entropy regions of the `bytes` are more likely, otherwise all offsets are equally likely.
+#[inline]
+pub fn rand_position<S>(state: &mut S, bytes: &[u8], upper: usize) -> usize
+where
+    S: HasRand,
+{
+    state.rand_position(bytes, upper)
+}
+
 /// The max value that will be added or subtracted during add mutations
 pub const ARITH_MAX: u64 = 35;
 
@@ -127,8 +143,8 @@ where
             Ok(MutationResult::Skipped)
         } else {
             let bit = 1 << state.rand_mut().choose(0..8);
-            let byte = state.rand_mut().choose(input.bytes_mut());
-            *byte ^= bit;
+            let idx = rand_position(state, input.bytes(), input.bytes().len());
+            input.bytes_mut()[idx] ^= bit;
             Ok(MutationResult::Mutated)
         }
     }
@@ -148,6 +164,54 @@ impl BitFlipMutator {
     }
 }
 
+/// Bitflip mutation for inputs with a bytes vector, that only flips bits of the bytes
+/// marked as effective in the [`EffectorMapMetadata`] of the current testcase.
+///
+/// Without an [`EffectorMapMetadata`], for example if no [`crate::stages::EffectorMapStage`] ran yet,
+/// it flips bits of any byte, like the [`BitFlipMutator`].
+#[derive(Default, Debug)]
+pub struct EffectorBitFlipMutator;
+
+impl<I, S> Mutator<I, S> for EffectorBitFlipMutator
+where
+    S: HasRand + HasCorpus + HasCurrentCorpusIdx,
+    I: HasBytesVec,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let len = input.bytes().len();
+        let effective = match state.current_testcase() {
+            Ok(testcase) => match testcase.metadata::<EffectorMapMetadata>() {
+                Ok(effector) => Some(effector.effective_indexes(len)),
+                Err(_) => None,
+            },
+            Err(_) => None,
+        };
+        let idx = match effective {
+            Some(effective) if effective.is_empty() => return Ok(MutationResult::Skipped),
+            Some(effective) => *state.rand_mut().choose(&effective),
+            None if len == 0 => return Ok(MutationResult::Skipped),
+            None => state.rand_mut().below(len as u64) as usize,
+        };
+        let bit = 1 << state.rand_mut().choose(0..8);
+        input.bytes_mut()[idx] ^= bit;
+        Ok(MutationResult::Mutated)
+    }
+}
+
+impl Named for EffectorBitFlipMutator {
+    fn name(&self) -> &str {
+        "EffectorBitFlipMutator"
+    }
+}
+
+impl EffectorBitFlipMutator {
+    /// Creates a new [`EffectorBitFlipMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
 /// Byteflip mutation for inputs with a bytes vector
 #[derive(Default, Debug)]
 pub struct ByteFlipMutator;
@@ -161,7 +225,8 @@ where
         if input.bytes().is_empty() {
             Ok(MutationResult::Skipped)
         } else {
-            *state.rand_mut().choose(input.bytes_mut()) ^= 0xff;
+            let idx = rand_position(state, input.bytes(), input.bytes().len());
+            input.bytes_mut()[idx] ^= 0xff;
             Ok(MutationResult::Mutated)
         }
     }
@@ -194,7 +259,8 @@ where
         if input.bytes().is_empty() {
             Ok(MutationResult::Skipped)
         } else {
-            let byte = state.rand_mut().choose(input.bytes_mut());
+            let idx = rand_position(state, input.bytes(), input.bytes().len());
+            let byte = &mut input.bytes_mut()[idx];
             *byte = byte.wrapping_add(1);
             Ok(MutationResult::Mutated)
         }
@@ -228,7 +294,8 @@ where
         if input.bytes().is_empty() {
             Ok(MutationResult::Skipped)
         } else {
-            let byte = state.rand_mut().choose(input.bytes_mut());
+            let idx = rand_position(state, input.bytes(), input.bytes().len());
+            let byte = &mut input.bytes_mut()[idx];
             *byte = byte.wrapping_sub(1);
             Ok(MutationResult::Mutated)
         }
@@ -262,7 +329,8 @@ where
         if input.bytes().is_empty() {
             Ok(MutationResult::Skipped)
         } else {
-            let byte = state.rand_mut().choose(input.bytes_mut());
+            let idx = rand_position(state, input.bytes(), input.bytes().len());
+            let byte = &mut input.bytes_mut()[idx];
             *byte = (!(*byte)).wrapping_add(1);
             Ok(MutationResult::Mutated)
         }
@@ -296,8 +364,8 @@ where
         if input.bytes().is_empty() {
             Ok(MutationResult::Skipped)
         } else {
-            let byte = state.rand_mut().choose(input.bytes_mut());
-            *byte ^= 1 + state.rand_mut().below(254) as u8;
+            let idx = rand_position(state, input.bytes(), input.bytes().len());
+            input.bytes_mut()[idx] ^= 1 + state.rand_mut().below(254) as u8;
             Ok(MutationResult::Mutated)
         }
     }
@@ -341,9 +409,9 @@ macro_rules! add_mutator_impl {
                     Ok(MutationResult::Skipped)
                 } else {
                     // choose a random window of bytes (windows overlap) and convert to $size
-                    let (index, bytes) = state
-                        .rand_mut()
-                        .choose(input.bytes().windows(size_of::<$size>()).enumerate());
+                    let upper = input.bytes().len() + 1 - size_of::<$size>();
+                    let index = rand_position(state, input.bytes(), upper);
+                    let bytes = &input.bytes()[index..index + size_of::<$size>()];
                     let val = <$size>::from_ne_bytes(bytes.try_into().unwrap());
 
                     // mutate
@@ -402,9 +470,9 @@ macro_rules! interesting_mutator_impl {
                 if input.bytes().len() < size_of::<$size>() {
                     Ok(MutationResult::Skipped)
                 } else {
+                    let upper_bound = input.bytes().len() + 1 - size_of::<$size>();
+                    let idx = rand_position(state, input.bytes(), upper_bound);
                     let bytes = input.bytes_mut();
-                    let upper_bound = (bytes.len() + 1 - size_of::<$size>()) as u64;
-                    let idx = state.rand_mut().below(upper_bound) as usize;
                     let val = *state.rand_mut().choose(&$interesting) as $size;
                     let new_bytes = match state.rand_mut().choose(&[0, 1]) {
                         0 => val.to_be_bytes(),
@@ -536,7 +604,7 @@ where
         }
 
         let mut amount = 1 + state.rand_mut().below(16) as usize;
-        let offset = state.rand_mut().below(size as u64 + 1) as usize;
+        let offset = rand_position(state, input.bytes(), size + 1);
 
         if size + amount > max_size {
             if max_size > size {
@@ -589,7 +657,7 @@ where
         }
 
         let mut amount = 1 + state.rand_mut().below(16) as usize;
-        let offset = state.rand_mut().below(size as u64 + 1) as usize;
+        let offset = rand_position(state, input.bytes(), size + 1);
 
         if size + amount > max_size {
             if max_size > size {
@@ -716,7 +784,7 @@ where
             return Ok(MutationResult::Skipped);
         }
 
-        let target = state.rand_mut().below(size as u64) as usize;
+        let target = rand_position(state, input.bytes(), size);
         let range = rand_range(state, size, size - target);
 
         unsafe {
@@ -758,7 +826,7 @@ where
             return Ok(MutationResult::Skipped);
         }
 
-        let target = state.rand_mut().below(size as u64) as usize;
+        let target = rand_position(state, input.bytes(), size);
         // make sure that the sampled range is both in bounds and of an acceptable size
         let max_insert_len = min(size - target, state.max_size() - size);
         let range = rand_range(state, size, min(16, max_insert_len));
@@ -1003,6 +1071,133 @@ impl BytesSwapMutator {
     }
 }
 
+/// Bytes swap mutation for inputs with a bytes vector, exchanging two random, equal-length slices.
+/// Unlike the [`BytesSwapMutator`], the bytes between the two slices stay in place.
+#[derive(Debug, Default)]
+pub struct BytesEqualLenSwapMutator;
+
+impl<I, S> Mutator<I, S> for BytesEqualLenSwapMutator
+where
+    S: HasRand,
+    I: HasBytesVec,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let size = input.bytes().len();
+        if size <= 1 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        // both slices have to fit into the input without overlapping
+        let len = 1 + state.rand_mut().below((size / 2) as u64) as usize;
+        let first = state.rand_mut().below((size - 2 * len + 1) as u64) as usize;
+        let second =
+            first + len + state.rand_mut().below((size - 2 * len - first + 1) as u64) as usize;
+
+        let (front, back) = input.bytes_mut().split_at_mut(second);
+        front[first..first + len].swap_with_slice(&mut back[..len]);
+
+        Ok(MutationResult::Mutated)
+    }
+}
+
+impl Named for BytesEqualLenSwapMutator {
+    fn name(&self) -> &str {
+        "BytesEqualLenSwapMutator"
+    }
+}
+
+impl BytesEqualLenSwapMutator {
+    /// Creates a new [`BytesEqualLenSwapMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Bytes reverse mutation for inputs with a bytes vector, reversing the byte order of a random region.
+/// Flips the endianness of integers of any width, if the region happens to cover one.
+#[derive(Debug, Default)]
+pub struct BytesReverseMutator;
+
+impl<I, S> Mutator<I, S> for BytesReverseMutator
+where
+    S: HasRand,
+    I: HasBytesVec,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let size = input.bytes().len();
+        if size <= 1 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        // reversing a single byte would be a no-op
+        let len = 2 + state.rand_mut().below((size - 1) as u64) as usize;
+        let from = state.rand_mut().below((size - len + 1) as u64) as usize;
+
+        input.bytes_mut()[from..from + len].reverse();
+
+        Ok(MutationResult::Mutated)
+    }
+}
+
+impl Named for BytesReverseMutator {
+    fn name(&self) -> &str {
+        "BytesReverseMutator"
+    }
+}
+
+impl BytesReverseMutator {
+    /// Creates a new [`BytesReverseMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Bit reverse mutation for inputs with a bytes vector, reversing the bit order of a random region,
+/// i.e., the order of its bytes and the bits within each of them.
+/// Hits bugs of bit-order (MSB vs. LSB first) handling in bit streams.
+#[derive(Debug, Default)]
+pub struct BitsReverseMutator;
+
+impl<I, S> Mutator<I, S> for BitsReverseMutator
+where
+    S: HasRand,
+    I: HasBytesVec,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let size = input.bytes().len();
+        if size == 0 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let len = 1 + state.rand_mut().below(size as u64) as usize;
+        let from = state.rand_mut().below((size - len + 1) as u64) as usize;
+
+        let region = &mut input.bytes_mut()[from..from + len];
+        region.reverse();
+        for byte in region {
+            *byte = byte.reverse_bits();
+        }
+
+        Ok(MutationResult::Mutated)
+    }
+}
+
+impl Named for BitsReverseMutator {
+    fn name(&self) -> &str {
+        "BitsReverseMutator"
+    }
+}
+
+impl BitsReverseMutator {
+    /// Creates a new [`BitsReverseMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
 /// Crossover insert mutation for inputs with a bytes vector
 #[derive(Debug, Default)]
 pub struct CrossoverInsertMutator<I> {
@@ -1255,6 +1450,138 @@ impl SpliceMutator {
     }
 }
 
+/// The offsets in `bytes` where an operand of one of the `cmps` starts or ends.
+/// These are likely boundaries of fields in the input.
+fn cmp_boundaries(bytes: &[u8], cmps: &[CmpValues]) -> Vec<usize> {
+    let mut patterns: Vec<Vec<u8>> = vec![];
+    for cmp in cmps {
+        match cmp {
+            // Single bytes match way too often to tell us anything
+            CmpValues::U8(_) => {}
+            CmpValues::U16((v1, v2)) => {
+                for v in [v1, v2] {
+                    patterns.push(v.to_le_bytes().to_vec());
+                    patterns.push(v.to_be_bytes().to_vec());
+                }
+            }
+            CmpValues::U32((v1, v2)) => {
+                for v in [v1, v2] {
+                    patterns.push(v.to_le_bytes().to_vec());
+                    patterns.push(v.to_be_bytes().to_vec());
+                }
+            }
+            CmpValues::U64((v1, v2)) => {
+                for v in [v1, v2] {
+                    patterns.push(v.to_le_bytes().to_vec());
+                    patterns.push(v.to_be_bytes().to_vec());
+                }
+            }
+            CmpValues::Bytes((v1, v2)) => {
+                for v in [v1, v2] {
+                    if v.len() > 1 {
+                        patterns.push(v.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut boundaries = vec![];
+    for pattern in &patterns {
+        if pattern.len() > bytes.len() {
+            continue;
+        }
+        for (start, window) in bytes.windows(pattern.len()).enumerate() {
+            if window == pattern.as_slice() {
+                boundaries.push(start);
+                boundaries.push(start + pattern.len());
+            }
+        }
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+    boundaries
+}
+
+/// Splice mutation for inputs with a bytes vector, cutting at likely field boundaries.
+///
+/// The crossover point is chosen among the offsets where an operand recorded in the
+/// [`CmpValuesMetadata`] starts or ends in the input.
+/// Without (usable) comparison data, it picks a random point, just like the [`SpliceMutator`].
+#[derive(Debug, Default)]
+pub struct CmpAlignedSpliceMutator;
+
+impl<S> Mutator<S::Input, S> for CmpAlignedSpliceMutator
+where
+    S: HasCorpus + HasRand + HasMetadata,
+    S::Input: HasBytesVec,
+{
+    #[allow(clippy::cast_sign_loss)]
+    fn mutate(&mut self, state: &mut S, input: &mut S::Input) -> Result<MutationResult, Error> {
+        // We don't want to use the testcase we're already using for splicing
+        let idx = random_corpus_id_with_disabled!(state.corpus(), state.rand_mut());
+        if let Some(cur) = state.corpus().current() {
+            if idx == *cur {
+                return Ok(MutationResult::Skipped);
+            }
+        }
+
+        let (first_diff, last_diff) = {
+            let mut other_testcase = state.corpus().get_from_all(idx)?.borrow_mut();
+            let other = other_testcase.load_input(state.corpus())?;
+
+            let (f, l) = locate_diffs(input.bytes(), other.bytes());
+
+            if f != l && f >= 0 && l >= 2 {
+                (f as usize, l as usize)
+            } else {
+                return Ok(MutationResult::Skipped);
+            }
+        };
+
+        let candidates: Vec<usize> = state
+            .metadata_map()
+            .get::<CmpValuesMetadata>()
+            .map(|meta| cmp_boundaries(input.bytes(), meta.as_slice()))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|offset| (first_diff..=last_diff).contains(offset))
+            .collect();
+
+        let split_at = if candidates.is_empty() {
+            state
+                .rand_mut()
+                .between(first_diff as u64, last_diff as u64) as usize
+        } else {
+            *state.rand_mut().choose(&candidates)
+        };
+
+        let other_testcase = state.corpus().get_from_all(idx)?.borrow_mut();
+        // Input will already be loaded.
+        let other = other_testcase.input().as_ref().unwrap();
+
+        input
+            .bytes_mut()
+            .splice(split_at.., other.bytes()[split_at..].iter().copied());
+
+        Ok(MutationResult::Mutated)
+    }
+}
+
+impl Named for CmpAlignedSpliceMutator {
+    fn name(&self) -> &str {
+        "CmpAlignedSpliceMutator"
+    }
+}
+
+impl CmpAlignedSpliceMutator {
+    /// Creates a new [`CmpAlignedSpliceMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
 // Converts a hex u8 to its u8 value: 'A' -> 10 etc.
 fn from_hex(hex: u8) -> Result<u8, Error> {
     match hex {
@@ -1335,6 +1662,7 @@ mod tests {
         BytesRandSetMutator,
         BytesCopyMutator,
         BytesSwapMutator,
+        BytesEqualLenSwapMutator,
     );
 
     fn test_mutations() -> TestMutatorsTupleType {
@@ -1363,10 +1691,11 @@ mod tests {
             BytesRandSetMutator::new(),
             BytesCopyMutator::new(),
             BytesSwapMutator::new(),
+            BytesEqualLenSwapMutator::new(),
         )
     }
 
-    fn test_state() -> impl HasCorpus + HasMetadata + HasRand + HasMaxSize {
+    fn test_state() -> impl HasCorpus<Input = BytesInput> + HasMetadata + HasRand + HasMaxSize {
         let rand = StdRand::with_seed(1337);
         let mut corpus = InMemoryCorpus::new();
 
@@ -1475,6 +1804,177 @@ mod tests {
         Ok(())
     }
 
+    /// Swapping two slices must keep all bytes, but change their arrangement
+    #[test]
+    fn test_equal_len_swap() -> Result<(), Error> {
+        let base = BytesInput::new((0..16).collect());
+
+        let mut state = test_state();
+        let mut mutator = BytesEqualLenSwapMutator::new();
+
+        let mut too_short = BytesInput::new(vec![0x41]);
+        assert_eq!(
+            mutator.mutate(&mut state, &mut too_short)?,
+            MutationResult::Skipped
+        );
+
+        for _ in 0..100 {
+            let mut mutated = base.clone();
+            assert_eq!(
+                mutator.mutate(&mut state, &mut mutated)?,
+                MutationResult::Mutated
+            );
+            assert_ne!(mutated.bytes, base.bytes);
+
+            let mut sorted = mutated.bytes.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, base.bytes);
+        }
+        Ok(())
+    }
+
+    /// The changed region must be the exact reverse of the original, and the rest untouched
+    #[test]
+    fn test_bytes_reverse() -> Result<(), Error> {
+        // distinct bytes, so the changed region is exactly the reversed one
+        let base = BytesInput::new((0..16).collect());
+
+        let mut state = test_state();
+        let mut mutator = BytesReverseMutator::new();
+
+        for short in [vec![], vec![0x41]] {
+            let mut too_short = BytesInput::new(short);
+            assert_eq!(
+                mutator.mutate(&mut state, &mut too_short)?,
+                MutationResult::Skipped
+            );
+        }
+
+        for _ in 0..100 {
+            let mut mutated = base.clone();
+            assert_eq!(
+                mutator.mutate(&mut state, &mut mutated)?,
+                MutationResult::Mutated
+            );
+            let from = mutated
+                .bytes
+                .iter()
+                .zip(&base.bytes)
+                .position(|(a, b)| a != b)
+                .unwrap();
+            let to = mutated
+                .bytes
+                .iter()
+                .zip(&base.bytes)
+                .rposition(|(a, b)| a != b)
+                .unwrap()
+                + 1;
+
+            let mut reversed = base.bytes[from..to].to_vec();
+            reversed.reverse();
+            assert_eq!(mutated.bytes[from..to], reversed);
+            assert_eq!(mutated.bytes[..from], base.bytes[..from]);
+            assert_eq!(mutated.bytes[to..], base.bytes[to..]);
+        }
+        Ok(())
+    }
+
+    /// The changed region must be the exact bit-reverse of the original, and the rest untouched
+    #[test]
+    fn test_bits_reverse() -> Result<(), Error> {
+        // only the low nibble is set, so every bit-reversed byte differs from all original bytes
+        let base = BytesInput::new((1..16).collect());
+
+        let mut state = test_state();
+        let mut mutator = BitsReverseMutator::new();
+
+        let mut empty = BytesInput::new(vec![]);
+        assert_eq!(
+            mutator.mutate(&mut state, &mut empty)?,
+            MutationResult::Skipped
+        );
+        let mut single = BytesInput::new(vec![0b0000_0011]);
+        assert_eq!(
+            mutator.mutate(&mut state, &mut single)?,
+            MutationResult::Mutated
+        );
+        assert_eq!(single.bytes, [0b1100_0000]);
+
+        for _ in 0..100 {
+            let mut mutated = base.clone();
+            assert_eq!(
+                mutator.mutate(&mut state, &mut mutated)?,
+                MutationResult::Mutated
+            );
+            let from = mutated
+                .bytes
+                .iter()
+                .zip(&base.bytes)
+                .position(|(a, b)| a != b)
+                .unwrap();
+            let to = mutated
+                .bytes
+                .iter()
+                .zip(&base.bytes)
+                .rposition(|(a, b)| a != b)
+                .unwrap()
+                + 1;
+
+            let reversed: Vec<u8> = base.bytes[from..to]
+                .iter()
+                .rev()
+                .map(|b| b.reverse_bits())
+                .collect();
+            assert_eq!(mutated.bytes[from..to], reversed);
+            assert_eq!(mutated.bytes[..from], base.bytes[..from]);
+            assert_eq!(mutated.bytes[to..], base.bytes[to..]);
+        }
+        Ok(())
+    }
+
+    /// The splice must cut right where a recorded comparison operand starts or ends
+    #[test]
+    fn test_cmp_aligned_splice() -> Result<(), Error> {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            CmpValuesMetadata::register();
+        }
+
+        let mut bytes = vec![0; 4];
+        bytes.extend_from_slice(&0xdead_beef_u32.to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        let base = BytesInput::new(bytes);
+
+        let mut state = test_state();
+        let mut mutator = CmpAlignedSpliceMutator::new();
+
+        // Without any comparisons, the cut is random
+        let mut mutated = base.clone();
+        assert_eq!(
+            mutator.mutate(&mut state, &mut mutated)?,
+            MutationResult::Mutated
+        );
+
+        state.add_metadata(CmpValuesMetadata {
+            list: vec![CmpValues::U32((0xdead_beef, 0x1122_3344))],
+        });
+
+        for _ in 0..100 {
+            let mut mutated = base.clone();
+            assert_eq!(
+                mutator.mutate(&mut state, &mut mutated)?,
+                MutationResult::Mutated
+            );
+            // The other input in the corpus is all `0x42`
+            let cut = mutated.bytes.iter().position(|b| *b == 0x42).unwrap();
+            assert!(cut == 4 || cut == 8);
+            assert_eq!(mutated.bytes[..cut], base.bytes[..cut]);
+        }
+        Ok(())
+    }
+
     /// This test guarantees that the likelihood of a byte being involved in an expansion is equally
     /// likely for all indices
     #[test]
@@ -0,0 +1,178 @@
+//! A [`PipelineMutator`] applies a fixed, ordered list of mutators, instead of scheduling random ones.
+//!
+//! This is useful for deterministic steps around the actual mutation, for example decode, then havoc, then fix the checksum.
+
+use alloc::string::String;
+use core::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+};
+
+use libafl_bolts::Named;
+
+use crate::{
+    corpus::CorpusId,
+    mutators::{ComposedByMutations, MutationId, MutationResult, Mutator, MutatorsTuple},
+    Error,
+};
+
+/// A [`Mutator`] that runs each of its mutations exactly once per call, in the given order.
+///
+/// If one of the mutations is [`MutationResult::Skipped`], the rest of the pipeline is not run,
+/// and the whole mutation is skipped, so later steps never see an input an earlier step failed on.
+pub struct PipelineMutator<I, MT, S>
+where
+    MT: MutatorsTuple<I, S>,
+{
+    name: String,
+    mutations: MT,
+    phantom: PhantomData<(I, S)>,
+}
+
+impl<I, MT, S> PipelineMutator<I, MT, S>
+where
+    MT: MutatorsTuple<I, S>,
+{
+    /// Create a new [`PipelineMutator`], running the `mutations` in order
+    pub fn new(mutations: MT) -> Self {
+        Self {
+            name: format!("PipelineMutator[{}]", mutations.names().join(", ")),
+            mutations,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<I, MT, S> Debug for PipelineMutator<I, MT, S>
+where
+    MT: MutatorsTuple<I, S>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PipelineMutator with {} mutations for Input type {}",
+            self.mutations.len(),
+            core::any::type_name::<I>()
+        )
+    }
+}
+
+impl<I, MT, S> Named for PipelineMutator<I, MT, S>
+where
+    MT: MutatorsTuple<I, S>,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<I, MT, S> Mutator<I, S> for PipelineMutator<I, MT, S>
+where
+    MT: MutatorsTuple<I, S>,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        if self.mutations.is_empty() {
+            return Ok(MutationResult::Skipped);
+        }
+        for idx in 0..self.mutations.len() {
+            if self
+                .mutations
+                .get_and_mutate(MutationId::from(idx), state, input)?
+                == MutationResult::Skipped
+            {
+                return Ok(MutationResult::Skipped);
+            }
+        }
+        Ok(MutationResult::Mutated)
+    }
+
+    #[inline]
+    fn post_exec(&mut self, state: &mut S, new_corpus_idx: Option<CorpusId>) -> Result<(), Error> {
+        self.mutations.post_exec_all(state, new_corpus_idx)
+    }
+}
+
+impl<I, MT, S> ComposedByMutations<I, MT, S> for PipelineMutator<I, MT, S>
+where
+    MT: MutatorsTuple<I, S>,
+{
+    /// Get the mutations
+    #[inline]
+    fn mutations(&self) -> &MT {
+        &self.mutations
+    }
+
+    /// Get the mutations (mutable)
+    #[inline]
+    fn mutations_mut(&mut self) -> &mut MT {
+        &mut self.mutations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::{tuples::tuple_list, Named};
+
+    use super::PipelineMutator;
+    use crate::{
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{MutationResult, Mutator},
+        state::NopState,
+        Error,
+    };
+
+    /// Appends its byte to the input, or skips, if it is `None`
+    struct AppendMutator(Option<u8>);
+
+    impl Named for AppendMutator {
+        fn name(&self) -> &str {
+            "AppendMutator"
+        }
+    }
+
+    impl<S> Mutator<BytesInput, S> for AppendMutator {
+        fn mutate(
+            &mut self,
+            _state: &mut S,
+            input: &mut BytesInput,
+        ) -> Result<MutationResult, Error> {
+            let Some(byte) = self.0 else {
+                return Ok(MutationResult::Skipped);
+            };
+            input.bytes_mut().push(byte);
+            Ok(MutationResult::Mutated)
+        }
+    }
+
+    #[test]
+    fn test_pipeline_mutator() {
+        let mut state: NopState<BytesInput> = NopState::new();
+        let mut input = BytesInput::new(vec![]);
+
+        let mut pipeline = PipelineMutator::new(tuple_list!(
+            AppendMutator(Some(b'a')),
+            AppendMutator(Some(b'b')),
+            AppendMutator(Some(b'c'))
+        ));
+        for _ in 0..2 {
+            assert_eq!(
+                pipeline.mutate(&mut state, &mut input).unwrap(),
+                MutationResult::Mutated
+            );
+        }
+        assert_eq!(input.bytes(), b"abcabc");
+
+        // The last step is never reached
+        let mut input = BytesInput::new(vec![]);
+        let mut pipeline = PipelineMutator::new(tuple_list!(
+            AppendMutator(Some(b'a')),
+            AppendMutator(None),
+            AppendMutator(Some(b'c'))
+        ));
+        assert_eq!(
+            pipeline.mutate(&mut state, &mut input).unwrap(),
+            MutationResult::Skipped
+        );
+        assert_eq!(input.bytes(), b"a");
+    }
+}
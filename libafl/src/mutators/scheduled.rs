@@ -5,12 +5,18 @@ use core::{
     fmt::{self, Debug},
     marker::PhantomData,
 };
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
 
+#[cfg(feature = "std")]
+use libafl_bolts::fs::write_file_atomic;
 use libafl_bolts::{
     rands::Rand,
     tuples::{tuple_list, tuple_list_type, Merge, NamedTuple},
     AsMutSlice, AsSlice, Named,
 };
+#[cfg(feature = "std")]
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use super::MutationId;
@@ -105,6 +111,204 @@ where
         }
         Ok(r)
     }
+
+    /// Like [`ScheduledMutator::scheduled_mutate`], but calls `on_step` after each mutation that changed the input,
+    /// except for the last one.
+    fn scheduled_mutate_with_intermediates(
+        &mut self,
+        state: &mut S,
+        input: &mut I,
+        on_step: &mut dyn FnMut(&mut S, &I) -> Result<(), Error>,
+    ) -> Result<MutationResult, Error> {
+        let mut r = MutationResult::Skipped;
+        let num = self.iterations(state, input);
+        for i in 0..num {
+            let idx = self.schedule(state, input);
+            let outcome = self.mutations_mut().get_and_mutate(idx, state, input)?;
+            if outcome == MutationResult::Mutated {
+                r = MutationResult::Mutated;
+                if i + 1 < num {
+                    on_step(state, input)?;
+                }
+            }
+        }
+        Ok(r)
+    }
+}
+
+/// A single step of a [`MutationRecipe`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MutationStep {
+    /// The index of the mutation in the mutations tuple
+    pub mutation_id: MutationId,
+    /// The seed of the [`Rand`] right before the mutation ran, which determines all of its random choices
+    pub seed: u64,
+}
+
+/// The mutations a [`StdScheduledMutator`] applied to an input, in order, recorded with
+/// [`StdScheduledMutator::with_recipe_recording`].
+///
+/// Replaying the recipe on the parent input using [`replay_recipe`] reproduces the child,
+/// as long as the mutations only depend on the input and the [`Rand`], but not on, for example, the corpus.
+/// A [`StdScheduledMutator`] recording recipes attaches it to the resulting [`crate::corpus::Testcase`] as metadata.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct MutationRecipe {
+    /// The steps of this recipe
+    pub steps: Vec<MutationStep>,
+}
+
+libafl_bolts::impl_serdeany!(MutationRecipe);
+
+/// Replays a [`MutationRecipe`] on the given input, using the mutations it was recorded with
+pub fn replay_recipe<I, MT, S>(
+    mutations: &mut MT,
+    state: &mut S,
+    input: &mut I,
+    recipe: &MutationRecipe,
+) -> Result<MutationResult, Error>
+where
+    MT: MutatorsTuple<I, S>,
+    S: HasRand,
+{
+    let mut r = MutationResult::Skipped;
+    for step in &recipe.steps {
+        state.rand_mut().set_seed(step.seed);
+        if mutations.get_and_mutate(step.mutation_id, state, input)? == MutationResult::Mutated {
+            r = MutationResult::Mutated;
+        }
+    }
+    Ok(r)
+}
+
+/// A parent input, and the [`MutationRecipe`] that turns it into a child, for example one that crashed the target.
+///
+/// Stored to a file, it reproduces the child without storing the child itself,
+/// see [`crate::fuzzer::StdFuzzer::reproduce_from`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReproductionRecipe<I> {
+    /// The input the mutations start from
+    pub parent: I,
+    /// The mutations that turn the `parent` into the child
+    pub recipe: MutationRecipe,
+}
+
+impl<I> ReproductionRecipe<I> {
+    /// Creates a new [`ReproductionRecipe`]
+    #[must_use]
+    pub fn new(parent: I, recipe: MutationRecipe) -> Self {
+        Self { parent, recipe }
+    }
+
+    /// Reconstructs the child, by replaying the recipe on a copy of the parent, see [`replay_recipe`]
+    pub fn reconstruct<MT, S>(&self, mutations: &mut MT, state: &mut S) -> Result<I, Error>
+    where
+        I: Clone,
+        MT: MutatorsTuple<I, S>,
+        S: HasRand,
+    {
+        let mut child = self.parent.clone();
+        replay_recipe(mutations, state, &mut child, &self.recipe)?;
+        Ok(child)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I> ReproductionRecipe<I>
+where
+    I: Serialize + DeserializeOwned,
+{
+    /// Writes this [`ReproductionRecipe`] to a file
+    pub fn to_file<P>(&self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        write_file_atomic(path, &postcard::to_allocvec(self)?)
+    }
+
+    /// Reads a [`ReproductionRecipe`] from a file
+    pub fn from_file<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(postcard::from_bytes(&fs::read(path)?)?)
+    }
+}
+
+/// The distribution the number of stacked mutations of a [`StdScheduledMutator`] is drawn from, per call.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StackingDistribution {
+    /// AFL's stacking: `2^k` mutations, with `k` uniformly drawn from `1..=max_stack_pow`
+    PowerOfTwo {
+        /// The maximum exponent
+        max_stack_pow: u64,
+    },
+    /// The number of trials up to the first success, each succeeding with probability `p`, capped at `max`.
+    /// Few mutations are the most likely, on average, there are `1/p`.
+    Geometric {
+        /// The success probability of each trial, in `(0, 1]`
+        p: f64,
+        /// The maximum number of mutations
+        max: u64,
+    },
+    /// A number of mutations uniformly drawn from `min..=max`
+    Uniform {
+        /// The minimum number of mutations
+        min: u64,
+        /// The maximum number of mutations
+        max: u64,
+    },
+}
+
+impl Default for StackingDistribution {
+    fn default() -> Self {
+        Self::PowerOfTwo { max_stack_pow: 7 }
+    }
+}
+
+impl StackingDistribution {
+    /// Checks the parameters of this distribution, so that it always draws at least one mutation
+    pub fn validate(&self) -> Result<(), Error> {
+        match *self {
+            Self::PowerOfTwo { max_stack_pow }
+                if !(1..u64::BITS.into()).contains(&max_stack_pow) =>
+            {
+                Err(Error::illegal_argument(format!(
+                    "The maximum exponent {max_stack_pow} of the stacked mutations is not in 1..64"
+                )))
+            }
+            Self::Geometric { p, .. } if !(p > 0.0 && p <= 1.0) => Err(Error::illegal_argument(
+                format!("The success probability {p} of the stacked mutations is not in (0, 1]"),
+            )),
+            Self::Geometric { max: 0, .. } => Err(Error::illegal_argument(
+                "The maximum number of stacked mutations is 0",
+            )),
+            Self::Uniform { min, max } if min == 0 || min > max => Err(Error::illegal_argument(
+                format!("The number of stacked mutations {min}..={max} is empty or includes 0"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Draws a number of mutations from this distribution
+    #[allow(clippy::cast_precision_loss)]
+    pub fn sample<R: Rand>(&self, rand: &mut R) -> u64 {
+        match *self {
+            Self::PowerOfTwo { max_stack_pow } => 1 << (1 + rand.below(max_stack_pow)),
+            Self::Geometric { p, max } => {
+                let threshold = p * u64::MAX as f64;
+                let mut count = 1;
+                while count < max && rand.next() as f64 >= threshold {
+                    count += 1;
+                }
+                count
+            }
+            Self::Uniform { min, max } => rand.between(min, max),
+        }
+    }
 }
 
 /// A [`Mutator`] that schedules one of the embedded mutations on each call.
@@ -115,7 +319,12 @@ where
 {
     name: String,
     mutations: MT,
-    max_stack_pow: u64,
+    /// The distribution of the number of stacked mutations
+    stacking: StackingDistribution,
+    /// If a [`MutationRecipe`] is recorded for each mutation
+    record_recipes: bool,
+    /// The recipe of the last mutation
+    recipe: MutationRecipe,
     phantom: PhantomData<(I, S)>,
 }
 
@@ -147,11 +356,37 @@ where
 impl<I, MT, S> Mutator<I, S> for StdScheduledMutator<I, MT, S>
 where
     MT: MutatorsTuple<I, S>,
-    S: HasRand,
+    S: HasRand + HasCorpus,
 {
     #[inline]
     fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
-        self.scheduled_mutate(state, input)
+        if self.record_recipes {
+            self.recorded_mutate(state, input, None)
+        } else {
+            self.scheduled_mutate(state, input)
+        }
+    }
+
+    #[inline]
+    fn mutate_with_intermediates(
+        &mut self,
+        state: &mut S,
+        input: &mut I,
+        on_step: &mut dyn FnMut(&mut S, &I) -> Result<(), Error>,
+    ) -> Result<MutationResult, Error> {
+        if self.record_recipes {
+            self.recorded_mutate(state, input, Some(on_step))
+        } else {
+            self.scheduled_mutate_with_intermediates(state, input, on_step)
+        }
+    }
+
+    fn post_exec(&mut self, state: &mut S, corpus_idx: Option<CorpusId>) -> Result<(), Error> {
+        if let Some(idx) = corpus_idx.filter(|_| self.record_recipes) {
+            let mut testcase = (*state.corpus_mut().get(idx)?).borrow_mut();
+            testcase.add_metadata(self.recipe.clone());
+        }
+        Ok(())
     }
 }
 
@@ -176,11 +411,11 @@ where
 impl<I, MT, S> ScheduledMutator<I, MT, S> for StdScheduledMutator<I, MT, S>
 where
     MT: MutatorsTuple<I, S>,
-    S: HasRand,
+    S: HasRand + HasCorpus,
 {
     /// Compute the number of iterations used to apply stacked mutations
     fn iterations(&self, state: &mut S, _: &I) -> u64 {
-        1 << (1 + state.rand_mut().below(self.max_stack_pow))
+        self.stacking.sample(state.rand_mut())
     }
 
     /// Get the next mutation to apply
@@ -200,7 +435,9 @@ where
         StdScheduledMutator {
             name: format!("StdScheduledMutator[{}]", mutations.names().join(", ")),
             mutations,
-            max_stack_pow: 7,
+            stacking: StackingDistribution::default(),
+            record_recipes: false,
+            recipe: MutationRecipe::default(),
             phantom: PhantomData,
         }
     }
@@ -210,10 +447,79 @@ where
         StdScheduledMutator {
             name: format!("StdScheduledMutator[{}]", mutations.names().join(", ")),
             mutations,
-            max_stack_pow,
+            stacking: StackingDistribution::PowerOfTwo { max_stack_pow },
+            record_recipes: false,
+            recipe: MutationRecipe::default(),
             phantom: PhantomData,
         }
     }
+
+    /// Draw the number of stacked mutations from the given [`StackingDistribution`],
+    /// instead of AFL's powers of two.
+    /// Fails if the parameters of the distribution are invalid, see [`StackingDistribution::validate`].
+    pub fn with_stacking(mut self, stacking: StackingDistribution) -> Result<Self, Error> {
+        stacking.validate()?;
+        self.stacking = stacking;
+        Ok(self)
+    }
+
+    /// The [`StackingDistribution`] of the number of stacked mutations
+    #[must_use]
+    pub fn stacking(&self) -> &StackingDistribution {
+        &self.stacking
+    }
+
+    /// Record a [`MutationRecipe`] for each mutation, see [`StdScheduledMutator::last_recipe`].
+    /// Each new corpus entry gets the recipe that produced it as metadata.
+    ///
+    /// To make each step replayable, the [`Rand`] of the state gets reseeded before each mutation.
+    #[must_use]
+    pub fn with_recipe_recording(mut self, record_recipes: bool) -> Self {
+        self.record_recipes = record_recipes;
+        self
+    }
+
+    /// The [`MutationRecipe`] of the last mutation, if recipes are recorded
+    #[must_use]
+    pub fn last_recipe(&self) -> &MutationRecipe {
+        &self.recipe
+    }
+}
+
+impl<I, MT, S> StdScheduledMutator<I, MT, S>
+where
+    MT: MutatorsTuple<I, S>,
+    S: HasRand + HasCorpus,
+{
+    /// Like [`ScheduledMutator::scheduled_mutate_with_intermediates`], but records each step in the recipe
+    #[allow(clippy::type_complexity)]
+    fn recorded_mutate(
+        &mut self,
+        state: &mut S,
+        input: &mut I,
+        mut on_step: Option<&mut dyn FnMut(&mut S, &I) -> Result<(), Error>>,
+    ) -> Result<MutationResult, Error> {
+        self.recipe.steps.clear();
+        let mut r = MutationResult::Skipped;
+        let num = self.iterations(state, input);
+        for i in 0..num {
+            let mutation_id = self.schedule(state, input);
+            let seed = state.rand_mut().next();
+            state.rand_mut().set_seed(seed);
+            self.recipe.steps.push(MutationStep { mutation_id, seed });
+
+            let outcome = self.mutations.get_and_mutate(mutation_id, state, input)?;
+            if outcome == MutationResult::Mutated {
+                r = MutationResult::Mutated;
+                if let Some(on_step) = on_step.as_mut() {
+                    if i + 1 < num {
+                        on_step(state, input)?;
+                    }
+                }
+            }
+        }
+        Ok(r)
+    }
 }
 
 /// Tuple type of the mutations that compose the Havoc mutator without crossover mutations
@@ -478,10 +784,17 @@ mod tests {
         inputs::{BytesInput, HasBytesVec},
         mutators::{
             mutations::SpliceMutator,
-            scheduled::{havoc_mutations, StdScheduledMutator},
-            Mutator,
+            scheduled::{
+                havoc_mutations, havoc_mutations_no_crossover, replay_recipe, MutationRecipe,
+                ScheduledMutator, StackingDistribution, StdScheduledMutator,
+            },
+            ComposedByMutations, Mutator,
         },
-        state::StdState,
+        state::{
+            test::{test_std_state, TestStdState},
+            HasCorpus, HasRand, StdState,
+        },
+        HasMetadata,
     };
 
     #[test]
@@ -567,4 +880,121 @@ mod tests {
             assert_ne!(equal_in_a_row, 5);
         }
     }
+
+    #[test]
+    fn test_replay_recipe() {
+        let mut state = test_std_state::<BytesInput>();
+
+        let parent = BytesInput::new(b"a fairly boring parent input".to_vec());
+        let mut havoc =
+            StdScheduledMutator::new(havoc_mutations_no_crossover()).with_recipe_recording(true);
+
+        for _ in 0..16 {
+            let mut child = parent.clone();
+            havoc.mutate(&mut state, &mut child).unwrap();
+            let recipe = havoc.last_recipe().clone();
+            assert!(!recipe.steps.is_empty());
+
+            // Mess with the rand, the recipe does not depend on it
+            state.rand_mut().next();
+
+            let mut replayed = parent.clone();
+            replay_recipe(havoc.mutations_mut(), &mut state, &mut replayed, &recipe).unwrap();
+            assert_eq!(replayed, child);
+        }
+    }
+
+    #[test]
+    fn test_recipe_metadata() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MutationRecipe::register();
+        }
+
+        let mut state = test_std_state::<BytesInput>();
+        let parent = BytesInput::new(b"a fairly boring parent input".to_vec());
+        let mut havoc =
+            StdScheduledMutator::new(havoc_mutations_no_crossover()).with_recipe_recording(true);
+
+        let mut child = parent.clone();
+        havoc.mutate(&mut state, &mut child).unwrap();
+        let idx = state
+            .corpus_mut()
+            .add(Testcase::new(child.clone()))
+            .unwrap();
+        havoc.post_exec(&mut state, Some(idx)).unwrap();
+
+        // The recipe read back from the corpus reproduces the child
+        let recipe = state
+            .corpus()
+            .get(idx)
+            .unwrap()
+            .borrow()
+            .metadata::<MutationRecipe>()
+            .unwrap()
+            .clone();
+        assert_eq!(&recipe, havoc.last_recipe());
+        let mut replayed = parent;
+        replay_recipe(havoc.mutations_mut(), &mut state, &mut replayed, &recipe).unwrap();
+        assert_eq!(replayed, child);
+    }
+
+    #[test]
+    fn test_invalid_stacking() {
+        for stacking in [
+            StackingDistribution::PowerOfTwo { max_stack_pow: 0 },
+            StackingDistribution::PowerOfTwo { max_stack_pow: 64 },
+            StackingDistribution::Geometric { p: 0.0, max: 16 },
+            StackingDistribution::Geometric { p: 1.5, max: 16 },
+            StackingDistribution::Geometric {
+                p: f64::NAN,
+                max: 16,
+            },
+            StackingDistribution::Geometric { p: 0.5, max: 0 },
+            StackingDistribution::Uniform { min: 0, max: 4 },
+            StackingDistribution::Uniform { min: 5, max: 4 },
+        ] {
+            assert!(
+                StdScheduledMutator::<BytesInput, _, TestStdState<BytesInput>>::new(
+                    havoc_mutations_no_crossover()
+                )
+                .with_stacking(stacking)
+                .is_err(),
+                "{stacking:?}"
+            );
+        }
+        assert!(StackingDistribution::Uniform { min: 4, max: 4 }
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_geometric_stacking() {
+        let mut state = test_std_state::<BytesInput>();
+
+        let input = BytesInput::new(vec![0; 8]);
+        let havoc = StdScheduledMutator::new(havoc_mutations_no_crossover())
+            .with_stacking(StackingDistribution::Geometric { p: 0.5, max: 16 })
+            .unwrap();
+
+        let mut histogram = [0_u64; 17];
+        for _ in 0..10_000 {
+            let count = havoc.iterations(&mut state, &input);
+            histogram[usize::try_from(count).unwrap()] += 1;
+        }
+
+        // Each count is about half as likely as the one before, with a mean of 2
+        assert_eq!(histogram[0], 0);
+        assert!((4_700..5_300).contains(&histogram[1]), "{histogram:?}");
+        assert!((2_200..2_800).contains(&histogram[2]), "{histogram:?}");
+        assert!((1_000..1_500).contains(&histogram[3]), "{histogram:?}");
+        assert!(
+            histogram[1..8].windows(2).all(|w| w[0] > w[1]),
+            "{histogram:?}"
+        );
+        let total: u64 = histogram.iter().zip(0..).map(|(n, count)| n * count).sum();
+        assert!((19_000..21_000).contains(&total), "{total}");
+    }
 }
@@ -38,6 +38,9 @@ pub struct TuneableScheduledMutatorMetadata {
     /// Will not be used when `mutation_ids` are set.
     /// Clear to fall back to random.
     pub mutation_probabilities_cumulative: Vec<f32>,
+    /// The minimum probability of each mutation, applied to the `mutation_probabilities` when they are set,
+    /// so no single mutation can monopolize the selection.
+    pub min_mutation_probability: f32,
     /// The count of mutations to stack.
     /// If `mutation_ids` is of length `10`, and this number is `20`,
     /// the mutations will be iterated through twice.
@@ -54,6 +57,7 @@ impl Default for TuneableScheduledMutatorMetadata {
             mutation_ids: Vec::default(),
             next_id: 0.into(),
             mutation_probabilities_cumulative: Vec::default(),
+            min_mutation_probability: 0.0,
             iters: None,
             iter_probabilities_pow_cumulative: Vec::default(),
         }
@@ -322,20 +326,73 @@ where
     /// The `Vec` contains a probability per [`MutationId`]: between 0 and 1, and they have to add
     /// up to 1.
     /// Setting the probabilities will remove the value set through `set_mutation_ids`.
+    ///
+    /// The probabilities are scaled to respect the minimum set through `set_min_mutation_probability`.
     pub fn set_mutation_probabilities(
         state: &mut S,
         mut mutation_probabilities: Vec<f32>,
     ) -> Result<(), Error> {
         let metadata = TuneableScheduledMutatorMetadata::get_mut(state).unwrap();
+        #[allow(clippy::cast_precision_loss)]
+        let count = mutation_probabilities.len() as f32;
+        let min = metadata.min_mutation_probability;
+        if min * count > 1.0 {
+            return Err(Error::illegal_argument(format!(
+                "The minimum probability {min} is too high for {count} mutations"
+            )));
+        }
         metadata.mutation_ids.clear();
         metadata.next_id = 0.into();
 
         // we precalculate the cumulative probability to be faster when sampling later.
         calculate_cumulative_distribution_in_place(&mut mutation_probabilities)?;
+        if min > 0.0 {
+            // Each mutation gets the minimum probability, the rest is distributed as requested:
+            // `p' = min + (1 - count * min) * p`, accumulated.
+            #[allow(clippy::cast_precision_loss)]
+            for (idx, cumulative) in mutation_probabilities.iter_mut().enumerate() {
+                *cumulative = min * (idx + 1) as f32 + (1.0 - min * count) * *cumulative;
+            }
+            // Clamp the end again, to account for floating point errors.
+            *mutation_probabilities.last_mut().unwrap() = f32::INFINITY;
+        }
         metadata.mutation_probabilities_cumulative = mutation_probabilities;
         Ok(())
     }
 
+    /// Sets the mutation probabilities from relative weights, see `set_mutation_probabilities`.
+    /// The slice contains a non-negative weight per [`MutationId`].
+    pub fn set_mutation_weights(state: &mut S, mutation_weights: &[f32]) -> Result<(), Error> {
+        let total: f32 = mutation_weights.iter().sum();
+        if total <= 0.0 || mutation_weights.iter().any(|weight| *weight < 0.0) {
+            return Err(Error::illegal_argument(format!(
+                "invalid mutation weights: {mutation_weights:?}"
+            )));
+        }
+        Self::set_mutation_probabilities(
+            state,
+            mutation_weights
+                .iter()
+                .map(|weight| weight / total)
+                .collect(),
+        )
+    }
+
+    /// Sets the minimum probability of each mutation, epsilon-greedy style,
+    /// so a mutation with a high probability cannot monopolize the selection.
+    /// With `n` mutations, the probability of each one is also capped at `1 - (n - 1) * min`.
+    ///
+    /// Only applies to probabilities set afterwards.
+    pub fn set_min_mutation_probability(state: &mut S, min: f32) -> Result<(), Error> {
+        if !(0.0..=1.0).contains(&min) {
+            return Err(Error::illegal_argument(format!(
+                "invalid minimum probability: {min}"
+            )));
+        }
+        Self::metadata_mut(state).min_mutation_probability = min;
+        Ok(())
+    }
+
     /// mutation ids and iterations
     pub fn set_mutation_ids_and_iters(state: &mut S, mutations: Vec<MutationId>, iters: u64) {
         let metadata = TuneableScheduledMutatorMetadata::get_mut(state).unwrap();
@@ -458,4 +515,44 @@ mod test {
         .is_ok());
         assert!(tuneable.schedule(&mut state, &input) != 1.into());
     }
+
+    #[test]
+    fn test_min_mutation_probability() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            TuneableScheduledMutatorMetadata::register();
+        }
+
+        let mut state: NopState<BytesInput> = NopState::new();
+        let mutators = tuple_list!(
+            BitFlipMutator::new(),
+            ByteDecMutator::new(),
+            ByteRandMutator::new()
+        );
+        let tuneable = TuneableScheduledMutator::new(&mut state, mutators);
+        let input = BytesInput::new(vec![42]);
+
+        assert!(TuneableScheduledMutator::set_min_mutation_probability(&mut state, 0.5).is_ok());
+        // 3 mutations can not get 50% each
+        assert!(TuneableScheduledMutator::set_mutation_weights(&mut state, &[1.0; 3]).is_err());
+
+        assert!(TuneableScheduledMutator::set_min_mutation_probability(&mut state, 0.1).is_ok());
+        assert!(
+            TuneableScheduledMutator::set_mutation_weights(&mut state, &[1000.0, 1.0, 1.0])
+                .is_ok()
+        );
+
+        let runs = 10_000;
+        let mut counts = [0_usize; 3];
+        for _ in 0..runs {
+            counts[tuneable.schedule(&mut state, &input).0] += 1;
+        }
+        // 10% each, allowing for some random variation below the exact minimum
+        let expected_min = runs / 10 * 9 / 10;
+        assert!(counts[1] >= expected_min, "{counts:?}");
+        assert!(counts[2] >= expected_min, "{counts:?}");
+        assert!(counts[0] > counts[1] + counts[2], "{counts:?}");
+    }
 }
@@ -0,0 +1,146 @@
+//! The [`Utf8PreservingMutator`] keeps the inputs of text targets valid UTF-8.
+//!
+//! Parsers of JSON, JavaScript, or other text formats often reject invalid UTF-8 before doing any actual parsing.
+//! Byte-level mutations easily produce such inputs, so the wrapped mutations rarely reach the parser.
+
+use alloc::string::String;
+
+use libafl_bolts::Named;
+
+use crate::{
+    corpus::CorpusId,
+    inputs::HasBytesVec,
+    mutators::{MutationResult, Mutator},
+    Error,
+};
+
+/// Repairs the bytes to valid UTF-8 in place, by clearing the high bit of each byte of an invalid sequence.
+///
+/// The repaired bytes keep their length and all valid sequences, so offsets into the buffer stay meaningful.
+/// Returns `true` if any byte had to be changed.
+pub fn repair_utf8(bytes: &mut [u8]) -> bool {
+    let mut repaired = false;
+    let mut start = 0;
+    while let Err(err) = core::str::from_utf8(&bytes[start..]) {
+        let invalid_start = start + err.valid_up_to();
+        // `None` means the input ends in the middle of a sequence
+        let invalid_end = err
+            .error_len()
+            .map_or(bytes.len(), |len| invalid_start + len);
+        for byte in &mut bytes[invalid_start..invalid_end] {
+            *byte &= 0x7f;
+        }
+        repaired = true;
+        start = invalid_end;
+    }
+    repaired
+}
+
+/// A [`Mutator`] running its inner mutator, then repairing the result to valid UTF-8, see [`repair_utf8`].
+///
+/// If the repair undid the whole mutation, it is skipped.
+#[derive(Debug)]
+pub struct Utf8PreservingMutator<M> {
+    name: String,
+    inner: M,
+}
+
+impl<M> Utf8PreservingMutator<M>
+where
+    M: Named,
+{
+    /// Creates a new [`Utf8PreservingMutator`], wrapping the `inner` mutator
+    pub fn new(inner: M) -> Self {
+        Self {
+            name: format!("Utf8PreservingMutator[{}]", inner.name()),
+            inner,
+        }
+    }
+
+    /// The wrapped mutator
+    #[must_use]
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// The wrapped mutator (mutable)
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.inner
+    }
+}
+
+impl<M> Named for Utf8PreservingMutator<M> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<I, M, S> Mutator<I, S> for Utf8PreservingMutator<M>
+where
+    I: HasBytesVec,
+    M: Mutator<I, S>,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let original = input.bytes().to_vec();
+        if self.inner.mutate(state, input)? == MutationResult::Skipped {
+            return Ok(MutationResult::Skipped);
+        }
+        if repair_utf8(input.bytes_mut()) && input.bytes() == original.as_slice() {
+            return Ok(MutationResult::Skipped);
+        }
+        Ok(MutationResult::Mutated)
+    }
+
+    #[inline]
+    fn post_exec(&mut self, state: &mut S, new_corpus_idx: Option<CorpusId>) -> Result<(), Error> {
+        self.inner.post_exec(state, new_corpus_idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::tuples::tuple_list;
+
+    use super::{repair_utf8, Utf8PreservingMutator};
+    use crate::{
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{
+            BitFlipMutator, ByteFlipMutator, ByteRandMutator, BytesRandSetMutator, Mutator,
+            StdScheduledMutator,
+        },
+        state::test::test_std_state,
+    };
+
+    #[test]
+    fn test_repair_utf8() {
+        let mut bytes = "a€b".as_bytes().to_vec();
+        assert!(!repair_utf8(&mut bytes));
+        assert_eq!(bytes, "a€b".as_bytes());
+
+        // A broken sequence in the middle, and a truncated one at the end
+        let mut bytes = b"a\xe2\x28b\xe2\x82".to_vec();
+        assert!(repair_utf8(&mut bytes));
+        assert!(core::str::from_utf8(&bytes).is_ok());
+        assert_eq!(bytes, b"ab(bb\x02");
+    }
+
+    #[test]
+    fn test_utf8_preserving_mutator() {
+        let mut state = test_std_state::<BytesInput>();
+        let mut mutator = Utf8PreservingMutator::new(StdScheduledMutator::with_max_stack_pow(
+            tuple_list!(
+                BitFlipMutator::new(),
+                ByteFlipMutator::new(),
+                ByteRandMutator::new(),
+                BytesRandSetMutator::new()
+            ),
+            6,
+        ));
+
+        let mut input = BytesInput::new(r#"{"key": "välue", "list": [1, 2, 3]}"#.into());
+        for _ in 0..1000 {
+            mutator.mutate(&mut state, &mut input).unwrap();
+            assert!(core::str::from_utf8(input.bytes()).is_ok());
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! The [`MapAggregator`] combines the maps of a [`MapObserver`] over several runs of the same input.
+//!
+//! Non-deterministic targets cover slightly different entries in each run.
+//! Stages running an input several times, such as a calibration, can feed the union of all runs to the feedbacks
+//! instead of whatever the last run happened to cover.
+
+use alloc::vec::Vec;
+
+use crate::{observers::MapObserver, Error};
+
+/// Accumulates the maps of a [`MapObserver`] across runs, and yields their union.
+///
+/// An entry of the union is set if it was set in any run, and holds the largest value seen across all runs.
+#[derive(Debug, Clone, Default)]
+pub struct MapAggregator<T> {
+    /// The union of all maps added so far
+    map: Vec<T>,
+    /// The initial value of the entries
+    initial: Option<T>,
+    /// The number of maps added so far
+    runs: usize,
+}
+
+impl<T> MapAggregator<T>
+where
+    T: PartialOrd + Copy,
+{
+    /// Creates a new, empty [`MapAggregator`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            map: Vec::new(),
+            initial: None,
+            runs: 0,
+        }
+    }
+
+    /// Adds the current map of the `observer` to the union.
+    /// Errors, if the map does not have the same size as the maps added before.
+    pub fn add<M>(&mut self, observer: &M) -> Result<(), Error>
+    where
+        M: MapObserver<Entry = T>,
+    {
+        let len = observer.usable_count();
+        let Some(initial) = self.initial else {
+            self.map = (0..len).map(|idx| *observer.get(idx)).collect();
+            self.initial = Some(observer.initial());
+            self.runs = 1;
+            return Ok(());
+        };
+        if len != self.map.len() {
+            return Err(Error::illegal_argument(format!(
+                "Cannot aggregate a map of size {len} with maps of size {}",
+                self.map.len()
+            )));
+        }
+        for (idx, entry) in self.map.iter_mut().enumerate() {
+            let value = *observer.get(idx);
+            if value != initial && (*entry == initial || value > *entry) {
+                *entry = value;
+            }
+        }
+        self.runs += 1;
+        Ok(())
+    }
+
+    /// The union of all maps added so far
+    #[must_use]
+    pub fn map(&self) -> &[T] {
+        &self.map
+    }
+
+    /// The number of maps added so far
+    #[must_use]
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    /// Writes the union into the `observer`, so that the feedbacks see the coverage of all runs.
+    /// Errors, if the map of the `observer` does not have the size of the aggregated maps.
+    pub fn write_to<M>(&self, observer: &mut M) -> Result<(), Error>
+    where
+        M: MapObserver<Entry = T>,
+    {
+        if observer.usable_count() != self.map.len() {
+            return Err(Error::illegal_argument(format!(
+                "Cannot write the aggregate of size {} to a map of size {}",
+                self.map.len(),
+                observer.usable_count()
+            )));
+        }
+        for (idx, entry) in self.map.iter().enumerate() {
+            *observer.get_mut(idx) = *entry;
+        }
+        Ok(())
+    }
+
+    /// Forgets all maps added so far
+    pub fn reset(&mut self) {
+        self.map.clear();
+        self.initial = None;
+        self.runs = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::AsMutSlice;
+
+    use crate::observers::{MapAggregator, MapObserver, StdMapObserver};
+
+    #[test]
+    fn test_map_aggregator() {
+        let mut observer = StdMapObserver::owned("map", vec![0_u8; 6]);
+        let mut aggregator = MapAggregator::new();
+
+        for map in [[1, 1, 0, 0, 0, 0], [1, 0, 3, 0, 0, 0], [2, 1, 0, 0, 1, 0]] {
+            observer.as_mut_slice().copy_from_slice(&map);
+            aggregator.add(&observer).unwrap();
+        }
+        assert_eq!(aggregator.runs(), 3);
+        assert_eq!(aggregator.map(), [2, 1, 3, 0, 1, 0]);
+
+        observer.reset_map().unwrap();
+        aggregator.write_to(&mut observer).unwrap();
+        assert_eq!(observer.to_vec(), [2, 1, 3, 0, 1, 0]);
+
+        let other = StdMapObserver::owned("other", vec![0_u8; 4]);
+        assert!(aggregator.add(&other).is_err());
+
+        aggregator.reset();
+        assert_eq!(aggregator.runs(), 0);
+        assert!(aggregator.map().is_empty());
+    }
+}
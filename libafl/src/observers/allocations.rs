@@ -0,0 +1,158 @@
+//! The [`AllocationObserver`] counts the heap allocations an in-process harness makes during each run.
+//!
+//! The counts come from the [`CountingAllocator`], which has to be installed as the global allocator of the fuzzer:
+//!
+//! ```rust,ignore
+//! use std::alloc::System;
+//! use libafl::observers::CountingAllocator;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator<System> = CountingAllocator::new(System);
+//! ```
+//!
+//! The counters are thread-local, so allocations of other threads, such as those of a broker, are not counted.
+
+use alloc::string::String;
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    cell::Cell,
+};
+
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{executors::ExitKind, inputs::UsesInput, observers::Observer, Error};
+
+thread_local! {
+    /// The number of allocations of the current thread
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    /// The number of bytes the current thread allocated
+    static ALLOCATED_BYTES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts an allocation of `size` bytes for the current thread
+fn count_allocation(size: usize) {
+    // During thread teardown, the counters may already be gone
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get().wrapping_add(1)));
+    let _ = ALLOCATED_BYTES.try_with(|bytes| bytes.set(bytes.get().wrapping_add(size)));
+}
+
+/// The number of allocations, and allocated bytes, of the current thread so far.
+/// Only allocations through the [`CountingAllocator`] are counted.
+#[must_use]
+pub fn thread_allocations() -> (usize, usize) {
+    (
+        ALLOCATIONS.try_with(Cell::get).unwrap_or(0),
+        ALLOCATED_BYTES.try_with(Cell::get).unwrap_or(0),
+    )
+}
+
+/// A [`GlobalAlloc`] shim that counts the allocations of each thread, before forwarding them to the inner allocator.
+///
+/// A `realloc` is counted as a new allocation of the new size.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CountingAllocator<A> {
+    inner: A,
+}
+
+impl<A> CountingAllocator<A> {
+    /// Creates a new [`CountingAllocator`], forwarding to the `inner` allocator, usually [`std::alloc::System`]
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+unsafe impl<A> GlobalAlloc for CountingAllocator<A>
+where
+    A: GlobalAlloc,
+{
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation(layout.size());
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation(layout.size());
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation(new_size);
+        self.inner.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+    }
+}
+
+/// An observer for the number of heap allocations, and allocated bytes, of each run.
+///
+/// This only works for in-process harnesses running on the fuzzer thread,
+/// with the [`CountingAllocator`] installed as global allocator. Else, it reports `0` allocations.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AllocationObserver {
+    name: String,
+    /// The counters of the thread before the current run
+    #[serde(skip)]
+    start: (usize, usize),
+    /// The number of allocations of the last run
+    allocations: Option<usize>,
+    /// The number of bytes allocated in the last run
+    allocated_bytes: Option<usize>,
+}
+
+impl AllocationObserver {
+    /// Creates a new [`AllocationObserver`] with the given name
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            start: (0, 0),
+            allocations: None,
+            allocated_bytes: None,
+        }
+    }
+
+    /// The number of allocations of the last run, if a run was observed
+    #[must_use]
+    pub fn allocations(&self) -> Option<usize> {
+        self.allocations
+    }
+
+    /// The number of bytes allocated in the last run, if a run was observed
+    #[must_use]
+    pub fn allocated_bytes(&self) -> Option<usize> {
+        self.allocated_bytes
+    }
+}
+
+impl<S> Observer<S> for AllocationObserver
+where
+    S: UsesInput,
+{
+    fn pre_exec(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
+        self.allocations = None;
+        self.allocated_bytes = None;
+        self.start = thread_allocations();
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &S::Input,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        let (allocations, allocated_bytes) = thread_allocations();
+        self.allocations = Some(allocations.wrapping_sub(self.start.0));
+        self.allocated_bytes = Some(allocated_bytes.wrapping_sub(self.start.1));
+        Ok(())
+    }
+}
+
+impl Named for AllocationObserver {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
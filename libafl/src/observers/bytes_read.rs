@@ -0,0 +1,80 @@
+//! The [`BytesReadObserver`] observes how many bytes of the input an in-process harness actually consumed.
+//!
+//! Harnesses opt in by calling [`report_bytes_read`].
+//! Inputs with many unread bytes are larger than needed, and good candidates for trimming,
+//! see the [`crate::feedbacks::BytesReadFeedback`].
+
+use alloc::string::String;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{executors::ExitKind, inputs::UsesInput, observers::Observer, Error};
+
+/// Marks that the harness did not report the bytes it read during the current run
+const NOT_REPORTED: usize = usize::MAX;
+
+/// The bytes read, as last reported by the harness
+static BYTES_READ: AtomicUsize = AtomicUsize::new(NOT_REPORTED);
+
+/// Reports, from within an in-process harness, how many bytes of the current input it consumed.
+/// The value is picked up by the [`BytesReadObserver`] after the run.
+pub fn report_bytes_read(bytes_read: usize) {
+    BYTES_READ.store(bytes_read, Ordering::Relaxed);
+}
+
+/// An observer for the number of input bytes the harness consumed, as reported using [`report_bytes_read`].
+///
+/// This only works for in-process harnesses, and it is shared by all executors in the process.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BytesReadObserver {
+    name: String,
+    /// The bytes read in the last run, if the harness reported them
+    last_bytes_read: Option<usize>,
+}
+
+impl BytesReadObserver {
+    /// Creates a new [`BytesReadObserver`] with the given name
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            last_bytes_read: None,
+        }
+    }
+
+    /// The number of bytes the harness read in the last run, or `None`, if it did not report them
+    #[must_use]
+    pub fn last_bytes_read(&self) -> Option<usize> {
+        self.last_bytes_read
+    }
+}
+
+impl<S> Observer<S> for BytesReadObserver
+where
+    S: UsesInput,
+{
+    fn pre_exec(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
+        BYTES_READ.store(NOT_REPORTED, Ordering::Relaxed);
+        self.last_bytes_read = None;
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &S::Input,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        let bytes_read = BYTES_READ.swap(NOT_REPORTED, Ordering::Relaxed);
+        self.last_bytes_read = (bytes_read != NOT_REPORTED).then_some(bytes_read);
+        Ok(())
+    }
+}
+
+impl Named for BytesReadObserver {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
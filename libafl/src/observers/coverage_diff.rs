@@ -0,0 +1,318 @@
+//! The [`CoverageDiffObserver`] compares the coverage maps of the two executors of a [`crate::executors::DiffExecutor`].
+//!
+//! Running the same corpus through two builds of a target, for example before and after a code change,
+//! it lists the map entries covered by one build but not the other, to find coverage regressions.
+
+use alloc::{
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::marker::PhantomData;
+
+use libafl_bolts::{tuples::MatchName, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::Corpus,
+    executors::{Executor, HasObservers},
+    inputs::UsesInput,
+    observers::{DifferentialObserver, MapObserver, Observer, ObserversTuple},
+    state::{HasCorpus, UsesState},
+    Error,
+};
+
+/// The map entries covered by only one of two executors
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageDiff {
+    /// The map indexes covered by the first executor, but not by the second
+    pub only_first: BTreeSet<usize>,
+    /// The map indexes covered by the second executor, but not by the first
+    pub only_second: BTreeSet<usize>,
+}
+
+impl CoverageDiff {
+    /// Creates a new, empty [`CoverageDiff`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// If both executors covered the same map entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.only_first.is_empty() && self.only_second.is_empty()
+    }
+
+    /// Adds the entries of another [`CoverageDiff`] to this one
+    pub fn extend(&mut self, other: &CoverageDiff) {
+        self.only_first.extend(&other.only_first);
+        self.only_second.extend(&other.only_second);
+    }
+}
+
+/// A [`DifferentialObserver`] comparing a [`MapObserver`] of the first executor with one of the second executor.
+///
+/// After each run, it records the map entries covered by only one of the executors,
+/// both for the last input, and accumulated over all inputs since the last [`CoverageDiffObserver::reset`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CoverageDiffObserver<O> {
+    name: String,
+    first_name: String,
+    second_name: String,
+    #[serde(skip)]
+    first_covered: BTreeSet<usize>,
+    last: CoverageDiff,
+    total: CoverageDiff,
+    phantom: PhantomData<O>,
+}
+
+impl<O> CoverageDiffObserver<O>
+where
+    O: MapObserver,
+{
+    /// Creates a new [`CoverageDiffObserver`], comparing the map observer named `first_name`
+    /// of the first executor with the one named `second_name` of the second executor
+    #[must_use]
+    pub fn new(name: &str, first_name: &str, second_name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            first_name: first_name.to_string(),
+            second_name: second_name.to_string(),
+            first_covered: BTreeSet::new(),
+            last: CoverageDiff::new(),
+            total: CoverageDiff::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// The map entries covered by only one of the executors for the last input
+    #[must_use]
+    pub fn last(&self) -> &CoverageDiff {
+        &self.last
+    }
+
+    /// The map entries covered by only one of the executors, over all inputs since the last reset
+    #[must_use]
+    pub fn total(&self) -> &CoverageDiff {
+        &self.total
+    }
+
+    /// Forgets all recorded differences
+    pub fn reset(&mut self) {
+        self.first_covered.clear();
+        self.last = CoverageDiff::new();
+        self.total = CoverageDiff::new();
+    }
+
+    /// The indexes of the map entries the named map observer covered in the last run
+    fn covered<OT>(observers: &OT, name: &str) -> Result<BTreeSet<usize>, Error>
+    where
+        OT: MatchName,
+    {
+        let map = observers
+            .match_name::<O>(name)
+            .ok_or_else(|| Error::key_not_found(format!("MapObserver {name} not found")))?;
+        let initial = map.initial();
+        Ok((0..map.usable_count())
+            .filter(|idx| *map.get(*idx) != initial)
+            .collect())
+    }
+}
+
+impl<O, S> Observer<S> for CoverageDiffObserver<O> where S: UsesInput {}
+
+impl<O> Named for CoverageDiffObserver<O> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<O, OTA, OTB, S> DifferentialObserver<OTA, OTB, S> for CoverageDiffObserver<O>
+where
+    O: MapObserver,
+    OTA: ObserversTuple<S>,
+    OTB: ObserversTuple<S>,
+    S: UsesInput,
+{
+    fn post_observe_first(&mut self, observers: &mut OTA) -> Result<(), Error> {
+        self.first_covered = Self::covered(observers, &self.first_name)?;
+        Ok(())
+    }
+
+    fn post_observe_second(&mut self, observers: &mut OTB) -> Result<(), Error> {
+        let second_covered = Self::covered(observers, &self.second_name)?;
+        self.last = CoverageDiff {
+            only_first: self
+                .first_covered
+                .difference(&second_covered)
+                .copied()
+                .collect(),
+            only_second: second_covered
+                .difference(&self.first_covered)
+                .copied()
+                .collect(),
+        };
+        self.total.extend(&self.last);
+        Ok(())
+    }
+}
+
+/// Runs each input of the corpus through a [`crate::executors::DiffExecutor`], and returns the map entries
+/// covered by only one of its executors, as recorded by the [`CoverageDiffObserver`] named `observer_name`.
+///
+/// The entries are accumulated over the whole corpus: an entry is listed, if one executor covered it for some input,
+/// while the other executor did not cover it for the same input.
+pub fn corpus_coverage_diff<E, EM, O, Z>(
+    fuzzer: &mut Z,
+    executor: &mut E,
+    state: &mut E::State,
+    mgr: &mut EM,
+    observer_name: &str,
+) -> Result<CoverageDiff, Error>
+where
+    E: Executor<EM, Z> + HasObservers,
+    E::State: HasCorpus,
+    EM: UsesState<State = E::State>,
+    O: MapObserver,
+    Z: UsesState<State = E::State>,
+{
+    executor
+        .observers_mut()
+        .match_name_mut::<CoverageDiffObserver<O>>(observer_name)
+        .ok_or_else(|| {
+            Error::key_not_found(format!("CoverageDiffObserver {observer_name} not found"))
+        })?
+        .reset();
+
+    let ids: Vec<_> = state.corpus().ids().collect();
+    for id in ids {
+        let input = state.corpus().cloned_input_for_id(id)?;
+        executor.observers_mut().pre_exec_all(state, &input)?;
+        let exit_kind = executor.run_target(fuzzer, state, mgr, &input)?;
+        executor
+            .observers_mut()
+            .post_exec_all(state, &input, &exit_kind)?;
+    }
+
+    Ok(executor
+        .observers()
+        .match_name::<CoverageDiffObserver<O>>(observer_name)
+        .ok_or_else(|| {
+            Error::key_not_found(format!("CoverageDiffObserver {observer_name} not found"))
+        })?
+        .total()
+        .clone())
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use alloc::vec::Vec;
+    use core::ptr::addr_of_mut;
+
+    use libafl_bolts::{ownedref::OwnedMutSlice, tuples::tuple_list};
+
+    use super::{corpus_coverage_diff, CoverageDiffObserver};
+    use crate::{
+        corpus::{Corpus, Testcase},
+        executors::{DiffExecutor, ExitKind, InProcessExecutor},
+        feedbacks::ConstFeedback,
+        fuzzer::test::test_fuzzer,
+        inputs::{BytesInput, HasBytesVec},
+        observers::StdMapObserver,
+        schedulers::QueueScheduler,
+        state::HasCorpus,
+    };
+
+    static mut FIRST_MAP: [u8; 4] = [0; 4];
+    static mut SECOND_MAP: [u8; 4] = [0; 4];
+
+    #[test]
+    fn test_corpus_coverage_diff() {
+        // Both builds agree, except for the input `B`
+        let mut first_harness = |input: &BytesInput| {
+            unsafe {
+                let map = &mut *addr_of_mut!(FIRST_MAP);
+                map[0] = 1;
+                if input.bytes() == b"B" {
+                    map[1] = 1;
+                }
+                if input.bytes() == b"C" {
+                    map[3] = 1;
+                }
+            }
+            ExitKind::Ok
+        };
+        let mut second_harness = |input: &BytesInput| {
+            unsafe {
+                let map = &mut *addr_of_mut!(SECOND_MAP);
+                map[0] = 1;
+                if input.bytes() == b"B" {
+                    map[2] = 1;
+                }
+                if input.bytes() == b"C" {
+                    map[3] = 1;
+                }
+            }
+            ExitKind::Ok
+        };
+
+        let first_observer = unsafe {
+            StdMapObserver::from_ownedref(
+                "first",
+                OwnedMutSlice::from_raw_parts_mut(addr_of_mut!(FIRST_MAP) as *mut u8, 4),
+            )
+        };
+        let second_observer = unsafe {
+            StdMapObserver::from_ownedref(
+                "second",
+                OwnedMutSlice::from_raw_parts_mut(addr_of_mut!(SECOND_MAP) as *mut u8, 4),
+            )
+        };
+
+        let (mut fuzzer, first_executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            (),
+            &mut first_harness,
+            tuple_list!(first_observer),
+        );
+        for input in [b"A", b"B", b"C"] {
+            state
+                .corpus_mut()
+                .add(Testcase::new(BytesInput::new(input.to_vec())))
+                .unwrap();
+        }
+        let second_executor = InProcessExecutor::new(
+            &mut second_harness,
+            tuple_list!(second_observer),
+            &mut fuzzer,
+            &mut state,
+            &mut mgr,
+        )
+        .unwrap();
+        let mut executor = DiffExecutor::new(
+            first_executor,
+            second_executor,
+            tuple_list!(CoverageDiffObserver::<StdMapObserver<u8, false>>::new(
+                "coverage_diff",
+                "first",
+                "second"
+            )),
+        );
+
+        let diff = corpus_coverage_diff::<_, _, StdMapObserver<u8, false>, _>(
+            &mut fuzzer,
+            &mut executor,
+            &mut state,
+            &mut mgr,
+            "coverage_diff",
+        )
+        .unwrap();
+        assert_eq!(diff.only_first.into_iter().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(diff.only_second.into_iter().collect::<Vec<_>>(), vec![2]);
+    }
+}
@@ -0,0 +1,93 @@
+//! The [`FdCountObserver`] counts the open file descriptors of the fuzzer process before and after each run,
+//! to find inputs that make an in-process harness leak file descriptors.
+//! Use it together with the [`crate::feedbacks::FdLeakFeedback`].
+
+use alloc::string::String;
+use std::fs;
+
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{executors::ExitKind, inputs::UsesInput, observers::Observer, Error};
+
+/// The directory listing the open file descriptors of the current process
+const PROC_SELF_FD: &str = "/proc/self/fd";
+
+/// Counts the open file descriptors of the current process, as listed in `/proc/self/fd`
+pub fn count_open_fds() -> Result<usize, Error> {
+    Ok(fs::read_dir(PROC_SELF_FD)?.count())
+}
+
+/// An observer that samples the number of open file descriptors of the current process before and after each run.
+///
+/// This only works for in-process harnesses, since it reads `/proc/self/fd`.
+/// Other threads opening or closing files at the same time will distort the counts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FdCountObserver {
+    name: String,
+    /// The number of open file descriptors before the last run
+    before: Option<usize>,
+    /// The number of open file descriptors after the last run
+    after: Option<usize>,
+}
+
+impl FdCountObserver {
+    /// Creates a new [`FdCountObserver`] with the given name
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            before: None,
+            after: None,
+        }
+    }
+
+    /// The number of open file descriptors before the last run
+    #[must_use]
+    pub fn before(&self) -> Option<usize> {
+        self.before
+    }
+
+    /// The number of open file descriptors after the last run
+    #[must_use]
+    pub fn after(&self) -> Option<usize> {
+        self.after
+    }
+
+    /// The number of file descriptors the last run opened, but did not close.
+    /// Returns `0`, if the run closed more file descriptors than it opened, or if no run was observed.
+    #[must_use]
+    pub fn leaked(&self) -> usize {
+        match (self.before, self.after) {
+            (Some(before), Some(after)) => after.saturating_sub(before),
+            _ => 0,
+        }
+    }
+}
+
+impl<S> Observer<S> for FdCountObserver
+where
+    S: UsesInput,
+{
+    fn pre_exec(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
+        self.before = Some(count_open_fds()?);
+        self.after = None;
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &S::Input,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.after = Some(count_open_fds()?);
+        Ok(())
+    }
+}
+
+impl Named for FdCountObserver {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
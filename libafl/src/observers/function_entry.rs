@@ -0,0 +1,110 @@
+//! The [`FunctionEntryObserver`] observes a coverage map with one entry per function of the target,
+//! set when the function is entered, and knows the names of these functions.
+//!
+//! See the [`crate::feedbacks::NewFunctionFeedback`], reporting each function the first time it is reached.
+
+use alloc::{string::String, vec::Vec};
+
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    executors::ExitKind,
+    inputs::UsesInput,
+    observers::{MapObserver, Observer},
+    Error,
+};
+
+/// A function-entry coverage map, with the symbol name of the function behind each map index
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(bound = "M: serde::de::DeserializeOwned")]
+pub struct FunctionEntryObserver<M>
+where
+    M: Serialize,
+{
+    base: M,
+    /// The symbol name for each map index
+    symbols: Vec<String>,
+}
+
+impl<M> FunctionEntryObserver<M>
+where
+    M: MapObserver,
+{
+    /// Creates a new [`FunctionEntryObserver`], wrapping the function-entry map `base`.
+    /// The entry at index `i` belongs to the function `symbols[i]`.
+    pub fn new(base: M, symbols: Vec<String>) -> Self {
+        Self { base, symbols }
+    }
+
+    /// The wrapped function-entry map
+    #[must_use]
+    pub fn base(&self) -> &M {
+        &self.base
+    }
+
+    /// The wrapped function-entry map (mutable)
+    pub fn base_mut(&mut self) -> &mut M {
+        &mut self.base
+    }
+
+    /// The symbol name of the function at the given map index, if known
+    #[must_use]
+    pub fn symbol(&self, idx: usize) -> Option<&str> {
+        self.symbols.get(idx).map(String::as_str)
+    }
+
+    /// The map indexes of all functions entered in the last run
+    #[must_use]
+    pub fn entered(&self) -> Vec<usize> {
+        let initial = self.base.initial();
+        (0..self.base.usable_count())
+            .filter(|idx| *self.base.get(*idx) != initial)
+            .collect()
+    }
+}
+
+impl<M> Named for FunctionEntryObserver<M>
+where
+    M: Named + Serialize,
+{
+    fn name(&self) -> &str {
+        self.base.name()
+    }
+}
+
+impl<M, S> Observer<S> for FunctionEntryObserver<M>
+where
+    M: Observer<S> + Serialize,
+    S: UsesInput,
+{
+    #[inline]
+    fn pre_exec(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
+        self.base.pre_exec(state, input)
+    }
+
+    #[inline]
+    fn post_exec(
+        &mut self,
+        state: &mut S,
+        input: &S::Input,
+        exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.base.post_exec(state, input, exit_kind)
+    }
+
+    #[inline]
+    fn pre_exec_child(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
+        self.base.pre_exec_child(state, input)
+    }
+
+    #[inline]
+    fn post_exec_child(
+        &mut self,
+        state: &mut S,
+        input: &S::Input,
+        exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.base.post_exec_child(state, input, exit_kind)
+    }
+}
@@ -16,7 +16,7 @@ use core::{
 use ahash::RandomState;
 use libafl_bolts::{
     ownedref::{OwnedMutPtr, OwnedMutSlice},
-    AsIter, AsIterMut, AsMutSlice, AsSlice, HasLen, Named, Truncate,
+    AsIter, AsIterMut, AsMutSlice, AsSlice, HasLen, Named, StableHasher, Truncate,
 };
 use meminterval::IntervalTree;
 use num_traits::Bounded;
@@ -431,6 +431,20 @@ pub trait MapObserver:
     /// Compute the hash of the map without needing to provide a hasher
     fn hash_simple(&self) -> u64;
 
+    /// Compute a hash of the usable part of the map that is reproducible across runs and platforms,
+    /// using a [`StableHasher`]. Useful to bucket findings by their execution signature.
+    ///
+    /// Slower than [`MapObserver::hash_simple`], which may differ between hosts.
+    fn hash_stable(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        let cnt = self.usable_count();
+        hasher.write_usize(cnt);
+        for i in 0..cnt {
+            self.get(i).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
     /// Get the initial value for `reset()`
     fn initial(&self) -> Self::Entry;
 
@@ -1831,11 +1845,12 @@ where
             len -= 1;
         }
 
-        // Fix the last element
+        // Fix the last element, behind the (aligned) part handled as u16
         if (len & 1) != 0 {
+            let last = map.len() - 1;
             unsafe {
-                *map.get_unchecked_mut(len - 1) =
-                    *COUNT_CLASS_LOOKUP.get_unchecked(*map.get_unchecked(len - 1) as usize);
+                *map.get_unchecked_mut(last) =
+                    *COUNT_CLASS_LOOKUP.get_unchecked(*map.get_unchecked(last) as usize);
             }
         }
 
@@ -2085,6 +2100,196 @@ where
     }
 }
 
+/// Saturates each raw hit count of the `map` at `255`, like the classic 8-bit counters of AFL,
+/// and classifies it into its AFL hitcount bucket, in place, with a single table lookup per entry.
+///
+/// The buckets are `0`, `1`, `2`, `3`, `4-7`, `8-15`, `16-31`, `32-127`, and `128+`,
+/// represented by `0`, `1`, `2`, `4`, `8`, `16`, `32`, `64`, and `128`.
+#[inline]
+pub fn classify_counts_saturating<T>(map: &mut [T])
+where
+    T: Copy + From<u8> + Into<u64>,
+{
+    for item in map {
+        let count: u64 = (*item).into();
+        // `min` keeps the index in the bounds of the table
+        #[allow(clippy::cast_possible_truncation)]
+        let idx = count.min(255) as usize;
+        *item = T::from(COUNT_CLASS_LOOKUP[idx]);
+    }
+}
+
+/// Map observer with AFL-like hitcounts postprocessing, for maps with entries wider than `u8`.
+///
+/// Instead of wrapping around, like 8-bit counters would, the counts saturate at `255`,
+/// before they get classified into buckets, see [`classify_counts_saturating`].
+/// Feedbacks then compare the bucketed maps.
+/// For `u8` maps, the [`HitcountsMapObserver`] is faster.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash)]
+#[serde(bound = "M: serde::de::DeserializeOwned")]
+pub struct SaturatingHitcountsMapObserver<M>
+where
+    M: Serialize,
+{
+    base: M,
+}
+
+impl<S, M> Observer<S> for SaturatingHitcountsMapObserver<M>
+where
+    M: MapObserver + Observer<S> + AsMutSlice<Entry = <M as MapObserver>::Entry>,
+    <M as MapObserver>::Entry: From<u8> + Into<u64>,
+    S: UsesInput,
+{
+    #[inline]
+    fn pre_exec(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
+        self.base.pre_exec(state, input)
+    }
+
+    #[inline]
+    fn post_exec(
+        &mut self,
+        state: &mut S,
+        input: &S::Input,
+        exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        classify_counts_saturating(self.base.as_mut_slice());
+        self.base.post_exec(state, input, exit_kind)
+    }
+}
+
+impl<M> Named for SaturatingHitcountsMapObserver<M>
+where
+    M: Named + Serialize + serde::de::DeserializeOwned,
+{
+    #[inline]
+    fn name(&self) -> &str {
+        self.base.name()
+    }
+}
+
+impl<M> HasLen for SaturatingHitcountsMapObserver<M>
+where
+    M: MapObserver,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.base.len()
+    }
+}
+
+impl<M> AsRef<Self> for SaturatingHitcountsMapObserver<M>
+where
+    M: MapObserver,
+{
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<M> AsMut<Self> for SaturatingHitcountsMapObserver<M>
+where
+    M: MapObserver,
+{
+    fn as_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
+impl<M> MapObserver for SaturatingHitcountsMapObserver<M>
+where
+    M: MapObserver,
+{
+    type Entry = M::Entry;
+
+    #[inline]
+    fn initial(&self) -> M::Entry {
+        self.base.initial()
+    }
+
+    #[inline]
+    fn usable_count(&self) -> usize {
+        self.base.usable_count()
+    }
+
+    #[inline]
+    fn get(&self, idx: usize) -> &M::Entry {
+        self.base.get(idx)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, idx: usize) -> &mut M::Entry {
+        self.base.get_mut(idx)
+    }
+
+    /// Count the set bytes in the map
+    fn count_bytes(&self) -> u64 {
+        self.base.count_bytes()
+    }
+
+    /// Reset the map
+    #[inline]
+    fn reset_map(&mut self) -> Result<(), Error> {
+        self.base.reset_map()
+    }
+
+    #[inline]
+    fn hash_simple(&self) -> u64 {
+        self.base.hash_simple()
+    }
+
+    fn to_vec(&self) -> Vec<M::Entry> {
+        self.base.to_vec()
+    }
+
+    fn how_many_set(&self, indexes: &[usize]) -> usize {
+        self.base.how_many_set(indexes)
+    }
+}
+
+impl<M> AsSlice for SaturatingHitcountsMapObserver<M>
+where
+    M: MapObserver + AsSlice,
+{
+    type Entry = <M as AsSlice>::Entry;
+    #[inline]
+    fn as_slice(&self) -> &[Self::Entry] {
+        self.base.as_slice()
+    }
+}
+
+impl<M> AsMutSlice for SaturatingHitcountsMapObserver<M>
+where
+    M: MapObserver + AsMutSlice,
+{
+    type Entry = <M as AsMutSlice>::Entry;
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [Self::Entry] {
+        self.base.as_mut_slice()
+    }
+}
+
+impl<'it, M> AsIter<'it> for SaturatingHitcountsMapObserver<M>
+where
+    M: Named + Serialize + serde::de::DeserializeOwned + AsIter<'it>,
+{
+    type Item = <M as AsIter<'it>>::Item;
+    type IntoIter = <M as AsIter<'it>>::IntoIter;
+
+    fn as_iter(&'it self) -> Self::IntoIter {
+        self.base.as_iter()
+    }
+}
+
+impl<M> SaturatingHitcountsMapObserver<M>
+where
+    M: MapObserver,
+{
+    /// Creates a new [`SaturatingHitcountsMapObserver`]
+    pub fn new(base: M) -> Self {
+        Self { base }
+    }
+}
+
 /// Map observer with hitcounts postprocessing
 /// Less optimized version for non-slice iterators.
 /// Slice-backed observers should use a [`HitcountsMapObserver`].
@@ -2943,3 +3148,221 @@ where
         }
     }
 }
+
+/// A view on a window `[start, start + len)` of another slice-backed [`MapObserver`].
+///
+/// Feedbacks using this observer only consider the entries inside the window,
+/// for example, to only score the edges of a specific module in a global coverage map.
+/// Indices reported by this observer are relative to `start`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(bound(
+    serialize = "M: Serialize",
+    deserialize = "M: serde::de::DeserializeOwned"
+))]
+pub struct WindowedMapObserver<M> {
+    base: M,
+    start: usize,
+    len: usize,
+}
+
+impl<M> WindowedMapObserver<M>
+where
+    M: MapObserver + AsSlice<Entry = <M as MapObserver>::Entry>,
+{
+    /// Creates a new [`WindowedMapObserver`], looking at `len` entries of `base`, starting at `start`.
+    /// The window is clamped to the size of the `base` map.
+    pub fn new(base: M, start: usize, len: usize) -> Self {
+        let start = start.min(base.as_slice().len());
+        let len = len.min(base.as_slice().len() - start);
+        Self { base, start, len }
+    }
+
+    /// The first entry of the window, in the `base` map
+    #[must_use]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The wrapped observer
+    pub fn base(&self) -> &M {
+        &self.base
+    }
+
+    /// The wrapped observer (mutable)
+    pub fn base_mut(&mut self) -> &mut M {
+        &mut self.base
+    }
+}
+
+impl<S, M> Observer<S> for WindowedMapObserver<M>
+where
+    M: Observer<S>,
+    S: UsesInput,
+{
+    #[inline]
+    fn pre_exec(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
+        self.base.pre_exec(state, input)
+    }
+
+    #[inline]
+    fn post_exec(
+        &mut self,
+        state: &mut S,
+        input: &S::Input,
+        exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.base.post_exec(state, input, exit_kind)
+    }
+}
+
+impl<M> Named for WindowedMapObserver<M>
+where
+    M: Named,
+{
+    #[inline]
+    fn name(&self) -> &str {
+        self.base.name()
+    }
+}
+
+impl<M> HasLen for WindowedMapObserver<M> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<M> AsRef<Self> for WindowedMapObserver<M> {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<M> AsMut<Self> for WindowedMapObserver<M> {
+    fn as_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
+impl<M> AsSlice for WindowedMapObserver<M>
+where
+    M: AsSlice,
+{
+    type Entry = <M as AsSlice>::Entry;
+    #[inline]
+    fn as_slice(&self) -> &[Self::Entry] {
+        &self.base.as_slice()[self.start..self.start + self.len]
+    }
+}
+
+impl<M> AsMutSlice for WindowedMapObserver<M>
+where
+    M: AsMutSlice,
+{
+    type Entry = <M as AsMutSlice>::Entry;
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [Self::Entry] {
+        &mut self.base.as_mut_slice()[self.start..self.start + self.len]
+    }
+}
+
+impl<M> Hash for WindowedMapObserver<M>
+where
+    M: AsSlice,
+    <M as AsSlice>::Entry: Hash,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        self.as_slice().hash(hasher);
+    }
+}
+
+impl<'it, M> AsIter<'it> for WindowedMapObserver<M>
+where
+    M: AsSlice,
+    <M as AsSlice>::Entry: 'it,
+{
+    type Item = <M as AsSlice>::Entry;
+    type IntoIter = Iter<'it, <M as AsSlice>::Entry>;
+
+    fn as_iter(&'it self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+impl<M> MapObserver for WindowedMapObserver<M>
+where
+    M: MapObserver
+        + AsSlice<Entry = <M as MapObserver>::Entry>
+        + AsMutSlice<Entry = <M as MapObserver>::Entry>,
+{
+    type Entry = <M as MapObserver>::Entry;
+
+    #[inline]
+    fn get(&self, idx: usize) -> &Self::Entry {
+        &self.as_slice()[idx]
+    }
+
+    #[inline]
+    fn get_mut(&mut self, idx: usize) -> &mut Self::Entry {
+        &mut self.as_mut_slice()[idx]
+    }
+
+    /// Count the set bytes in the window
+    fn count_bytes(&self) -> u64 {
+        let initial = self.initial();
+        let cnt = self.usable_count();
+        let mut res = 0;
+        for x in &self.as_slice()[0..cnt] {
+            if *x != initial {
+                res += 1;
+            }
+        }
+        res
+    }
+
+    #[inline]
+    fn usable_count(&self) -> usize {
+        self.base
+            .usable_count()
+            .saturating_sub(self.start)
+            .min(self.len)
+    }
+
+    #[inline]
+    fn hash_simple(&self) -> u64 {
+        RandomState::with_seeds(0, 0, 0, 0).hash_one(self)
+    }
+
+    #[inline]
+    fn initial(&self) -> Self::Entry {
+        self.base.initial()
+    }
+
+    /// Reset the window, the rest of the map is left untouched
+    #[inline]
+    fn reset_map(&mut self) -> Result<(), Error> {
+        let initial = self.initial();
+        for x in self.as_mut_slice() {
+            *x = initial;
+        }
+        Ok(())
+    }
+
+    fn to_vec(&self) -> Vec<Self::Entry> {
+        self.as_slice().to_vec()
+    }
+
+    fn how_many_set(&self, indexes: &[usize]) -> usize {
+        let initial = self.initial();
+        let cnt = self.usable_count();
+        let map = self.as_slice();
+        let mut res = 0;
+        for i in indexes {
+            if *i < cnt && map[*i] != initial {
+                res += 1;
+            }
+        }
+        res
+    }
+}
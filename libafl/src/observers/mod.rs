@@ -6,11 +6,39 @@ pub use map::*;
 pub mod cmp;
 pub use cmp::*;
 
+pub mod bytes_read;
+pub use bytes_read::{report_bytes_read, BytesReadObserver};
+
+pub mod function_entry;
+pub use function_entry::FunctionEntryObserver;
+
+pub mod msan;
+pub use msan::MsanReportObserver;
+
+pub mod stack_depth;
+pub use stack_depth::{report_stack_depth, StackDepthObserver};
+
+pub mod coverage_diff;
+pub use coverage_diff::{corpus_coverage_diff, CoverageDiff, CoverageDiffObserver};
+
+pub mod aggregate;
+pub use aggregate::MapAggregator;
+
+#[cfg(feature = "std")]
+pub mod allocations;
+#[cfg(feature = "std")]
+pub use allocations::{thread_allocations, AllocationObserver, CountingAllocator};
+
 #[cfg(feature = "std")]
 pub mod stdio;
 #[cfg(feature = "std")]
 pub use stdio::{StdErrObserver, StdOutObserver};
 
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod fd;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use fd::FdCountObserver;
+
 #[cfg(feature = "regex")]
 pub mod stacktrace;
 #[cfg(feature = "regex")]
@@ -530,6 +558,7 @@ where
 #[cfg(test)]
 mod tests {
 
+    use alloc::vec::Vec;
     use core::ptr::addr_of_mut;
 
     use libafl_bolts::{
@@ -538,7 +567,15 @@ mod tests {
         Named,
     };
 
-    use crate::observers::{StdMapObserver, TimeObserver};
+    use crate::{
+        executors::ExitKind,
+        inputs::BytesInput,
+        observers::{
+            classify_counts_saturating, HitcountsMapObserver, MapObserver, Observer,
+            SaturatingHitcountsMapObserver, StdMapObserver, TimeObserver,
+        },
+        state::NopState,
+    };
 
     static mut MAP: [u32; 4] = [0; 4];
 
@@ -556,4 +593,87 @@ mod tests {
             postcard::from_bytes(&vec).unwrap();
         assert_eq!(obv.0.name(), obv2.0.name());
     }
+
+    #[test]
+    fn test_map_hash_stable() {
+        let mut first = [0_u8, 1, 2, 0, 0, 0, 0, 3];
+        let mut second = first;
+        let first_obs =
+            unsafe { StdMapObserver::from_mut_ptr("first", first.as_mut_ptr(), first.len()) };
+        let mut second_obs =
+            unsafe { StdMapObserver::from_mut_ptr("second", second.as_mut_ptr(), second.len()) };
+
+        // Identical maps hash the same, regardless of the observer
+        assert_eq!(first_obs.hash_stable(), second_obs.hash_stable());
+        // ... and on every host
+        assert_eq!(first_obs.hash_stable(), 0x8191_8d09_cbf0_cc39);
+
+        *second_obs.get_mut(3) ^= 1;
+        assert_ne!(first_obs.hash_stable(), second_obs.hash_stable());
+    }
+
+    #[test]
+    fn test_saturating_hitcounts() {
+        // AFL's classification: 0, 1, 2, 3, 4-7, 8-15, 16-31, 32-127, 128+
+        let classify = |count: u32| match count {
+            0 => 0,
+            1 => 1,
+            2 => 2,
+            3 => 4,
+            4..=7 => 8,
+            8..=15 => 16,
+            16..=31 => 32,
+            32..=127 => 64,
+            _ => 128,
+        };
+
+        let mut raw: Vec<u32> = (0..300).chain([1 << 16, u32::MAX]).collect();
+        let mut map = raw.clone();
+        classify_counts_saturating(&mut map);
+        for (count, bucket) in raw.iter().zip(&map) {
+            assert_eq!(*bucket, classify(*count), "count {count}");
+        }
+
+        // The observer bucketizes in place after each run
+        let mut observer = unsafe {
+            SaturatingHitcountsMapObserver::new(StdMapObserver::from_mut_ptr(
+                "raw",
+                raw.as_mut_ptr(),
+                raw.len(),
+            ))
+        };
+        let mut state = NopState::<BytesInput>::new();
+        let input = BytesInput::new(vec![]);
+        observer
+            .post_exec(&mut state, &input, &ExitKind::Ok)
+            .unwrap();
+        assert_eq!(observer.to_vec(), map);
+        assert_eq!(*observer.get(256), 128);
+    }
+
+    #[test]
+    fn test_hitcounts_unaligned() {
+        let mut state = NopState::<BytesInput>::new();
+        let input = BytesInput::new(vec![]);
+
+        // Maps that do not start at an u16 boundary, or have an odd length, are classified byte by byte at the edges
+        for offset in 0..2 {
+            for len in 1..8 {
+                // Three hits go into the bucket of 4, classifying them twice would give 8
+                let mut buf = [u16::from_ne_bytes([3, 3]); 5];
+                let map = unsafe { (buf.as_mut_ptr() as *mut u8).add(offset) };
+                let mut observer = unsafe {
+                    HitcountsMapObserver::new(StdMapObserver::from_mut_ptr("map", map, len))
+                };
+                observer
+                    .post_exec(&mut state, &input, &ExitKind::Ok)
+                    .unwrap();
+                assert_eq!(
+                    observer.to_vec(),
+                    vec![4; len],
+                    "offset {offset}, len {len}"
+                );
+            }
+        }
+    }
 }
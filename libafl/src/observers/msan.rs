@@ -0,0 +1,104 @@
+//! The [`MsanReportObserver`] observes the reports of uninitialized reads of an MSAN-instrumented target.
+//!
+//! Instead of aborting, the target reports each uninitialized read through a callback,
+//! which bumps the entry of the report site, i.e., the hash of its location, in a map.
+//!
+//! See the [`crate::feedbacks::NewMsanSiteFeedback`], treating each new report site as a solution.
+
+use alloc::vec::Vec;
+
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    executors::ExitKind,
+    inputs::UsesInput,
+    observers::{MapObserver, Observer},
+    Error,
+};
+
+/// A map of MSAN report sites, counting the uninitialized reads reported at each site in the last run
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(bound = "M: serde::de::DeserializeOwned")]
+pub struct MsanReportObserver<M>
+where
+    M: Serialize,
+{
+    base: M,
+}
+
+impl<M> MsanReportObserver<M>
+where
+    M: MapObserver,
+{
+    /// Creates a new [`MsanReportObserver`], wrapping the report map `base`.
+    /// The entry of a report site is at the index its site hash maps to.
+    pub fn new(base: M) -> Self {
+        Self { base }
+    }
+
+    /// The wrapped report map
+    #[must_use]
+    pub fn base(&self) -> &M {
+        &self.base
+    }
+
+    /// The wrapped report map (mutable)
+    pub fn base_mut(&mut self) -> &mut M {
+        &mut self.base
+    }
+
+    /// The map indexes of all sites that reported an uninitialized read in the last run
+    #[must_use]
+    pub fn sites(&self) -> Vec<usize> {
+        let initial = self.base.initial();
+        (0..self.base.usable_count())
+            .filter(|idx| *self.base.get(*idx) != initial)
+            .collect()
+    }
+}
+
+impl<M> Named for MsanReportObserver<M>
+where
+    M: Named + Serialize,
+{
+    fn name(&self) -> &str {
+        self.base.name()
+    }
+}
+
+impl<M, S> Observer<S> for MsanReportObserver<M>
+where
+    M: Observer<S> + Serialize,
+    S: UsesInput,
+{
+    #[inline]
+    fn pre_exec(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
+        self.base.pre_exec(state, input)
+    }
+
+    #[inline]
+    fn post_exec(
+        &mut self,
+        state: &mut S,
+        input: &S::Input,
+        exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.base.post_exec(state, input, exit_kind)
+    }
+
+    #[inline]
+    fn pre_exec_child(&mut self, state: &mut S, input: &S::Input) -> Result<(), Error> {
+        self.base.pre_exec_child(state, input)
+    }
+
+    #[inline]
+    fn post_exec_child(
+        &mut self,
+        state: &mut S,
+        input: &S::Input,
+        exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.base.post_exec_child(state, input, exit_kind)
+    }
+}
@@ -0,0 +1,76 @@
+//! The [`StackDepthObserver`] observes the maximum recursion or stack depth an in-process harness reached.
+//!
+//! Harnesses opt in by calling [`report_stack_depth`], for example on entry of their recursive functions.
+//! Guiding the fuzzer towards ever deeper nesting finds stack exhaustion bugs,
+//! see the [`crate::feedbacks::StackDepthFeedback`].
+
+use alloc::string::String;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{executors::ExitKind, inputs::UsesInput, observers::Observer, Error};
+
+/// The maximum stack depth reported by the harness during the current run
+static MAX_STACK_DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Reports, from within an in-process harness, the current stack depth.
+/// The [`StackDepthObserver`] picks up the maximum depth reported during the run.
+pub fn report_stack_depth(depth: usize) {
+    MAX_STACK_DEPTH.fetch_max(depth, Ordering::Relaxed);
+}
+
+/// An observer for the maximum stack depth the harness reached, as reported using [`report_stack_depth`].
+///
+/// This only works for in-process harnesses, and it is shared by all executors in the process.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StackDepthObserver {
+    name: String,
+    /// The maximum depth reached in the last run
+    max_depth: usize,
+}
+
+impl StackDepthObserver {
+    /// Creates a new [`StackDepthObserver`] with the given name
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            max_depth: 0,
+        }
+    }
+
+    /// The maximum stack depth the harness reported in the last run, `0` if it reported none
+    #[must_use]
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+}
+
+impl<S> Observer<S> for StackDepthObserver
+where
+    S: UsesInput,
+{
+    fn pre_exec(&mut self, _state: &mut S, _input: &S::Input) -> Result<(), Error> {
+        MAX_STACK_DEPTH.store(0, Ordering::Relaxed);
+        self.max_depth = 0;
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &S::Input,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.max_depth = MAX_STACK_DEPTH.swap(0, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl Named for StackDepthObserver {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
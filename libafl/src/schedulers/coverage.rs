@@ -0,0 +1,219 @@
+//! The [`CoverageTrackingScheduler`] keeps a running count of the map entries covered by the corpus,
+//! so the coverage can be read in constant time, see [`crate::state::HasCorpusCoverage`].
+
+use crate::{
+    corpus::{Corpus, CorpusId, Testcase},
+    feedbacks::MapIndexesMetadata,
+    inputs::UsesInput,
+    observers::ObserversTuple,
+    schedulers::{EdgeRarityMetadata, RemovableScheduler, Scheduler},
+    state::{HasCorpus, UsesState},
+    Error, HasMetadata,
+};
+
+/// A [`Scheduler`] wrapper that counts the map entries covered by each corpus entry in the [`EdgeRarityMetadata`] of the state,
+/// whenever the corpus changes.
+///
+/// The covered map indexes of a testcase are taken from its [`MapIndexesMetadata`], so the map feedback needs to track indexes.
+/// The [`crate::schedulers::RareEdgeScheduler`] keeps the same counts, so it does not need to be wrapped.
+#[derive(Debug, Clone)]
+pub struct CoverageTrackingScheduler<CS> {
+    base: CS,
+}
+
+impl<CS> CoverageTrackingScheduler<CS>
+where
+    CS: UsesState,
+    CS::State: HasCorpus + HasMetadata,
+{
+    /// Creates a new [`CoverageTrackingScheduler`], scheduling with the `base` scheduler
+    pub fn new(base: CS) -> Self {
+        Self { base }
+    }
+
+    /// The map indexes covered by the given testcase
+    fn indexes(testcase: &Testcase<<CS::State as UsesInput>::Input>) -> Result<&[usize], Error> {
+        testcase
+            .metadata::<MapIndexesMetadata>()
+            .map(|meta| meta.list.as_slice())
+            .map_err(|_| {
+                Error::key_not_found(
+                    "MapIndexesMetadata needed by the CoverageTrackingScheduler not found, track the indexes in the map feedback",
+                )
+            })
+    }
+}
+
+impl<CS> UsesState for CoverageTrackingScheduler<CS>
+where
+    CS: UsesState,
+{
+    type State = CS::State;
+}
+
+impl<CS> RemovableScheduler for CoverageTrackingScheduler<CS>
+where
+    CS: RemovableScheduler,
+    CS::State: HasCorpus + HasMetadata,
+{
+    fn on_remove(
+        &mut self,
+        state: &mut Self::State,
+        idx: CorpusId,
+        testcase: &Option<Testcase<<Self::State as UsesInput>::Input>>,
+    ) -> Result<(), Error> {
+        self.base.on_remove(state, idx, testcase)?;
+        if let Some(testcase) = testcase {
+            state
+                .metadata_or_insert_with(EdgeRarityMetadata::new)
+                .remove(Self::indexes(testcase)?);
+        }
+        Ok(())
+    }
+
+    fn on_replace(
+        &mut self,
+        state: &mut Self::State,
+        idx: CorpusId,
+        prev: &Testcase<<Self::State as UsesInput>::Input>,
+    ) -> Result<(), Error> {
+        // Read the indexes before the base scheduler gets the chance to strip them
+        let indexes = Self::indexes(&state.corpus().get(idx)?.borrow())?.to_vec();
+        self.base.on_replace(state, idx, prev)?;
+        let counts = state.metadata_or_insert_with(EdgeRarityMetadata::new);
+        counts.remove(Self::indexes(prev)?);
+        counts.add(&indexes);
+        Ok(())
+    }
+}
+
+impl<CS> Scheduler for CoverageTrackingScheduler<CS>
+where
+    CS: Scheduler,
+    CS::State: HasCorpus + HasMetadata,
+{
+    fn on_add(&mut self, state: &mut Self::State, idx: CorpusId) -> Result<(), Error> {
+        // Read the indexes before the base scheduler gets the chance to strip them
+        let indexes = Self::indexes(&state.corpus().get(idx)?.borrow())?.to_vec();
+        self.base.on_add(state, idx)?;
+        state
+            .metadata_or_insert_with(EdgeRarityMetadata::new)
+            .add(&indexes);
+        Ok(())
+    }
+
+    fn on_evaluation<OT>(
+        &mut self,
+        state: &mut Self::State,
+        input: &<Self::State as UsesInput>::Input,
+        observers: &OT,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<Self::State>,
+    {
+        self.base.on_evaluation(state, input, observers)
+    }
+
+    fn next(&mut self, state: &mut Self::State) -> Result<CorpusId, Error> {
+        self.base.next(state)
+    }
+
+    fn set_current_scheduled(
+        &mut self,
+        _state: &mut Self::State,
+        _next_idx: Option<CorpusId>,
+    ) -> Result<(), Error> {
+        // We do nothing here, the inner scheduler will take care of it
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use hashbrown::HashSet;
+
+    use crate::{
+        corpus::{Corpus, Testcase},
+        feedbacks::MapIndexesMetadata,
+        inputs::BytesInput,
+        schedulers::{
+            coverage::CoverageTrackingScheduler, EdgeRarityMetadata, QueueScheduler,
+            RemovableScheduler, Scheduler,
+        },
+        state::{
+            test::{test_std_state, TestStdState},
+            HasCorpus, HasCorpusCoverage,
+        },
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_coverage_tracking_scheduler() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            EdgeRarityMetadata::register();
+            MapIndexesMetadata::register();
+        }
+
+        let mut state = test_std_state::<BytesInput>();
+        let mut scheduler = CoverageTrackingScheduler::new(QueueScheduler::new());
+
+        let recount = |state: &TestStdState<BytesInput>| {
+            let mut covered = HashSet::new();
+            for id in state.corpus().ids() {
+                let testcase = state.corpus().get(id).unwrap().borrow();
+                covered.extend(
+                    testcase
+                        .metadata::<MapIndexesMetadata>()
+                        .unwrap()
+                        .list
+                        .clone(),
+                );
+            }
+            covered.len()
+        };
+
+        let mut ids = vec![];
+        for (i, indexes) in [
+            vec![1, 2, 3],
+            vec![2, 3],
+            vec![3, 4, 5],
+            vec![6],
+            vec![1, 6],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut testcase = Testcase::new(BytesInput::new(vec![i as u8; 4]));
+            testcase.add_metadata(MapIndexesMetadata::new(indexes));
+            let id = state.corpus_mut().add(testcase).unwrap();
+            scheduler.on_add(&mut state, id).unwrap();
+            ids.push(id);
+            assert_eq!(state.corpus_coverage(), recount(&state));
+        }
+        assert_eq!(state.corpus_coverage(), 6);
+
+        for id in [ids[0], ids[3], ids[2]] {
+            let testcase = state.corpus_mut().remove(id).unwrap();
+            scheduler
+                .on_remove(&mut state, id, &Some(testcase))
+                .unwrap();
+            assert_eq!(state.corpus_coverage(), recount(&state));
+        }
+        // Only [2, 3] and [1, 6] are left
+        assert_eq!(state.corpus_coverage(), 4);
+        let counts = &state.metadata::<EdgeRarityMetadata>().unwrap().counts;
+        assert_eq!(counts.get(&3), Some(&1));
+        assert_eq!(counts.get(&6), Some(&1));
+
+        // The covered indexes of each testcase need to be tracked
+        let id = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![0xff; 4])))
+            .unwrap();
+        assert!(scheduler.on_add(&mut state, id).is_err());
+    }
+}
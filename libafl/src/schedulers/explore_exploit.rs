@@ -0,0 +1,258 @@
+//! The [`ExploreExploitScheduler`] switches between exploiting recent finds and exploring the whole corpus,
+//! depending on how often new testcases were found recently.
+
+use alloc::{borrow::ToOwned, vec::Vec};
+use core::{marker::PhantomData, time::Duration};
+
+use libafl_bolts::{impl_serdeany, rands::Rand};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::{Corpus, CorpusId, HasTestcase},
+    random_corpus_id,
+    schedulers::{RemovableScheduler, Scheduler},
+    state::{HasClock, HasCorpus, HasRand, State, UsesState},
+    Error, HasMetadata,
+};
+
+/// The default time window in which finds are counted
+pub const DEFAULT_FIND_WINDOW: Duration = Duration::from_secs(60);
+
+/// The default number of finds in the window that switch the [`ExploreExploitScheduler`] to exploitation
+pub const DEFAULT_EXPLOIT_MIN_FINDS: usize = 4;
+
+/// The default number of most recent corpus entries the [`ExploreExploitScheduler`] picks from, while exploiting
+pub const DEFAULT_EXPLOIT_RECENT: usize = 16;
+
+/// The way the [`ExploreExploitScheduler`] selects the next testcase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelectionStrategy {
+    /// Finds are frequent, pick one of the most recent corpus entries
+    Exploit,
+    /// Finds are rare, pick any corpus entry at random
+    Explore,
+}
+
+/// The times at which the recent testcases were added to the corpus
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct FindRateMetadata {
+    /// The times of the recent finds, oldest first
+    pub finds: Vec<Duration>,
+}
+
+impl_serdeany!(FindRateMetadata);
+
+impl FindRateMetadata {
+    /// Creates a new, empty [`FindRateMetadata`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets all finds that happened before `now - window`
+    pub fn prune(&mut self, now: Duration, window: Duration) {
+        let start = now.saturating_sub(window);
+        self.finds.retain(|time| *time >= start);
+    }
+
+    /// The number of finds in the `window` before `now`
+    #[must_use]
+    pub fn finds_in(&self, now: Duration, window: Duration) -> usize {
+        let start = now.saturating_sub(window);
+        self.finds
+            .iter()
+            .filter(|time| **time >= start && **time <= now)
+            .count()
+    }
+}
+
+/// A [`Scheduler`] that exploits the most recent corpus entries, while new testcases are found frequently,
+/// and explores the whole corpus at random, once the finds dry up.
+///
+/// The find rate is computed from the [`crate::corpus::Testcase::added_time`] of the new testcases,
+/// kept in the [`FindRateMetadata`] of the state.
+#[derive(Debug, Clone)]
+pub struct ExploreExploitScheduler<S> {
+    /// The time window in which finds are counted
+    window: Duration,
+    /// The number of finds in the window needed to exploit
+    min_finds: usize,
+    /// The number of most recent entries to pick from, while exploiting
+    recent: usize,
+    phantom: PhantomData<S>,
+}
+
+impl<S> UsesState for ExploreExploitScheduler<S>
+where
+    S: State,
+{
+    type State = S;
+}
+
+impl<S> RemovableScheduler for ExploreExploitScheduler<S> where
+    S: HasCorpus + HasMetadata + HasRand + HasTestcase + HasClock + State
+{
+}
+
+impl<S> Scheduler for ExploreExploitScheduler<S>
+where
+    S: HasCorpus + HasMetadata + HasRand + HasTestcase + HasClock + State,
+{
+    fn on_add(&mut self, state: &mut Self::State, idx: CorpusId) -> Result<(), Error> {
+        let current_idx = *state.corpus().current();
+        let now = state.now();
+        let added_time = {
+            let mut testcase = state.corpus().get(idx)?.borrow_mut();
+            // Set parent id
+            testcase.set_parent_id_optional(current_idx);
+            testcase.added_time().unwrap_or(now)
+        };
+
+        let window = self.window;
+        let metadata = state.metadata_or_insert_with(FindRateMetadata::new);
+        metadata.finds.push(added_time);
+        metadata.prune(added_time, window);
+        Ok(())
+    }
+
+    /// Gets the next entry, following the current [`SelectionStrategy`]
+    fn next(&mut self, state: &mut Self::State) -> Result<CorpusId, Error> {
+        let count = state.corpus().count();
+        if count == 0 {
+            return Err(Error::empty(
+                "No entries in corpus. This often implies the target is not properly instrumented."
+                    .to_owned(),
+            ));
+        }
+
+        let id = match self.strategy(state, state.now()) {
+            SelectionStrategy::Exploit => {
+                let recent = self.recent.clamp(1, count);
+                let back = state.rand_mut().below(recent as u64) as usize;
+                state.corpus().nth(count - 1 - back)
+            }
+            SelectionStrategy::Explore => random_corpus_id!(state.corpus(), state.rand_mut()),
+        };
+        self.set_current_scheduled(state, Some(id))?;
+        Ok(id)
+    }
+}
+
+impl<S> ExploreExploitScheduler<S>
+where
+    S: HasMetadata,
+{
+    /// Creates a new [`ExploreExploitScheduler`] with the default thresholds
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_thresholds(
+            DEFAULT_FIND_WINDOW,
+            DEFAULT_EXPLOIT_MIN_FINDS,
+            DEFAULT_EXPLOIT_RECENT,
+        )
+    }
+
+    /// Creates a new [`ExploreExploitScheduler`] that exploits the `recent` most recent entries,
+    /// as long as at least `min_finds` testcases were found in the last `window`
+    #[must_use]
+    pub fn with_thresholds(window: Duration, min_finds: usize, recent: usize) -> Self {
+        Self {
+            window,
+            min_finds,
+            recent,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The [`SelectionStrategy`] to use at the time `now`
+    #[must_use]
+    pub fn strategy(&self, state: &S, now: Duration) -> SelectionStrategy {
+        let finds = state
+            .metadata::<FindRateMetadata>()
+            .map_or(0, |metadata| metadata.finds_in(now, self.window));
+        if finds >= self.min_finds {
+            SelectionStrategy::Exploit
+        } else {
+            SelectionStrategy::Explore
+        }
+    }
+}
+
+impl<S> Default for ExploreExploitScheduler<S>
+where
+    S: HasMetadata,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use core::time::Duration;
+
+    use crate::{
+        corpus::{Corpus, Testcase},
+        inputs::BytesInput,
+        schedulers::{
+            explore_exploit::{ExploreExploitScheduler, SelectionStrategy},
+            Scheduler,
+        },
+        state::{test::test_std_state, HasClock, HasCorpus, MockClock},
+    };
+
+    #[test]
+    fn test_explore_exploit_scheduler() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            crate::schedulers::explore_exploit::FindRateMetadata::register();
+        }
+
+        let mut state = test_std_state::<BytesInput>();
+        state.set_clock(MockClock::new(Duration::from_secs(100_000)));
+        let mut scheduler = ExploreExploitScheduler::with_thresholds(Duration::from_secs(60), 4, 2);
+
+        // A long time ago, the seeds were added
+        let start = state.now();
+        for i in 0..8 {
+            let mut testcase = Testcase::new(BytesInput::new(vec![i; 4]));
+            testcase.set_added_time(start - Duration::from_secs(3600));
+            let id = state.corpus_mut().add(testcase).unwrap();
+            scheduler.on_add(&mut state, id).unwrap();
+        }
+        assert_eq!(
+            scheduler.strategy(&state, start),
+            SelectionStrategy::Explore
+        );
+
+        // Lots of new finds in a short time
+        for i in 8..12 {
+            let mut testcase = Testcase::new(BytesInput::new(vec![i; 4]));
+            testcase.set_added_time(start);
+            let id = state.corpus_mut().add(testcase).unwrap();
+            scheduler.on_add(&mut state, id).unwrap();
+        }
+        assert_eq!(
+            scheduler.strategy(&state, start),
+            SelectionStrategy::Exploit
+        );
+        let recent = [state.corpus().nth(10), state.corpus().nth(11)];
+        for _ in 0..16 {
+            let id = scheduler.next(&mut state).unwrap();
+            assert!(recent.contains(&id));
+        }
+
+        // After a dry period, the whole corpus is explored again
+        assert_eq!(
+            scheduler.strategy(&state, start + Duration::from_secs(120)),
+            SelectionStrategy::Explore
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! The [`FairScheduler`] makes sure every testcase gets fuzzed at least once.
+//!
+//! Weighted schedulers may starve new testcases with a low weight for a long time.
+//! Wrapped in a [`FairScheduler`], each new testcase is scheduled once, in the order they were added,
+//! before the wrapped scheduler takes over.
+
+use alloc::collections::VecDeque;
+
+use crate::{
+    corpus::{Corpus, CorpusId, Testcase},
+    inputs::UsesInput,
+    observers::ObserversTuple,
+    schedulers::{RemovableScheduler, Scheduler},
+    state::{HasCorpus, UsesState},
+    Error,
+};
+
+/// A [`Scheduler`] that first schedules each testcase that was never fuzzed,
+/// as flagged by [`Testcase::never_fuzzed`], and only then asks the `base` scheduler
+#[derive(Debug, Clone)]
+pub struct FairScheduler<CS> {
+    base: CS,
+    /// The testcases that may not have been fuzzed yet, in the order they were added
+    pending: VecDeque<CorpusId>,
+    /// If the corpus was already scanned for testcases that were never fuzzed, i.e., after a restart
+    scanned: bool,
+}
+
+impl<CS> FairScheduler<CS>
+where
+    CS: Scheduler,
+    CS::State: HasCorpus,
+{
+    /// Creates a new [`FairScheduler`], wrapping the `base` scheduler
+    pub fn new(base: CS) -> Self {
+        Self {
+            base,
+            pending: VecDeque::new(),
+            scanned: false,
+        }
+    }
+
+    /// The next pending testcase that was never fuzzed, if any
+    fn next_never_fuzzed(&mut self, state: &CS::State) -> Option<CorpusId> {
+        while let Some(idx) = self.pending.pop_front() {
+            // Removed testcases, and testcases the base scheduler already picked, are skipped
+            if let Ok(testcase) = state.corpus().get(idx) {
+                if testcase.borrow().never_fuzzed() {
+                    return Some(idx);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<CS> UsesState for FairScheduler<CS>
+where
+    CS: UsesState,
+{
+    type State = CS::State;
+}
+
+impl<CS> RemovableScheduler for FairScheduler<CS>
+where
+    CS: RemovableScheduler,
+    CS::State: HasCorpus,
+{
+    fn on_remove(
+        &mut self,
+        state: &mut CS::State,
+        idx: CorpusId,
+        testcase: &Option<Testcase<<CS::State as UsesInput>::Input>>,
+    ) -> Result<(), Error> {
+        self.pending.retain(|pending| *pending != idx);
+        self.base.on_remove(state, idx, testcase)
+    }
+
+    fn on_replace(
+        &mut self,
+        state: &mut CS::State,
+        idx: CorpusId,
+        prev: &Testcase<<CS::State as UsesInput>::Input>,
+    ) -> Result<(), Error> {
+        self.base.on_replace(state, idx, prev)
+    }
+}
+
+impl<CS> Scheduler for FairScheduler<CS>
+where
+    CS: Scheduler,
+    CS::State: HasCorpus,
+{
+    fn on_add(&mut self, state: &mut CS::State, idx: CorpusId) -> Result<(), Error> {
+        self.base.on_add(state, idx)?;
+        self.pending.push_back(idx);
+        Ok(())
+    }
+
+    fn on_evaluation<OT>(
+        &mut self,
+        state: &mut Self::State,
+        input: &<Self::State as UsesInput>::Input,
+        observers: &OT,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<Self::State>,
+    {
+        self.base.on_evaluation(state, input, observers)
+    }
+
+    fn next(&mut self, state: &mut CS::State) -> Result<CorpusId, Error> {
+        if !self.scanned {
+            self.scanned = true;
+            let ids: VecDeque<_> = state.corpus().ids().collect();
+            self.pending = ids;
+        }
+
+        let idx = if let Some(idx) = self.next_never_fuzzed(state) {
+            self.base.set_current_scheduled(state, Some(idx))?;
+            idx
+        } else {
+            self.base.next(state)?
+        };
+        state.corpus().get(idx)?.borrow_mut().set_fuzzed(true);
+        Ok(idx)
+    }
+
+    fn set_current_scheduled(
+        &mut self,
+        state: &mut Self::State,
+        next_idx: Option<CorpusId>,
+    ) -> Result<(), Error> {
+        self.base.set_current_scheduled(state, next_idx)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use alloc::collections::BTreeSet;
+
+    use crate::{
+        corpus::{Corpus, Testcase},
+        inputs::BytesInput,
+        schedulers::{fair::FairScheduler, Scheduler, TuneableScheduler},
+        state::{test::test_std_state, HasCorpus},
+    };
+
+    #[test]
+    fn test_fair_scheduler() {
+        let mut state = test_std_state::<BytesInput>();
+        let base = TuneableScheduler::new(&mut state);
+        let mut scheduler = FairScheduler::new(base);
+
+        let mut ids = BTreeSet::new();
+        for i in 0..5 {
+            let id = state
+                .corpus_mut()
+                .add(Testcase::new(BytesInput::new(vec![i])))
+                .unwrap();
+            scheduler.on_add(&mut state, id).unwrap();
+            ids.insert(id);
+        }
+
+        // The base scheduler always picks the first testcase
+        let first = state.corpus().first().unwrap();
+        TuneableScheduler::set_next(&mut state, first);
+
+        let selected: BTreeSet<_> = (0..5)
+            .map(|_| scheduler.next(&mut state).unwrap())
+            .collect();
+        assert_eq!(selected, ids);
+
+        // Then, the bias takes over
+        assert_eq!(scheduler.next(&mut state).unwrap(), first);
+        assert!(!state.corpus().get(first).unwrap().borrow().never_fuzzed());
+    }
+}
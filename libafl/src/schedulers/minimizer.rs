@@ -14,7 +14,9 @@ use crate::{
     inputs::UsesInput,
     observers::{CanTrack, ObserversTuple},
     require_index_tracking,
-    schedulers::{LenTimeMulTestcaseScore, RemovableScheduler, Scheduler, TestcaseScore},
+    schedulers::{
+        LenTimeMulTestcaseScore, RemovableScheduler, RescoringScheduler, Scheduler, TestcaseScore,
+    },
     state::{HasCorpus, HasRand, UsesState},
     Error, HasMetadata,
 };
@@ -249,6 +251,39 @@ where
     }
 }
 
+impl<CS, F, M, O> RescoringScheduler for MinimizerScheduler<CS, F, M, O>
+where
+    CS: Scheduler,
+    F: TestcaseScore<CS::State>,
+    M: AsSlice<Entry = usize> + SerdeAny + HasRefCnt,
+    CS::State: HasCorpus + HasMetadata + HasRand,
+    O: CanTrack,
+{
+    /// Recomputes the top rated [`Testcase`]s and the favored ones from scratch.
+    ///
+    /// Only [`Testcase`]s that still have the metadata `M` can be rescored,
+    /// so this is most useful for a scheduler created with [`MinimizerScheduler::non_metadata_removing`].
+    /// The base scheduler is not rescored.
+    fn rescore(&mut self, state: &mut CS::State) -> Result<(), Error> {
+        state.add_metadata(TopRatedsMetadata::new());
+
+        let ids = state.corpus().ids().collect::<Vec<_>>();
+        for idx in &ids {
+            let mut entry = state.corpus().get(*idx)?.borrow_mut();
+            drop(entry.metadata_map_mut().remove::<IsFavoredMetadata>());
+            if let Some(meta) = entry.metadata_map_mut().get_mut::<M>() {
+                *meta.refcnt_mut() = 0;
+            }
+        }
+        for idx in ids {
+            if state.corpus().get(idx)?.borrow().has_metadata::<M>() {
+                self.update_score(state, idx)?;
+            }
+        }
+        self.cull(state)
+    }
+}
+
 impl<CS, F, M, O> MinimizerScheduler<CS, F, M, O>
 where
     CS: Scheduler,
@@ -371,6 +406,18 @@ where
         &mut self.base
     }
 
+    /// The probability, in percent, to skip a non-favored [`Testcase`] picked by the base scheduler
+    #[must_use]
+    pub fn skip_non_favored_prob(&self) -> u64 {
+        self.skip_non_favored_prob
+    }
+
+    /// Sets the probability, in percent, to skip a non-favored [`Testcase`] picked by the base scheduler.
+    /// The higher it is, the more often favored [`Testcase`]s get fuzzed. Values above `100` are capped.
+    pub fn set_skip_non_favored_prob(&mut self, skip_non_favored_prob: u64) {
+        self.skip_non_favored_prob = skip_non_favored_prob.min(100);
+    }
+
     /// Creates a new [`MinimizerScheduler`] that wraps a `base` [`Scheduler`]
     /// and has a default probability to skip non-faved [`Testcase`]s of [`DEFAULT_SKIP_NON_FAVORED_PROB`].
     /// This will remove the metadata `M` when it is no longer needed, after consumption. This might
@@ -429,3 +476,123 @@ pub type IndexesLenTimeMinimizerScheduler<CS, O> = MinimizerScheduler<
     MapIndexesMetadata,
     O,
 >;
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use crate::{
+        corpus::{Corpus, CorpusId, Testcase},
+        feedbacks::MapIndexesMetadata,
+        inputs::BytesInput,
+        observers::{CanTrack, StdMapObserver},
+        schedulers::{
+            minimizer::IsFavoredMetadata, IndexesLenTimeMinimizerScheduler, QueueScheduler,
+            RescoringScheduler, Scheduler,
+        },
+        state::{
+            test::{test_std_state, TestStdState},
+            HasCorpus,
+        },
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_minimizer_rescore() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            IsFavoredMetadata::register();
+            MapIndexesMetadata::register();
+            crate::schedulers::minimizer::TopRatedsMetadata::register();
+        }
+
+        let observer = StdMapObserver::owned("map", vec![0_u8; 4]).track_indices();
+        let mut scheduler = IndexesLenTimeMinimizerScheduler::non_metadata_removing(
+            &observer,
+            QueueScheduler::new(),
+        );
+
+        let mut state = test_std_state::<BytesInput>();
+
+        let mut add = |state: &mut TestStdState<BytesInput>, len: usize| {
+            let mut testcase = Testcase::new(BytesInput::new(vec![0; len]));
+            testcase.add_metadata(MapIndexesMetadata::new(vec![1, 2]));
+            let idx = state.corpus_mut().add(testcase).unwrap();
+            scheduler.on_add(state, idx).unwrap();
+            scheduler.cull(state).unwrap();
+            idx
+        };
+        let is_favored = |state: &TestStdState<BytesInput>, idx: CorpusId| {
+            state
+                .corpus()
+                .get(idx)
+                .unwrap()
+                .borrow()
+                .has_metadata::<IsFavoredMetadata>()
+        };
+
+        let old = add(&mut state, 8);
+        assert!(is_favored(&state, old));
+
+        // A smaller testcase with the same coverage is better, but the old one stays favored
+        let new = add(&mut state, 2);
+        assert!(is_favored(&state, new));
+        assert!(is_favored(&state, old));
+
+        scheduler.rescore(&mut state).unwrap();
+        assert!(is_favored(&state, new));
+        assert!(!is_favored(&state, old));
+    }
+
+    #[test]
+    fn test_skip_non_favored_prob() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            IsFavoredMetadata::register();
+            MapIndexesMetadata::register();
+            crate::schedulers::minimizer::TopRatedsMetadata::register();
+        }
+
+        let observer = StdMapObserver::owned("map", vec![0_u8; 4]).track_indices();
+        let mut scheduler = IndexesLenTimeMinimizerScheduler::non_metadata_removing(
+            &observer,
+            QueueScheduler::new(),
+        );
+
+        let mut state = test_std_state::<BytesInput>();
+
+        // Only the first, smallest, testcase is favored
+        for len in [2, 8, 8, 8, 8] {
+            let mut testcase = Testcase::new(BytesInput::new(vec![0; len]));
+            testcase.add_metadata(MapIndexesMetadata::new(vec![1]));
+            let idx = state.corpus_mut().add(testcase).unwrap();
+            scheduler.on_add(&mut state, idx).unwrap();
+        }
+
+        let mut favored_share = |scheduler: &mut IndexesLenTimeMinimizerScheduler<_, _>| {
+            (0..1000)
+                .filter(|_| {
+                    let idx = scheduler.next(&mut state).unwrap();
+                    state
+                        .corpus()
+                        .get(idx)
+                        .unwrap()
+                        .borrow()
+                        .has_metadata::<IsFavoredMetadata>()
+                })
+                .count()
+        };
+
+        scheduler.set_skip_non_favored_prob(0);
+        let unbiased = favored_share(&mut scheduler);
+        assert_eq!(unbiased, 200);
+
+        scheduler.set_skip_non_favored_prob(90);
+        assert_eq!(scheduler.skip_non_favored_prob(), 90);
+        let biased = favored_share(&mut scheduler);
+        assert!(biased > 600, "{biased}");
+    }
+}
@@ -1,6 +1,6 @@
 //! Schedule the access to the Corpus.
 
-use alloc::{borrow::ToOwned, string::ToString};
+use alloc::{borrow::ToOwned, string::ToString, vec::Vec};
 use core::marker::PhantomData;
 
 pub mod testcase_score;
@@ -23,6 +23,24 @@ pub use probabilistic_sampling::ProbabilitySamplingScheduler;
 pub mod accounting;
 pub use accounting::CoverageAccountingScheduler;
 
+pub mod coverage;
+pub use coverage::CoverageTrackingScheduler;
+
+pub mod fair;
+pub use fair::FairScheduler;
+
+pub mod quarantine;
+pub use quarantine::QuarantineScheduler;
+
+pub mod explore_exploit;
+pub use explore_exploit::{ExploreExploitScheduler, FindRateMetadata, SelectionStrategy};
+
+pub mod rare_edge;
+pub use rare_edge::{EdgeRarityMetadata, RareEdgeScheduler};
+
+pub mod size_histogram;
+pub use size_histogram::{InputSizeHistogramMetadata, SizeHistogramScheduler};
+
 pub mod weighted;
 pub use weighted::{StdWeightedScheduler, WeightedScheduler};
 
@@ -65,6 +83,16 @@ where
     }
 }
 
+/// A [`Scheduler`] that keeps scores for the testcases in the corpus, which may go stale when new testcases are added,
+/// for example because the new coverage changes which edges are rare.
+pub trait RescoringScheduler: Scheduler
+where
+    Self::State: HasCorpus,
+{
+    /// Recomputes the scores of all testcases in the corpus
+    fn rescore(&mut self, state: &mut Self::State) -> Result<(), Error>;
+}
+
 /// Defines the common metadata operations for the AFL-style schedulers
 pub trait AflScheduler<C, O, S>: Scheduler
 where
@@ -86,10 +114,7 @@ where
         let current_idx = *state.corpus().current();
 
         let mut depth = match current_idx {
-            Some(parent_idx) => state
-                .testcase(parent_idx)?
-                .metadata::<SchedulerTestcaseMetadata>()?
-                .depth(),
+            Some(parent_idx) => state.testcase(parent_idx)?.scheduler_metadata()?.depth(),
             None => 0,
         };
 
@@ -99,7 +124,7 @@ where
         // Attach a `SchedulerTestcaseMetadata` to the queue entry.
         depth += 1;
         let mut testcase = state.testcase_mut(idx)?;
-        testcase.add_metadata(SchedulerTestcaseMetadata::with_n_fuzz_entry(
+        testcase.set_scheduler_metadata(SchedulerTestcaseMetadata::with_n_fuzz_entry(
             depth,
             self.last_hash(),
         ));
@@ -145,7 +170,7 @@ where
 
         if let Some(idx) = current_idx {
             let mut testcase = state.testcase_mut(idx)?;
-            let tcmeta = testcase.metadata_mut::<SchedulerTestcaseMetadata>()?;
+            let tcmeta = testcase.scheduler_metadata_mut()?;
 
             if tcmeta.handicap() >= 4 {
                 tcmeta.set_handicap(tcmeta.handicap() - 4);
@@ -185,6 +210,14 @@ where
     fn next(&mut self, state: &mut Self::State) -> Result<CorpusId, Error>;
     // Increment corpus.current() here if it has no inner
 
+    /// The entries that [`Scheduler::next`] likely returns after the current one, in order, at most `count` of them.
+    ///
+    /// This is only a hint, for example to prefetch their inputs, see [`Corpus::prefetch`].
+    /// Schedulers that can't predict their next entries return none.
+    fn upcoming(&self, _state: &Self::State, _count: usize) -> Vec<CorpusId> {
+        Vec::new()
+    }
+
     /// Set current fuzzed corpus id and `scheduled_count`
     fn set_current_scheduled(
         &mut self,
@@ -196,6 +229,29 @@ where
     }
 }
 
+/// The next `count` entries in corpus order after the current one, wrapping around, as picked by queue-like schedulers
+fn upcoming_in_queue<C>(corpus: &C, count: usize) -> Vec<CorpusId>
+where
+    C: Corpus,
+{
+    let Some(current) = *corpus.current() else {
+        return corpus.ids().take(count).collect();
+    };
+    let mut upcoming = Vec::new();
+    let mut id = current;
+    while upcoming.len() < count {
+        let Some(next) = corpus.next(id).or_else(|| corpus.first()) else {
+            break;
+        };
+        if next == current {
+            break;
+        }
+        upcoming.push(next);
+        id = next;
+    }
+    upcoming
+}
+
 /// Feed the fuzzer simply with a random testcase on request
 #[derive(Debug, Clone)]
 pub struct RandScheduler<S> {
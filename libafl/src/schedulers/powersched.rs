@@ -13,7 +13,7 @@ use crate::{
     corpus::{Corpus, CorpusId, HasTestcase, Testcase},
     inputs::UsesInput,
     observers::{MapObserver, ObserversTuple},
-    schedulers::{AflScheduler, RemovableScheduler, Scheduler},
+    schedulers::{upcoming_in_queue, AflScheduler, RemovableScheduler, Scheduler},
     state::{HasCorpus, State, UsesState},
     Error, HasMetadata,
 };
@@ -278,6 +278,11 @@ where
         }
     }
 
+    /// The next entries in the queue
+    fn upcoming(&self, state: &Self::State, count: usize) -> Vec<CorpusId> {
+        upcoming_in_queue(state.corpus(), count)
+    }
+
     /// Set current fuzzed corpus id and `scheduled_count`
     fn set_current_scheduled(
         &mut self,
@@ -0,0 +1,126 @@
+//! The [`QuarantineScheduler`] disables quarantined testcases, so they are no longer scheduled.
+//!
+//! A testcase is quarantined by the [`crate::StdFuzzer`], once fuzzing it failed with an executor error too often in a row,
+//! see [`crate::fuzzer::Quarantine`].
+
+use crate::{
+    corpus::{Corpus, CorpusId, Testcase},
+    inputs::UsesInput,
+    observers::ObserversTuple,
+    schedulers::{RemovableScheduler, Scheduler},
+    state::{HasCorpus, UsesState},
+    Error,
+};
+
+/// A [`Scheduler`] that moves the last scheduled testcase to the disabled testcases of the corpus,
+/// once it is flagged by [`Testcase::quarantined`], and removes it from the `base` scheduler,
+/// before asking the `base` scheduler for the next testcase.
+#[derive(Debug, Clone)]
+pub struct QuarantineScheduler<CS> {
+    base: CS,
+}
+
+impl<CS> QuarantineScheduler<CS>
+where
+    CS: RemovableScheduler,
+    CS::State: HasCorpus,
+{
+    /// Creates a new [`QuarantineScheduler`], wrapping the `base` scheduler
+    pub fn new(base: CS) -> Self {
+        Self { base }
+    }
+
+    /// The wrapped scheduler
+    #[must_use]
+    pub fn base(&self) -> &CS {
+        &self.base
+    }
+
+    /// Disables the last scheduled testcase, if it got quarantined
+    fn disable_quarantined(&mut self, state: &mut CS::State) -> Result<(), Error> {
+        let Some(idx) = *state.corpus().current() else {
+            return Ok(());
+        };
+        let quarantined = match state.corpus().get(idx) {
+            Ok(testcase) => testcase.borrow().quarantined(),
+            Err(_) => false,
+        };
+        if !quarantined {
+            return Ok(());
+        }
+
+        // Continue with the testcase after it
+        *state.corpus_mut().current_mut() = state.corpus().prev(idx);
+        let testcase = state.corpus_mut().remove(idx)?;
+        self.base.on_remove(state, idx, &Some(testcase.clone()))?;
+        let disabled = state.corpus_mut().add_disabled(testcase)?;
+        log::info!("Disabled quarantined testcase {idx}, now {disabled}");
+        Ok(())
+    }
+}
+
+impl<CS> UsesState for QuarantineScheduler<CS>
+where
+    CS: UsesState,
+{
+    type State = CS::State;
+}
+
+impl<CS> RemovableScheduler for QuarantineScheduler<CS>
+where
+    CS: RemovableScheduler,
+    CS::State: HasCorpus,
+{
+    fn on_remove(
+        &mut self,
+        state: &mut CS::State,
+        idx: CorpusId,
+        testcase: &Option<Testcase<<CS::State as UsesInput>::Input>>,
+    ) -> Result<(), Error> {
+        self.base.on_remove(state, idx, testcase)
+    }
+
+    fn on_replace(
+        &mut self,
+        state: &mut CS::State,
+        idx: CorpusId,
+        prev: &Testcase<<CS::State as UsesInput>::Input>,
+    ) -> Result<(), Error> {
+        self.base.on_replace(state, idx, prev)
+    }
+}
+
+impl<CS> Scheduler for QuarantineScheduler<CS>
+where
+    CS: RemovableScheduler,
+    CS::State: HasCorpus,
+{
+    fn on_add(&mut self, state: &mut CS::State, idx: CorpusId) -> Result<(), Error> {
+        self.base.on_add(state, idx)
+    }
+
+    fn on_evaluation<OT>(
+        &mut self,
+        state: &mut Self::State,
+        input: &<Self::State as UsesInput>::Input,
+        observers: &OT,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<Self::State>,
+    {
+        self.base.on_evaluation(state, input, observers)
+    }
+
+    fn next(&mut self, state: &mut CS::State) -> Result<CorpusId, Error> {
+        self.disable_quarantined(state)?;
+        self.base.next(state)
+    }
+
+    fn set_current_scheduled(
+        &mut self,
+        state: &mut Self::State,
+        next_idx: Option<CorpusId>,
+    ) -> Result<(), Error> {
+        self.base.set_current_scheduled(state, next_idx)
+    }
+}
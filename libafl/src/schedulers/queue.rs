@@ -1,11 +1,11 @@
 //! The queue corpus scheduler implements an AFL-like queue mechanism
 
-use alloc::borrow::ToOwned;
+use alloc::{borrow::ToOwned, vec::Vec};
 use core::marker::PhantomData;
 
 use crate::{
     corpus::{Corpus, CorpusId, HasTestcase},
-    schedulers::{RemovableScheduler, Scheduler},
+    schedulers::{upcoming_in_queue, RemovableScheduler, Scheduler},
     state::{HasCorpus, State, UsesState},
     Error,
 };
@@ -59,6 +59,11 @@ where
             Ok(id)
         }
     }
+
+    /// The next entries in the queue
+    fn upcoming(&self, state: &Self::State, count: usize) -> Vec<CorpusId> {
+        upcoming_in_queue(state.corpus(), count)
+    }
 }
 
 impl<S> QueueScheduler<S> {
@@ -90,7 +95,7 @@ mod tests {
         feedbacks::ConstFeedback,
         inputs::bytes::BytesInput,
         schedulers::{QueueScheduler, Scheduler},
-        state::{HasCorpus, StdState},
+        state::{test::test_std_state, HasCorpus, StdState},
     };
 
     #[test]
@@ -127,4 +132,31 @@ mod tests {
 
         fs::remove_dir_all("target/.test/fancy/path").unwrap();
     }
+
+    #[test]
+    fn test_queue_remaining_in_cycle() {
+        let mut scheduler = QueueScheduler::new();
+        let mut state = test_std_state::<BytesInput>();
+        for i in 0..3 {
+            state
+                .corpus_mut()
+                .add(Testcase::new(BytesInput::new(vec![i; 4])))
+                .unwrap();
+        }
+
+        assert_eq!(state.corpus().current_index(), None);
+        assert_eq!(state.corpus().remaining_in_cycle(), 3);
+
+        for cycle in 0..2 {
+            for expected in (0..3).rev() {
+                scheduler.next(&mut state).unwrap();
+                assert_eq!(state.corpus().current_index(), Some(2 - expected));
+                assert_eq!(
+                    state.corpus().remaining_in_cycle(),
+                    expected,
+                    "cycle {cycle}"
+                );
+            }
+        }
+    }
 }
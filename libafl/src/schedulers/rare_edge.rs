@@ -0,0 +1,278 @@
+//! The [`RareEdgeScheduler`] prefers testcases that cover edges only few other testcases in the corpus cover.
+//!
+//! The edges of each testcase are taken from its [`MapIndexesMetadata`], so the map feedback needs to track indices.
+
+use alloc::{string::String, vec::Vec};
+use core::marker::PhantomData;
+
+use hashbrown::HashMap;
+use libafl_bolts::{impl_serdeany, rands::Rand};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::{Corpus, CorpusId, HasTestcase, Testcase},
+    feedbacks::MapIndexesMetadata,
+    inputs::UsesInput,
+    schedulers::{RemovableScheduler, Scheduler},
+    state::{HasCorpus, HasRand, State, UsesState},
+    Error, HasMetadata,
+};
+
+/// The number of corpus entries that cover each edge
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct EdgeRarityMetadata {
+    /// edge index -> number of corpus entries covering it
+    pub counts: HashMap<usize, u64>,
+}
+
+impl_serdeany!(EdgeRarityMetadata);
+
+impl EdgeRarityMetadata {
+    /// Creates a new, empty [`EdgeRarityMetadata`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of edges covered by at least one corpus entry
+    #[must_use]
+    pub fn covered(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Counts the edges of a new corpus entry
+    pub fn add(&mut self, edges: &[usize]) {
+        for edge in edges {
+            *self.counts.entry(*edge).or_default() += 1;
+        }
+    }
+
+    /// Forgets the edges of a removed corpus entry
+    pub fn remove(&mut self, edges: &[usize]) {
+        for edge in edges {
+            if let Some(count) = self.counts.get_mut(edge) {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(edge);
+                }
+            }
+        }
+    }
+
+    /// The weight of a testcase covering the given edges.
+    /// Each edge adds the inverse of the number of corpus entries covering it.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn weight(&self, edges: &[usize]) -> f64 {
+        edges
+            .iter()
+            .map(|edge| 1.0 / self.counts.get(edge).copied().unwrap_or(1).max(1) as f64)
+            .sum()
+    }
+}
+
+/// A [`Scheduler`] that selects testcases with a probability inverse to how common their covered edges are,
+/// so that inputs exercising rare paths get revisited more often.
+///
+/// Testcases without a [`MapIndexesMetadata`] get the weight of a single edge covered by the whole corpus.
+#[derive(Debug, Clone)]
+pub struct RareEdgeScheduler<S> {
+    phantom: PhantomData<S>,
+}
+
+impl<S> RareEdgeScheduler<S>
+where
+    S: HasCorpus + HasMetadata,
+{
+    /// Creates a new [`RareEdgeScheduler`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+
+    /// The edges covered by the given testcase, if known
+    fn edges(testcase: &Testcase<<S as UsesInput>::Input>) -> Option<&[usize]> {
+        testcase
+            .metadata::<MapIndexesMetadata>()
+            .ok()
+            .map(|meta| meta.list.as_slice())
+    }
+
+    /// The current selection weight of each corpus entry
+    #[allow(clippy::cast_precision_loss, clippy::unused_self)]
+    pub fn weights(&self, state: &S) -> Result<Vec<(CorpusId, f64)>, Error> {
+        let default_weight = 1.0 / state.corpus().count().max(1) as f64;
+        let rarity = state.metadata::<EdgeRarityMetadata>().ok();
+        let mut weights = Vec::with_capacity(state.corpus().count());
+        for id in state.corpus().ids() {
+            let testcase = state.corpus().get(id)?.borrow();
+            let weight = match (rarity, Self::edges(&testcase)) {
+                (Some(rarity), Some(edges)) if !edges.is_empty() => rarity.weight(edges),
+                _ => default_weight,
+            };
+            weights.push((id, weight));
+        }
+        Ok(weights)
+    }
+}
+
+impl<S> UsesState for RareEdgeScheduler<S>
+where
+    S: State,
+{
+    type State = S;
+}
+
+impl<S> RemovableScheduler for RareEdgeScheduler<S>
+where
+    S: HasCorpus + HasMetadata + HasRand + HasTestcase + State,
+{
+    fn on_remove(
+        &mut self,
+        state: &mut Self::State,
+        _idx: CorpusId,
+        testcase: &Option<Testcase<<Self::State as UsesInput>::Input>>,
+    ) -> Result<(), Error> {
+        if let Some(edges) = testcase.as_ref().and_then(Self::edges) {
+            state
+                .metadata_or_insert_with(EdgeRarityMetadata::new)
+                .remove(edges);
+        }
+        Ok(())
+    }
+
+    fn on_replace(
+        &mut self,
+        state: &mut Self::State,
+        idx: CorpusId,
+        prev: &Testcase<<Self::State as UsesInput>::Input>,
+    ) -> Result<(), Error> {
+        let edges = Self::edges(&state.corpus().get(idx)?.borrow()).map(<[usize]>::to_vec);
+        let rarity = state.metadata_or_insert_with(EdgeRarityMetadata::new);
+        if let Some(prev_edges) = Self::edges(prev) {
+            rarity.remove(prev_edges);
+        }
+        if let Some(edges) = edges {
+            rarity.add(&edges);
+        }
+        Ok(())
+    }
+}
+
+impl<S> Scheduler for RareEdgeScheduler<S>
+where
+    S: HasCorpus + HasMetadata + HasRand + HasTestcase + State,
+{
+    fn on_add(&mut self, state: &mut Self::State, idx: CorpusId) -> Result<(), Error> {
+        let current_idx = *state.corpus().current();
+        let edges = {
+            let mut testcase = state.corpus().get(idx)?.borrow_mut();
+            // Set parent id
+            testcase.set_parent_id_optional(current_idx);
+            Self::edges(&testcase).map(<[usize]>::to_vec)
+        };
+        let rarity = state.metadata_or_insert_with(EdgeRarityMetadata::new);
+        if let Some(edges) = edges {
+            rarity.add(&edges);
+        }
+        Ok(())
+    }
+
+    /// Gets the next entry, weighted by the rarity of its edges
+    #[allow(clippy::cast_precision_loss)]
+    fn next(&mut self, state: &mut Self::State) -> Result<CorpusId, Error> {
+        if state.corpus().count() == 0 {
+            return Err(Error::empty(String::from(
+                "No entries in corpus. This often implies the target is not properly instrumented.",
+            )));
+        }
+
+        let weights = self.weights(state)?;
+        let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+        let threshold = total * (state.rand_mut().below(1 << 20) as f64 / f64::from(1_u32 << 20));
+        let mut sum = 0.0;
+        let mut ret = weights.last().unwrap().0;
+        for (id, weight) in weights {
+            sum += weight;
+            if sum > threshold {
+                ret = id;
+                break;
+            }
+        }
+        self.set_current_scheduled(state, Some(ret))?;
+        Ok(ret)
+    }
+}
+
+impl<S> Default for RareEdgeScheduler<S>
+where
+    S: HasCorpus + HasMetadata,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use crate::{
+        corpus::{Corpus, Testcase},
+        feedbacks::MapIndexesMetadata,
+        inputs::BytesInput,
+        schedulers::{
+            rare_edge::{EdgeRarityMetadata, RareEdgeScheduler},
+            Scheduler,
+        },
+        state::{test::test_std_state, HasCorpus},
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_rare_edge_scheduler() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            EdgeRarityMetadata::register();
+            MapIndexesMetadata::register();
+        }
+
+        let mut state = test_std_state::<BytesInput>();
+        let mut scheduler = RareEdgeScheduler::new();
+
+        // Only the first testcase covers the rare edge 5, all others cover the common edge 1
+        let mut ids = vec![];
+        for (i, edges) in [vec![5], vec![1], vec![1], vec![1], vec![1]]
+            .into_iter()
+            .enumerate()
+        {
+            let mut testcase = Testcase::new(BytesInput::new(vec![i as u8; 4]));
+            testcase.add_metadata(MapIndexesMetadata::new(edges));
+            let id = state.corpus_mut().add(testcase).unwrap();
+            scheduler.on_add(&mut state, id).unwrap();
+            ids.push(id);
+        }
+        assert_eq!(
+            state.metadata::<EdgeRarityMetadata>().unwrap().counts[&1],
+            4
+        );
+
+        let mut selected = [0_usize; 5];
+        for _ in 0..1000 {
+            let id = scheduler.next(&mut state).unwrap();
+            selected[ids.iter().position(|x| *x == id).unwrap()] += 1;
+        }
+        // Half of the time, the rare testcase is selected
+        assert!(selected[0] > 400);
+        for common in &selected[1..] {
+            assert!(selected[0] > 2 * common);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Keeps track of the input sizes in the corpus, bucketed by power-of-two lengths.
+//!
+//! The [`SizeHistogramScheduler`] wraps another scheduler and keeps an [`InputSizeHistogramMetadata`] in the state up to date,
+//! so that it's easy to see if the corpus is trending towards huge inputs.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+use libafl_bolts::{impl_serdeany, HasLen};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::{Corpus, CorpusId, Testcase},
+    inputs::UsesInput,
+    observers::ObserversTuple,
+    schedulers::{RemovableScheduler, Scheduler},
+    state::{HasCorpus, UsesState},
+    Error, HasMetadata,
+};
+
+/// A histogram of the input sizes in the corpus.
+///
+/// Bucket `0` counts empty inputs, bucket `b > 0` counts inputs with a length in `[2^(b-1), 2^b)`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct InputSizeHistogramMetadata {
+    buckets: Vec<u64>,
+}
+
+impl_serdeany!(InputSizeHistogramMetadata);
+
+impl InputSizeHistogramMetadata {
+    /// Creates a new, empty [`InputSizeHistogramMetadata`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bucket for inputs of the given length
+    #[must_use]
+    pub fn bucket_for(len: usize) -> usize {
+        (usize::BITS - len.leading_zeros()) as usize
+    }
+
+    /// The (inclusive) range of lengths that end up in the given bucket
+    #[must_use]
+    pub fn bucket_range(bucket: usize) -> (usize, usize) {
+        if bucket == 0 {
+            (0, 0)
+        } else {
+            let lo = 1_usize << (bucket - 1);
+            (lo, lo.wrapping_mul(2).wrapping_sub(1))
+        }
+    }
+
+    /// The number of inputs per bucket
+    #[must_use]
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    /// The number of inputs in the histogram
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Adds an input of the given length
+    pub fn add(&mut self, len: usize) {
+        let bucket = Self::bucket_for(len);
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+    }
+
+    /// Removes an input of the given length
+    pub fn remove(&mut self, len: usize) {
+        if let Some(count) = self.buckets.get_mut(Self::bucket_for(len)) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// A short, human-readable summary of all non-empty buckets, i.e. `1-1:3 2-3:1 1024-2047:2`
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            if !summary.is_empty() {
+                summary.push(' ');
+            }
+            let (lo, hi) = Self::bucket_range(bucket);
+            write!(summary, "{lo}-{hi}:{count}").unwrap();
+        }
+        summary
+    }
+}
+
+/// A [`Scheduler`] that wraps another scheduler and keeps the [`InputSizeHistogramMetadata`] of the state up to date
+#[derive(Debug, Clone)]
+pub struct SizeHistogramScheduler<CS> {
+    base: CS,
+}
+
+impl<CS> SizeHistogramScheduler<CS>
+where
+    CS: Scheduler,
+    CS::State: HasCorpus + HasMetadata,
+    <CS::State as UsesInput>::Input: HasLen,
+{
+    /// Creates a new [`SizeHistogramScheduler`], wrapping the `base` scheduler
+    pub fn new(base: CS) -> Self {
+        Self { base }
+    }
+
+    /// The length of the input of a testcase that is no longer in the corpus, if it is still loaded
+    fn removed_len(testcase: &Testcase<<CS::State as UsesInput>::Input>) -> Option<usize> {
+        testcase.input().as_ref().map(HasLen::len)
+    }
+}
+
+impl<CS> UsesState for SizeHistogramScheduler<CS>
+where
+    CS: UsesState,
+{
+    type State = CS::State;
+}
+
+impl<CS> RemovableScheduler for SizeHistogramScheduler<CS>
+where
+    CS: RemovableScheduler,
+    CS::State: HasCorpus + HasMetadata,
+    <CS::State as UsesInput>::Input: HasLen,
+{
+    fn on_remove(
+        &mut self,
+        state: &mut CS::State,
+        idx: CorpusId,
+        testcase: &Option<Testcase<<CS::State as UsesInput>::Input>>,
+    ) -> Result<(), Error> {
+        self.base.on_remove(state, idx, testcase)?;
+        if let Some(len) = testcase.as_ref().and_then(Self::removed_len) {
+            state
+                .metadata_or_insert_with(InputSizeHistogramMetadata::new)
+                .remove(len);
+        }
+        Ok(())
+    }
+
+    fn on_replace(
+        &mut self,
+        state: &mut CS::State,
+        idx: CorpusId,
+        prev: &Testcase<<CS::State as UsesInput>::Input>,
+    ) -> Result<(), Error> {
+        self.base.on_replace(state, idx, prev)?;
+        let len = state
+            .corpus()
+            .get(idx)?
+            .borrow_mut()
+            .load_len(state.corpus())?;
+        let histogram = state.metadata_or_insert_with(InputSizeHistogramMetadata::new);
+        if let Some(prev_len) = Self::removed_len(prev) {
+            histogram.remove(prev_len);
+        }
+        histogram.add(len);
+        Ok(())
+    }
+}
+
+impl<CS> Scheduler for SizeHistogramScheduler<CS>
+where
+    CS: Scheduler,
+    CS::State: HasCorpus + HasMetadata,
+    <CS::State as UsesInput>::Input: HasLen,
+{
+    fn on_add(&mut self, state: &mut CS::State, idx: CorpusId) -> Result<(), Error> {
+        self.base.on_add(state, idx)?;
+        let len = state
+            .corpus()
+            .get(idx)?
+            .borrow_mut()
+            .load_len(state.corpus())?;
+        state
+            .metadata_or_insert_with(InputSizeHistogramMetadata::new)
+            .add(len);
+        Ok(())
+    }
+
+    fn on_evaluation<OT>(
+        &mut self,
+        state: &mut Self::State,
+        input: &<Self::State as UsesInput>::Input,
+        observers: &OT,
+    ) -> Result<(), Error>
+    where
+        OT: ObserversTuple<Self::State>,
+    {
+        self.base.on_evaluation(state, input, observers)
+    }
+
+    fn next(&mut self, state: &mut CS::State) -> Result<CorpusId, Error> {
+        self.base.next(state)
+    }
+
+    fn set_current_scheduled(
+        &mut self,
+        state: &mut Self::State,
+        next_idx: Option<CorpusId>,
+    ) -> Result<(), Error> {
+        self.base.set_current_scheduled(state, next_idx)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use crate::{
+        corpus::{Corpus, Testcase},
+        inputs::BytesInput,
+        schedulers::{
+            size_histogram::{InputSizeHistogramMetadata, SizeHistogramScheduler},
+            QueueScheduler, RemovableScheduler, Scheduler,
+        },
+        state::{test::test_std_state, HasCorpus},
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_size_histogram() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            InputSizeHistogramMetadata::register();
+        }
+
+        let mut state = test_std_state::<BytesInput>();
+        let mut scheduler = SizeHistogramScheduler::new(QueueScheduler::new());
+
+        let mut ids = vec![];
+        for len in [0, 1, 2, 3, 4, 1000, 1024] {
+            let id = state
+                .corpus_mut()
+                .add(Testcase::new(BytesInput::new(vec![0; len])))
+                .unwrap();
+            scheduler.on_add(&mut state, id).unwrap();
+            ids.push(id);
+        }
+
+        let histogram = state.metadata::<InputSizeHistogramMetadata>().unwrap();
+        assert_eq!(histogram.buckets(), &[1, 1, 2, 1, 0, 0, 0, 0, 0, 0, 1, 1]);
+        assert_eq!(histogram.count(), 7);
+        assert_eq!(
+            histogram.summary(),
+            "0-0:1 1-1:1 2-3:2 4-7:1 512-1023:1 1024-2047:1"
+        );
+
+        // remove the input of length 1000
+        let removed = state.corpus_mut().remove(ids[5]).unwrap();
+        scheduler
+            .on_remove(&mut state, ids[5], &Some(removed))
+            .unwrap();
+
+        let histogram = state.metadata::<InputSizeHistogramMetadata>().unwrap();
+        assert_eq!(histogram.buckets()[10], 0);
+        assert_eq!(histogram.count(), 6);
+    }
+}
@@ -5,7 +5,7 @@ use core::marker::PhantomData;
 use libafl_bolts::{HasLen, HasRefCnt};
 
 use crate::{
-    corpus::{Corpus, SchedulerTestcaseMetadata, Testcase},
+    corpus::{Corpus, Testcase},
     feedbacks::MapIndexesMetadata,
     schedulers::{
         minimizer::{IsFavoredMetadata, TopRatedsMetadata},
@@ -78,14 +78,12 @@ where
                 let cur_index = state.corpus().current().unwrap();
                 for idx in corpus.ids() {
                     let n_fuzz_entry = if cur_index == idx {
-                        entry
-                            .metadata::<SchedulerTestcaseMetadata>()?
-                            .n_fuzz_entry()
+                        entry.scheduler_metadata()?.n_fuzz_entry()
                     } else {
                         corpus
                             .get(idx)?
                             .borrow()
-                            .metadata::<SchedulerTestcaseMetadata>()?
+                            .scheduler_metadata()?
                             .n_fuzz_entry()
                     };
                     v += libm::log2(f64::from(psmeta.n_fuzz()[n_fuzz_entry]));
@@ -119,7 +117,7 @@ where
         };
 
         let favored = entry.has_metadata::<IsFavoredMetadata>();
-        let tcmeta = entry.metadata::<SchedulerTestcaseMetadata>()?;
+        let tcmeta = entry.scheduler_metadata()?;
 
         if q_exec_us * 0.1 > avg_exec_us {
             perf_score = 10.0;
@@ -283,7 +281,7 @@ where
         let mut weight = 1.0;
         let psmeta = state.metadata::<SchedulerMetadata>()?;
 
-        let tcmeta = entry.metadata::<SchedulerTestcaseMetadata>()?;
+        let tcmeta = entry.scheduler_metadata()?;
 
         // This means that this testcase has never gone through the calibration stage before1,
         // In this case we'll just return the default weight
@@ -344,3 +342,57 @@ where
         Ok(weight)
     }
 }
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use core::time::Duration;
+
+    use crate::{
+        corpus::{SchedulerTestcaseMetadata, Testcase},
+        inputs::BytesInput,
+        schedulers::{
+            powersched::SchedulerMetadata,
+            testcase_score::{CorpusPowerTestcaseScore, TestcaseScore},
+        },
+        state::test::test_std_state,
+        HasMetadata,
+    };
+
+    /// Entries discovered in a later queue cycle get a handicap, and more energy until they caught up
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_power_score_handicap() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            SchedulerMetadata::register();
+        }
+
+        let mut state = test_std_state::<BytesInput>();
+
+        // An average entry, so only the handicap changes the score
+        let mut psmeta = SchedulerMetadata::new(None);
+        psmeta.set_exec_time(Duration::from_millis(1));
+        psmeta.set_cycles(1);
+        psmeta.set_bitmap_size(10);
+        psmeta.set_bitmap_entries(1);
+        state.add_metadata(psmeta);
+
+        let score = |handicap| {
+            let mut testcase = Testcase::new(BytesInput::new(vec![0x41]));
+            testcase.set_exec_time(Duration::from_millis(1));
+            let mut tcmeta = SchedulerTestcaseMetadata::new(0);
+            tcmeta.set_bitmap_size(10);
+            tcmeta.set_handicap(handicap);
+            testcase.set_scheduler_metadata(tcmeta);
+            CorpusPowerTestcaseScore::compute(&state, &mut testcase).unwrap()
+        };
+
+        // The handicap decays by 4, then by 1, per scheduling of the entry, see `AflScheduler::on_next_metadata`
+        assert_eq!(score(0), 100.0);
+        assert_eq!(score(1), 200.0);
+        assert_eq!(score(5), 400.0);
+    }
+}
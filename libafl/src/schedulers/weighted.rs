@@ -16,7 +16,7 @@ use crate::{
     schedulers::{
         powersched::{PowerSchedule, SchedulerMetadata},
         testcase_score::{CorpusWeightTestcaseScore, TestcaseScore},
-        AflScheduler, RemovableScheduler, Scheduler,
+        AflScheduler, RemovableScheduler, RescoringScheduler, Scheduler,
     },
     state::{HasCorpus, HasRand, State, UsesState},
     Error, HasMetadata,
@@ -276,6 +276,21 @@ where
     }
 }
 
+impl<C, F, O, S> RescoringScheduler for WeightedScheduler<C, F, O, S>
+where
+    F: TestcaseScore<S>,
+    O: MapObserver,
+    S: HasCorpus + HasMetadata + HasRand + HasTestcase + State,
+    C: AsRef<O> + Named,
+{
+    /// Recomputes the weights of all testcases, and the alias table built from them
+    fn rescore(&mut self, state: &mut S) -> Result<(), Error> {
+        self.create_alias_table(state)?;
+        self.table_invalidated = false;
+        Ok(())
+    }
+}
+
 impl<C, F, O, S> Scheduler for WeightedScheduler<C, F, O, S>
 where
     F: TestcaseScore<S>,
@@ -274,27 +274,18 @@ where
             // log::trace!("time: {:#?}", testcase.exec_time());
 
             // If the testcase doesn't have its own `SchedulerTestcaseMetadata`, create it.
-            let data = if let Ok(metadata) = testcase.metadata_mut::<SchedulerTestcaseMetadata>() {
-                metadata
-            } else {
+            if testcase.scheduler_metadata().is_err() {
                 let depth = if let Some(parent_id) = testcase.parent_id() {
-                    if let Some(parent_metadata) = (*state.corpus().get(parent_id)?)
+                    (*state.corpus().get(parent_id)?)
                         .borrow()
-                        .metadata_map()
-                        .get::<SchedulerTestcaseMetadata>()
-                    {
-                        parent_metadata.depth() + 1
-                    } else {
-                        0
-                    }
+                        .depth()
+                        .map_or(0, |depth| depth + 1)
                 } else {
                     0
                 };
-                testcase.add_metadata(SchedulerTestcaseMetadata::new(depth));
-                testcase
-                    .metadata_mut::<SchedulerTestcaseMetadata>()
-                    .unwrap()
-            };
+                testcase.set_scheduler_metadata(SchedulerTestcaseMetadata::new(depth));
+            }
+            let data = testcase.scheduler_metadata_mut()?;
 
             data.set_cycle_and_time((total_time, iter));
             data.set_bitmap_size(bitmap_size);
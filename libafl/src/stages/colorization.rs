@@ -430,3 +430,103 @@ where
         }
     }
 }
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use core::ptr::addr_of_mut;
+
+    use libafl_bolts::{ownedref::OwnedMutSlice, tuples::tuple_list};
+
+    use crate::{
+        corpus::{Corpus, HasCurrentCorpusIdx, Testcase},
+        executors::ExitKind,
+        feedbacks::ConstFeedback,
+        fuzzer::test::test_fuzzer,
+        inputs::{BytesInput, HasBytesVec},
+        observers::{MapObserver, StdMapObserver},
+        schedulers::QueueScheduler,
+        stages::{ColorizationStage, Stage, TaintMetadata},
+        state::HasCorpus,
+        HasMetadata,
+    };
+
+    static mut MAP: [u8; 4] = [0; 4];
+
+    /// Only the first byte of the input is compared, the rest does not change the coverage
+    fn harness(input: &BytesInput) -> ExitKind {
+        unsafe {
+            let map = &mut *addr_of_mut!(MAP);
+            map[0] = 1;
+            if input.bytes()[0] == b'A' {
+                map[1] = 1;
+            }
+        }
+        ExitKind::Ok
+    }
+
+    #[test]
+    fn test_colorization() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            TaintMetadata::register();
+        }
+
+        let original = b"A0101010".to_vec();
+        let observer = unsafe {
+            StdMapObserver::from_ownedref(
+                "map",
+                OwnedMutSlice::from_raw_parts_mut(addr_of_mut!(MAP) as *mut u8, 4),
+            )
+        };
+        let mut stage = ColorizationStage::<_, _, _, StdMapObserver<u8, false>, _>::new(&observer);
+
+        let mut harness = harness;
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(observer),
+        );
+        let id = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(original.clone())))
+            .unwrap();
+        state.set_corpus_idx(id).unwrap();
+
+        stage
+            .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+            .unwrap();
+        drop(executor);
+
+        let taint = state.metadata::<TaintMetadata>().unwrap();
+        let colorized = taint.input_vec().clone();
+        assert_eq!(taint.ranges(), &vec![1..original.len()]);
+
+        // The compared byte is kept, everything else got colorized
+        assert_eq!(colorized[0], original[0]);
+        assert!(colorized[1..]
+            .iter()
+            .zip(&original[1..])
+            .all(|(colorized, original)| colorized != original));
+
+        // ... while the coverage is unchanged
+        let mut observer = unsafe {
+            StdMapObserver::from_ownedref(
+                "map",
+                OwnedMutSlice::from_raw_parts_mut(addr_of_mut!(MAP) as *mut u8, 4),
+            )
+        };
+        observer.reset_map().unwrap();
+        harness(&BytesInput::new(original));
+        let original_hash = observer.hash_simple();
+        observer.reset_map().unwrap();
+        harness(&BytesInput::new(colorized));
+        assert_eq!(observer.hash_simple(), original_hash);
+        assert_eq!(state.corpus().count(), 1);
+    }
+}
@@ -0,0 +1,289 @@
+//! The effector map stage, like the effector map of AFL's deterministic stage.
+//!
+//! It finds the bytes of a testcase that have an effect on the coverage, so that mutations,
+//! like the [`crate::mutators::EffectorBitFlipMutator`], can skip the bytes without effect.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt::Debug, marker::PhantomData};
+
+use libafl_bolts::{impl_serdeany, tuples::MatchName, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    executors::{Executor, HasObservers},
+    inputs::HasBytesVec,
+    observers::{MapObserver, ObserversTuple},
+    stages::{RetryRestartHelper, Stage},
+    state::{HasCorpus, HasCurrentTestcase, UsesState},
+    Error, HasMetadata, HasNamedMetadata,
+};
+
+/// The effector map of a testcase: which of its bytes change the coverage, if they get flipped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct EffectorMapMetadata {
+    effective: Vec<bool>,
+}
+
+impl_serdeany!(EffectorMapMetadata);
+
+impl EffectorMapMetadata {
+    /// Creates a new [`EffectorMapMetadata`], with one entry for each byte of the input
+    #[must_use]
+    pub fn new(effective: Vec<bool>) -> Self {
+        Self { effective }
+    }
+
+    /// If the byte at `idx` has an effect on the coverage.
+    /// Bytes past the end of the map, for example of a grown input, are unknown, and thus considered effective.
+    #[must_use]
+    pub fn is_effective(&self, idx: usize) -> bool {
+        self.effective.get(idx).copied().unwrap_or(true)
+    }
+
+    /// The indexes of the bytes with an effect on the coverage, for an input with `len` bytes
+    #[must_use]
+    pub fn effective_indexes(&self, len: usize) -> Vec<usize> {
+        (0..len).filter(|idx| self.is_effective(*idx)).collect()
+    }
+
+    /// The number of bytes in the effector map
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.effective.len()
+    }
+
+    /// If the effector map is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.effective.is_empty()
+    }
+}
+
+/// A stage that builds the [`EffectorMapMetadata`] of the current testcase.
+///
+/// It flips each byte of the input in turn, and marks the byte as effective, if the hash of the map changes.
+/// This costs one execution per byte, so it only runs once per testcase.
+#[derive(Clone, Debug)]
+pub struct EffectorMapStage<C, E, EM, O, Z> {
+    map_observer_name: String,
+    #[allow(clippy::type_complexity)]
+    phantom: PhantomData<(C, E, EM, O, Z)>,
+}
+
+impl<C, E, EM, O, Z> UsesState for EffectorMapStage<C, E, EM, O, Z>
+where
+    E: UsesState,
+{
+    type State = E::State;
+}
+
+impl<C, E, EM, O, Z> Named for EffectorMapStage<C, E, EM, O, Z>
+where
+    E: UsesState,
+{
+    fn name(&self) -> &str {
+        &self.map_observer_name
+    }
+}
+
+impl<C, E, EM, O, Z> Stage<E, EM, Z> for EffectorMapStage<C, E, EM, O, Z>
+where
+    EM: UsesState<State = E::State>,
+    E: HasObservers + Executor<EM, Z>,
+    E::State: HasCorpus + HasMetadata + HasNamedMetadata,
+    E::Input: HasBytesVec,
+    O: MapObserver,
+    C: AsRef<O> + Named,
+    Z: UsesState<State = E::State>,
+{
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut E::State,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        if state
+            .current_testcase()?
+            .has_metadata::<EffectorMapMetadata>()
+        {
+            return Ok(());
+        }
+
+        let mut input = state.current_input_cloned()?;
+        let orig_hash = self.map_hash_run(fuzzer, executor, state, manager, &input)?;
+
+        let mut effective = Vec::with_capacity(input.bytes().len());
+        for idx in 0..input.bytes().len() {
+            input.bytes_mut()[idx] ^= 0xff;
+            let hash = self.map_hash_run(fuzzer, executor, state, manager, &input)?;
+            input.bytes_mut()[idx] ^= 0xff;
+            effective.push(hash != orig_hash);
+        }
+
+        state
+            .current_testcase_mut()?
+            .add_metadata(EffectorMapMetadata::new(effective));
+        Ok(())
+    }
+
+    fn restart_progress_should_run(&mut self, state: &mut Self::State) -> Result<bool, Error> {
+        RetryRestartHelper::restart_progress_should_run(state, self, 3)
+    }
+
+    fn clear_restart_progress(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        RetryRestartHelper::clear_restart_progress(state, self)
+    }
+}
+
+impl<C, E, EM, O, Z> EffectorMapStage<C, E, EM, O, Z>
+where
+    EM: UsesState<State = E::State>,
+    E: HasObservers + Executor<EM, Z>,
+    O: MapObserver,
+    C: AsRef<O> + Named,
+    Z: UsesState<State = E::State>,
+{
+    /// Creates a new [`EffectorMapStage`], comparing the map of the given observer
+    #[must_use]
+    pub fn new(map_observer: &C) -> Self {
+        Self {
+            map_observer_name: map_observer.name().to_string(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Runs the target and hashes the map, before the post exec of the observers, like hitcounts, changes it
+    fn map_hash_run(
+        &self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut E::State,
+        manager: &mut EM,
+        input: &E::Input,
+    ) -> Result<u64, Error> {
+        executor.observers_mut().pre_exec_all(state, input)?;
+
+        let exit_kind = executor.run_target(fuzzer, state, manager, input)?;
+
+        let hash = executor
+            .observers()
+            .match_name::<C>(&self.map_observer_name)
+            .ok_or_else(|| Error::key_not_found("MapObserver not found".to_string()))?
+            .as_ref()
+            .hash_simple();
+
+        executor
+            .observers_mut()
+            .post_exec_all(state, input, &exit_kind)?;
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use core::ptr::addr_of_mut;
+
+    use libafl_bolts::{ownedref::OwnedMutSlice, tuples::tuple_list};
+
+    use crate::{
+        corpus::{Corpus, HasCurrentCorpusIdx, Testcase},
+        executors::ExitKind,
+        feedbacks::ConstFeedback,
+        fuzzer::test::test_fuzzer,
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{EffectorBitFlipMutator, MutationResult, Mutator},
+        observers::StdMapObserver,
+        schedulers::QueueScheduler,
+        stages::{EffectorMapMetadata, EffectorMapStage, Stage},
+        state::HasCorpus,
+        HasMetadata,
+    };
+
+    static mut MAP: [u8; 4] = [0; 4];
+
+    /// Only the bytes at index 1 and 3 change the coverage
+    fn harness(input: &BytesInput) -> ExitKind {
+        unsafe {
+            let map = &mut *addr_of_mut!(MAP);
+            map[0] = 1;
+            if input.bytes()[1] == b'B' {
+                map[1] = 1;
+            }
+            if input.bytes()[3] == b'D' {
+                map[2] = 1;
+            }
+        }
+        ExitKind::Ok
+    }
+
+    #[test]
+    fn test_effector_map() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            EffectorMapMetadata::register();
+        }
+
+        let original = b"ABCDEF".to_vec();
+        let observer = unsafe {
+            StdMapObserver::from_ownedref(
+                "map",
+                OwnedMutSlice::from_raw_parts_mut(addr_of_mut!(MAP) as *mut u8, 4),
+            )
+        };
+        let mut stage = EffectorMapStage::<_, _, _, StdMapObserver<u8, false>, _>::new(&observer);
+
+        let mut harness = harness;
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(observer),
+        );
+        let id = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(original.clone())))
+            .unwrap();
+        state.set_corpus_idx(id).unwrap();
+
+        stage
+            .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+            .unwrap();
+        drop(executor);
+
+        let effector = state
+            .corpus()
+            .get(id)
+            .unwrap()
+            .borrow()
+            .metadata::<EffectorMapMetadata>()
+            .unwrap()
+            .clone();
+        assert_eq!(effector.effective_indexes(original.len()), vec![1, 3]);
+
+        // The mutations only touch the effective bytes
+        let mut mutator = EffectorBitFlipMutator::new();
+        for _ in 0..100 {
+            let mut input = BytesInput::new(original.clone());
+            assert_eq!(
+                mutator.mutate(&mut state, &mut input).unwrap(),
+                MutationResult::Mutated
+            );
+            for (idx, (mutated, original)) in input.bytes().iter().zip(&original).enumerate() {
+                assert!(mutated == original || effector.is_effective(idx));
+            }
+        }
+    }
+}
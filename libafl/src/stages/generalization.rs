@@ -6,12 +6,11 @@ use alloc::{
 };
 use core::{fmt::Debug, marker::PhantomData};
 
-use libafl_bolts::{AsSlice, Named};
+use libafl_bolts::Named;
 
 use crate::{
     corpus::{Corpus, HasCurrentCorpusIdx},
     executors::{Executor, HasObservers},
-    feedbacks::map::MapNoveltiesMetadata,
     inputs::{BytesInput, GeneralizedInputMetadata, GeneralizedItem, HasBytesVec, UsesInput},
     mark_feature_time,
     observers::{CanTrack, MapObserver, ObserversTuple},
@@ -105,15 +104,15 @@ where
 
             let payload: Vec<_> = input.bytes().iter().map(|&x| Some(x)).collect();
             let original = input.clone();
-            let meta = entry.metadata_map().get::<MapNoveltiesMetadata>().ok_or_else(|| {
+            let novelties = entry.novelties().ok_or_else(|| {
                     Error::key_not_found(format!(
                         "MapNoveltiesMetadata needed for GeneralizationStage not found in testcase #{corpus_idx} (check the arguments of MapFeedback::new(...))"
                     ))
                 })?;
-            if meta.as_slice().is_empty() {
+            if novelties.is_empty() {
                 return Ok(()); // don't generalise inputs which don't have novelties
             }
-            (payload, original, meta.as_slice().to_vec())
+            (payload, original, novelties.to_vec())
         };
 
         // Do not generalized unstable inputs
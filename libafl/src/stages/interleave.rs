@@ -0,0 +1,278 @@
+//! The [`InterleaveStage`] merges the chunks of two testcases in different orders.
+//!
+//! Stateful targets, such as protocol implementations, often process their input as a sequence of messages.
+//! Interleaving the messages of two testcases explores message sequences neither of them contains.
+
+use alloc::vec::Vec;
+use core::{any::type_name, marker::PhantomData};
+
+use libafl_bolts::{rands::Rand, Named};
+
+use crate::{
+    corpus::{Corpus, HasCurrentCorpusIdx},
+    fuzzer::Evaluator,
+    inputs::HasBytesVec,
+    stages::{RetryRestartHelper, Stage},
+    state::{HasCorpus, HasCurrentTestcase, HasRand, UsesState},
+    Error, HasNamedMetadata,
+};
+
+/// Creates up to `max_children` distinct interleavings of the chunks of `first` and `second`.
+///
+/// Both inputs are split into chunks of `chunk_size` bytes. Each interleaving contains all chunks of both inputs,
+/// and keeps the chunks of each input in their original order. The plain concatenations, and the strict alternations,
+/// come first, followed by random interleavings.
+pub fn interleavings<R>(
+    rand: &mut R,
+    first: &[u8],
+    second: &[u8],
+    chunk_size: usize,
+    max_children: usize,
+) -> Vec<Vec<u8>>
+where
+    R: Rand,
+{
+    let first: Vec<&[u8]> = first.chunks(chunk_size.max(1)).collect();
+    let second: Vec<&[u8]> = second.chunks(chunk_size.max(1)).collect();
+    let total = first.len() + second.len();
+
+    // The order of the chunks, `true` taking the next chunk of `first`
+    let mut orders: Vec<Vec<bool>> = vec![
+        (0..total).map(|idx| idx < first.len()).collect(),
+        (0..total).map(|idx| idx >= second.len()).collect(),
+    ];
+    for first_starts in [true, false] {
+        let (mut left, mut right) = (first.len(), second.len());
+        let mut take_first = first_starts;
+        let mut order = Vec::with_capacity(total);
+        while left + right > 0 {
+            let take = if left == 0 || right == 0 {
+                left > 0
+            } else {
+                take_first
+            };
+            if take {
+                left -= 1;
+            } else {
+                right -= 1;
+            }
+            order.push(take);
+            take_first = !take_first;
+        }
+        orders.push(order);
+    }
+
+    let mut children: Vec<Vec<u8>> = Vec::new();
+    let add_child = |order: &[bool], children: &mut Vec<Vec<u8>>| {
+        let (mut first_chunks, mut second_chunks) = (first.iter(), second.iter());
+        let child: Vec<u8> = order
+            .iter()
+            .filter_map(|take_first| {
+                if *take_first {
+                    first_chunks.next()
+                } else {
+                    second_chunks.next()
+                }
+            })
+            .flat_map(|chunk| chunk.iter().copied())
+            .collect();
+        if children.len() < max_children && !children.contains(&child) {
+            children.push(child);
+        }
+    };
+    for order in &orders {
+        add_child(order, &mut children);
+    }
+
+    // Random interleavings, giving up after too many duplicates
+    for _ in 0..max_children.saturating_mul(4) {
+        if children.len() >= max_children {
+            break;
+        }
+        let (mut left, mut right) = (first.len() as u64, second.len() as u64);
+        let mut order = Vec::with_capacity(total);
+        while left + right > 0 {
+            // Each remaining chunk is equally likely to come next
+            let take = rand.below(left + right) < left;
+            if take {
+                left -= 1;
+            } else {
+                right -= 1;
+            }
+            order.push(take);
+        }
+        add_child(&order, &mut children);
+    }
+    children
+}
+
+/// A [`Stage`] that interleaves the chunks of the current testcase and another, random, testcase,
+/// and evaluates each of the resulting children, see [`interleavings`].
+#[derive(Clone, Debug)]
+pub struct InterleaveStage<E, EM, Z> {
+    chunk_size: usize,
+    max_children: usize,
+    phantom: PhantomData<(E, EM, Z)>,
+}
+
+impl<E, EM, Z> InterleaveStage<E, EM, Z> {
+    /// Creates a new [`InterleaveStage`], splitting the inputs into chunks of `chunk_size` bytes,
+    /// and evaluating up to `max_children` interleavings per testcase
+    #[must_use]
+    pub fn new(chunk_size: usize, max_children: usize) -> Self {
+        Self {
+            chunk_size,
+            max_children,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E, EM, Z> UsesState for InterleaveStage<E, EM, Z>
+where
+    Z: UsesState,
+{
+    type State = Z::State;
+}
+
+impl<E, EM, Z> Named for InterleaveStage<E, EM, Z> {
+    fn name(&self) -> &str {
+        type_name::<Self>()
+    }
+}
+
+impl<E, EM, Z> Stage<E, EM, Z> for InterleaveStage<E, EM, Z>
+where
+    E: UsesState<State = Z::State>,
+    EM: UsesState<State = Z::State>,
+    Z: Evaluator<E, EM>,
+    Z::Input: HasBytesVec,
+    Z::State: HasCorpus + HasCurrentCorpusIdx + HasRand + HasNamedMetadata,
+{
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut Z::State,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let Some(current) = state.current_corpus_idx()? else {
+            return Err(Error::illegal_state(
+                "state is not currently processing a corpus index",
+            ));
+        };
+        if state.corpus().count() < 2 {
+            return Ok(());
+        }
+
+        // Any testcase, but the current one
+        let mut other = crate::random_corpus_id!(state.corpus(), state.rand_mut());
+        if other == current {
+            other = state
+                .corpus()
+                .next(current)
+                .or_else(|| state.corpus().first())
+                .ok_or(Error::empty("The corpus is empty"))?;
+        }
+
+        let input = state.current_input_cloned()?;
+        let other = state.corpus().cloned_input_for_id(other)?;
+        let children = interleavings(
+            state.rand_mut(),
+            input.bytes(),
+            other.bytes(),
+            self.chunk_size,
+            self.max_children,
+        );
+
+        for bytes in children {
+            let mut child = input.clone();
+            *child.bytes_mut() = bytes;
+            fuzzer.evaluate_input(state, executor, manager, child)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn restart_progress_should_run(&mut self, state: &mut Self::State) -> Result<bool, Error> {
+        // Make sure we don't get stuck crashing on a single testcase
+        RetryRestartHelper::restart_progress_should_run(state, self, 3)
+    }
+
+    #[inline]
+    fn clear_restart_progress(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        RetryRestartHelper::clear_restart_progress(state, self)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use alloc::vec::Vec;
+
+    use libafl_bolts::tuples::tuple_list;
+
+    use crate::{
+        corpus::{Corpus, HasCurrentCorpusIdx, Testcase},
+        executors::ExitKind,
+        feedbacks::ConstFeedback,
+        fuzzer::test::test_fuzzer,
+        inputs::{BytesInput, HasBytesVec},
+        schedulers::QueueScheduler,
+        stages::{InterleaveStage, Stage},
+        state::HasCorpus,
+    };
+
+    /// If the `chunks` appear in `child` in their order, possibly with other chunks in between
+    fn contains_in_order(child: &[&[u8]], chunks: &[&[u8]]) -> bool {
+        let mut remaining = child.iter();
+        chunks
+            .iter()
+            .all(|chunk| remaining.any(|other| other == chunk))
+    }
+
+    #[test]
+    fn test_interleave_stage() {
+        let mut executed = Vec::new();
+        {
+            let mut harness = |input: &BytesInput| {
+                executed.push(input.bytes().to_vec());
+                ExitKind::Ok
+            };
+            let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+                QueueScheduler::new(),
+                ConstFeedback::new(false),
+                ConstFeedback::new(false),
+                (),
+                &mut harness,
+                tuple_list!(),
+            );
+            let first = state
+                .corpus_mut()
+                .add(Testcase::new(b"a1a2a3".to_vec().into()))
+                .unwrap();
+            state
+                .corpus_mut()
+                .add(Testcase::new(b"b1b2b3".to_vec().into()))
+                .unwrap();
+            state.set_corpus_idx(first).unwrap();
+            let mut stage = InterleaveStage::new(2, 8);
+            stage
+                .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+                .unwrap();
+        }
+
+        assert!(!executed.is_empty() && executed.len() <= 8);
+        let first_chunks: [&[u8]; 3] = [b"a1", b"a2", b"a3"];
+        let second_chunks: [&[u8]; 3] = [b"b1", b"b2", b"b3"];
+        for child in &executed {
+            let chunks: Vec<&[u8]> = child.chunks(2).collect();
+            assert_eq!(chunks.len(), 6);
+            assert!(contains_in_order(&chunks, &first_chunks));
+            assert!(contains_in_order(&chunks, &second_chunks));
+        }
+        // The chunks are interleaved, not just concatenated
+        assert!(executed.contains(&b"a1b1a2b2a3b3".to_vec()));
+        assert!(executed.contains(&b"b1a1b2a2b3a3".to_vec()));
+    }
+}
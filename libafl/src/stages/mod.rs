@@ -5,7 +5,7 @@ Other stages may enrich [`crate::corpus::Testcase`]s with metadata.
 */
 
 use alloc::{boxed::Box, vec::Vec};
-use core::{any, marker::PhantomData};
+use core::{any, cmp::Reverse, marker::PhantomData, time::Duration};
 
 pub use calibrate::CalibrationStage;
 pub use colorization::*;
@@ -15,16 +15,22 @@ pub use concolic::ConcolicTracingStage;
 pub use concolic::SimpleConcolicMutationalStage;
 #[cfg(feature = "std")]
 pub use dump::*;
+pub use effector::{EffectorMapMetadata, EffectorMapStage};
 pub use generalization::GeneralizationStage;
 use hashbrown::HashSet;
+pub use interleave::{interleavings, InterleaveStage};
 use libafl_bolts::{
     impl_serdeany,
     tuples::{HasConstLen, IntoVec},
     Named,
 };
 pub use logics::*;
-pub use mutational::{MutationalStage, StdMutationalStage};
+pub use mutational::{testcase_seed, EnergyMetadata, MutationalStage, StdMutationalStage};
+#[cfg(feature = "std")]
+pub use parallel::ParallelMutationalStage;
 pub use power::{PowerMutationalStage, StdPowerMutationalStage};
+pub use repairing::RepairingStage;
+pub use rescore::RescoreStage;
 use serde::{Deserialize, Serialize};
 pub use stats::AflStatsStage;
 #[cfg(feature = "unicode")]
@@ -37,6 +43,7 @@ pub use tmin::{
 pub use tracing::{ShadowTracingStage, TracingStage};
 pub use tuneable::*;
 use tuple_list::NonEmptyTuple;
+pub use window::{focus_window, FocusOffsetMetadata, WindowedHavocStage};
 
 use crate::{
     corpus::{CorpusId, HasCurrentCorpusIdx},
@@ -46,7 +53,7 @@ use crate::{
     observers::ObserversTuple,
     schedulers::Scheduler,
     stages::push::PushStage,
-    state::{HasCorpus, HasExecutions, HasLastReportTime, HasRand, State, UsesState},
+    state::{HasClock, HasCorpus, HasExecutions, HasLastReportTime, HasRand, State, UsesState},
     Error, EvaluatorObservers, ExecutesInput, ExecutionProcessor, HasMetadata, HasNamedMetadata,
     HasScheduler,
 };
@@ -62,9 +69,15 @@ pub mod colorization;
 pub mod concolic;
 #[cfg(feature = "std")]
 pub mod dump;
+pub mod effector;
 pub mod generalization;
+pub mod interleave;
 pub mod logics;
+#[cfg(feature = "std")]
+pub mod parallel;
 pub mod power;
+pub mod repairing;
+pub mod rescore;
 pub mod stats;
 #[cfg(feature = "unicode")]
 pub mod string;
@@ -72,6 +85,10 @@ pub mod string;
 pub mod sync;
 pub mod tracing;
 pub mod tuneable;
+pub mod window;
+
+/// The default [`Stage::priority`]
+pub const DEFAULT_STAGE_PRIORITY: i32 = 0;
 
 /// A stage is one step in the fuzzing process.
 /// Multiple stages will be scheduled one by one for each input.
@@ -91,6 +108,20 @@ where
     /// Clear the current status tracking of the associated stage
     fn clear_restart_progress(&mut self, state: &mut Self::State) -> Result<(), Error>;
 
+    /// The priority of this stage, used by [`StagesTuple::perform_all_by_priority`].
+    /// Stages with a higher priority run first, stages with the same priority run in registration order.
+    #[inline]
+    fn priority(&self) -> i32 {
+        DEFAULT_STAGE_PRIORITY
+    }
+
+    /// Returns `false` if this stage should be skipped for the current testcase,
+    /// for example based on its metadata. Checked by [`Stage::perform_restartable`], before anything else.
+    #[inline]
+    fn should_run(&mut self, _state: &mut Self::State) -> Result<bool, Error> {
+        Ok(true)
+    }
+
     /// Run the stage.
     ///
     /// Before a call to perform, [`Stage::restart_progress_should_run`] will be (must be!) called.
@@ -112,6 +143,9 @@ where
         state: &mut Self::State,
         manager: &mut EM,
     ) -> Result<(), Error> {
+        if !self.should_run(state)? {
+            return Ok(());
+        }
         if self.restart_progress_should_run(state)? {
             self.perform(fuzzer, executor, state, manager)?;
         }
@@ -135,6 +169,62 @@ where
         state: &mut S,
         manager: &mut EM,
     ) -> Result<(), Error>;
+
+    /// Appends the [`Stage::priority`] of each stage to `priorities`, in registration order
+    fn priorities(&self, priorities: &mut Vec<i32>);
+
+    /// Performs the `n`-th stage in this tuple, in registration order
+    fn perform_nth(
+        &mut self,
+        n: usize,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error>;
+
+    /// Performs all `Stages` in this tuple, ordered by their [`Stage::priority`].
+    /// Stages with the same priority run in registration order,
+    /// so if no stage changes its priority, this is the same as [`StagesTuple::perform_all`].
+    fn perform_all_by_priority(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let mut priorities = Vec::new();
+        self.priorities(&mut priorities);
+        if priorities.windows(2).all(|w| w[0] == w[1]) {
+            return self.perform_all(fuzzer, executor, state, manager);
+        }
+
+        // `sort_by_key` is stable, so equal priorities keep their registration order
+        let mut order: Vec<usize> = (0..priorities.len()).collect();
+        order.sort_by_key(|idx| Reverse(priorities[*idx]));
+
+        for (pos, idx) in order.into_iter().enumerate() {
+            match state.current_stage()? {
+                Some(resume) if pos < resume => {
+                    // do nothing; we are resuming
+                    continue;
+                }
+                // we are resuming this stage
+                Some(_) => {}
+                None => state.set_stage(pos)?,
+            }
+            self.perform_nth(idx, fuzzer, executor, state, manager)?;
+            state.clear_stage()?;
+        }
+
+        if state.current_stage()?.is_some() {
+            Err(Error::illegal_state(
+                "Got to the end of the stages without completing resume.",
+            ))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl<E, EM, S, Z> StagesTuple<E, EM, S, Z> for ()
@@ -159,6 +249,19 @@ where
             Ok(())
         }
     }
+
+    fn priorities(&self, _priorities: &mut Vec<i32>) {}
+
+    fn perform_nth(
+        &mut self,
+        n: usize,
+        _: &mut Z,
+        _: &mut E,
+        _: &mut S,
+        _: &mut EM,
+    ) -> Result<(), Error> {
+        Err(Error::key_not_found(format!("No stage at index {n}")))
+    }
 }
 
 impl<Head, Tail, E, EM, Z> StagesTuple<E, EM, Head::State, Z> for (Head, Tail)
@@ -206,6 +309,26 @@ where
         // Execute the remaining stages
         self.1.perform_all(fuzzer, executor, state, manager)
     }
+
+    fn priorities(&self, priorities: &mut Vec<i32>) {
+        priorities.push(self.0.priority());
+        self.1.priorities(priorities);
+    }
+
+    fn perform_nth(
+        &mut self,
+        n: usize,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut Head::State,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        if n == 0 {
+            self.0.perform_restartable(fuzzer, executor, state, manager)
+        } else {
+            self.1.perform_nth(n - 1, fuzzer, executor, state, manager)
+        }
+    }
 }
 
 impl<Head, Tail, E, EM, Z>
@@ -279,6 +402,23 @@ where
         self.iter_mut()
             .try_for_each(|x| x.perform_restartable(fuzzer, executor, state, manager))
     }
+
+    fn priorities(&self, priorities: &mut Vec<i32>) {
+        priorities.extend(self.iter().map(|x| x.priority()));
+    }
+
+    fn perform_nth(
+        &mut self,
+        n: usize,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        self.get_mut(n)
+            .ok_or_else(|| Error::key_not_found(format!("No stage at index {n}")))?
+            .perform_restartable(fuzzer, executor, state, manager)
+    }
 }
 
 /// A [`Stage`] that will call a closure
@@ -642,17 +782,52 @@ impl ExecutionCountRestartHelper {
     }
 }
 
+/// The wall-clock deadline of a single run of a stage with a time budget,
+/// for example [`StdMutationalStage::with_time_budget`].
+///
+/// Stages check it in their iteration loop, and return early once it passed.
+#[derive(Debug, Clone, Copy)]
+pub struct StageDeadline {
+    deadline: Option<Duration>,
+}
+
+impl StageDeadline {
+    /// Starts the deadline, `budget` from now on the clock of the `state`, or no deadline at all, if `budget` is `None`
+    #[must_use]
+    pub fn new<S>(state: &S, budget: Option<Duration>) -> Self
+    where
+        S: HasClock,
+    {
+        Self {
+            deadline: budget.map(|budget| state.now() + budget),
+        }
+    }
+
+    /// If the deadline passed, on the clock of the `state`
+    #[must_use]
+    pub fn exceeded<S>(&self, state: &S) -> bool
+    where
+        S: HasClock,
+    {
+        matches!(self.deadline, Some(deadline) if state.now() >= deadline)
+    }
+}
+
 #[cfg(test)]
 pub mod test {
-    use core::marker::PhantomData;
+    use alloc::{rc::Rc, vec::Vec};
+    use core::{cell::RefCell, marker::PhantomData};
 
-    use libafl_bolts::{impl_serdeany, Error, Named};
+    use libafl_bolts::{impl_serdeany, tuples::tuple_list, Error, Named};
     use serde::{Deserialize, Serialize};
 
     use crate::{
         corpus::{Corpus, HasCurrentCorpusIdx, Testcase},
+        events::NopEventManager,
+        executors::test::NopExecutor,
+        fuzzer::test::NopFuzzer,
         inputs::NopInput,
-        stages::{RetryRestartHelper, Stage},
+        stages::{RetryRestartHelper, Stage, StagesTuple},
         state::{test::test_std_state, HasCorpus, State, UsesState},
         HasMetadata,
     };
@@ -733,6 +908,96 @@ pub mod test {
         }
     }
 
+    /// Records the order in which the stages run
+    #[derive(Debug)]
+    pub struct PriorityStage<S> {
+        id: usize,
+        priority: i32,
+        skip: bool,
+        order: Rc<RefCell<Vec<usize>>>,
+        phantom: PhantomData<S>,
+    }
+
+    impl<S> PriorityStage<S> {
+        fn new(id: usize, priority: i32, skip: bool, order: &Rc<RefCell<Vec<usize>>>) -> Self {
+            Self {
+                id,
+                priority,
+                skip,
+                order: order.clone(),
+                phantom: PhantomData,
+            }
+        }
+    }
+
+    impl<S> UsesState for PriorityStage<S>
+    where
+        S: State,
+    {
+        type State = S;
+    }
+
+    impl<E, EM, Z> Stage<E, EM, Z> for PriorityStage<Z::State>
+    where
+        E: UsesState<State = Z::State>,
+        EM: UsesState<State = Z::State>,
+        Z: UsesState,
+    {
+        fn perform(
+            &mut self,
+            _fuzzer: &mut Z,
+            _executor: &mut E,
+            _state: &mut Self::State,
+            _manager: &mut EM,
+        ) -> Result<(), Error> {
+            self.order.borrow_mut().push(self.id);
+            Ok(())
+        }
+
+        fn restart_progress_should_run(&mut self, _state: &mut Self::State) -> Result<bool, Error> {
+            Ok(true)
+        }
+
+        fn clear_restart_progress(&mut self, _state: &mut Self::State) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+
+        fn should_run(&mut self, _state: &mut Self::State) -> Result<bool, Error> {
+            Ok(!self.skip)
+        }
+    }
+
+    #[test]
+    fn test_stage_priority() -> Result<(), Error> {
+        let mut state = test_std_state::<NopInput>();
+        let order = Rc::new(RefCell::new(vec![]));
+
+        let mut stages = tuple_list!(
+            // a "splice" stage, registered first, but with a low priority
+            PriorityStage::new(0, -10, false, &order),
+            PriorityStage::new(1, 0, false, &order),
+            PriorityStage::new(2, 0, true, &order),
+            // a "calibration" stage, registered last, but with a high priority
+            PriorityStage::new(3, 10, false, &order),
+        );
+
+        stages.perform_all_by_priority(
+            &mut NopFuzzer::new(),
+            &mut NopExecutor::new(),
+            &mut state,
+            &mut NopEventManager::new(),
+        )?;
+
+        // The skipped stage never runs
+        assert_eq!(*order.borrow(), vec![3, 1, 0]);
+
+        Ok(())
+    }
+
     #[test]
     fn test_tries_progress() -> Result<(), Error> {
         // # Safety
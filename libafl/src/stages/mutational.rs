@@ -1,19 +1,22 @@
 //| The [`MutationalStage`] is the default stage used during fuzzing.
 //! For the current input, it will perform a range of random mutations, and then run them in the executor.
 
-use core::{any::type_name, marker::PhantomData};
+use core::{any::type_name, marker::PhantomData, time::Duration};
 
-use libafl_bolts::{rands::Rand, Named};
+use hashbrown::HashMap;
+use libafl_bolts::{impl_serdeany, rands::Rand, Named};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    corpus::{Corpus, CorpusId, Testcase},
+    corpus::{Corpus, CorpusId, HasCurrentCorpusIdx, Testcase},
     fuzzer::Evaluator,
     inputs::Input,
     mark_feature_time,
     mutators::{MultiMutator, MutationResult, Mutator},
-    stages::{ExecutionCountRestartHelper, RetryRestartHelper, Stage},
+    schedulers::SchedulerMetadata,
+    stages::{ExecutionCountRestartHelper, RetryRestartHelper, Stage, StageDeadline},
     start_timer,
-    state::{HasCorpus, HasCurrentTestcase, HasExecutions, HasRand, UsesState},
+    state::{HasClock, HasCorpus, HasCurrentTestcase, HasExecutions, HasRand, UsesState},
     Error, HasMetadata, HasNamedMetadata,
 };
 #[cfg(feature = "introspection")]
@@ -81,7 +84,7 @@ where
     M: Mutator<I, Self::State>,
     EM: UsesState<State = Self::State>,
     Z: Evaluator<E, EM, State = Self::State>,
-    Self::State: HasCorpus,
+    Self::State: HasCorpus + HasClock,
     I: MutatedTransform<Self::Input, Self::State> + Clone,
 {
     /// The mutator registered for this stage
@@ -96,6 +99,32 @@ where
     /// Gets the number of executions this mutator already did since it got first called in this fuzz round.
     fn execs_since_progress_start(&mut self, state: &mut Z::State) -> Result<u64, Error>;
 
+    /// If the intermediate results of stacked mutations should be evaluated, too,
+    /// see [`Mutator::mutate_with_intermediates`].
+    #[inline]
+    fn evaluates_intermediates(&self) -> bool {
+        false
+    }
+
+    /// The wall-clock budget of a single run of this stage, if any.
+    /// Once exceeded, the stage returns early, without finishing all of its iterations.
+    #[inline]
+    fn time_budget(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Repairs a mutated input in place, right before it is evaluated, e.g., to fix up a checksum.
+    /// Returns `false` to drop the input without executing it.
+    /// Intermediate inputs, see [`Self::evaluates_intermediates`], are evaluated as they are.
+    #[inline]
+    fn repair_input(
+        &mut self,
+        _state: &mut Z::State,
+        _input: &mut Self::Input,
+    ) -> Result<bool, Error> {
+        Ok(true)
+    }
+
     /// Runs this (mutational) stage for the given testcase
     #[allow(clippy::cast_possible_wrap)] // more than i32 stages on 32 bit system - highly unlikely...
     fn perform_mutational(
@@ -122,20 +151,46 @@ where
         drop(testcase);
         mark_feature_time!(state, PerfFeature::GetInputFromCorpus);
 
+        let deadline = StageDeadline::new(state, self.time_budget());
+        // If any child made it to the corpus
+        let mut found = false;
         for _ in 0..num {
+            if deadline.exceeded(state) {
+                break;
+            }
             let mut input = input.clone();
 
             start_timer!(state);
-            let mutated = self.mutator_mut().mutate(state, &mut input)?;
+            let mutated = if self.evaluates_intermediates() {
+                self.mutator_mut().mutate_with_intermediates(
+                    state,
+                    &mut input,
+                    &mut |state: &mut Z::State, intermediate: &I| {
+                        let (untransformed, post) =
+                            intermediate.clone().try_transform_into(state)?;
+                        let (_, corpus_idx) =
+                            fuzzer.evaluate_input(state, executor, manager, untransformed)?;
+                        found |= corpus_idx.is_some();
+                        post.post_exec(state, corpus_idx)
+                    },
+                )?
+            } else {
+                self.mutator_mut().mutate(state, &mut input)?
+            };
             mark_feature_time!(state, PerfFeature::Mutate);
 
             if mutated == MutationResult::Skipped {
                 continue;
             }
 
+            let (mut untransformed, post) = input.try_transform_into(state)?;
+            if !self.repair_input(state, &mut untransformed)? {
+                continue;
+            }
+
             // Time is measured directly the `evaluate_input` function
-            let (untransformed, post) = input.try_transform_into(state)?;
             let (_, corpus_idx) = fuzzer.evaluate_input(state, executor, manager, untransformed)?;
+            found |= corpus_idx.is_some();
 
             start_timer!(state);
             self.mutator_mut().post_exec(state, corpus_idx)?;
@@ -143,14 +198,59 @@ where
             mark_feature_time!(state, PerfFeature::MutatePostExec);
         }
 
+        let mut testcase = state.current_testcase_mut()?;
+        let dry_streak = if found { 0 } else { testcase.dry_streak() + 1 };
+        testcase.set_dry_streak(dry_streak);
+
         Ok(())
     }
 }
 
+/// The executions each testcase received in the current corpus cycle, see [`StdMutationalStage::with_energy_cap`]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct EnergyMetadata {
+    /// The queue cycle of the scheduler the executions belong to, see [`SchedulerMetadata::queue_cycles`]
+    pub cycles: u64,
+    /// The executions of each testcase in the current cycle
+    pub spent: HashMap<CorpusId, u64>,
+}
+
+impl_serdeany!(EnergyMetadata);
+
+impl EnergyMetadata {
+    /// The executions of the testcase `idx` in the current cycle
+    #[must_use]
+    pub fn spent(&self, idx: CorpusId) -> u64 {
+        self.spent.get(&idx).copied().unwrap_or(0)
+    }
+
+    /// Starts over, if the scheduler started the queue cycle `queue_cycles` since the last call
+    fn enter(&mut self, queue_cycles: u64) {
+        if queue_cycles != self.cycles {
+            self.cycles = queue_cycles;
+            self.spent.clear();
+        }
+    }
+}
+
 /// Default value, how many iterations each stage gets, as an upper bound.
 /// It may randomly continue earlier.
 pub static DEFAULT_MUTATIONAL_MAX_ITERATIONS: u64 = 128;
 
+/// The seed of the [`Rand`] used to mutate the testcase `corpus_idx`,
+/// derived from the `master_seed`, see [`StdMutationalStage::with_per_testcase_seed`]
+#[must_use]
+pub fn testcase_seed(master_seed: u64, corpus_idx: CorpusId) -> u64 {
+    master_seed
+        ^ (corpus_idx.0 as u64)
+            .wrapping_add(1)
+            .wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
 /// The default mutational stage
 #[derive(Clone, Debug)]
 pub struct StdMutationalStage<E, EM, I, M, Z> {
@@ -158,6 +258,14 @@ pub struct StdMutationalStage<E, EM, I, M, Z> {
     mutator: M,
     /// The maximum amount of iterations we should do each round
     max_iterations: u64,
+    /// If the intermediate results of stacked mutations are evaluated, too
+    evaluate_intermediates: bool,
+    /// The master seed to derive the seed of each testcase from, if mutations are reproducible per testcase
+    master_seed: Option<u64>,
+    /// The wall-clock budget of a single run of this stage
+    time_budget: Option<Duration>,
+    /// The maximum executions of each testcase per corpus cycle
+    energy_cap: Option<u64>,
     /// The progress helper for this mutational stage
     restart_helper: ExecutionCountRestartHelper,
     #[allow(clippy::type_complexity)]
@@ -170,7 +278,7 @@ where
     EM: UsesState<State = Z::State>,
     M: Mutator<I, Z::State>,
     Z: Evaluator<E, EM>,
-    Z::State: HasCorpus + HasRand + HasExecutions + HasMetadata,
+    Z::State: HasCorpus + HasRand + HasExecutions + HasMetadata + HasClock,
     I: MutatedTransform<Self::Input, Self::State> + Clone,
 {
     /// The mutator, added to this stage
@@ -185,14 +293,35 @@ where
         &mut self.mutator
     }
 
-    /// Gets the number of iterations as a random number
+    /// Gets the number of iterations as a random number, at most the energy left for the current testcase
     fn iterations(&self, state: &mut Z::State) -> Result<u64, Error> {
-        Ok(1 + state.rand_mut().below(self.max_iterations))
+        let iterations = 1 + state.rand_mut().below(self.max_iterations);
+        let Some(energy_cap) = self.energy_cap else {
+            return Ok(iterations);
+        };
+        let spent = match (
+            state.current_corpus_idx()?,
+            state.metadata::<EnergyMetadata>(),
+        ) {
+            (Some(idx), Ok(energy)) => energy.spent(idx),
+            _ => 0,
+        };
+        Ok(iterations.min(energy_cap.saturating_sub(spent)))
     }
 
     fn execs_since_progress_start(&mut self, state: &mut <Z>::State) -> Result<u64, Error> {
         self.restart_helper.execs_since_progress_start(state)
     }
+
+    #[inline]
+    fn evaluates_intermediates(&self) -> bool {
+        self.evaluate_intermediates
+    }
+
+    #[inline]
+    fn time_budget(&self) -> Option<Duration> {
+        self.time_budget
+    }
 }
 
 impl<E, EM, I, M, Z> UsesState for StdMutationalStage<E, EM, I, M, Z>
@@ -212,7 +341,7 @@ where
     EM: UsesState<State = Z::State>,
     M: Mutator<I, Z::State>,
     Z: Evaluator<E, EM>,
-    Z::State: HasCorpus + HasRand + HasMetadata + HasExecutions,
+    Z::State: HasCorpus + HasRand + HasMetadata + HasExecutions + HasClock,
     I: MutatedTransform<Self::Input, Self::State> + Clone,
 {
     #[inline]
@@ -224,8 +353,52 @@ where
         state: &mut Z::State,
         manager: &mut EM,
     ) -> Result<(), Error> {
+        let energy_idx = match self.energy_cap {
+            Some(energy_cap) => {
+                let corpus_idx = state.current_corpus_idx()?.ok_or_else(|| {
+                    Error::key_not_found("No corpus idx is currently being fuzzed")
+                })?;
+                let queue_cycles = state
+                    .metadata::<SchedulerMetadata>()
+                    .map_or(0, SchedulerMetadata::queue_cycles);
+                let energy = state.metadata_or_insert_with(EnergyMetadata::default);
+                energy.enter(queue_cycles);
+                if energy.spent(corpus_idx) >= energy_cap {
+                    return Ok(());
+                }
+                Some((corpus_idx, *state.executions()))
+            }
+            None => None,
+        };
+
+        // Mutate with a sub-rand, only seeded from the testcase and the master seed,
+        // then continue the main rand from where it was, independent of the mutations
+        let resume_seed = match self.master_seed {
+            Some(master_seed) => {
+                let resume_seed = state.rand_mut().next();
+                let corpus_idx = state.current_corpus_idx()?.ok_or_else(|| {
+                    Error::key_not_found("No corpus idx is currently being fuzzed")
+                })?;
+                state
+                    .rand_mut()
+                    .set_seed(testcase_seed(master_seed, corpus_idx));
+                Some(resume_seed)
+            }
+            None => None,
+        };
+
         let ret = self.perform_mutational(fuzzer, executor, state, manager);
 
+        if let Some(resume_seed) = resume_seed {
+            state.rand_mut().set_seed(resume_seed);
+        }
+
+        if let Some((corpus_idx, executions)) = energy_idx {
+            let spent = state.executions().saturating_sub(executions);
+            let energy = state.metadata_or_insert_with(EnergyMetadata::default);
+            *energy.spent.entry(corpus_idx).or_insert(0) += spent;
+        }
+
         #[cfg(feature = "introspection")]
         state.introspection_monitor_mut().finish_stage();
 
@@ -280,10 +453,54 @@ where
         Self {
             mutator,
             max_iterations,
+            evaluate_intermediates: false,
+            master_seed: None,
+            time_budget: None,
+            energy_cap: None,
             restart_helper: ExecutionCountRestartHelper::default(),
             phantom: PhantomData,
         }
     }
+
+    /// Also evaluate the intermediate results of stacked mutations, so that children that are interesting
+    /// on their own are not lost, if later mutations in the stack change them again.
+    #[must_use]
+    pub fn with_intermediate_evaluation(mut self, evaluate_intermediates: bool) -> Self {
+        self.evaluate_intermediates = evaluate_intermediates;
+        self
+    }
+
+    /// Makes the mutations of each testcase reproducible: before mutating a testcase,
+    /// the [`Rand`] of the state is seeded from the `master_seed` and the [`CorpusId`] of the testcase, see [`testcase_seed`].
+    /// The same testcase then gets the same mutations, no matter which testcases were processed before.
+    /// Afterwards, the [`Rand`] continues independently of the mutations.
+    #[must_use]
+    pub fn with_per_testcase_seed(mut self, master_seed: u64) -> Self {
+        self.master_seed = Some(master_seed);
+        self
+    }
+
+    /// Stops mutating the current testcase once the given wall-clock budget is used up,
+    /// even if not all iterations ran, so that a slow target cannot stall the fuzzing loop in this stage.
+    #[must_use]
+    pub fn with_time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Caps the executions each testcase gets in one cycle through the corpus, its energy,
+    /// so that a single testcase cannot starve the others.
+    /// Once a testcase used up its energy, this stage skips it until the next cycle, see [`EnergyMetadata`].
+    ///
+    /// The cycles are the queue cycles counted by the scheduler in its [`SchedulerMetadata`],
+    /// for example by the [`crate::schedulers::PowerQueueScheduler`] or the [`crate::schedulers::StdWeightedScheduler`].
+    /// Without such a scheduler, the energy of a testcase is never refilled.
+    /// Intermediate evaluations may overshoot the cap within a single run of this stage.
+    #[must_use]
+    pub fn with_energy_cap(mut self, energy_cap: u64) -> Self {
+        self.energy_cap = Some(energy_cap);
+        self
+    }
 }
 
 /// A mutational stage that operates on multiple inputs, as returned by [`MultiMutator::multi_mutate`].
@@ -400,3 +617,289 @@ where
         }
     }
 }
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use alloc::vec::Vec;
+    use core::{
+        cell::{Cell, RefCell},
+        ptr::addr_of_mut,
+        time::Duration,
+    };
+    use std::thread;
+
+    use libafl_bolts::{rands::Rand, tuples::tuple_list};
+
+    #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+    use crate::feedbacks::MapFeedbackMetadata;
+    use crate::{
+        corpus::{Corpus, HasCurrentCorpusIdx, Testcase},
+        executors::ExitKind,
+        feedbacks::{ConstFeedback, CrashFeedback, MaxMapFeedback},
+        fuzzer::test::test_fuzzer,
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{havoc_mutations, ByteIncMutator, StdScheduledMutator},
+        observers::StdMapObserver,
+        schedulers::{QueueScheduler, SchedulerMetadata},
+        stages::{EnergyMetadata, Stage, StdMutationalStage},
+        state::{HasCorpus, HasRand, StdState},
+        HasMetadata,
+    };
+
+    static mut MAP: [u8; 4] = [0; 4];
+
+    /// Runs the stage once, with exactly two stacked increments of `[0]`, returns the size of the corpus
+    fn corpus_after_stacked_mutation(evaluate_intermediates: bool) -> usize {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapFeedbackMetadata::<u8>::register();
+        }
+
+        let observer =
+            unsafe { StdMapObserver::from_mut_ptr("map", addr_of_mut!(MAP) as *mut u8, 4) };
+        let mut harness = |input: &BytesInput| {
+            unsafe {
+                (*addr_of_mut!(MAP))[input.bytes()[0] as usize % 4] = 1;
+            }
+            ExitKind::Ok
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            MaxMapFeedback::new(&observer),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(observer),
+        );
+        let id = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![0])))
+            .unwrap();
+        state.set_corpus_idx(id).unwrap();
+
+        // A max stack pow of 1 always stacks two mutations
+        let mutator =
+            StdScheduledMutator::with_max_stack_pow(tuple_list!(ByteIncMutator::new()), 1);
+        let mut stage = StdMutationalStage::with_max_iterations(mutator, 1)
+            .with_intermediate_evaluation(evaluate_intermediates);
+        stage
+            .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+            .unwrap();
+
+        state.corpus().count()
+    }
+
+    #[test]
+    fn test_intermediate_evaluation() {
+        // Only `[2]` is evaluated
+        assert_eq!(corpus_after_stacked_mutation(false), 2);
+        // `[1]` is interesting on its own
+        assert_eq!(corpus_after_stacked_mutation(true), 3);
+    }
+
+    /// Mutates each testcase once, in the given order, returns the inputs executed for each testcase
+    fn mutations_per_testcase(state_seed: u64, order: &[usize]) -> Vec<Vec<BytesInput>> {
+        let executed = RefCell::new(Vec::new());
+        let mut harness = |input: &BytesInput| {
+            executed.borrow_mut().push(input.clone());
+            ExitKind::Ok
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+        state.rand_mut().set_seed(state_seed);
+        let ids: Vec<_> = [b"AAAAAAAA", b"BBBBBBBB", b"CCCCCCCC"]
+            .into_iter()
+            .map(|input| {
+                state
+                    .corpus_mut()
+                    .add(Testcase::new(BytesInput::new(input.to_vec())))
+                    .unwrap()
+            })
+            .collect();
+
+        let mutator = StdScheduledMutator::new(havoc_mutations());
+        let mut stage =
+            StdMutationalStage::with_max_iterations(mutator, 16).with_per_testcase_seed(1337);
+
+        let mut mutations = vec![Vec::new(); ids.len()];
+        for idx in order {
+            // Randomness consumed elsewhere must not matter
+            state.rand_mut().next();
+            state.set_corpus_idx(ids[*idx]).unwrap();
+            stage
+                .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+                .unwrap();
+            mutations[*idx] = executed.borrow_mut().drain(..).collect();
+        }
+        mutations
+    }
+
+    #[test]
+    fn test_per_testcase_seed() {
+        let first = mutations_per_testcase(0, &[0, 1, 2]);
+        let second = mutations_per_testcase(42, &[2, 0, 1]);
+        assert!(first.iter().all(|inputs| !inputs.is_empty()));
+        assert_eq!(first, second);
+        assert_ne!(first[0], first[1]);
+    }
+
+    /// Runs the stage once, with the first execution being slow, returns the number of executions
+    fn executions_with_budget(time_budget: Option<Duration>) -> usize {
+        let executions = Cell::new(0);
+        let mut harness = |_input: &BytesInput| {
+            if executions.get() == 0 {
+                thread::sleep(Duration::from_millis(50));
+            }
+            executions.set(executions.get() + 1);
+            ExitKind::Ok
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+        let id = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![0])))
+            .unwrap();
+        state.set_corpus_idx(id).unwrap();
+
+        let mutator = StdScheduledMutator::new(tuple_list!(ByteIncMutator::new()));
+        let mut stage = StdMutationalStage::with_max_iterations(mutator, 1000);
+        if let Some(time_budget) = time_budget {
+            stage = stage.with_time_budget(time_budget);
+        }
+        stage
+            .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+            .unwrap();
+
+        executions.get()
+    }
+
+    #[test]
+    fn test_time_budget() {
+        assert!(executions_with_budget(None) > 1);
+        // The budget is exceeded after the first, slow, execution
+        assert_eq!(executions_with_budget(Some(Duration::from_millis(10))), 1);
+    }
+
+    #[test]
+    fn test_dry_streak() {
+        // Inputs are interesting while `productive` is set
+        let productive = Cell::new(false);
+        let mut harness = |_input: &BytesInput| {
+            if productive.get() {
+                ExitKind::Crash
+            } else {
+                ExitKind::Ok
+            }
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            CrashFeedback::new(),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+        let id = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![0])))
+            .unwrap();
+        state.set_corpus_idx(id).unwrap();
+
+        let mutator = StdScheduledMutator::new(tuple_list!(ByteIncMutator::new()));
+        let mut stage = StdMutationalStage::with_max_iterations(mutator, 4);
+        let mut fuzz_once = |state: &mut StdState<_, _, _, _>, productive_run: bool| {
+            productive.set(productive_run);
+            stage
+                .perform(&mut fuzzer, &mut executor, state, &mut mgr)
+                .unwrap();
+            state.corpus().get(id).unwrap().borrow().dry_streak()
+        };
+
+        assert_eq!(fuzz_once(&mut state, false), 1);
+        assert_eq!(fuzz_once(&mut state, false), 2);
+        assert_eq!(fuzz_once(&mut state, false), 3);
+        // A find resets the streak
+        assert_eq!(fuzz_once(&mut state, true), 0);
+        assert_eq!(fuzz_once(&mut state, false), 1);
+    }
+
+    #[test]
+    fn test_energy_cap() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            EnergyMetadata::register();
+            SchedulerMetadata::register();
+        }
+
+        let executions = Cell::new(0);
+        let mut harness = |_input: &BytesInput| {
+            executions.set(executions.get() + 1);
+            ExitKind::Ok
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+        let first = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![0])))
+            .unwrap();
+        let second = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![1])))
+            .unwrap();
+        state.add_metadata(SchedulerMetadata::new(None));
+
+        let mutator = StdScheduledMutator::new(tuple_list!(ByteIncMutator::new()));
+        let mut stage = StdMutationalStage::with_max_iterations(mutator, 16).with_energy_cap(20);
+        // Runs the stage `runs` times on the scheduled testcase, returns the number of executions
+        let mut fuzz = |state: &mut StdState<_, _, _, _>, runs: usize| {
+            let before = executions.get();
+            for _ in 0..runs {
+                stage
+                    .perform(&mut fuzzer, &mut executor, state, &mut mgr)
+                    .unwrap();
+            }
+            executions.get() - before
+        };
+
+        // The first testcase stops at its cap
+        state.set_corpus_idx(first).unwrap();
+        assert_eq!(fuzz(&mut state, 10), 20);
+        assert_eq!(fuzz(&mut state, 1), 0);
+
+        // The others still get fuzzed in this cycle
+        state.set_corpus_idx(second).unwrap();
+        assert!(fuzz(&mut state, 1) > 0);
+
+        // The next cycle of the scheduler starts over
+        state
+            .metadata_mut::<SchedulerMetadata>()
+            .unwrap()
+            .set_queue_cycles(1);
+        state.set_corpus_idx(first).unwrap();
+        assert!(fuzz(&mut state, 1) > 0);
+        assert_eq!(state.metadata::<EnergyMetadata>().unwrap().cycles, 1);
+    }
+}
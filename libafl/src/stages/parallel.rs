@@ -0,0 +1,537 @@
+//! A mutational stage that runs batches of mutated inputs on multiple threads.
+//!
+//! For CPU-bound, thread-safe in-process harnesses, the [`ParallelMutationalStage`] mutates a batch of inputs,
+//! runs them in parallel on a pool of worker threads, each with its own set of observers,
+//! and then evaluates the results on the main thread.
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{any::type_name, marker::PhantomData};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc::{channel, Receiver, Sender},
+    thread::{self, JoinHandle},
+};
+
+use libafl_bolts::{rands::Rand, Named};
+
+use crate::{
+    corpus::HasCurrentCorpusIdx,
+    events::EventFirer,
+    executors::{ExitKind, HasObservers},
+    mutators::{MutationResult, Mutator},
+    observers::ObserversTuple,
+    stages::{mutational::DEFAULT_MUTATIONAL_MAX_ITERATIONS, RetryRestartHelper, Stage},
+    state::{HasCorpus, HasCurrentTestcase, HasExecutions, HasRand, UsesState},
+    Error, ExecutesInput, ExecutionProcessor, HasNamedMetadata,
+};
+
+/// The result of a run on a [`Worker`]: the input, the observers, and the exit kind, unless the harness panicked
+type WorkerResult<I, OT> = (I, OT, thread::Result<ExitKind>);
+
+/// A long-lived thread of the [`ParallelMutationalStage`], running the harness on the inputs it receives
+#[derive(Debug)]
+struct Worker<I, OT> {
+    jobs: Option<Sender<(I, OT)>>,
+    results: Receiver<WorkerResult<I, OT>>,
+    handle: Option<JoinHandle<()>>,
+    /// The observers of this worker, while it is idle
+    observers: Option<OT>,
+}
+
+impl<I, OT> Worker<I, OT>
+where
+    I: Send + 'static,
+    OT: Send + 'static,
+{
+    fn spawn<H>(harness: Arc<H>, observers: OT) -> Result<Self, Error>
+    where
+        H: Fn(&I, &mut OT) -> ExitKind + Send + Sync + 'static,
+    {
+        let (jobs, job_receiver) = channel::<(I, OT)>();
+        let (result_sender, results) = channel();
+        let handle = thread::Builder::new()
+            .name("ParallelMutationalStage worker".into())
+            .spawn(move || {
+                while let Ok((input, mut observers)) = job_receiver.recv() {
+                    let exit_kind =
+                        panic::catch_unwind(AssertUnwindSafe(|| harness(&input, &mut observers)));
+                    if result_sender.send((input, observers, exit_kind)).is_err() {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self {
+            jobs: Some(jobs),
+            results,
+            handle: Some(handle),
+            observers: Some(observers),
+        })
+    }
+
+    /// The observers of this worker, waiting for a pending run to finish first, for example after an error
+    fn idle_observers(&mut self) -> Result<&mut OT, Error> {
+        if self.observers.is_none() {
+            let (_, observers, _) = self.wait()?;
+            self.observers = Some(observers);
+        }
+        Ok(self.observers.as_mut().unwrap())
+    }
+
+    /// Hands the input to the worker, together with its observers
+    fn run(&mut self, input: I) -> Result<(), Error> {
+        self.idle_observers()?;
+        let observers = self.observers.take().unwrap();
+        self.jobs
+            .as_ref()
+            .unwrap()
+            .send((input, observers))
+            .map_err(|_| Error::unknown("A ParallelMutationalStage worker thread died"))
+    }
+
+    /// Waits for the result of the last run. The caller has to hand back the observers.
+    fn wait(&mut self) -> Result<WorkerResult<I, OT>, Error> {
+        self.results
+            .recv()
+            .map_err(|_| Error::unknown("A ParallelMutationalStage worker thread died"))
+    }
+}
+
+impl<I, OT> Drop for Worker<I, OT> {
+    fn drop(&mut self) {
+        // Closing the channel ends the loop of the thread
+        drop(self.jobs.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// A mutational stage that runs the mutated inputs on a pool of threads.
+///
+/// Each batch has one input per thread. The first input runs on the main thread, through the executor given to the stage,
+/// the others run on long-lived worker threads, spawned once when the stage is created.
+/// Each worker owns a clone of the observers and calls the `harness` with the input and its observers.
+/// The harness therefore needs to write its results into the given observers, instead of some global map.
+///
+/// All inputs of a batch are mutated before the batch runs,
+/// and the observers are evaluated by the fuzzer on the main thread, in the same order the inputs got mutated.
+/// As long as the mutator does not look at the corpus, the results are the same as for the
+/// [`super::StdMutationalStage`] with the same seed.
+/// A panic of the harness on a worker thread is caught and reported to the fuzzer as an [`ExitKind::Crash`].
+///
+/// The observers are moved between the threads, so they need to be [`Send`].
+/// This rules out the map observers, such as the [`crate::observers::StdMapObserver`],
+/// since their maps may be backed by raw pointers, even if they own them.
+/// Instead, the observers need to own their state, for example in a `Vec`, and the harness has to fill them.
+#[derive(Debug)]
+pub struct ParallelMutationalStage<E, EM, H, M, OT, Z>
+where
+    Z: UsesState,
+{
+    /// The mutator(s) to use
+    mutator: M,
+    /// The worker threads, one less than the threads of the stage
+    workers: Vec<Worker<Z::Input, OT>>,
+    /// The maximum amount of iterations we should do each round
+    max_iterations: u64,
+    #[allow(clippy::type_complexity)]
+    phantom: PhantomData<(E, EM, H, Z)>,
+}
+
+impl<E, EM, H, M, OT, Z> ParallelMutationalStage<E, EM, H, M, OT, Z>
+where
+    Z: UsesState,
+    Z::Input: Send + 'static,
+    H: Fn(&Z::Input, &mut OT) -> ExitKind + Send + Sync + 'static,
+    OT: ObserversTuple<Z::State> + Clone + Send + 'static,
+{
+    /// Creates a new [`ParallelMutationalStage`], running on `threads` threads, the main one included.
+    /// Each worker thread runs the `harness` with a clone of the given `observers`.
+    pub fn new(mutator: M, harness: H, observers: &OT, threads: usize) -> Result<Self, Error> {
+        Self::with_max_iterations(
+            mutator,
+            harness,
+            observers,
+            threads,
+            DEFAULT_MUTATIONAL_MAX_ITERATIONS,
+        )
+    }
+
+    /// Creates a new [`ParallelMutationalStage`] with the given max iterations
+    pub fn with_max_iterations(
+        mutator: M,
+        harness: H,
+        observers: &OT,
+        threads: usize,
+        max_iterations: u64,
+    ) -> Result<Self, Error> {
+        if threads == 0 {
+            return Err(Error::illegal_argument(
+                "A ParallelMutationalStage needs at least one thread",
+            ));
+        }
+        let harness = Arc::new(harness);
+        let workers = (1..threads)
+            .map(|_| Worker::spawn(harness.clone(), observers.clone()))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            mutator,
+            workers,
+            max_iterations,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<E, EM, H, M, OT, Z> ParallelMutationalStage<E, EM, H, M, OT, Z>
+where
+    Z: UsesState,
+{
+    /// The number of threads this stage runs on, the main one included
+    #[must_use]
+    pub fn threads(&self) -> usize {
+        self.workers.len() + 1
+    }
+}
+
+impl<E, EM, H, M, OT, Z> ParallelMutationalStage<E, EM, H, M, OT, Z>
+where
+    E: HasObservers<Observers = OT, State = Z::State>,
+    EM: EventFirer<State = Z::State>,
+    M: Mutator<Z::Input, Z::State>,
+    OT: ObserversTuple<Z::State> + Send + 'static,
+    Z: ExecutesInput<E, EM> + ExecutionProcessor<OT>,
+    Z::State: HasExecutions,
+    Z::Input: Send + 'static,
+{
+    /// Runs a batch of inputs, one per thread, and evaluates them in order
+    fn run_batch(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut Z::State,
+        manager: &mut EM,
+        batch: Vec<Z::Input>,
+    ) -> Result<(), Error> {
+        let mut batch = batch.into_iter();
+        let Some(first) = batch.next() else {
+            return Ok(());
+        };
+
+        let mut busy = 0;
+        for (worker, input) in self.workers.iter_mut().zip(batch) {
+            worker.idle_observers()?.pre_exec_all(state, &input)?;
+            worker.run(input)?;
+            busy += 1;
+        }
+
+        // Meanwhile, run the first input through the executor
+        let exit_kind = fuzzer.execute_input(state, executor, manager, &first)?;
+        let (_, corpus_idx) = fuzzer.execute_and_process(
+            state,
+            manager,
+            first,
+            executor.observers(),
+            &exit_kind,
+            true,
+        )?;
+        self.mutator.post_exec(state, corpus_idx)?;
+
+        for worker in &mut self.workers[..busy] {
+            let (input, mut observers, exit_kind) = worker.wait()?;
+            // A panic would crash the target on the main thread, so it is a crash here as well
+            let exit_kind = exit_kind.unwrap_or(ExitKind::Crash);
+            *state.executions_mut() += 1;
+            let res = observers
+                .post_exec_all(state, &input, &exit_kind)
+                .and_then(|()| {
+                    fuzzer.execute_and_process(state, manager, input, &observers, &exit_kind, true)
+                });
+            worker.observers = Some(observers);
+            let (_, corpus_idx) = res?;
+            self.mutator.post_exec(state, corpus_idx)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E, EM, H, M, OT, Z> UsesState for ParallelMutationalStage<E, EM, H, M, OT, Z>
+where
+    Z: UsesState,
+{
+    type State = Z::State;
+}
+
+impl<E, EM, H, M, OT, Z> Named for ParallelMutationalStage<E, EM, H, M, OT, Z>
+where
+    Z: UsesState,
+{
+    fn name(&self) -> &str {
+        type_name::<Self>()
+    }
+}
+
+impl<E, EM, H, M, OT, Z> Stage<E, EM, Z> for ParallelMutationalStage<E, EM, H, M, OT, Z>
+where
+    E: HasObservers<Observers = OT, State = Z::State>,
+    EM: EventFirer<State = Z::State>,
+    M: Mutator<Z::Input, Z::State>,
+    OT: ObserversTuple<Z::State> + Send + 'static,
+    Z: ExecutesInput<E, EM> + ExecutionProcessor<OT>,
+    Z::State: HasCorpus + HasRand + HasExecutions + HasNamedMetadata + HasCurrentCorpusIdx,
+    Z::Input: Send + 'static,
+{
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut Z::State,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let num = 1 + state.rand_mut().below(self.max_iterations);
+        let input = state.current_input_cloned()?;
+
+        let mut batch = Vec::with_capacity(self.threads());
+        for _ in 0..num {
+            let mut mutated = input.clone();
+            if self.mutator.mutate(state, &mut mutated)? == MutationResult::Skipped {
+                continue;
+            }
+            batch.push(mutated);
+            if batch.len() == self.threads() {
+                self.run_batch(
+                    fuzzer,
+                    executor,
+                    state,
+                    manager,
+                    core::mem::take(&mut batch),
+                )?;
+            }
+        }
+        self.run_batch(fuzzer, executor, state, manager, batch)
+    }
+
+    #[inline]
+    fn restart_progress_should_run(&mut self, state: &mut Self::State) -> Result<bool, Error> {
+        // Make sure we don't get stuck crashing on a single testcase
+        RetryRestartHelper::restart_progress_should_run(state, self, 3)
+    }
+
+    #[inline]
+    fn clear_restart_progress(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        RetryRestartHelper::clear_restart_progress(state, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+    use core::marker::PhantomData;
+
+    use libafl_bolts::{rands::Rand, tuples::tuple_list, Named};
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        corpus::{Corpus, HasCurrentCorpusIdx, Testcase},
+        events::NopEventManager,
+        executors::{Executor, ExitKind, HasObservers},
+        feedbacks::{ConstFeedback, CrashFeedback, NewHashFeedback},
+        inputs::{BytesInput, HasBytesVec, UsesInput},
+        mutators::{BitFlipMutator, ByteRandMutator, StdScheduledMutator},
+        observers::{Observer, ObserverWithHashField, UsesObservers},
+        schedulers::QueueScheduler,
+        stages::{parallel::ParallelMutationalStage, Stage, StdMutationalStage},
+        state::{
+            test::test_std_state_with, HasCorpus, HasExecutions, HasRand, HasSolutions, State,
+            UsesState,
+        },
+        Error, StdFuzzer,
+    };
+
+    /// Stores the hash the harness wrote
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct HashObserver {
+        name: String,
+        hash: Option<u64>,
+    }
+
+    impl<S> Observer<S> for HashObserver where S: UsesInput {}
+
+    impl ObserverWithHashField for HashObserver {
+        fn hash(&self) -> Option<u64> {
+            self.hash
+        }
+    }
+
+    impl Named for HashObserver {
+        fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    type Observers = (HashObserver, ());
+
+    fn harness(input: &BytesInput, observers: &mut Observers) -> ExitKind {
+        observers.0.hash = Some(u64::from(input.bytes()[0] & 0xf));
+        ExitKind::Ok
+    }
+
+    /// Runs the harness on the current thread, to compare against
+    struct HarnessExecutor<S> {
+        observers: Observers,
+        phantom: PhantomData<S>,
+    }
+
+    impl<S> UsesState for HarnessExecutor<S>
+    where
+        S: State,
+    {
+        type State = S;
+    }
+
+    impl<S> UsesObservers for HarnessExecutor<S>
+    where
+        S: State,
+    {
+        type Observers = Observers;
+    }
+
+    impl<S> HasObservers for HarnessExecutor<S>
+    where
+        S: State,
+    {
+        fn observers(&self) -> &Observers {
+            &self.observers
+        }
+
+        fn observers_mut(&mut self) -> &mut Observers {
+            &mut self.observers
+        }
+    }
+
+    impl<EM, S, Z> Executor<EM, Z> for HarnessExecutor<S>
+    where
+        EM: UsesState<State = S>,
+        S: State<Input = BytesInput> + HasExecutions,
+        Z: UsesState<State = S>,
+    {
+        fn run_target(
+            &mut self,
+            _fuzzer: &mut Z,
+            state: &mut S,
+            _mgr: &mut EM,
+            input: &BytesInput,
+        ) -> Result<ExitKind, Error> {
+            *state.executions_mut() += 1;
+            Ok(harness(input, &mut self.observers))
+        }
+    }
+
+    /// Fuzzes the first testcase, either on four threads or with the [`StdMutationalStage`], returns the new corpus
+    fn fuzz_corpus(parallel: bool) -> Vec<Vec<u8>> {
+        let observers = tuple_list!(HashObserver {
+            name: "hash".into(),
+            hash: None,
+        });
+        let mut feedback = NewHashFeedback::<HashObserver, _>::new(&observers.0);
+        let mut objective = ConstFeedback::new(false);
+
+        let mut state = test_std_state_with(&mut feedback, &mut objective);
+        state.rand_mut().set_seed(1337);
+        let id = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(b"\x00abcdefg".to_vec())))
+            .unwrap();
+        state.set_corpus_idx(id).unwrap();
+        let mut mgr = NopEventManager::new();
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+        let mut executor = HarnessExecutor {
+            observers: observers.clone(),
+            phantom: PhantomData,
+        };
+        let mutator =
+            StdScheduledMutator::new(tuple_list!(BitFlipMutator::new(), ByteRandMutator::new()));
+
+        if parallel {
+            let mut stage =
+                ParallelMutationalStage::with_max_iterations(mutator, harness, &observers, 4, 64)
+                    .unwrap();
+            for _ in 0..8 {
+                stage
+                    .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+                    .unwrap();
+            }
+        } else {
+            let mut stage = StdMutationalStage::with_max_iterations(mutator, 64);
+            for _ in 0..8 {
+                stage
+                    .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+                    .unwrap();
+            }
+        }
+
+        assert!(*state.executions() > 0);
+        let corpus = state.corpus();
+        corpus
+            .ids()
+            .map(|id| corpus.cloned_input_for_id(id).unwrap().bytes().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_parallel_mutational_stage() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            crate::feedbacks::NewHashFeedbackMetadata::register();
+        }
+
+        let single = fuzz_corpus(false);
+        let parallel = fuzz_corpus(true);
+        assert!(single.len() > 1);
+        assert_eq!(single, parallel);
+    }
+
+    #[test]
+    fn test_parallel_mutational_stage_panic() {
+        let observers = tuple_list!(HashObserver {
+            name: "hash".into(),
+            hash: None,
+        });
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = CrashFeedback::new();
+
+        let mut state = test_std_state_with(&mut feedback, &mut objective);
+        state.rand_mut().set_seed(1337);
+        let id = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(b"\x00abcdefg".to_vec())))
+            .unwrap();
+        state.set_corpus_idx(id).unwrap();
+        let mut mgr = NopEventManager::new();
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+        let mut executor = HarnessExecutor {
+            observers: observers.clone(),
+            phantom: PhantomData,
+        };
+
+        // Only the harness of the worker threads panics
+        let mut stage = ParallelMutationalStage::with_max_iterations(
+            StdScheduledMutator::new(tuple_list!(BitFlipMutator::new())),
+            |_input: &BytesInput, _observers: &mut Observers| -> ExitKind {
+                panic!("The harness panicked")
+            },
+            &observers,
+            2,
+            64,
+        )
+        .unwrap();
+        for _ in 0..8 {
+            stage
+                .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+                .unwrap();
+        }
+        assert!(state.solutions().count() > 0);
+    }
+}
@@ -0,0 +1,227 @@
+//! The [`RepairingStage`] wraps a [`MutationalStage`] and repairs each mutated input before it gets executed.
+//!
+//! This is useful for structured formats, where most random mutations break checksums, length fields, and the like,
+//! so that they never reach any interesting code in the target.
+
+use core::{any::type_name, marker::PhantomData, time::Duration};
+
+use libafl_bolts::Named;
+
+use crate::{
+    fuzzer::Evaluator,
+    mutators::Mutator,
+    stages::{mutational::MutatedTransform, MutationalStage, Stage},
+    state::{HasClock, HasCorpus, UsesState},
+    Error,
+};
+
+/// A stage that uses the mutator and iterations of the wrapped [`MutationalStage`],
+/// but runs a `repair` function on each mutated input before it is evaluated, see [`MutationalStage::repair_input`].
+///
+/// The `repair` function may fix up the input in place (e.g., recalculate a length field or a checksum).
+/// If it returns `false`, the input is considered invalid and is dropped without being executed.
+/// Intermediate inputs are never evaluated, since they could not be repaired.
+#[derive(Clone, Debug)]
+pub struct RepairingStage<E, EM, I, M, R, ST, Z> {
+    /// The wrapped mutational stage
+    stage: ST,
+    /// The repair function, called for each mutated input
+    repair: R,
+    #[allow(clippy::type_complexity)]
+    phantom: PhantomData<(E, EM, I, M, Z)>,
+}
+
+impl<E, EM, I, M, R, ST, Z> RepairingStage<E, EM, I, M, R, ST, Z>
+where
+    Z: UsesState,
+    R: FnMut(&mut Z::State, &mut Z::Input) -> Result<bool, Error>,
+{
+    /// Creates a new [`RepairingStage`], repairing the inputs mutated by `stage` using the `repair` function
+    pub fn new(stage: ST, repair: R) -> Self {
+        Self {
+            stage,
+            repair,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The wrapped mutational stage
+    pub fn stage(&self) -> &ST {
+        &self.stage
+    }
+
+    /// The wrapped mutational stage (mutable)
+    pub fn stage_mut(&mut self) -> &mut ST {
+        &mut self.stage
+    }
+}
+
+impl<E, EM, I, M, R, ST, Z> UsesState for RepairingStage<E, EM, I, M, R, ST, Z>
+where
+    E: UsesState<State = Z::State>,
+    EM: UsesState<State = Z::State>,
+    Z: Evaluator<E, EM>,
+{
+    type State = Z::State;
+}
+
+impl<E, EM, I, M, R, ST, Z> Named for RepairingStage<E, EM, I, M, R, ST, Z> {
+    fn name(&self) -> &str {
+        type_name::<Self>()
+    }
+}
+
+impl<E, EM, I, M, R, ST, Z> MutationalStage<E, EM, I, M, Z>
+    for RepairingStage<E, EM, I, M, R, ST, Z>
+where
+    E: UsesState<State = Z::State>,
+    EM: UsesState<State = Z::State>,
+    M: Mutator<I, Z::State>,
+    Z: Evaluator<E, EM>,
+    Z::State: HasCorpus + HasClock,
+    I: MutatedTransform<Z::Input, Z::State> + Clone,
+    ST: MutationalStage<E, EM, I, M, Z, State = Z::State>,
+    R: FnMut(&mut Z::State, &mut Z::Input) -> Result<bool, Error>,
+{
+    #[inline]
+    fn mutator(&self) -> &M {
+        self.stage.mutator()
+    }
+
+    #[inline]
+    fn mutator_mut(&mut self) -> &mut M {
+        self.stage.mutator_mut()
+    }
+
+    #[inline]
+    fn iterations(&self, state: &mut Z::State) -> Result<u64, Error> {
+        self.stage.iterations(state)
+    }
+
+    #[inline]
+    fn execs_since_progress_start(&mut self, state: &mut Z::State) -> Result<u64, Error> {
+        self.stage.execs_since_progress_start(state)
+    }
+
+    #[inline]
+    fn time_budget(&self) -> Option<Duration> {
+        self.stage.time_budget()
+    }
+
+    fn repair_input(&mut self, state: &mut Z::State, input: &mut Z::Input) -> Result<bool, Error> {
+        // Not worth executing if either repair fails, the target would bail out early
+        Ok(self.stage.repair_input(state, input)? && (self.repair)(state, input)?)
+    }
+}
+
+impl<E, EM, I, M, R, ST, Z> Stage<E, EM, Z> for RepairingStage<E, EM, I, M, R, ST, Z>
+where
+    E: UsesState<State = Z::State>,
+    EM: UsesState<State = Z::State>,
+    M: Mutator<I, Z::State>,
+    Z: Evaluator<E, EM>,
+    Z::State: HasCorpus + HasClock,
+    I: MutatedTransform<Z::Input, Z::State> + Clone,
+    ST: MutationalStage<E, EM, I, M, Z, State = Z::State>,
+    R: FnMut(&mut Z::State, &mut Z::Input) -> Result<bool, Error>,
+{
+    #[inline]
+    fn restart_progress_should_run(&mut self, state: &mut Self::State) -> Result<bool, Error> {
+        self.stage.restart_progress_should_run(state)
+    }
+
+    #[inline]
+    fn clear_restart_progress(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        self.stage.clear_restart_progress(state)
+    }
+
+    #[inline]
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut Z::State,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        self.perform_mutational(fuzzer, executor, state, manager)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use libafl_bolts::{rands::Rand, tuples::tuple_list};
+
+    #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+    use crate::stages::ExecutionCountRestartHelperMetadata;
+    use crate::{
+        corpus::{Corpus, Testcase},
+        executors::ExitKind,
+        feedbacks::ConstFeedback,
+        fuzzer::test::test_fuzzer,
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{havoc_mutations, StdScheduledMutator},
+        schedulers::RandScheduler,
+        stages::{RepairingStage, StdMutationalStage},
+        state::{HasCorpus, HasExecutions, HasRand},
+        Fuzzer,
+    };
+
+    /// Inputs are only valid if the first byte is the length of the remaining input
+    fn is_valid(buf: &[u8]) -> bool {
+        !buf.is_empty() && buf[0] as usize == buf.len() - 1
+    }
+
+    #[test]
+    fn test_repairing_stage() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            ExecutionCountRestartHelperMetadata::register();
+        }
+
+        let mut invalid = 0_usize;
+        let mut harness = |input: &BytesInput| {
+            if !is_valid(input.bytes()) {
+                invalid += 1;
+            }
+            ExitKind::Ok
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            RandScheduler::new(),
+            ConstFeedback::new(true),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+        state.rand_mut().set_seed(1337);
+        state
+            .corpus_mut()
+            .add(Testcase::new(vec![3, b'a', b'b', b'c'].into()))
+            .unwrap();
+
+        let mutator = StdScheduledMutator::new(havoc_mutations());
+        let mut stages = tuple_list!(RepairingStage::new(
+            StdMutationalStage::with_max_iterations(mutator, 16),
+            |_state, input: &mut BytesInput| {
+                let bytes = input.bytes_mut();
+                if bytes.is_empty() {
+                    return Ok(false);
+                }
+                // Fix up the length prefix
+                bytes.truncate(256);
+                bytes[0] = (bytes.len() - 1) as u8;
+                Ok(true)
+            }
+        ));
+
+        fuzzer
+            .fuzz_loop_for(&mut stages, &mut executor, &mut state, &mut mgr, 10)
+            .unwrap();
+
+        assert!(*state.executions() > 0);
+        assert_eq!(invalid, 0);
+    }
+}
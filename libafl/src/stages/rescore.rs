@@ -0,0 +1,77 @@
+//! The [`RescoreStage`] recomputes the scores of all testcases in the corpus.
+//!
+//! New coverage may change which edges are rare, so the scores a [`RescoringScheduler`] computed
+//! when a testcase was added go stale over time.
+
+use core::marker::PhantomData;
+
+use crate::{
+    fuzzer::HasScheduler,
+    schedulers::RescoringScheduler,
+    stages::Stage,
+    state::{HasCorpus, UsesState},
+    Error,
+};
+
+/// A stage that lets the [`RescoringScheduler`] of the fuzzer recompute the scores of all testcases.
+///
+/// Rescoring looks at the whole corpus, so this stage should not run for every testcase.
+/// Wrap it into an [`super::IfStage`], for example to only run it once per cycle.
+#[derive(Debug)]
+pub struct RescoreStage<E, EM, Z> {
+    phantom: PhantomData<(E, EM, Z)>,
+}
+
+impl<E, EM, Z> RescoreStage<E, EM, Z> {
+    /// Creates a new [`RescoreStage`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E, EM, Z> Default for RescoreStage<E, EM, Z> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E, EM, Z> UsesState for RescoreStage<E, EM, Z>
+where
+    Z: UsesState,
+{
+    type State = Z::State;
+}
+
+impl<E, EM, Z> Stage<E, EM, Z> for RescoreStage<E, EM, Z>
+where
+    E: UsesState<State = Z::State>,
+    EM: UsesState<State = Z::State>,
+    Z: HasScheduler,
+    Z::Scheduler: RescoringScheduler,
+    Z::State: HasCorpus,
+{
+    #[inline]
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut Z::State,
+        _manager: &mut EM,
+    ) -> Result<(), Error> {
+        fuzzer.scheduler_mut().rescore(state)
+    }
+
+    #[inline]
+    fn restart_progress_should_run(&mut self, _state: &mut Self::State) -> Result<bool, Error> {
+        // Rescoring is idempotent
+        Ok(true)
+    }
+
+    #[inline]
+    fn clear_restart_progress(&mut self, _state: &mut Self::State) -> Result<(), Error> {
+        Ok(())
+    }
+}
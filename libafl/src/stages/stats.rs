@@ -4,16 +4,15 @@
 use alloc::string::ToString;
 use core::{marker::PhantomData, time::Duration};
 
-use libafl_bolts::current_time;
 #[cfg(feature = "std")]
 use serde_json::json;
 
 use crate::{
     corpus::{Corpus, HasCurrentCorpusIdx},
     events::EventFirer,
-    schedulers::minimizer::IsFavoredMetadata,
+    schedulers::{minimizer::IsFavoredMetadata, InputSizeHistogramMetadata},
     stages::Stage,
-    state::{HasCorpus, HasImported, UsesState},
+    state::{HasClock, HasCorpus, HasImported, UsesState},
     Error, HasMetadata,
 };
 #[cfg(feature = "std")]
@@ -38,8 +37,8 @@ where
     own_finds_size: usize,
     // the number of testcases imported by other fuzzers
     imported_size: usize,
-    // the last time that we report all stats
-    last_report_time: Duration,
+    // the last time that we report all stats, or when the first interval started
+    last_report_time: Option<Duration>,
     // the interval that we report all stats
     stats_report_interval: Duration,
 
@@ -60,7 +59,7 @@ where
     E: UsesState,
     EM: EventFirer<State = E::State>,
     Z: UsesState<State = E::State>,
-    E::State: HasImported + HasCorpus + HasMetadata,
+    E::State: HasImported + HasCorpus + HasMetadata + HasClock,
 {
     fn perform(
         &mut self,
@@ -95,17 +94,21 @@ where
         self.imported_size = *state.imported();
         self.own_finds_size = corpus_size - self.imported_size;
 
-        let cur = current_time();
+        let cur = state.now();
+        let last_report_time = *self.last_report_time.get_or_insert(cur);
 
-        if cur.checked_sub(self.last_report_time).unwrap_or_default() > self.stats_report_interval {
+        if cur.checked_sub(last_report_time).unwrap_or_default() > self.stats_report_interval {
             #[cfg(feature = "std")]
             {
-                let json = json!({
+                let mut json = json!({
                         "pending":pending_size,
                         "pend_fav":pend_favored_size,
                         "own_finds":self.own_finds_size,
                         "imported":self.imported_size,
                 });
+                if let Some(histogram) = state.metadata_map().get::<InputSizeHistogramMetadata>() {
+                    json["sizes"] = histogram.summary().into();
+                }
                 _manager.fire(
                     state,
                     Event::UpdateUserStats {
@@ -126,7 +129,11 @@ where
                 self.own_finds_size,
                 self.imported_size
             );
-            self.last_report_time = cur;
+            #[cfg(not(feature = "std"))]
+            if let Some(histogram) = state.metadata_map().get::<InputSizeHistogramMetadata>() {
+                log::info!("sizes: {}", histogram.summary());
+            }
+            self.last_report_time = Some(cur);
         }
 
         Ok(())
@@ -177,7 +184,7 @@ where
             is_favored_size: 0,
             own_finds_size: 0,
             imported_size: 0,
-            last_report_time: current_time(),
+            last_report_time: None,
             stats_report_interval: Duration::from_secs(15),
             phantom: PhantomData,
         }
@@ -304,6 +304,7 @@ where
                         time: current_time(),
                         executions: 0,
                         forward_id: None,
+                        map_checksum: None,
                     },
                 )?;
 
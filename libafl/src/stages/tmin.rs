@@ -1,7 +1,7 @@
 //! The [`TMinMutationalStage`] is a stage which will attempt to minimize corpus entries.
 
 use alloc::string::{String, ToString};
-use core::{borrow::BorrowMut, fmt::Debug, hash::Hash, marker::PhantomData};
+use core::{borrow::BorrowMut, fmt::Debug, hash::Hash, marker::PhantomData, time::Duration};
 
 use ahash::RandomState;
 use libafl_bolts::{HasLen, Named};
@@ -18,11 +18,12 @@ use crate::{
     schedulers::{RemovableScheduler, Scheduler},
     stages::{
         mutational::{MutatedTransform, MutatedTransformPost},
-        ExecutionCountRestartHelper, Stage,
+        ExecutionCountRestartHelper, Stage, StageDeadline,
     },
     start_timer,
     state::{
-        HasCorpus, HasCurrentTestcase, HasExecutions, HasMaxSize, HasSolutions, State, UsesState,
+        HasClock, HasCorpus, HasCurrentTestcase, HasExecutions, HasMaxSize, HasSolutions, State,
+        UsesState,
     },
     Error, ExecutesInput, ExecutionProcessor, HasFeedback, HasMetadata, HasScheduler,
 };
@@ -35,7 +36,7 @@ use crate::{monitors::PerfFeature, state::HasClientPerfMonitor};
 pub trait TMinMutationalStage<CS, E, EM, F1, F2, I, IP, M, OT, Z>:
     Stage<E, EM, Z> + FeedbackFactory<F2, CS::State, OT>
 where
-    Self::State: HasCorpus + HasSolutions + HasExecutions + HasMaxSize,
+    Self::State: HasCorpus + HasSolutions + HasExecutions + HasMaxSize + HasClock,
     <Self::State as UsesInput>::Input: HasLen + Hash,
     CS: Scheduler<State = Self::State> + RemovableScheduler,
     E: Executor<EM, Z> + HasObservers<Observers = OT, State = Self::State>,
@@ -60,6 +61,13 @@ where
     /// Gets the number of iterations this mutator should run for.
     fn iterations(&self, state: &mut CS::State) -> Result<usize, Error>;
 
+    /// The wall-clock budget of a single run of this stage, if any.
+    /// Once exceeded, the minimization stops, keeping the smallest input found so far.
+    #[inline]
+    fn time_budget(&self) -> Option<Duration> {
+        None
+    }
+
     /// Runs this (mutational) stage for new objectives
     #[allow(clippy::cast_possible_wrap)] // more than i32 stages on 32 bit system - highly unlikely...
     fn perform_minification(
@@ -93,9 +101,10 @@ where
 
         let mut feedback = self.create_feedback(observers);
 
+        let deadline = StageDeadline::new(state, self.time_budget());
         let mut i = 0;
         loop {
-            if i >= num {
+            if i >= num || deadline.exceeded(state) {
                 break;
             }
 
@@ -206,6 +215,8 @@ pub struct StdTMinMutationalStage<CS, E, EM, F1, F2, FF, I, IP, M, OT, Z> {
     factory: FF,
     /// The runs (=iterations) we are supposed to do
     runs: usize,
+    /// The wall-clock budget of a single run of this stage
+    time_budget: Option<Duration>,
     /// The progress helper for this stage, keeping track of resumes after timeouts/crashes
     restart_helper: ExecutionCountRestartHelper,
     #[allow(clippy::type_complexity)]
@@ -229,7 +240,8 @@ impl<CS, E, EM, F1, F2, FF, I, IP, M, OT, Z> Stage<E, EM, Z>
     for StdTMinMutationalStage<CS, E, EM, F1, F2, FF, I, IP, M, OT, Z>
 where
     CS: Scheduler + RemovableScheduler,
-    CS::State: HasCorpus + HasSolutions + HasExecutions + HasMaxSize + HasCorpus + HasMetadata,
+    CS::State:
+        HasCorpus + HasSolutions + HasExecutions + HasMaxSize + HasCorpus + HasMetadata + HasClock,
     <CS::State as UsesInput>::Input: HasLen + Hash,
     E: Executor<EM, Z> + HasObservers<Observers = OT, State = CS::State>,
     EM: EventFirer<State = CS::State>,
@@ -293,7 +305,7 @@ where
     <CS::State as UsesInput>::Input: HasLen + Hash,
     M: Mutator<I, CS::State>,
     OT: ObserversTuple<CS::State>,
-    CS::State: HasCorpus + HasSolutions + HasExecutions + HasMaxSize + HasMetadata,
+    CS::State: HasCorpus + HasSolutions + HasExecutions + HasMaxSize + HasMetadata + HasClock,
     Z: ExecutionProcessor<OT, State = CS::State>
         + ExecutesInput<E, EM>
         + HasFeedback<Feedback = F1>
@@ -318,6 +330,11 @@ where
         Ok(self.runs)
     }
 
+    #[inline]
+    fn time_budget(&self) -> Option<Duration> {
+        self.time_budget
+    }
+
     fn execs_since_progress_start(&mut self, state: &mut <Z>::State) -> Result<u64, Error> {
         self.restart_helper.execs_since_progress_start(state)
     }
@@ -339,10 +356,19 @@ where
             mutator,
             factory,
             runs,
+            time_budget: None,
             restart_helper: ExecutionCountRestartHelper::default(),
             phantom: PhantomData,
         }
     }
+
+    /// Stops minimizing once the given wall-clock budget is used up, keeping the smallest input found so far,
+    /// so that minimizing a huge input cannot stall the fuzzing loop.
+    #[must_use]
+    pub fn with_time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
 }
 
 /// A feedback which checks if the hash of the currently observed map is equal to the original hash
@@ -14,7 +14,7 @@ use crate::{
         ExecutionCountRestartHelper, MutationalStage, Stage,
     },
     start_timer,
-    state::{HasCorpus, HasCurrentTestcase, HasExecutions, HasRand, UsesState},
+    state::{HasClock, HasCorpus, HasCurrentTestcase, HasExecutions, HasRand, UsesState},
     Error, Evaluator, HasMetadata, HasNamedMetadata,
 };
 #[cfg(feature = "introspection")]
@@ -164,7 +164,7 @@ where
     EM: UsesState<State = Z::State>,
     M: Mutator<I, Z::State>,
     Z: Evaluator<E, EM>,
-    Z::State: HasCorpus + HasRand + HasNamedMetadata + HasMetadata + HasExecutions,
+    Z::State: HasCorpus + HasRand + HasNamedMetadata + HasMetadata + HasExecutions + HasClock,
     I: MutatedTransform<Z::Input, Z::State> + Clone,
 {
     /// Runs this (mutational) stage for the given `testcase`
@@ -274,7 +274,7 @@ where
     EM: UsesState<State = Z::State>,
     M: Mutator<I, Z::State>,
     Z: Evaluator<E, EM>,
-    Z::State: HasCorpus + HasRand + HasNamedMetadata + HasMetadata + HasExecutions,
+    Z::State: HasCorpus + HasRand + HasNamedMetadata + HasMetadata + HasExecutions + HasClock,
     I: MutatedTransform<Z::Input, Z::State> + Clone,
 {
     #[inline]
@@ -309,7 +309,7 @@ where
     EM: UsesState<State = Z::State>,
     M: Mutator<I, Z::State>,
     Z: Evaluator<E, EM>,
-    Z::State: HasCorpus + HasRand + HasNamedMetadata + HasMetadata + HasExecutions,
+    Z::State: HasCorpus + HasRand + HasNamedMetadata + HasMetadata + HasExecutions + HasClock,
     I: MutatedTransform<Z::Input, Z::State> + Clone,
 {
     /// Creates a new default tuneable mutational stage
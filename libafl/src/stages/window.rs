@@ -0,0 +1,236 @@
+//! The [`WindowedHavocStage`] concentrates its mutations in a window around an offset of interest.
+//!
+//! For directed fuzzing, the offset, for example where a parser stopped consuming the input,
+//! gets attached to a testcase as [`FocusOffsetMetadata`]. The bytes outside of the window stay intact.
+
+use core::{any::type_name, marker::PhantomData, ops::Range};
+
+use libafl_bolts::{impl_serdeany, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fuzzer::Evaluator,
+    inputs::HasBytesVec,
+    mutators::{MutationResult, Mutator},
+    stages::{RetryRestartHelper, Stage},
+    state::{HasCorpus, HasCurrentTestcase, HasMaxSize, UsesState},
+    Error, HasMetadata, HasNamedMetadata,
+};
+
+/// The offset of a testcase the [`WindowedHavocStage`] mutates around
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct FocusOffsetMetadata {
+    /// The offset into the input bytes
+    pub offset: usize,
+}
+
+impl_serdeany!(FocusOffsetMetadata);
+
+/// The window of `radius` bytes on each side of `offset`, clamped to an input of `len` bytes
+#[must_use]
+pub fn focus_window(len: usize, offset: usize, radius: usize) -> Range<usize> {
+    let start = offset.saturating_sub(radius).min(len);
+    let end = offset.saturating_add(radius).min(len);
+    start..end
+}
+
+/// A stage that mutates only the bytes in a window around the [`FocusOffsetMetadata`] of the current testcase,
+/// and evaluates the resulting inputs. Testcases without the metadata are skipped.
+///
+/// The mutator only sees the bytes of the window, so mutations changing the length grow or shrink the window,
+/// and the bytes before and after it are never changed.
+/// While mutating, the max size of the state is lowered to the bytes left for the window,
+/// so that the whole input stays within the max size.
+#[derive(Clone, Debug)]
+pub struct WindowedHavocStage<E, EM, M, Z> {
+    mutator: M,
+    /// The number of bytes on each side of the offset
+    radius: usize,
+    /// The number of mutated inputs per testcase
+    iterations: usize,
+    phantom: PhantomData<(E, EM, Z)>,
+}
+
+impl<E, EM, M, Z> WindowedHavocStage<E, EM, M, Z>
+where
+    E: UsesState<State = Z::State>,
+    EM: UsesState<State = Z::State>,
+    M: Mutator<Z::Input, Z::State>,
+    Z: Evaluator<E, EM>,
+{
+    /// Creates a new [`WindowedHavocStage`], applying the `mutator` to `radius` bytes on each side of the offset,
+    /// `iterations` times per testcase
+    pub fn new(mutator: M, radius: usize, iterations: usize) -> Self {
+        Self {
+            mutator,
+            radius,
+            iterations,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The number of bytes on each side of the offset
+    #[must_use]
+    pub fn radius(&self) -> usize {
+        self.radius
+    }
+}
+
+impl<E, EM, M, Z> UsesState for WindowedHavocStage<E, EM, M, Z>
+where
+    E: UsesState<State = Z::State>,
+    EM: UsesState<State = Z::State>,
+    Z: Evaluator<E, EM>,
+{
+    type State = Z::State;
+}
+
+impl<E, EM, M, Z> Named for WindowedHavocStage<E, EM, M, Z> {
+    fn name(&self) -> &str {
+        type_name::<Self>()
+    }
+}
+
+impl<E, EM, M, Z> Stage<E, EM, Z> for WindowedHavocStage<E, EM, M, Z>
+where
+    E: UsesState<State = Z::State>,
+    EM: UsesState<State = Z::State>,
+    M: Mutator<Z::Input, Z::State>,
+    Z: Evaluator<E, EM>,
+    Z::State: HasCorpus + HasMetadata + HasNamedMetadata + HasMaxSize,
+    Z::Input: HasBytesVec,
+{
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut Z::State,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let Ok(focus) = state
+            .current_testcase()?
+            .metadata::<FocusOffsetMetadata>()
+            .copied()
+        else {
+            return Ok(());
+        };
+
+        let input = state.current_input_cloned()?;
+        let window = focus_window(input.bytes().len(), focus.offset, self.radius);
+        let max_size = state.max_size();
+        // The bytes the window may grow to, without the whole input exceeding the max size
+        let window_max_size = max_size
+            .saturating_sub(input.bytes().len() - window.len())
+            .max(window.len());
+
+        for _ in 0..self.iterations {
+            let mut windowed = input.clone();
+            *windowed.bytes_mut() = input.bytes()[window.clone()].to_vec();
+            state.set_max_size(window_max_size);
+            let mutated = self.mutator.mutate(state, &mut windowed);
+            state.set_max_size(max_size);
+            if mutated? == MutationResult::Skipped || windowed.bytes().len() > window_max_size {
+                continue;
+            }
+
+            let mut mutated = input.clone();
+            mutated
+                .bytes_mut()
+                .splice(window.clone(), windowed.bytes().iter().copied());
+            let (_, corpus_idx) = fuzzer.evaluate_input(state, executor, manager, mutated)?;
+            self.mutator.post_exec(state, corpus_idx)?;
+        }
+        Ok(())
+    }
+
+    fn restart_progress_should_run(&mut self, state: &mut Self::State) -> Result<bool, Error> {
+        RetryRestartHelper::restart_progress_should_run(state, self, 3)
+    }
+
+    fn clear_restart_progress(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        RetryRestartHelper::clear_restart_progress(state, self)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use alloc::vec::Vec;
+
+    use libafl_bolts::tuples::tuple_list;
+
+    use crate::{
+        corpus::{Corpus, HasCurrentCorpusIdx, Testcase},
+        executors::ExitKind,
+        feedbacks::ConstFeedback,
+        fuzzer::test::test_fuzzer,
+        inputs::{BytesInput, HasBytesVec},
+        mutators::{havoc_mutations_no_crossover, StdScheduledMutator},
+        schedulers::QueueScheduler,
+        stages::{
+            window::{focus_window, FocusOffsetMetadata},
+            Stage, WindowedHavocStage,
+        },
+        state::{HasCorpus, HasMaxSize},
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_windowed_havoc() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            FocusOffsetMetadata::register();
+        }
+
+        assert_eq!(focus_window(64, 40, 4), 36..44);
+        assert_eq!(focus_window(64, 2, 4), 0..6);
+        assert_eq!(focus_window(64, 80, 4), 64..64);
+
+        let original: Vec<u8> = (0..64).collect();
+        let mut executed = Vec::new();
+        let mut harness = |input: &BytesInput| {
+            executed.push(input.bytes().to_vec());
+            ExitKind::Ok
+        };
+        let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+            (),
+            &mut harness,
+            tuple_list!(),
+        );
+
+        let mut testcase = Testcase::new(BytesInput::new(original.clone()));
+        testcase.add_metadata(FocusOffsetMetadata { offset: 40 });
+        let id = state.corpus_mut().add(testcase).unwrap();
+        state.set_corpus_idx(id).unwrap();
+        // Leaves room for the window to grow by two bytes
+        state.set_max_size(66);
+
+        let mutator = StdScheduledMutator::new(havoc_mutations_no_crossover());
+        let mut stage = WindowedHavocStage::new(mutator, 4, 100);
+        stage
+            .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+            .unwrap();
+
+        assert!(executed.len() > 50);
+        let mut changed = 0;
+        for bytes in &executed {
+            assert!(bytes.len() <= 66, "{bytes:?}");
+            // Outside of the window, the input is untouched
+            assert!(bytes.starts_with(&original[..36]), "{bytes:?}");
+            assert!(bytes.ends_with(&original[44..]), "{bytes:?}");
+            if *bytes != original {
+                changed += 1;
+            }
+        }
+        assert!(changed > 50);
+    }
+}
@@ -0,0 +1,133 @@
+//! The [`CampaignConfigMetadata`] records how a fuzzing campaign was configured.
+//!
+//! Added to the [`super::State`] at startup, it gets serialized with every state snapshot,
+//! so that reports, like crash reports, can tell exactly how the fuzzer that found them was set up.
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+use libafl_bolts::impl_serdeany;
+use serde::{Deserialize, Serialize};
+
+/// The configuration of a fuzzing campaign, stored as [`crate::HasMetadata`] of the state
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct CampaignConfigMetadata {
+    /// The directories the initial inputs were loaded from
+    pub seed_dirs: Vec<String>,
+    /// The names of the mutators in use
+    pub mutators: Vec<String>,
+    /// The weight of each feedback, by name
+    pub feedback_weights: BTreeMap<String, f64>,
+    /// The seed of the random number generator, if the campaign was seeded deterministically
+    pub rng_seed: Option<u64>,
+    /// Any other configuration, as key-value pairs
+    pub extra: BTreeMap<String, String>,
+}
+
+impl_serdeany!(CampaignConfigMetadata);
+
+impl CampaignConfigMetadata {
+    /// Creates a new, empty [`CampaignConfigMetadata`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a directory the initial inputs were loaded from
+    #[must_use]
+    pub fn with_seed_dir<P>(mut self, dir: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.seed_dirs.push(dir.into());
+        self
+    }
+
+    /// Records the names of the mutators in use, for example from [`crate::mutators::MutatorsTuple::names`]
+    #[must_use]
+    pub fn with_mutators<IT, N>(mut self, names: IT) -> Self
+    where
+        IT: IntoIterator<Item = N>,
+        N: Into<String>,
+    {
+        self.mutators.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Records the weight of a feedback
+    #[must_use]
+    pub fn with_feedback_weight<N>(mut self, name: N, weight: f64) -> Self
+    where
+        N: Into<String>,
+    {
+        self.feedback_weights.insert(name.into(), weight);
+        self
+    }
+
+    /// Records the seed of the random number generator
+    #[must_use]
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.rng_seed = Some(seed);
+        self
+    }
+
+    /// Records any other configuration value
+    #[must_use]
+    pub fn with_extra<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.extra.insert(key.into(), value.into());
+        self
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use libafl_bolts::rands::StdRand;
+
+    use crate::{
+        corpus::InMemoryCorpus,
+        inputs::BytesInput,
+        state::{test::test_std_state, CampaignConfigMetadata, StdState},
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_campaign_config_roundtrip() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            CampaignConfigMetadata::register();
+        }
+
+        let mut state = test_std_state::<BytesInput>();
+
+        let config = CampaignConfigMetadata::new()
+            .with_seed_dir("./corpus")
+            .with_mutators(["BitFlipMutator", "BytesInsertMutator"])
+            .with_feedback_weight("MapFeedback", 1.0)
+            .with_feedback_weight("TimeFeedback", 0.5)
+            .with_rng_seed(1337)
+            .with_extra("target", "./harness --fast");
+        state.add_metadata(config.clone());
+
+        let bytes = postcard::to_allocvec(&state).unwrap();
+        let restored: StdState<
+            BytesInput,
+            InMemoryCorpus<BytesInput>,
+            StdRand,
+            InMemoryCorpus<BytesInput>,
+        > = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            restored.metadata::<CampaignConfigMetadata>().unwrap(),
+            &config
+        );
+    }
+}
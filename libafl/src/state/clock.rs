@@ -0,0 +1,80 @@
+//! Pluggable time sources for the [`super::StdState`].
+//!
+//! Everything reading the time through [`HasClock::now`], such as the progress reports of the event managers,
+//! can be driven by a [`MockClock`] in tests, instead of waiting for the real clock.
+
+use alloc::sync::Arc;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::{fmt::Debug, time::Duration};
+
+use libafl_bolts::current_time;
+
+/// A source of the current time, as a [`Duration`] since some fixed point, usually the unix epoch
+pub trait Clock: Debug + Send + Sync {
+    /// The current time
+    fn now(&self) -> Duration;
+}
+
+/// The real clock, see [`current_time`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Duration {
+        current_time()
+    }
+}
+
+/// A clock that only moves when told to, for tests.
+///
+/// Clones share the same time, so a test can keep a clone to advance the clock of a state.
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug, Default, Clone)]
+pub struct MockClock {
+    nanos: Arc<AtomicU64>,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl MockClock {
+    /// Creates a new [`MockClock`], starting at the given time
+    #[must_use]
+    pub fn new(start: Duration) -> Self {
+        let clock = Self::default();
+        clock.set(start);
+        clock
+    }
+
+    /// Sets the current time
+    #[allow(clippy::cast_possible_truncation)] // more than 500 years are not going to be mocked
+    pub fn set(&self, now: Duration) {
+        self.nanos.store(now.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    /// Moves the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        self.set(self.now() + duration);
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Clock for MockClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::SeqCst))
+    }
+}
+
+/// The default clock of a state
+pub(crate) fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// Has a [`Clock`], to read the current time from
+pub trait HasClock {
+    /// The current time of the clock, defaults to the real time
+    #[inline]
+    fn now(&self) -> Duration {
+        current_time()
+    }
+}
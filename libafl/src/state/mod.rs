@@ -1,6 +1,6 @@
 //! The fuzzer, and state are the core pieces of every good fuzzer
 
-use alloc::vec::Vec;
+use alloc::{sync::Arc, vec::Vec};
 use core::{
     borrow::BorrowMut,
     cell::{Ref, RefMut},
@@ -18,7 +18,7 @@ use std::{
 use libafl_bolts::core_affinity::{CoreId, Cores};
 use libafl_bolts::{
     rands::{Rand, StdRand},
-    serdeany::{NamedSerdeAnyMap, SerdeAnyMap},
+    serdeany::{NamedSerdeAnyMap, SerdeAny, SerdeAnyMap},
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
@@ -29,14 +29,23 @@ use crate::monitors::ScalabilityMonitor;
 use crate::{
     corpus::{Corpus, CorpusId, HasCurrentCorpusIdx, HasTestcase, Testcase},
     events::{Event, EventFirer, LogSeverity},
-    feedbacks::Feedback,
+    feedbacks::{Feedback, MapFeedbackMetadata},
     fuzzer::{Evaluator, ExecuteInputResult},
     generators::Generator,
     inputs::{Input, UsesInput},
+    mutators::EntropyWeightingMetadata,
+    schedulers::EdgeRarityMetadata,
     stages::{HasCurrentStage, HasNestedStageStatus},
     Error, HasMetadata, HasNamedMetadata,
 };
 
+pub mod campaign;
+pub use campaign::CampaignConfigMetadata;
+pub mod clock;
+#[cfg(target_has_atomic = "64")]
+pub use clock::MockClock;
+pub use clock::{Clock, HasClock, SystemClock};
+
 /// The maximum size of a testcase
 pub const DEFAULT_MAX_SIZE: usize = 1_048_576;
 
@@ -87,6 +96,24 @@ pub trait HasMaxSize {
     fn set_max_size(&mut self, max_size: usize);
 }
 
+/// The running count of map entries covered by the corpus, kept up to date in the [`EdgeRarityMetadata`]
+/// by a [`crate::schedulers::CoverageTrackingScheduler`] or a [`crate::schedulers::RareEdgeScheduler`]
+pub trait HasCorpusCoverage {
+    /// The number of map entries covered by at least one corpus entry.
+    /// Returns `0`, if no scheduler counted any coverage yet.
+    fn corpus_coverage(&self) -> usize;
+}
+
+impl<T> HasCorpusCoverage for T
+where
+    T: HasMetadata,
+{
+    fn corpus_coverage(&self) -> usize {
+        self.metadata::<EdgeRarityMetadata>()
+            .map_or(0, EdgeRarityMetadata::covered)
+    }
+}
+
 /// Trait for elements offering a corpus of solutions
 pub trait HasSolutions: UsesInput {
     /// The associated type implementing [`Corpus`] for solutions
@@ -106,6 +133,13 @@ pub trait HasRand {
     fn rand(&self) -> &Self::Rand;
     /// The rand instance (mutable)
     fn rand_mut(&mut self) -> &mut Self::Rand;
+
+    /// Picks the offset of a positional mutation of the `bytes`, below `upper`, see [`crate::mutators::rand_position`].
+    /// All offsets are equally likely, unless the state weights them.
+    #[inline]
+    fn rand_position(&mut self, _bytes: &[u8], upper: usize) -> usize {
+        self.rand_mut().below(upper as u64) as usize
+    }
 }
 
 #[cfg(feature = "introspection")]
@@ -182,6 +216,21 @@ pub trait HasStartTime {
     fn start_time_mut(&mut self) -> &mut Duration;
 }
 
+/// Trait for the time it took this node to make its first finds
+pub trait HasFirstFindTimes: HasStartTime {
+    /// The time from the start until the first testcase was added to the corpus, if any
+    fn time_to_first_corpus(&self) -> Option<Duration>;
+
+    /// The time from the start until the first testcase was added to the corpus (mutable)
+    fn time_to_first_corpus_mut(&mut self) -> &mut Option<Duration>;
+
+    /// The time from the start until the first solution was found, if any
+    fn time_to_first_solution(&self) -> Option<Duration>;
+
+    /// The time from the start until the first solution was found (mutable)
+    fn time_to_first_solution_mut(&mut self) -> &mut Option<Duration>;
+}
+
 /// Trait for the last report time, the last time this node reported progress
 pub trait HasLastReportTime {
     /// The last time we reported progress,if available/used.
@@ -207,6 +256,10 @@ pub struct StdState<I, C, R, SC> {
     executions: u64,
     /// At what time the fuzzing started
     start_time: Duration,
+    /// How long it took until the first testcase was added to the corpus
+    time_to_first_corpus: Option<Duration>,
+    /// How long it took until the first solution was found
+    time_to_first_solution: Option<Duration>,
     /// the number of new paths that imported from other fuzzers
     imported: usize,
     /// The corpus
@@ -231,6 +284,9 @@ pub struct StdState<I, C, R, SC> {
     /// Remaining initial inputs to load, if any
     dont_reenter: Option<Vec<PathBuf>>,
     #[cfg(feature = "std")]
+    /// If initial inputs are loaded sorted by their path, instead of in directory order
+    sorted_initial_inputs: bool,
+    #[cfg(feature = "std")]
     /// If inputs have been processed for multicore loading
     /// relevant only for `load_initial_inputs_multicore`
     multicore_inputs_processed: Option<bool>,
@@ -243,6 +299,9 @@ pub struct StdState<I, C, R, SC> {
     stage_idx_stack: Vec<usize>,
     /// The current stage depth
     stage_depth: usize,
+    /// The source of the current time, not serialized, so restarts go back to the [`SystemClock`]
+    #[serde(skip, default = "clock::system_clock")]
+    clock: Arc<dyn Clock>,
     phantom: PhantomData<I>,
 }
 
@@ -279,6 +338,15 @@ where
     fn rand_mut(&mut self) -> &mut Self::Rand {
         &mut self.rand
     }
+
+    /// Weights the offsets by entropy, if there is an [`EntropyWeightingMetadata`]
+    #[inline]
+    fn rand_position(&mut self, bytes: &[u8], upper: usize) -> usize {
+        match self.metadata::<EntropyWeightingMetadata>().ok().copied() {
+            Some(weighting) => weighting.position(&mut self.rand, bytes, upper),
+            None => self.rand.below(upper as u64) as usize,
+        }
+    }
 }
 
 impl<I, C, R, SC> HasCorpus for StdState<I, C, R, SC>
@@ -439,6 +507,35 @@ impl<I, C, R, SC> HasStartTime for StdState<I, C, R, SC> {
     }
 }
 
+impl<I, C, R, SC> HasClock for StdState<I, C, R, SC> {
+    #[inline]
+    fn now(&self) -> Duration {
+        self.clock.now()
+    }
+}
+
+impl<I, C, R, SC> HasFirstFindTimes for StdState<I, C, R, SC> {
+    #[inline]
+    fn time_to_first_corpus(&self) -> Option<Duration> {
+        self.time_to_first_corpus
+    }
+
+    #[inline]
+    fn time_to_first_corpus_mut(&mut self) -> &mut Option<Duration> {
+        &mut self.time_to_first_corpus
+    }
+
+    #[inline]
+    fn time_to_first_solution(&self) -> Option<Duration> {
+        self.time_to_first_solution
+    }
+
+    #[inline]
+    fn time_to_first_solution_mut(&mut self) -> &mut Option<Duration> {
+        &mut self.time_to_first_solution
+    }
+}
+
 impl<I, C, R, SC> HasCurrentCorpusIdx for StdState<I, C, R, SC> {
     fn set_corpus_idx(&mut self, idx: CorpusId) -> Result<(), Error> {
         self.corpus_idx = Some(idx);
@@ -567,6 +664,26 @@ where
                 && !self.remaining_initial_files.as_ref().unwrap().is_empty())
     }
 
+    /// If initial inputs are loaded sorted by their path, see [`Self::set_sorted_initial_inputs`]
+    #[must_use]
+    pub fn sorted_initial_inputs(&self) -> bool {
+        self.sorted_initial_inputs
+    }
+
+    /// Loads the initial inputs sorted by their path, i.e., by their filename within each directory,
+    /// instead of in the order the filesystem lists them.
+    /// This makes the initial corpus, and with it the whole run, reproducible across machines.
+    pub fn set_sorted_initial_inputs(&mut self, sorted: bool) {
+        self.sorted_initial_inputs = sorted;
+    }
+
+    /// Sorts the given files so that they are popped from the stack of remaining files in ascending order
+    fn sort_pending_files(&self, files: &mut [PathBuf]) {
+        if self.sorted_initial_inputs {
+            files.sort_unstable_by(|a, b| b.cmp(a));
+        }
+    }
+
     /// List initial inputs from a directory.
     fn next_file(&mut self) -> Result<PathBuf, Error> {
         loop {
@@ -591,9 +708,13 @@ where
                 if attr.is_file() && attr.len() > 0 {
                     return Ok(path);
                 } else if attr.is_dir() {
+                    let mut entries = path
+                        .read_dir()?
+                        .map(|entry| entry.map(|e| e.path()))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    self.sort_pending_files(&mut entries);
                     let files = self.remaining_initial_files.as_mut().unwrap();
-                    path.read_dir()?
-                        .try_for_each(|entry| entry.map(|e| files.push(e.path())))?;
+                    files.append(&mut entries);
                 } else if attr.is_symlink() {
                     let path = fs::canonicalize(path)?;
                     let dont_reenter = self.dont_reenter.get_or_insert_with(Default::default);
@@ -626,12 +747,13 @@ where
                 return Ok(());
             }
         } else {
-            let files = in_dirs.iter().try_fold(Vec::new(), |mut res, file| {
+            let mut files = in_dirs.iter().try_fold(Vec::new(), |mut res, file| {
                 file.canonicalize().map(|canonicalized| {
                     res.push(canonicalized);
                     res
                 })
             })?;
+            self.sort_pending_files(&mut files);
             self.dont_reenter = Some(files.clone());
             self.remaining_initial_files = Some(files);
         }
@@ -661,7 +783,9 @@ where
                 return Ok(());
             }
         } else {
-            self.remaining_initial_files = Some(file_list.to_vec());
+            let mut files = file_list.to_vec();
+            self.sort_pending_files(&mut files);
+            self.remaining_initial_files = Some(files);
         }
 
         self.continue_loading_initial_inputs_custom(fuzzer, executor, manager, forced, loader)
@@ -1022,11 +1146,14 @@ where
         F: Feedback<Self>,
         O: Feedback<Self>,
     {
+        let clock = clock::system_clock();
         let mut state = Self {
             rand,
             executions: 0,
             imported: 0,
-            start_time: Duration::from_millis(0),
+            start_time: clock.now(),
+            time_to_first_corpus: None,
+            time_to_first_solution: None,
             metadata: SerdeAnyMap::default(),
             named_metadata: NamedSerdeAnyMap::default(),
             corpus,
@@ -1040,10 +1167,13 @@ where
             remaining_initial_files: None,
             #[cfg(feature = "std")]
             dont_reenter: None,
+            #[cfg(feature = "std")]
+            sorted_initial_inputs: false,
             last_report_time: None,
             corpus_idx: None,
             stage_depth: 0,
             stage_idx_stack: Vec::new(),
+            clock,
             phantom: PhantomData,
             #[cfg(feature = "std")]
             multicore_inputs_processed: None,
@@ -1052,6 +1182,39 @@ where
         objective.init_state(&mut state)?;
         Ok(state)
     }
+
+    /// Reads the time from the given [`Clock`] from now on, for example from a [`MockClock`] in tests.
+    /// The start time is reset to the current time of the new clock.
+    pub fn set_clock<CL>(&mut self, clock: CL)
+    where
+        CL: Clock + 'static,
+    {
+        self.clock = Arc::new(clock);
+        self.start_time = self.clock.now();
+    }
+}
+
+impl<I, C, R, SC> StdState<I, C, R, SC> {
+    /// Merges the coverage history of each [`MapFeedback`](crate::feedbacks::MapFeedback) with entries of type `T`
+    /// from `other` into this state, e.g., to combine the coverage of many workers into one global baseline.
+    ///
+    /// Histories of the same name are merged, see [`MapFeedbackMetadata::merge`], others are copied over.
+    pub fn merge_coverage_from<S, T>(&mut self, other: &S) -> Result<(), Error>
+    where
+        S: HasNamedMetadata,
+        T: Debug + Default + Copy + PartialOrd + Serialize + DeserializeOwned + 'static,
+        MapFeedbackMetadata<T>: SerdeAny,
+    {
+        let other_map = other.named_metadata_map();
+        for name in other_map.names::<MapFeedbackMetadata<T>>() {
+            let other_history = other_map.get::<MapFeedbackMetadata<T>>(name).unwrap();
+            match self.named_metadata.get_mut::<MapFeedbackMetadata<T>>(name) {
+                Some(history) => history.merge(other_history)?,
+                None => self.named_metadata.insert(name, other_history.clone()),
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(feature = "introspection")]
@@ -1149,6 +1312,8 @@ impl<I> HasRand for NopState<I> {
 
 impl<I> State for NopState<I> where I: Input {}
 
+impl<I> HasClock for NopState<I> {}
+
 impl<I> HasCurrentCorpusIdx for NopState<I> {
     fn set_corpus_idx(&mut self, _idx: CorpusId) -> Result<(), Error> {
         Ok(())
@@ -1201,21 +1366,137 @@ impl<I> HasScalabilityMonitor for NopState<I> {
 
 #[cfg(test)]
 pub mod test {
+    #[cfg(feature = "std")]
+    use alloc::vec::Vec;
+    #[cfg(feature = "std")]
+    use std::{fs, path::PathBuf};
+
     use libafl_bolts::rands::StdRand;
+    #[cfg(feature = "std")]
+    use libafl_bolts::tuples::tuple_list;
 
     use super::StdState;
-    use crate::{corpus::InMemoryCorpus, inputs::Input};
+    use crate::{
+        corpus::InMemoryCorpus,
+        feedbacks::{Feedback, MapFeedbackMetadata},
+        inputs::{BytesInput, Input},
+        HasNamedMetadata,
+    };
+    #[cfg(feature = "std")]
+    use crate::{
+        executors::ExitKind, feedbacks::ConstFeedback, fuzzer::test::test_fuzzer,
+        inputs::HasBytesVec, schedulers::QueueScheduler,
+    };
+
+    /// The state of the tests, see [`test_std_state`]
+    pub type TestStdState<I> = StdState<I, InMemoryCorpus<I>, StdRand, InMemoryCorpus<I>>;
 
     #[must_use]
-    pub fn test_std_state<I: Input>() -> StdState<I, InMemoryCorpus<I>, StdRand, InMemoryCorpus<I>>
+    pub fn test_std_state<I: Input>() -> TestStdState<I> {
+        test_std_state_with(&mut (), &mut ())
+    }
+
+    /// A [`test_std_state`], with the state of the `feedback` and the `objective` initialized
+    #[must_use]
+    pub fn test_std_state_with<I, F, O>(feedback: &mut F, objective: &mut O) -> TestStdState<I>
+    where
+        I: Input,
+        F: Feedback<TestStdState<I>>,
+        O: Feedback<TestStdState<I>>,
     {
         StdState::new(
             StdRand::with_seed(0),
             InMemoryCorpus::<I>::new(),
             InMemoryCorpus::new(),
-            &mut (),
-            &mut (),
+            feedback,
+            objective,
         )
         .expect("couldn't instantiate the test state")
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_sorted_initial_inputs() {
+        let dir = PathBuf::from("target/.test/sorted_initial_inputs");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        // Created out of order, the directory order depends on the filesystem
+        for name in ["c", "a", "e", "b"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+        fs::write(dir.join("sub").join("d"), "d").unwrap();
+
+        let mut executed = Vec::new();
+        {
+            let mut harness = |input: &BytesInput| {
+                executed.push(input.bytes().to_vec());
+                ExitKind::Ok
+            };
+            let (mut fuzzer, mut executor, mut state, mut mgr) = test_fuzzer(
+                QueueScheduler::new(),
+                ConstFeedback::new(false),
+                ConstFeedback::new(false),
+                (),
+                &mut harness,
+                tuple_list!(),
+            );
+            state.set_sorted_initial_inputs(true);
+            state
+                .load_initial_inputs(&mut fuzzer, &mut executor, &mut mgr, &[dir.clone()])
+                .unwrap();
+        }
+
+        // `sub` sorts after `e`
+        assert_eq!(
+            executed,
+            [b"a", b"b", b"c", b"e", b"d"].map(|name| name.to_vec())
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_coverage_from() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            MapFeedbackMetadata::<u8>::register();
+        }
+
+        let mut state = test_std_state::<BytesInput>();
+        state.add_named_metadata(
+            "edges",
+            MapFeedbackMetadata::with_history_map(vec![1_u8, 1, 0, 0, 0], 0),
+        );
+        let mut other = test_std_state::<BytesInput>();
+        other.add_named_metadata(
+            "edges",
+            MapFeedbackMetadata::with_history_map(vec![0_u8, 4, 2, 0, 0], 0),
+        );
+        other.add_named_metadata(
+            "cmps",
+            MapFeedbackMetadata::with_history_map(vec![0_u8, 7], 0),
+        );
+
+        state.merge_coverage_from::<_, u8>(&other).unwrap();
+
+        let edges = state
+            .named_metadata::<MapFeedbackMetadata<u8>>("edges")
+            .unwrap();
+        assert_eq!(edges.history_map, [1, 4, 2, 0, 0]);
+        // 2 + 2 covered entries, one of them shared
+        assert_eq!(edges.num_covered_map_indexes, 3);
+        let cmps = state
+            .named_metadata::<MapFeedbackMetadata<u8>>("cmps")
+            .unwrap();
+        assert_eq!(cmps.num_covered_map_indexes, 1);
+
+        // Histories of different map sizes do not belong together
+        other.add_named_metadata(
+            "edges",
+            MapFeedbackMetadata::with_history_map(vec![1_u8; 8], 0),
+        );
+        assert!(state.merge_coverage_from::<_, u8>(&other).is_err());
+    }
 }
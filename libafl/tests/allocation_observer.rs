@@ -0,0 +1,35 @@
+//! Tests the [`AllocationObserver`] with the [`CountingAllocator`] installed as global allocator.
+//! This lives in its own test binary, so the allocator does not replace the one of the unit tests.
+
+#![cfg(feature = "std")]
+
+use std::{alloc::System, hint::black_box};
+
+use libafl::{
+    executors::ExitKind,
+    inputs::BytesInput,
+    observers::{AllocationObserver, CountingAllocator, Observer},
+    state::NopState,
+};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator<System> = CountingAllocator::new(System);
+
+#[test]
+fn test_allocation_observer() {
+    let mut state = NopState::<BytesInput>::new();
+    let input = BytesInput::new(vec![0x41]);
+    let mut observer = AllocationObserver::new("allocations");
+
+    observer.pre_exec(&mut state, &input).unwrap();
+    // The harness allocates three buffers of 16 bytes
+    for _ in 0..3 {
+        black_box(Vec::<u8>::with_capacity(16));
+    }
+    observer
+        .post_exec(&mut state, &input, &ExitKind::Ok)
+        .unwrap();
+
+    assert_eq!(observer.allocations(), Some(3));
+    assert_eq!(observer.allocated_bytes(), Some(48));
+}
@@ -75,6 +75,17 @@ pub trait Rand: Debug + Serialize + DeserializeOwned {
         // return the item chosen
         iter.nth(index).unwrap()
     }
+
+    /// Shuffles the given slice in place, using the Fisher-Yates algorithm.
+    ///
+    /// The resulting order only depends on the state of this [`Rand`],
+    /// so the same seed will always yield the same permutation.
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            let other = self.below(idx as u64 + 1) as usize;
+            items.swap(idx, other);
+        }
+    }
 }
 
 // helper macro for deriving Default
@@ -403,6 +414,25 @@ mod tests {
         test_single_rand(&mut Xoshiro256StarRand::with_seed(0));
     }
 
+    #[test]
+    fn test_shuffle() {
+        let shuffled = |seed| {
+            let mut items: [usize; 32] = core::array::from_fn(|idx| idx);
+            StdRand::with_seed(seed).shuffle(&mut items);
+            items
+        };
+
+        let items = shuffled(0);
+        let mut sorted = items;
+        sorted.sort_unstable();
+        assert!(sorted.iter().enumerate().all(|(idx, item)| idx == *item));
+        assert_ne!(items, sorted);
+
+        // Reproducible for the same seed
+        assert_eq!(items, shuffled(0));
+        assert_ne!(items, shuffled(1));
+    }
+
     #[cfg(feature = "std")]
     #[test]
     fn test_random_seed() {
@@ -402,6 +402,12 @@ pub mod serdeany_registry {
             self.map.is_empty()
         }
 
+        /// Removes all elements from this map.
+        #[inline]
+        pub fn clear(&mut self) {
+            self.map.clear();
+        }
+
         /// Returns if the map contains the given type.
         #[must_use]
         #[inline]
@@ -532,6 +538,22 @@ pub mod serdeany_registry {
             }
         }
 
+        /// Get the names of all elements of a type contained in this map.
+        #[inline]
+        pub fn names<T>(&self) -> impl Iterator<Item = &str>
+        where
+            T: crate::serdeany::SerdeAny,
+        {
+            let type_repr = type_repr::<T>();
+            #[cfg(not(feature = "unsafe_stable_anymap"))]
+            let type_repr = &type_repr;
+
+            self.map
+                .get(type_repr)
+                .into_iter()
+                .flat_map(|h| h.keys().map(String::as_str))
+        }
+
         /// Get all elements contained in this map, as mut.
         #[inline]
         #[allow(unused_qualifications)]
@@ -1,4 +1,5 @@
 use std::{
+    any::type_name,
     collections::{BTreeSet, HashMap},
     marker::PhantomData,
 };
@@ -9,7 +10,7 @@ use libafl::{
     inputs::UsesInput,
     schedulers::{RemovableScheduler, Scheduler},
     state::{HasCorpus, State, UsesState},
-    Error, HasMetadata,
+    Error,
 };
 
 #[derive(Clone, Debug)]
@@ -48,8 +49,10 @@ where
     fn on_add(&mut self, state: &mut Self::State, idx: CorpusId) -> Result<(), Error> {
         self.all.insert(idx);
         let testcase = state.corpus().get(idx)?.borrow();
-        let meta = testcase.metadata::<MapNoveltiesMetadata>()?;
-        for cov_idx in &meta.list {
+        let novelties = testcase.novelties().ok_or_else(|| {
+            Error::key_not_found(format!("{} not found", type_name::<MapNoveltiesMetadata>()))
+        })?;
+        for cov_idx in novelties {
             self.mapping.insert(*cov_idx, idx);
         }
         Ok(())
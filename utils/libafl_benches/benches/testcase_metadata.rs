@@ -0,0 +1,51 @@
+//! Compare boxing the common metadata of testcases in their metadata map against inlining it
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use libafl::{
+    corpus::{SchedulerTestcaseMetadata, Testcase},
+    feedbacks::MapNoveltiesMetadata,
+    inputs::BytesInput,
+    HasMetadata,
+};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let input = BytesInput::new(vec![0x41; 64]);
+
+    c.bench_function("testcase_metadata_boxed", |b| {
+        b.iter(|| {
+            let mut testcase = Testcase::new(black_box(input.clone()));
+            // `add_metadata` would inline the scheduler metadata, so go through the map directly
+            testcase
+                .metadata_map_mut()
+                .insert(SchedulerTestcaseMetadata::new(black_box(3)));
+            testcase
+                .metadata_map_mut()
+                .insert(MapNoveltiesMetadata::new(vec![1, 2, 3]));
+            let depth = testcase
+                .metadata_map()
+                .get::<SchedulerTestcaseMetadata>()
+                .unwrap()
+                .depth();
+            let novelties = testcase
+                .metadata_map()
+                .get::<MapNoveltiesMetadata>()
+                .unwrap()
+                .list
+                .len();
+            black_box((depth, novelties))
+        });
+    });
+    c.bench_function("testcase_metadata_inline", |b| {
+        b.iter(|| {
+            let mut testcase = Testcase::new(black_box(input.clone()));
+            testcase.set_scheduler_metadata(SchedulerTestcaseMetadata::new(black_box(3)));
+            testcase.set_novelties(vec![1, 2, 3]);
+            let depth = testcase.scheduler_metadata().unwrap().depth();
+            let novelties = testcase.novelties().unwrap().len();
+            black_box((depth, novelties))
+        });
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);